@@ -8,8 +8,11 @@ use simplelog::{Config, ConfigBuilder, LevelFilter, SimpleLogger, TermLogger, Te
 
 use pueue_lib::settings::Settings;
 
-use pueue::client::cli::{CliArguments, Shell, SubCommand};
+use pueue::client::cli::{
+    no_color_requested, CliArguments, ColorChoice, ConfigCommand, Shell, SubCommand,
+};
 use pueue::client::client::Client;
+use pueue::client::{check_config, doctor};
 
 /// This is the main entry point of the client.
 ///
@@ -51,18 +54,48 @@ async fn main() -> Result<()> {
         Ok(builder) => builder.build(),
     };
 
+    // Error and log messages follow the same color choice as the rest of the output.
+    let log_colors = match opt.color_choice() {
+        ColorChoice::Always => simplelog::ColorChoice::Always,
+        ColorChoice::Never => simplelog::ColorChoice::Never,
+        ColorChoice::Auto if no_color_requested(std::env::var_os("NO_COLOR")) => {
+            simplelog::ColorChoice::Never
+        }
+        ColorChoice::Auto => simplelog::ColorChoice::Auto,
+    };
+
     // Init a terminal logger. If this fails for some reason, try fallback to a SimpleLogger
     if TermLogger::init(
         level,
         logger_config.clone(),
         TerminalMode::Stderr,
-        simplelog::ColorChoice::Auto,
+        log_colors,
     )
     .is_err()
     {
         SimpleLogger::init(level, logger_config).unwrap();
     }
 
+    // The doctor checks the configuration itself and reports all problems it finds, so it has
+    // to run before we bail on a missing or broken configuration file.
+    if let Some(SubCommand::Doctor) = &opt.cmd {
+        if !doctor(&opt).await? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // The same goes for the check of the configuration file.
+    if let Some(SubCommand::Config {
+        cmd: ConfigCommand::Check { config },
+    }) = &opt.cmd
+    {
+        if !check_config(&opt, config).await? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Try to read settings from the configuration file.
     let (mut settings, config_found) =
         Settings::read(&opt.config).context("Failed to read configuration.")?;
@@ -1,11 +1,12 @@
+use std::path::Path;
 use std::process::Command;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
-use log::warn;
-use simplelog::{Config, ConfigBuilder, LevelFilter, SimpleLogger, TermLogger, TerminalMode};
+use simplelog::LevelFilter;
 
-use pueue::daemon::{cli::CliArguments, run};
+use pueue::daemon::{cli::CliArguments, logging::init_logger, run};
+use pueue_lib::settings::CONFIG_FROM_STDIN;
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 4)]
 async fn main() -> Result<()> {
@@ -22,6 +23,11 @@ async fn main() -> Result<()> {
             return Ok(());
         }
 
+        // The forked daemon doesn't inherit our stdin, so it cannot read the config from there.
+        if opt.config.as_deref() == Some(Path::new(CONFIG_FROM_STDIN)) {
+            bail!("The config cannot be read from stdin when daemonizing.");
+        }
+
         return fork_daemon(&opt);
     }
 
@@ -33,27 +39,7 @@ async fn main() -> Result<()> {
         _ => LevelFilter::Trace,
     };
 
-    // Try to initialize the logger with the timezone set to the Local time of the machine.
-    let mut builder = ConfigBuilder::new();
-    let logger_config = match builder.set_time_offset_to_local() {
-        Err(_) => {
-            warn!("Failed to determine the local time of this machine. Fallback to UTC.");
-            Config::default()
-        }
-        Ok(builder) => builder.build(),
-    };
-
-    // Init a terminal logger. If this fails for some reason, try fallback to a SimpleLogger
-    if TermLogger::init(
-        level,
-        logger_config.clone(),
-        TerminalMode::Stderr,
-        simplelog::ColorChoice::Auto,
-    )
-    .is_err()
-    {
-        SimpleLogger::init(level, logger_config).unwrap();
-    }
+    init_logger(level, opt.log_target)?;
 
     #[cfg(target_os = "windows")]
     {
@@ -93,7 +79,7 @@ async fn main() -> Result<()> {
         }
     }
 
-    run(opt.config, opt.profile, false).await
+    run(opt.config, opt.profile, !opt.no_create_config, false).await
 }
 
 /// This is a simple and cheap custom fork method.
@@ -111,10 +97,17 @@ fn fork_daemon(opt: &CliArguments) -> Result<()> {
         arguments.push(profile.clone());
     }
 
+    if opt.no_create_config {
+        arguments.push("--no-create-config".to_string());
+    }
+
     if opt.verbose > 0 {
         arguments.push("-".to_string() + &"v".repeat(opt.verbose as usize));
     }
 
+    arguments.push("--log-target".to_string());
+    arguments.push(opt.log_target.as_arg().to_string());
+
     // Try to get the path to the current binary, since it may not be in the $PATH.
     // If we cannot detect it (for some unknown reason), fallback to the raw `pueued` binary name.
     let current_exe = if let Ok(path) = std::env::current_exe() {
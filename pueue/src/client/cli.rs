@@ -1,14 +1,17 @@
+use std::ffi::OsString;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use chrono::prelude::*;
 use chrono::TimeDelta;
 use clap::ArgAction;
-use clap::{Parser, ValueEnum, ValueHint};
+use clap::{ArgGroup, Parser, ValueEnum, ValueHint};
 use interim::*;
 
+use pueue_lib::duration;
 use pueue_lib::network::message::Signal;
-
-use super::commands::WaitTargetStatus;
+use pueue_lib::task::WaitTargetStatus;
 
 #[derive(Parser, Debug, Clone)]
 pub enum SubCommand {
@@ -24,13 +27,45 @@ pub enum SubCommand {
     #[command(trailing_var_arg = true)]
     Add {
         /// The command to be added.
-        #[arg(required = true, num_args(1..), value_hint = ValueHint::CommandWithArguments)]
+        #[arg(
+            required_unless_present = "from_file",
+            num_args(1..),
+            value_hint = ValueHint::CommandWithArguments
+        )]
         command: Vec<String>,
 
+        /// Add one task for each line of the given file.
+        ///
+        /// Surrounding whitespace is trimmed. Blank lines and lines starting with `#` are
+        /// ignored. Trailing comments aren't supported, a `#` anywhere else is part of the
+        /// command. All other options apply to each of the added tasks.
+        #[arg(
+            long,
+            value_hint = ValueHint::FilePath,
+            conflicts_with_all = ["command", "raw", "escape", "key"]
+        )]
+        from_file: Option<PathBuf>,
+
         /// Specify current working directory.
         #[arg(name = "working-directory", short = 'w', long, value_hint = ValueHint::DirPath)]
         working_directory: Option<PathBuf>,
 
+        /// Create the working directory right before the task is started, if it doesn't exist yet.
+        ///
+        /// Without this flag, tasks with a missing working directory fail to spawn.
+        /// As the directory is created by the daemon, this can't be combined with `--user`.
+        #[arg(long, conflicts_with = "user")]
+        create_workdir: bool,
+
+        /// Use the working directory of an existing task.
+        #[arg(
+            long,
+            visible_alias = "cwd-from",
+            value_name = "TASK_ID",
+            conflicts_with = "working-directory"
+        )]
+        working_directory_from: Option<usize>,
+
         /// Escape any special shell characters (" ", "&", "!", etc.).
         /// Beware: This implicitly disables nearly all shell specific syntax ("&&", "&>").
         #[arg(verbatim_doc_comment, short, long)]
@@ -72,6 +107,14 @@ pub enum SubCommand {
         #[arg(short = 'o', long)]
         priority: Option<i32>,
 
+        /// Run this task before everything else that's currently queued in its group.
+        ///
+        /// The priority is set just above the highest priority of all queued (or scheduled)
+        /// tasks in the target group at the time of adding.
+        /// If there're no queued tasks, the default priority is used.
+        #[arg(long, conflicts_with = "priority")]
+        front: bool,
+
         /// Add some information for yourself.
         ///
         /// This string will be shown in the "status" table.
@@ -79,19 +122,117 @@ pub enum SubCommand {
         #[arg(short, long)]
         label: Option<String>,
 
+        /// Run the task as a different user (Unix only).
+        ///
+        /// The daemon drops its privileges to this user before starting the task.
+        /// This is only possible if the daemon has the permission to do so, i.e. usually only if
+        /// it runs as root.
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Execute the command directly with the given arguments, without wrapping it in a shell.
+        ///
+        /// Shell syntax, such as pipes, variables or globs, isn't interpreted and aliases aren't
+        /// applied. The first argument is the program that's executed.
+        #[arg(long, visible_alias = "raw-command", conflicts_with = "escape")]
+        raw: bool,
+
+        /// Limit the amount of memory the task may use (Linux only), e.g. `512M` or `2G`.
+        ///
+        /// The task is executed in a transient systemd scope, whose cgroup enforces this limit.
+        /// This requires `systemd-run` and a running systemd user instance.
+        /// As the scope belongs to the daemon's user, this can't be combined with `--user`.
+        #[arg(long, value_parser = parse_memory_limit, conflicts_with = "user")]
+        memory_limit: Option<u64>,
+
+        /// Don't start the task anymore, if it hasn't been started after this duration,
+        /// e.g. `30m` or `1h`.
+        ///
+        /// Expired tasks are marked as failed instead of being run late.
+        #[arg(long, value_parser = parse_duration_from_now)]
+        expire_in: Option<DateTime<Local>>,
+
+        /// Kill the task, if it runs for longer than this duration, e.g. `30m` or `1h30m`.
+        ///
+        /// Tasks that exceed their timeout are marked as killed.
+        /// The timeout counts from the start of the task, which includes time it spent paused.
+        #[arg(long, value_parser = parse_timeout)]
+        timeout: Option<Duration>,
+
+        /// A unique key that identifies this task, e.g. `my-job-123`.
+        ///
+        /// Adding another task with the same key fails, unless `--replace` is given.
+        /// This makes it safe to add the same task repeatedly from scripts.
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Replace the existing task with the same key, instead of failing.
+        ///
+        /// The new task takes over the id of the old task.
+        /// Running or paused tasks cannot be replaced.
+        #[arg(long, requires = "key")]
+        replace: bool,
+
+        /// A command that's executed once the task finished successfully.
+        ///
+        /// The command is executed in the task's working directory.
+        /// Information about the task is available via the `PUEUE_TASK_ID`, `PUEUE_TASK_GROUP`,
+        /// `PUEUE_TASK_RESULT`, `PUEUE_TASK_EXIT_CODE` and `PUEUE_TASK_OUTPUT_PATH`
+        /// environment variables.
+        /// This runs in addition to the global callback of the daemon.
+        #[arg(long)]
+        on_success: Option<String>,
+
+        /// A command that's executed once the task failed, e.g. due to a non-zero exit code.
+        ///
+        /// Behaves the same as `--on-success`.
+        #[arg(long)]
+        on_failure: Option<String>,
+
+        /// Treat the termination of the task by this signal as a success instead of `killed`,
+        /// e.g. for services that exit via `SIGTERM` on a graceful shutdown (Unix only).
+        ///
+        /// Can be passed multiple times.
+        #[arg(long, value_name = "SIGNAL", ignore_case(true))]
+        success_on_signal: Vec<Signal>,
+
+        /// Resume the task's group, in case it's paused.
+        ///
+        /// Without this flag, tasks that're added to a paused group won't be started until the
+        /// group is resumed via `pueue start --group`.
+        #[arg(long)]
+        start_group: bool,
+
+        /// Create the task's group, if it doesn't exist yet.
+        ///
+        /// Without this flag, adding a task to an unknown group fails.
+        #[arg(long)]
+        create_group: bool,
+
         /// Only return the task id instead of a text.
         ///
         /// This is useful when working with dependencies in scripts.
         #[arg(short, long)]
         print_task_id: bool,
+
+        /// Print the created task as json, including its id, status, group and priority.
+        ///
+        /// The task's environment variables aren't included.
+        #[arg(long, conflicts_with = "print_task_id")]
+        output_json: bool,
+
+        /// Print the raw json data, without the envelope that contains the schema version and
+        /// the kind of the data.
+        #[arg(long, requires = "output_json")]
+        raw_json: bool,
     },
     /// Remove tasks from the list.
     /// Running or paused tasks need to be killed first.
     #[command(alias("rm"))]
     Remove {
         /// The task ids to be removed.
-        #[arg(required = true)]
-        task_ids: Vec<usize>,
+        #[arg(required = true, value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
     },
     /// Switches the queue position of two commands.
     ///
@@ -108,7 +249,8 @@ pub enum SubCommand {
     /// Stashed entries can also be explicitely started via `pueue start $task_id`.
     Stash {
         /// Stash these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Stash all queued tasks in a group
         #[arg(short, long, conflicts_with = "all")]
@@ -118,9 +260,27 @@ pub enum SubCommand {
         #[arg(short, long)]
         all: bool,
 
+        /// Stash all tasks that match this filter, e.g. `label=batch-7`.
+        /// The filter uses the same syntax as the query of `pueue status`.
+        #[arg(long, visible_alias = "query", conflicts_with_all = ["task_ids", "group", "all"])]
+        filter: Option<String>,
+
         /// Delay enqueuing these tasks until 'delay' elapses. See DELAY FORMAT below.
         #[arg(name = "delay", short, long, value_parser = parse_delay_until)]
         delay_until: Option<DateTime<Local>>,
+
+        /// Automatically enqueue these tasks again after they've been stashed for this duration.
+        ///
+        /// The duration is either a number of seconds or a combination of numbers with the
+        /// units `s`, `m`, `h` and `d`, e.g. `30m` or `1h30m`.
+        #[arg(
+            name = "for",
+            long = "for",
+            visible_alias = "duration",
+            conflicts_with = "delay",
+            value_parser = parse_duration_from_now
+        )]
+        stash_for: Option<DateTime<Local>>,
     },
     /// Enqueue stashed tasks. They'll be handled normally afterwards.
     ///
@@ -149,7 +309,8 @@ pub enum SubCommand {
 ")]
     Enqueue {
         /// Enqueue these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Enqueue all stashed tasks in a group
         #[arg(short, long, conflicts_with = "all")]
@@ -159,9 +320,29 @@ pub enum SubCommand {
         #[arg(short, long)]
         all: bool,
 
+        /// Enqueue all tasks that match this filter, e.g. `label=batch-7`.
+        /// The filter uses the same syntax as the query of `pueue status`.
+        #[arg(long, visible_alias = "query", conflicts_with_all = ["task_ids", "group", "all"])]
+        filter: Option<String>,
+
         /// Delay enqueuing these tasks until 'delay' elapses. See DELAY FORMAT below.
         #[arg(name = "delay", short, long, value_parser = parse_delay_until)]
         delay_until: Option<DateTime<Local>>,
+
+        /// Immediately start the tasks, no matter how many open slots there are.
+        /// This will ignore any dependencies tasks may have.
+        #[arg(long, visible_alias = "and-start", conflicts_with = "delay")]
+        start: bool,
+    },
+
+    /// Remove the scheduled enqueue time of stashed tasks.
+    ///
+    /// The tasks stay stashed and won't be enqueued automatically any longer.
+    /// Use `pueue status --scheduled` to list all scheduled tasks.
+    Unschedule {
+        /// The ids of the scheduled tasks.
+        #[arg(required = true, value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
     },
 
     /// Resume operation of specific tasks or groups of tasks.
@@ -172,7 +353,8 @@ pub enum SubCommand {
     Start {
         /// Start these specific tasks. Paused tasks will resumed. Queued/Stashed tasks will be
         /// force-started.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Resume a specific group and all paused tasks in it.
         ///
@@ -185,6 +367,11 @@ pub enum SubCommand {
         /// All groups will be set to running and paused tasks will be resumed.
         #[arg(short, long)]
         all: bool,
+
+        /// Start all tasks that match this filter, e.g. `status=paused and label%=batch`.
+        /// The filter uses the same syntax as the query of `pueue status`.
+        #[arg(long, visible_alias = "query", conflicts_with_all = ["task_ids", "group", "all"])]
+        filter: Option<String>,
     },
 
     /// Restart failed or successful task(s).
@@ -194,7 +381,8 @@ pub enum SubCommand {
     #[command(alias("re"))]
     Restart {
         /// Restart these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Restart all failed tasks across all groups.
         ///
@@ -207,6 +395,18 @@ pub enum SubCommand {
         #[arg(short = 'g', long, conflicts_with = "all_failed")]
         failed_in_group: Option<String>,
 
+        /// Only restart the failed tasks among the given task ids.
+        /// Successfully finished tasks are skipped.
+        ///
+        /// `--all-failed` and `--failed-in-group` always only restart failed tasks.
+        #[arg(
+            long,
+            visible_alias = "retry-failed-only",
+            requires = "task_ids",
+            conflicts_with_all = ["all_failed", "failed_in_group"]
+        )]
+        failed_only: bool,
+
         /// Immediately start the tasks, no matter how many open slots there are.
         /// This will ignore any dependencies tasks may have.
         #[arg(short = 'k', long, conflicts_with = "stashed")]
@@ -225,12 +425,14 @@ pub enum SubCommand {
         in_place: bool,
 
         /// Restart the task by creating a new identical tasks.
+        /// The original tasks are left untouched and keep their logs.
         /// Only necessary if you have the `restart_in_place` configuration set to true.
-        #[arg(long)]
+        #[arg(long, visible_alias = "as-new", conflicts_with = "in_place")]
         not_in_place: bool,
 
-        /// Edit the task before restarting.
-        #[arg(short, long)]
+        /// Edit the tasks before restarting.
+        /// If multiple tasks are restarted, all of them are edited in a single editor session.
+        #[arg(short, long, visible_alias = "edit-all")]
         edit: bool,
     },
 
@@ -240,7 +442,8 @@ pub enum SubCommand {
     /// A paused queue (group) won't start any new tasks.
     Pause {
         /// Pause these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Pause a specific group.
         #[arg(short, long, conflicts_with = "all")]
@@ -253,6 +456,18 @@ pub enum SubCommand {
         /// Pause the specified group[s], but let already running tasks finish by themselves.
         #[arg(short, long)]
         wait: bool,
+
+        /// Pause all tasks that match this filter, e.g. `status=running and label%=batch`.
+        /// The filter uses the same syntax as the query of `pueue status`.
+        #[arg(long, visible_alias = "query", conflicts_with_all = ["task_ids", "group", "all"])]
+        filter: Option<String>,
+
+        /// Kill the running and paused tasks instead of pausing them and stash them again.
+        /// This way, the group is truly idle and the tasks can be enqueued again later on.
+        ///
+        /// The killed tasks end up in the `Stashed` state, no callbacks are executed for them.
+        #[arg(long, visible_alias = "save-stashed", conflicts_with = "wait")]
+        restash: bool,
     },
 
     /// Kill specific running tasks or whole task groups.
@@ -260,7 +475,8 @@ pub enum SubCommand {
     /// Kills all tasks of the default group when no ids or a specific group are provided.
     Kill {
         /// Kill these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Kill all running tasks in a group. This also pauses the group.
         #[arg(short, long, conflicts_with = "all")]
@@ -270,6 +486,21 @@ pub enum SubCommand {
         #[arg(short, long)]
         all: bool,
 
+        /// Kill all running tasks across ALL groups without pausing any group.
+        ///
+        /// Queued tasks stay queued and will be started as usual once slots free up.
+        #[arg(long, conflicts_with_all = ["all", "group", "task_ids"])]
+        all_groups: bool,
+
+        /// Kill all tasks that match this filter, e.g. `status=running and label%=batch`.
+        /// The filter uses the same syntax as the query of `pueue status`.
+        #[arg(
+            long,
+            visible_alias = "query",
+            conflicts_with_all = ["task_ids", "group", "all", "all_groups"]
+        )]
+        filter: Option<String>,
+
         /// Send a UNIX signal instead of simply killing the process.
         /// DISCLAIMER: This bypasses Pueue's process handling logic!
         ///     You might enter weird invalid states, use at your own descretion.
@@ -279,8 +510,9 @@ pub enum SubCommand {
 
     /// Send something to a task. Useful for sending confirmations such as 'y\n'.
     Send {
-        /// The id of the task.
-        task_id: usize,
+        /// The ids of the tasks. The same input is sent to each of them.
+        #[arg(required = true, value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// The input that should be sent to the process.
         input: String,
@@ -292,7 +524,8 @@ pub enum SubCommand {
     /// A temporary folder folder/file will be opened by your $EDITOR to edit the tasks.
     Edit {
         /// The ids of all tasks that should be edited.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
     },
 
     /// Use this to add or remove environment variables from tasks.
@@ -301,6 +534,18 @@ pub enum SubCommand {
         cmd: EnvCommand,
     },
 
+    /// Attach a note to a task, e.g. to remember why it failed.
+    ///
+    /// Notes can be attached to tasks in any state and are shown by `pueue log`.
+    /// Omit the note to remove an existing one.
+    Annotate {
+        /// The id of the task that should be annotated.
+        task_id: usize,
+
+        /// The note that should be attached to the task.
+        note: Option<String>,
+    },
+
     /// Use this to add or remove groups.
     ///
     /// By default, this will simply display all known groups.
@@ -309,11 +554,27 @@ pub enum SubCommand {
         #[arg(short, long)]
         json: bool,
 
+        /// Print the raw json data, without the envelope that contains the schema version and
+        /// the kind of the data.
+        #[arg(long, requires = "json")]
+        raw_json: bool,
+
+        /// Set the amount of parallel tasks for groups that're created from now on.
+        /// Existing groups aren't affected and the value is reset when the daemon restarts.
+        #[arg(
+            long,
+            visible_alias = "default-parallel",
+            value_name = "PARALLEL",
+            conflicts_with = "json"
+        )]
+        set_default_parallel: Option<usize>,
+
         #[command(subcommand)]
         cmd: Option<GroupCommand>,
     },
 
     /// Display the current status of all tasks.
+    #[command(group(ArgGroup::new("json_output").args(["json", "json_pretty"])))]
     Status {
         /// Users can specify a custom query to filter for specific values, order by a column
         /// or limit the amount of tasks listed.
@@ -323,29 +584,48 @@ pub enum SubCommand {
 or limit the amount of tasks listed.
 
 Syntax:
-   [column_selection]? [filter]* [order_by]? [limit]?
+   [column_selection]? [filter_expression]? [order_by]? [limit]?
 
 where:
   - column_selection := `columns=[column]([column],)*`
-  - column := `id | status | command | label | path | enqueue_at | dependencies | start | end`
+  - column := `id | status | command | label | path | enqueue_at | dependencies | start | end | worker`
+  - filter_expression := `[filter_term] ([filter_combinator]? [filter_term])*`
+  - filter_term := `[filter] | ([filter_expression])`
+  - filter_combinator := `and | or`
+    (filters without a combinator are combined with `and`, `and` binds stronger than `or`)
   - filter := `[filter_column] [filter_op] [filter_value]`
     (note: not all columns support all operators, see \"Filter columns\" below.)
-  - filter_column := `status | command | label | start | end | enqueue_at`
-  - filter_op := `= | != | < | > | %=`
+  - filter_column := `id | status | priority | command | label | start | end | enqueue_at`
+  - filter_op := `= | != | < | > | <= | >= | %= | ~%= | ~=`
     (`%=` means 'contains', as in the test value is a substring of the column value)
-  - order_by := `order_by [column] [order_direction]`
+    (`~%=` means 'contains, ignoring case', like `%=` but without matching letter case)
+    (`~=` means 'matches', as in the column value matches the regex given as test value)
+  - order_by := `order_by [column] [order_direction]?(, [column] [order_direction]?)*`
+    (further columns break ties of the previous ones)
+    (tasks can also be ordered by their `runtime`, which isn't a visible column)
   - order_direction := `asc | desc`
-  - limit := `[limit_type]? [limit_count]`
+  - limit := `[limit_type]? [limit_count] (offset [limit_offset])?`
   - limit_type := `first | last`
   - limit_count := a positive integer
+  - limit_offset := the amount of tasks that's skipped from the respective end, e.g. for paging
 
 Filter columns:
   - `status` supports the operators `=`, `!=`
     against test values that are:
-      - strings like `queued`, `stashed`, `paused`, `running`, `success`, `failed`
-  - `command`, `label` support the operators `=`, `!=`, `%=`
+      - strings like `queued`, `stashed`, `paused`, `running`, `success`, `failed`, `locked`
+  - `id` supports the operators `=`, `!=`, `<`, `>`, `<=`, `>=`
+    against test values that are:
+      - task ids like `5`
+    and ranges via `id in [start]..[end]`, which include `start` but exclude `end`
+  - `priority` supports the operators `=`, `!=`, `<`, `>`, `<=`, `>=`
+    against test values that are:
+      - integers like `5` or `-1`
+  - `command`, `label` support the operators `=`, `!=`, `%=`, `~%=`, `~=`
     against test values that are:
       - strings like `some text`
+      - regexes like `^cargo (build|test)` for `~=`
+    (note: the test value ends in front of an ` and `/` or `, another filter like ` status=`
+     or an unmatched `)`, parenthesis within it have to be balanced)
   - `start`, `end`, `enqueue_at` contain a datetime
     which support the operators `=`, `!=`, `<`, `>`
     against test values that are:
@@ -353,15 +633,26 @@ Filter columns:
       - time like `HH:mm:ss` or `HH:mm`
       - datetime like `YYYY-MM-DDHH:mm:ss`
         (note there is currently no separator between the date and the time)
+  - `runtime` supports the operators `=`, `!=`, `<`, `>`, `<=`, `>=`
+    against test values that are:
+      - durations like `30s`, `5m`, `2h` or `1d`
+    (note: the runtime of running tasks is the time since their start)
 
 Examples:
   - `status=running`
   - `command%=echo`
   - `label=mytask`
+  - `priority>=5`
+  - `id in 10..20`
+  - `runtime>1h`
+  - `order_by id desc first 20 offset 40`
+  - `order_by status asc, id desc`
+  - `status=failed or status=running`
+  - `(label%=build or label%=test) and status=success`
   - `columns=id,status,command status=running start > 2023-05-2112:03:17 order_by command first 5`
 
 The formal syntax is defined here:
-https://github.com/Nukesor/pueue/blob/main/pueue/src/client/query/syntax.pest
+https://github.com/Nukesor/pueue/blob/main/pueue_lib/src/query/syntax.pest
 
 More documentation is on the query syntax PR:
 https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
@@ -374,16 +665,77 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         #[arg(short, long)]
         json: bool,
 
+        /// Print the current state as indented, human readable json to stdout.
+        /// `--json` prints compact json instead.
+        #[arg(long, conflicts_with = "json")]
+        json_pretty: bool,
+
+        /// Print the raw json data, without the envelope that contains the schema version and
+        /// the kind of the data. Requires `--json` or `--json-pretty`.
+        #[arg(long, requires = "json_output")]
+        raw_json: bool,
+
+        /// Print each task as a json object on a separate line (JSON Lines).
+        /// The tasks are filtered and ordered by the query, just like the table.
+        #[arg(
+            long,
+            conflicts_with_all = ["json", "json_pretty", "raw_json", "tree", "total", "relative"]
+        )]
+        json_stream: bool,
+
+        /// Order the tasks by a column or their runtime, e.g. `--sort start:desc` or
+        /// `--sort runtime:desc`.
+        /// The direction is either `asc` or `desc` and defaults to `asc`.
+        ///
+        /// This is a shortcut for `order_by [column] [direction]` in the query and cannot be
+        /// combined with it.
+        #[arg(long, value_name = "COLUMN[:asc|desc]")]
+        sort: Option<String>,
+
         #[arg(short, long)]
         /// Only show tasks of a specific group
         group: Option<String>,
+
+        /// Only show tasks of the given groups, e.g. `--groups build,deploy`.
+        #[arg(long, value_delimiter = ',', conflicts_with = "group")]
+        groups: Vec<String>,
+
+        /// Only show stashed tasks that are scheduled to be enqueued at some point,
+        /// including the time that's left until they're enqueued.
+        #[arg(long)]
+        scheduled: bool,
+
+        /// Show the tasks as a tree, in which tasks are nested below the tasks they depend on.
+        #[arg(long, conflicts_with_all = ["json", "json_pretty"])]
+        tree: bool,
+
+        /// Append a summary line with the amount of displayed tasks per status and group.
+        #[arg(long, conflicts_with_all = ["json", "json_pretty"])]
+        total: bool,
+
+        /// Show times relative to now, e.g. `5m ago` or `in 10m`.
+        ///
+        /// This can be enabled permanently via the `client.relative_times` setting.
+        #[arg(long, conflicts_with_all = ["json", "json_pretty"])]
+        relative: bool,
+
+        /// Use a query that has previously been saved via `--save-view` instead of passing one.
+        ///
+        /// Views are stored in the `client.views` section of the configuration file.
+        #[arg(long, value_name = "NAME", conflicts_with = "query")]
+        view: Option<String>,
+
+        /// Save the given query under a name, so it can be recalled via `--view` later on.
+        /// An existing view with the same name is overwritten.
+        #[arg(long, value_name = "NAME", requires = "query")]
+        save_view: Option<String>,
     },
 
     /// Accept a list or map of JSON pueue tasks via stdin and display it just like \"pueue status\".
     ///
     /// A simple example might look like this:
     ///
-    /// pueue status --json | jq -c '.tasks' | pueue format-status",
+    /// pueue status --json | jq -c '.data.tasks' | pueue format-status",
     #[command(after_help = "DISCLAIMER:\n\
         This command is a temporary workaround until a proper filtering language for \"status\" has
         been implemented. It might be removed in the future.")]
@@ -399,7 +751,8 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
     /// If you want to follow the output of a task, please use the \"follow\" subcommand.
     Log {
         /// View the task output of these specific tasks.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// View the outputs of this specific group's tasks.
         #[arg(short, long)]
@@ -417,6 +770,11 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         #[arg(short, long)]
         json: bool,
 
+        /// Print the raw json data, without the envelope that contains the schema version and
+        /// the kind of the data.
+        #[arg(long, requires = "json")]
+        raw_json: bool,
+
         /// Only print the last X lines of each task's output.
         ///
         /// This is done by default if you're looking at multiple tasks.
@@ -426,6 +784,15 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         /// Show the whole output.
         #[arg(short, long)]
         full: bool,
+
+        /// Hide the timestamps at the start of each line.
+        /// They're added to the output, if `daemon.timestamp_logs` is enabled.
+        #[arg(long)]
+        no_timestamps: bool,
+
+        /// Write the raw output of a single task to this file instead of printing it.
+        #[arg(long, value_hint = ValueHint::FilePath, conflicts_with_all = ["json", "group", "all"])]
+        out_file: Option<PathBuf>,
     },
 
     /// Follow the output of a currently running task.
@@ -441,6 +808,11 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         /// Only print the last X lines of the output before following
         #[arg(short, long)]
         lines: Option<usize>,
+
+        /// Hide the timestamps at the start of each line.
+        /// They're added to the output, if `daemon.timestamp_logs` is enabled.
+        #[arg(long)]
+        no_timestamps: bool,
     },
 
     /// Wait until tasks are finished.
@@ -451,7 +823,8 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
     /// Includes: [Paused, Stashed, Locked, Queued, ...]
     Wait {
         /// This allows you to wait for specific tasks to finish.
-        task_ids: Vec<usize>,
+        #[arg(value_parser = parse_task_id_range, value_delimiter = ',')]
+        task_ids: Vec<RangeInclusive<usize>>,
 
         /// Wait for all tasks in a specific group
         #[arg(short, long, conflicts_with = "all")]
@@ -478,8 +851,36 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         successful_only: bool,
 
         /// Only clean tasks of a specific group
+        #[arg(short, long, conflicts_with = "all")]
+        group: Option<String>,
+
+        /// Clean finished tasks across all groups.
+        /// This is also the default, if no group is specified.
+        #[arg(short, long)]
+        all: bool,
+    },
+
+    /// Show previously executed commands, including the ones of already cleaned tasks.
+    ///
+    /// Finished tasks are added to the persistent history when they're cleaned.
+    /// Removed tasks and tasks of a reset aren't part of the history.
+    History {
+        /// Only show commands of a specific group.
         #[arg(short, long)]
         group: Option<String>,
+
+        /// Add the command with the given index in the history as a new task.
+        #[arg(long, value_name = "INDEX")]
+        rerun: Option<usize>,
+
+        /// Print the history as json.
+        #[arg(short, long, conflicts_with = "rerun")]
+        json: bool,
+
+        /// Print the raw json data, without the envelope that contains the schema version and
+        /// the kind of the data.
+        #[arg(long, requires = "json")]
+        raw_json: bool,
     },
 
     /// Kill all tasks, clean up afterwards and reset EVERYTHING!
@@ -513,6 +914,19 @@ https://github.com/Nukesor/pueue/issues/350#issue-1359083118"
         group: Option<String>,
     },
 
+    /// Inspect Pueue's configuration file.
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCommand,
+    },
+
+    /// Check the local Pueue setup and print a checklist of found problems.
+    ///
+    /// This checks the configuration, Pueue's directories, the shared secret, the pid file and
+    /// whether the daemon can be reached and runs the same version as the client.
+    /// Exits with a non-zero exit code if any of the checks fails.
+    Doctor,
+
     /// Generates shell completion files.
     ///
     /// This can be ignored during normal operations.
@@ -550,6 +964,20 @@ pub enum EnvCommand {
     },
 }
 
+#[derive(Parser, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Check the configuration file for problems, such as unknown keys or invalid values.
+    ///
+    /// If the daemon is reachable, group specific settings are checked against its groups.
+    /// Exits with a non-zero exit code if any problem has been found.
+    Check {
+        /// The configuration file that should be checked.
+        /// Defaults to the file that's used by all other commands.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        config: Option<PathBuf>,
+    },
+}
+
 #[derive(Parser, Debug, Clone)]
 pub enum GroupCommand {
     /// Add a group by name.
@@ -561,6 +989,11 @@ pub enum GroupCommand {
         /// Setting this to 0 means an unlimited amount of parallel tasks.
         #[arg(short, long)]
         parallel: Option<usize>,
+
+        /// A note on what this group is used for.
+        /// It's shown next to the group's name.
+        #[arg(short, long)]
+        description: Option<String>,
     },
 
     /// Remove a group by name.
@@ -575,6 +1008,27 @@ pub enum ColorChoice {
     Always,
 }
 
+impl ColorChoice {
+    /// Decide whether output should be styled.
+    ///
+    /// An explicit `always` or `never` takes precedence over everything else. \
+    /// On `auto`, styling is disabled if `NO_COLOR` is set (see <https://no-color.org>).
+    /// Otherwise, output is only styled if it's written to a terminal.
+    pub fn enabled(&self, no_color: bool, is_tty: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => !no_color && is_tty,
+        }
+    }
+}
+
+/// Check whether the user requested uncolored output via the `NO_COLOR` environment variable.
+/// As defined by the convention, the variable only counts if it isn't empty.
+pub fn no_color_requested(value: Option<OsString>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
 #[derive(Parser, ValueEnum, Debug, Clone, PartialEq, Eq)]
 pub enum Shell {
     Bash,
@@ -590,6 +1044,7 @@ pub enum Shell {
     name = "pueue",
     about = concat!(
         "Interact with the Pueue daemon\n\n",
+        "Task ids can be passed as ranges (`5-10`) and comma separated lists (`5,8,12`).\n",
         "Use the `--help` long form to get detailed help output on each subcommand!"
     ),
     author,
@@ -601,13 +1056,21 @@ pub struct CliArguments {
     pub verbose: u8,
 
     /// Colorize the output; auto enables color output when connected to a tty.
+    ///
+    /// On auto, colors are disabled if the `NO_COLOR` environment variable is set.
     #[arg(long, value_enum, default_value = "auto")]
     pub color: ColorChoice,
 
+    /// Disable colored output. This is a shorthand for `--color never`.
+    #[arg(long, conflicts_with = "color")]
+    pub no_color: bool,
+
     /// If provided, Pueue only uses this config file.
     ///
     /// This path can also be set via the "PUEUE_CONFIG_PATH" environment variable.
     /// The commandline option overwrites the environment variable!
+    ///
+    /// Use `-` to read the config from stdin.
     #[arg(short, long, value_hint = ValueHint::FilePath)]
     pub config: Option<PathBuf>,
 
@@ -619,6 +1082,17 @@ pub struct CliArguments {
     pub cmd: Option<SubCommand>,
 }
 
+impl CliArguments {
+    /// The color choice of the user, with `--no-color` taken into account.
+    pub fn color_choice(&self) -> ColorChoice {
+        if self.no_color {
+            ColorChoice::Never
+        } else {
+            self.color.clone()
+        }
+    }
+}
+
 fn parse_delay_until(src: &str) -> Result<DateTime<Local>, String> {
     if let Ok(seconds) = src.parse::<i64>() {
         let delay_until = Local::now()
@@ -635,3 +1109,88 @@ fn parse_delay_until(src: &str) -> Result<DateTime<Local>, String> {
         "could not parse as seconds or date expression",
     ))
 }
+
+/// Expand the task id ranges that have been passed on the commandline into single task ids.
+pub fn expand_task_ids(task_ids: &[RangeInclusive<usize>]) -> Vec<usize> {
+    task_ids.iter().cloned().flatten().collect()
+}
+
+/// The maximum amount of task ids a single range may span.
+/// Ranges are expanded into single task ids, so a huge range would allocate a huge list.
+const MAX_TASK_ID_RANGE: usize = 10_000;
+
+/// Parse a single task id (`5`) or an inclusive range of task ids (`1-50`).
+///
+/// Comma separated lists are split by clap, before they're passed to this function.
+fn parse_task_id_range(src: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse_id = |id: &str| {
+        id.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("'{id}' isn't a valid task id"))
+    };
+
+    let Some((start, end)) = src.split_once('-') else {
+        let id = parse_id(src)?;
+        return Ok(id..=id);
+    };
+
+    let (start, end) = (parse_id(start)?, parse_id(end)?);
+    if start > end {
+        return Err(format!("the range '{src}' is empty"));
+    }
+    if end - start >= MAX_TASK_ID_RANGE {
+        return Err(format!(
+            "the range '{src}' spans more than {MAX_TASK_ID_RANGE} task ids"
+        ));
+    }
+
+    Ok(start..=end)
+}
+
+/// Parse a relative duration such as `90`, `30m` or `1h30m` and add it to the current time.
+fn parse_duration_from_now(src: &str) -> Result<DateTime<Local>, String> {
+    Ok(Local::now() + parse_duration(src)?)
+}
+
+/// Parse a positive duration such as `90`, `30m` or `1h30m`, after which a task is killed.
+fn parse_timeout(src: &str) -> Result<Duration, String> {
+    parse_duration(src)?
+        .to_std()
+        .ok()
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| format!("the timeout '{src}' must be positive"))
+}
+
+/// Parse a duration such as `90`, `30m` or `1h30m`.
+fn parse_duration(src: &str) -> Result<TimeDelta, String> {
+    duration::parse_duration(src)
+        .and_then(|duration| TimeDelta::from_std(duration).ok())
+        .ok_or_else(|| format!("could not parse '{src}' as a duration"))
+}
+
+/// Parse a memory size such as `1024`, `512K`, `512M` or `2G` into bytes.
+fn parse_memory_limit(src: &str) -> Result<u64, String> {
+    let error = || format!("could not parse '{src}' as a memory size");
+
+    let src = src.trim();
+    let (number, factor) = match src.char_indices().last() {
+        Some((index, unit)) if unit.is_ascii_alphabetic() => {
+            let factor: u64 = match unit.to_ascii_uppercase() {
+                'K' => 1 << 10,
+                'M' => 1 << 20,
+                'G' => 1 << 30,
+                'T' => 1 << 40,
+                _ => return Err(error()),
+            };
+            (&src[..index], factor)
+        }
+        _ => (src, 1),
+    };
+
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(factor))
+        .filter(|bytes| *bytes > 0)
+        .ok_or_else(error)
+}
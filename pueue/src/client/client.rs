@@ -1,19 +1,22 @@
 use std::env::{current_dir, vars};
-use std::io::{self, stdout, Write};
+use std::fs::read_to_string;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::{borrow::Cow, collections::HashMap};
 
 use anyhow::{bail, Context, Result};
 use clap::crate_version;
-use crossterm::tty::IsTty;
 use log::{error, warn};
 
 use pueue_lib::network::message::*;
 use pueue_lib::network::protocol::*;
-use pueue_lib::network::secret::read_shared_secret;
-use pueue_lib::settings::Settings;
-use pueue_lib::state::PUEUE_DEFAULT_GROUP;
+use pueue_lib::network::secret::read_handshake_secret;
+use pueue_lib::process_helper::process_exists;
+use pueue_lib::settings::{Settings, Shared, CONFIG_FROM_STDIN};
+use pueue_lib::state::{State, PUEUE_DEFAULT_GROUP};
 
-use crate::client::cli::{CliArguments, ColorChoice, GroupCommand, SubCommand};
+use crate::client::cli::{expand_task_ids, CliArguments, GroupCommand, SubCommand};
 use crate::client::commands::*;
 use crate::client::display::*;
 
@@ -49,26 +52,131 @@ pub fn group_or_default(group: &Option<String>) -> String {
 pub fn selection_from_params(
     all: bool,
     group: &Option<String>,
-    task_ids: &[usize],
+    task_ids: &[RangeInclusive<usize>],
 ) -> TaskSelection {
     if all {
         TaskSelection::All
     } else if let Some(group) = group {
         TaskSelection::Group(group.clone())
     } else if !task_ids.is_empty() {
-        TaskSelection::TaskIds(task_ids.to_owned())
+        TaskSelection::TaskIds(expand_task_ids(task_ids))
     } else {
         TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())
     }
 }
 
+/// Determine the priority that's needed for a new task to run before all tasks that're currently
+/// queued (or scheduled to be enqueued) in the given group.
+///
+/// Tasks with the same priority are started in ascending id order and a new task always gets
+/// the highest id. Hence, the new task must get a priority that's strictly higher than the
+/// current maximum to actually outrank those tasks.
+/// If there're no queued tasks in the group, the default priority `0` is used.
+pub fn priority_in_front_of_group(state: &State, group: &str) -> i32 {
+    state
+        .tasks
+        .values()
+        .filter(|task| task.group == group && task.is_queued())
+        .map(|task| task.priority)
+        .max()
+        .map(|priority| priority.saturating_add(1))
+        .unwrap_or(0)
+}
+
+/// Check whether the pid file of a local daemon points to a process that no longer exists.
+///
+/// This happens if the daemon died without cleaning up after itself, in which case connecting
+/// to its leftover socket would fail or hang.
+/// If there's no (readable) pid file, e.g. because the daemon runs on another machine, nothing
+/// is checked.
+///
+/// This is only done for unix sockets. A daemon that's reached via TCP might run on another
+/// machine, in which case a pid file in a shared directory doesn't tell us anything.
+fn check_for_stale_daemon(shared: &Shared) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    let use_unix_socket = false;
+    #[cfg(not(target_os = "windows"))]
+    let use_unix_socket = shared.use_unix_socket;
+    if !use_unix_socket {
+        return Ok(());
+    }
+
+    let pid_path = shared.pid_path();
+    let Ok(content) = read_to_string(&pid_path) else {
+        return Ok(());
+    };
+    let Ok(pid) = content.trim().parse::<u32>() else {
+        return Ok(());
+    };
+
+    if !process_exists(pid) {
+        bail!(
+            "Daemon not running (stale pid file/socket). The daemon with pid {pid} is gone.\n\
+            Start a new daemon via `pueued` or remove the stale pid file: {pid_path:?}"
+        );
+    }
+
+    Ok(())
+}
+
+/// Save the query of a `status` call as a named view in the configuration file.
+///
+/// The configuration is read from disk again, so that values of a loaded profile don't end up
+/// in the persisted config.
+fn save_status_view(
+    settings: &mut Settings,
+    config_path: &Option<PathBuf>,
+    name: &str,
+    query: &[String],
+) -> Result<()> {
+    // There's no configuration file to write to, if the config has been piped in via stdin.
+    if config_path.as_deref() == Some(Path::new(CONFIG_FROM_STDIN)) {
+        bail!("The configuration has been read from stdin. Views can't be saved.");
+    }
+
+    let query = query.join(" ");
+    let (mut file_settings, _) =
+        Settings::read(config_path).context("Failed to read configuration.")?;
+    file_settings
+        .client
+        .views
+        .insert(name.to_string(), query.clone());
+    file_settings
+        .save(config_path)
+        .context("Failed to save configuration.")?;
+
+    settings.client.views.insert(name.to_string(), query);
+    Ok(())
+}
+
+/// Get the query of a previously saved status view.
+fn resolve_status_view(settings: &Settings, name: &str) -> Result<Vec<String>> {
+    let Some(query) = settings.client.views.get(name) else {
+        let mut names: Vec<&String> = settings.client.views.keys().collect();
+        names.sort();
+        if names.is_empty() {
+            bail!("There's no view \"{name}\". No views have been saved yet.");
+        }
+        let names: Vec<&str> = names.into_iter().map(String::as_str).collect();
+        bail!(
+            "There's no view \"{name}\". Available views: {}",
+            names.join(", ")
+        );
+    };
+
+    Ok(vec![query.clone()])
+}
+
 impl Client {
     /// Initialize a new client.
     /// This includes establishing a connection to the daemon:
     ///     - Connect to the daemon.
     ///     - Authorize via secret.
     ///     - Check versions incompatibilities.
-    pub async fn new(settings: Settings, opt: CliArguments) -> Result<Self> {
+    pub async fn new(mut settings: Settings, opt: CliArguments) -> Result<Self> {
+        // Don't try to connect to a daemon that's known to be dead.
+        check_for_stale_daemon(&settings.shared)?;
+
         // Connect to daemon and get stream used for communication.
         let mut stream = get_client_stream(&settings.shared)
             .await
@@ -76,25 +184,12 @@ impl Client {
 
         // Next we do a handshake with the daemon
         // 1. Client sends the secret to the daemon.
+        //    This is skipped for trusted unix sockets, which are protected by their permissions.
         // 2. If successful, the daemon responds with their version.
-        let secret = read_shared_secret(&settings.shared.shared_secret_path())?;
-        send_bytes(&secret, &mut stream)
+        let secret = read_handshake_secret(&settings.shared)?;
+        let version = handshake(&mut stream, secret.as_deref())
             .await
-            .context("Failed to send secret.")?;
-
-        // Receive and parse the response. We expect the daemon's version as UTF-8.
-        let version_bytes = receive_bytes(&mut stream)
-            .await
-            .context("Failed to receive version during handshake with daemon.")?;
-        if version_bytes.is_empty() {
-            bail!("Daemon went away after sending secret. Did you use the correct secret?")
-        }
-        let version = match String::from_utf8(version_bytes) {
-            Ok(version) => version,
-            Err(_) => {
-                bail!("Daemon sent invalid UTF-8. Did you use the correct secret?")
-            }
-        };
+            .context("Failed to do the handshake with the daemon.")?;
 
         // Info if the daemon runs a different version.
         // Backward compatibility should work, but some features might not work as expected.
@@ -102,7 +197,12 @@ impl Client {
             // Only show warnings if we aren't supposed to output json.
             let show_warning = if let Some(subcommand) = &opt.cmd {
                 match subcommand {
-                    SubCommand::Status { json, .. } => !json,
+                    SubCommand::Status {
+                        json,
+                        json_pretty,
+                        json_stream,
+                        ..
+                    } => !json && !json_pretty && !json_stream,
                     SubCommand::Log { json, .. } => !json,
                     SubCommand::Group { json, .. } => !json,
                     _ => true,
@@ -118,23 +218,60 @@ impl Client {
             }
         }
 
+        // A daemon of the same version supports the negotiation of capabilities, such as the
+        // compression of large messages. Other versions might not, so we don't offer them.
+        if version == crate_version!() {
+            stream = offer_capabilities(stream)
+                .await
+                .context("Failed to negotiate capabilities with daemon.")?
+                .context("Daemon didn't answer the capability negotiation.")?;
+        }
+
         // Determine whether we should color/style our output or not.
-        // The user can explicitly disable/enable this, otherwise we check whether we are on a TTY.
-        let style_enabled = match opt.color {
-            ColorChoice::Auto => stdout().is_tty(),
-            ColorChoice::Always => true,
-            ColorChoice::Never => false,
-        };
-        let style = OutputStyle::new(&settings, style_enabled);
+        // The user can explicitly disable/enable this, otherwise we respect `NO_COLOR` and check
+        // whether we are on a TTY.
+        let style = OutputStyle::from_color_choice(&settings, &opt.color_choice());
 
         // Determine the subcommand that has been called by the user.
         // If no subcommand is given, we default to the `status` subcommand without any arguments.
-        let subcommand = opt.cmd.unwrap_or(SubCommand::Status {
+        let config_path = opt.config.clone();
+        let mut subcommand = opt.cmd.unwrap_or(SubCommand::Status {
             json: false,
+            json_pretty: false,
+            raw_json: false,
+            json_stream: false,
             group: None,
+            groups: Vec::new(),
             query: Vec::new(),
+            sort: None,
+            scheduled: false,
+            tree: false,
+            total: false,
+            relative: false,
+            view: None,
+            save_view: None,
         });
 
+        // The `--relative` flag takes precedence over the configuration.
+        if let SubCommand::Status { relative: true, .. } = &subcommand {
+            settings.client.relative_times = true;
+        }
+
+        // Persist or recall a named status query.
+        if let SubCommand::Status {
+            query,
+            view,
+            save_view,
+            ..
+        } = &mut subcommand
+        {
+            if let Some(name) = save_view {
+                save_status_view(&mut settings, &config_path, name, query)?;
+            } else if let Some(name) = view {
+                *query = resolve_status_view(&settings, name)?;
+            }
+        }
+
         Ok(Client {
             settings,
             style,
@@ -175,6 +312,99 @@ impl Client {
     async fn handle_complex_command(&mut self) -> Result<bool> {
         // This match handles all "complex" commands.
         match &self.subcommand {
+            SubCommand::Add {
+                front,
+                group,
+                working_directory_from,
+                ..
+            } if *front || working_directory_from.is_some() => {
+                let front = *front;
+                let working_directory_from = *working_directory_from;
+                let group = group_or_default(group);
+
+                // The priority is relative to the tasks that're currently queued in the target
+                // group and the working directory has to be looked up from an existing task,
+                // which is why we need to fetch the state first.
+                let state = get_state(&mut self.stream).await?;
+                let front_priority = front.then(|| priority_in_front_of_group(&state, &group));
+                let path = match working_directory_from {
+                    Some(task_id) => match state.tasks.get(&task_id) {
+                        Some(task) => Some(task.path.clone()),
+                        None => bail!("Task {task_id} doesn't exist."),
+                    },
+                    None => None,
+                };
+
+                if let SubCommand::Add {
+                    priority,
+                    working_directory,
+                    ..
+                } = &mut self.subcommand
+                {
+                    if front_priority.is_some() {
+                        *priority = front_priority;
+                    }
+                    if path.is_some() {
+                        *working_directory = path;
+                    }
+                }
+
+                // The add message can now be handled as usual.
+                Ok(false)
+            }
+
+            SubCommand::Stash {
+                filter: Some(filter),
+                ..
+            }
+            | SubCommand::Enqueue {
+                filter: Some(filter),
+                ..
+            }
+            | SubCommand::Start {
+                filter: Some(filter),
+                ..
+            }
+            | SubCommand::Pause {
+                filter: Some(filter),
+                ..
+            }
+            | SubCommand::Kill {
+                filter: Some(filter),
+                ..
+            } => {
+                let filter = filter.clone();
+                let matching_ids = get_task_ids_for_query(&mut self.stream, &filter).await?;
+                if matching_ids.is_empty() {
+                    println!("No tasks match the filter \"{filter}\". Nothing to do.");
+                    return Ok(true);
+                }
+
+                // Replace the filter with the ids of the matching tasks.
+                if let SubCommand::Stash {
+                    task_ids, filter, ..
+                }
+                | SubCommand::Enqueue {
+                    task_ids, filter, ..
+                }
+                | SubCommand::Start {
+                    task_ids, filter, ..
+                }
+                | SubCommand::Pause {
+                    task_ids, filter, ..
+                }
+                | SubCommand::Kill {
+                    task_ids, filter, ..
+                } = &mut self.subcommand
+                {
+                    *task_ids = matching_ids.into_iter().map(|id| id..=id).collect();
+                    *filter = None;
+                }
+
+                // The message can now be handled as usual.
+                Ok(false)
+            }
+
             SubCommand::Reset { force, groups } => {
                 // Get the current state and check if there're any running tasks.
                 // If there are, ask the user if they really want to reset the state.
@@ -205,8 +435,18 @@ impl Client {
                 Ok(false)
             }
 
+            SubCommand::History {
+                group,
+                rerun: Some(index),
+                ..
+            } => {
+                let message = rerun_history(&mut self.stream, group.clone(), *index).await?;
+                self.handle_response(message)?;
+                Ok(true)
+            }
             SubCommand::Edit { task_ids } => {
-                let message = edit(&mut self.stream, &self.settings, task_ids).await?;
+                let task_ids = expand_task_ids(task_ids);
+                let message = edit(&mut self.stream, &self.settings, &task_ids).await?;
                 self.handle_response(message)?;
                 Ok(true)
             }
@@ -218,13 +458,22 @@ impl Client {
                 status,
             } => {
                 let selection = selection_from_params(*all, group, task_ids);
-                wait(&mut self.stream, &self.style, selection, *quiet, status).await?;
+                wait(
+                    &mut self.stream,
+                    &self.style,
+                    &self.settings,
+                    selection,
+                    *quiet,
+                    status,
+                )
+                .await?;
                 Ok(true)
             }
             SubCommand::Restart {
                 task_ids,
                 all_failed,
                 failed_in_group,
+                failed_only,
                 start_immediately,
                 stashed,
                 in_place,
@@ -234,12 +483,14 @@ impl Client {
                 // `not_in_place` superseeds both other configs
                 let in_place =
                     (self.settings.client.restart_in_place || *in_place) && !*not_in_place;
+                let task_ids = expand_task_ids(task_ids);
                 restart(
                     &mut self.stream,
                     &self.settings,
-                    task_ids.clone(),
+                    task_ids,
                     *all_failed,
                     failed_in_group.clone(),
+                    *failed_only,
                     *start_immediately,
                     *stashed,
                     in_place,
@@ -248,22 +499,21 @@ impl Client {
                 .await?;
                 Ok(true)
             }
-            SubCommand::Follow { task_id, lines } => {
-                // If we're supposed to read the log files from the local system, we don't have to
-                // do any communication with the daemon.
-                // Thereby we handle this in a separate function.
-                if self.settings.client.read_local_logs {
-                    local_follow(
-                        &mut self.stream,
-                        &self.settings.shared.pueue_directory(),
-                        task_id,
-                        *lines,
-                    )
-                    .await?;
-                    return Ok(true);
-                }
-                // Otherwise, we forward this to the `handle_simple_command` function.
-                Ok(false)
+            SubCommand::Follow {
+                task_id,
+                lines,
+                no_timestamps,
+            } => {
+                follow(
+                    &mut self.stream,
+                    &self.settings,
+                    task_id,
+                    *lines,
+                    *no_timestamps,
+                    &self.style,
+                )
+                .await?;
+                Ok(true)
             }
             SubCommand::FormatStatus { .. } => {
                 format_state(
@@ -280,11 +530,8 @@ impl Client {
     }
 
     /// Handle logic that's super generic on the client-side.
-    /// This (almost) always follows a singular ping-pong pattern.
+    /// This always follows a singular ping-pong pattern.
     /// One message to the daemon, one response, done.
-    ///
-    /// The only exception is streaming of log output.
-    /// In that case, we send one request and contine receiving until the stream shuts down.
     async fn handle_simple_command(&mut self) -> Result<()> {
         // Create the message that should be sent to the daemon
         // depending on the given commandline options.
@@ -294,30 +541,28 @@ impl Client {
         send_message(message, &mut self.stream).await?;
 
         // Check if we can receive the response from the daemon
-        let mut response = receive_message(&mut self.stream).await?;
-
-        // Handle the message.
-        // In some scenarios, such as log streaming, we should continue receiving messages
-        // from the daemon, which is why we have a while loop in place.
-        while self.handle_response(response)? {
-            response = receive_message(&mut self.stream).await?;
-        }
+        let response = receive_message(&mut self.stream).await?;
 
-        Ok(())
+        self.handle_response(response)
     }
 
     /// Most returned messages can be handled in a generic fashion.
-    /// However, some commands require to continuously receive messages (streaming).
-    ///
-    /// If this function returns `Ok(true)`, the parent function will continue to receive
-    /// and handle messages from the daemon. Otherwise the client will simply exit.
-    fn handle_response(&self, message: Message) -> Result<bool> {
+    fn handle_response(&self, message: Message) -> Result<()> {
         match message {
             Message::Success(text) => print_success(&self.style, &text),
             Message::Failure(text) => {
                 print_error(&self.style, &text);
                 std::process::exit(1);
             }
+            Message::GroupNotFound(message) => {
+                let text = format!(
+                    "Group \"{}\" doesn't exist. Use one of these: {:?}\n\
+                    Pass `--create-group` to create it.",
+                    message.group, message.existing_groups
+                );
+                print_error(&self.style, &text);
+                std::process::exit(1);
+            }
             Message::StatusResponse(state) => {
                 let tasks = state.tasks.values().cloned().collect();
                 let output =
@@ -325,22 +570,30 @@ impl Client {
                 println!("{output}");
             }
             Message::LogResponse(task_logs) => {
-                print_logs(task_logs, &self.subcommand, &self.style, &self.settings)
+                print_logs(task_logs, &self.subcommand, &self.style, &self.settings)?
             }
             Message::GroupResponse(groups) => {
                 let group_text = format_groups(groups, &self.subcommand, &self.style);
                 println!("{group_text}");
             }
-            Message::Stream(text) => {
-                print!("{text}");
-                io::stdout().flush().unwrap();
-                return Ok(true);
+            Message::HistoryResponse(entries) => {
+                let history =
+                    format_history(entries, &self.subcommand, &self.style, &self.settings);
+                println!("{history}");
+            }
+            Message::AddResponse(task) => {
+                let raw_json = matches!(self.subcommand, SubCommand::Add { raw_json: true, .. });
+                print_added_task(*task, raw_json)?
+            }
+            Message::BatchResponse(responses) => {
+                for response in responses {
+                    self.handle_response(response)?;
+                }
             }
-            Message::Close => return Ok(false),
             _ => error!("Received unhandled response message"),
         };
 
-        Ok(false)
+        Ok(())
     }
 
     /// Prints a warning and prompt for a given action and tasks.
@@ -389,6 +642,7 @@ impl Client {
             SubCommand::Add {
                 command,
                 working_directory,
+                create_workdir,
                 escape,
                 start_immediately,
                 stashed,
@@ -397,27 +651,50 @@ impl Client {
                 dependencies,
                 priority,
                 label,
+                user,
+                raw,
+                memory_limit,
+                expire_in,
+                timeout,
+                key,
+                replace,
+                on_success,
+                on_failure,
+                success_on_signal,
+                start_group,
+                create_group,
                 print_task_id,
+                output_json,
+                from_file,
+                ..
             } => {
                 // Either take the user-specified path or default to the current working directory.
-                let path = working_directory
-                    .as_ref()
-                    .map(|path| Ok(path.clone()))
-                    .unwrap_or_else(current_dir)?;
+                // Relative paths are resolved against the client's working directory, as the
+                // daemon runs in a different one.
+                let path = match working_directory {
+                    Some(path) if path.is_absolute() => path,
+                    Some(path) => current_dir()?.join(path),
+                    None => current_dir()?,
+                };
+
+                // Raw commands are executed as they are, their string representation is only
+                // used for display purposes.
+                let raw_command = raw.then(|| command.clone());
 
                 let mut command = command.clone();
                 // The user can request to escape any special shell characters in all parameter strings before
                 // we concatenated them to a single string.
-                if escape {
+                if escape || raw {
                     command = command
                         .iter()
                         .map(|parameter| shell_escape::escape(Cow::from(parameter)).into_owned())
                         .collect();
                 }
 
-                AddMessage {
+                let message = AddMessage {
                     command: command.join(" "),
                     path,
+                    create_workdir,
                     // Catch the current environment for later injection into the task's process.
                     envs: HashMap::from_iter(vars()),
                     start_immediately,
@@ -427,29 +704,65 @@ impl Client {
                     dependencies,
                     priority,
                     label,
+                    user,
+                    raw_command,
+                    memory_limit,
+                    expire_at: expire_in,
+                    timeout,
+                    start_group,
+                    key,
+                    replace,
+                    on_success,
+                    on_failure,
+                    success_signals: success_on_signal,
                     print_task_id,
+                    return_task: output_json,
+                    create_group,
+                };
+
+                // Add a task for each command of the file in a single batch.
+                match from_file {
+                    Some(path) => Message::Batch(
+                        read_command_file(&path)?
+                            .into_iter()
+                            .map(|command| {
+                                AddMessage {
+                                    command,
+                                    ..message.clone()
+                                }
+                                .into()
+                            })
+                            .collect(),
+                    ),
+                    None => message.into(),
                 }
-                .into()
             }
             SubCommand::Remove { task_ids } => {
+                let task_ids = expand_task_ids(&task_ids);
                 if self.settings.client.show_confirmation_questions {
                     self.handle_user_confirmation("remove", &task_ids)?;
                 }
-                Message::Remove(task_ids.clone())
+                Message::Remove(task_ids)
             }
             SubCommand::Stash {
                 task_ids,
                 group,
                 all,
                 delay_until,
+                stash_for,
+                ..
             } => {
                 let selection = selection_from_params(all, &group, &task_ids);
                 StashMessage {
                     tasks: selection,
-                    enqueue_at: delay_until,
+                    enqueue_at: delay_until.or(stash_for),
                 }
                 .into()
             }
+            SubCommand::Unschedule { task_ids } => UnscheduleMessage {
+                task_ids: expand_task_ids(&task_ids),
+            }
+            .into(),
             SubCommand::Switch {
                 task_id_1,
                 task_id_2,
@@ -463,11 +776,14 @@ impl Client {
                 group,
                 all,
                 delay_until,
+                start,
+                ..
             } => {
                 let selection = selection_from_params(all, &group, &task_ids);
                 EnqueueMessage {
                     tasks: selection,
                     enqueue_at: delay_until,
+                    start_immediately: start,
                 }
             }
             .into(),
@@ -485,30 +801,34 @@ impl Client {
                 group,
                 wait,
                 all,
+                restash,
                 ..
             } => PauseMessage {
                 tasks: selection_from_params(all, &group, &task_ids),
                 wait,
+                restash,
             }
             .into(),
             SubCommand::Kill {
                 task_ids,
                 group,
                 all,
+                all_groups,
                 signal,
                 ..
             } => {
                 if self.settings.client.show_confirmation_questions {
-                    self.handle_user_confirmation("kill", &task_ids)?;
+                    self.handle_user_confirmation("kill", &expand_task_ids(&task_ids))?;
                 }
                 KillMessage {
-                    tasks: selection_from_params(all, &group, &task_ids),
+                    tasks: selection_from_params(all || all_groups, &group, &task_ids),
                     signal,
+                    pause_groups: !all_groups,
                 }
                 .into()
             }
-            SubCommand::Send { task_id, input } => SendMessage {
-                task_id,
+            SubCommand::Send { task_ids, input } => SendMessage {
+                task_ids: expand_task_ids(&task_ids),
                 input: input.clone(),
             }
             .into(),
@@ -524,11 +844,27 @@ impl Client {
                 },
                 EnvCommand::Unset { task_id, key } => EnvMessage::Unset { task_id, key },
             }),
+            SubCommand::Annotate { task_id, note } => AnnotateMessage { task_id, note }.into(),
 
+            SubCommand::Group {
+                set_default_parallel: Some(parallel_tasks),
+                cmd,
+                ..
+            } => {
+                if cmd.is_some() {
+                    bail!("--set-default-parallel cannot be combined with a group subcommand.");
+                }
+                GroupMessage::SetDefaultParallel(parallel_tasks).into()
+            }
             SubCommand::Group { cmd, .. } => match cmd {
-                Some(GroupCommand::Add { name, parallel }) => GroupMessage::Add {
+                Some(GroupCommand::Add {
+                    name,
+                    parallel,
+                    description,
+                }) => GroupMessage::Add {
                     name: name.to_owned(),
                     parallel_tasks: parallel.to_owned(),
+                    description: description.to_owned(),
                 },
                 Some(GroupCommand::Remove { name }) => GroupMessage::Remove(name.to_owned()),
                 None => GroupMessage::List,
@@ -553,15 +889,16 @@ impl Client {
                 };
                 Message::Log(message)
             }
-            SubCommand::Follow { task_id, lines } => StreamRequestMessage { task_id, lines }.into(),
             SubCommand::Clean {
                 successful_only,
                 group,
+                ..
             } => CleanMessage {
                 successful_only,
                 group,
             }
             .into(),
+            SubCommand::History { group, .. } => HistoryRequestMessage { group }.into(),
             SubCommand::Reset { force, groups, .. } => {
                 if self.settings.client.show_confirmation_questions && !force {
                     self.handle_user_confirmation("reset", &Vec::new())?;
@@ -592,9 +929,12 @@ impl Client {
             },
             SubCommand::FormatStatus { .. } => bail!("FormatStatus has to be handled earlier"),
             SubCommand::Completions { .. } => bail!("Completions have to be handled earlier"),
+            SubCommand::Doctor => bail!("Doctor has to be handled earlier"),
+            SubCommand::Config { .. } => bail!("Config checks have to be handled earlier"),
             SubCommand::Restart { .. } => bail!("Restarts have to be handled earlier"),
             SubCommand::Edit { .. } => bail!("Edits have to be handled earlier"),
             SubCommand::Wait { .. } => bail!("Wait has to be handled earlier"),
+            SubCommand::Follow { .. } => bail!("Follow has to be handled earlier"),
         })
     }
 }
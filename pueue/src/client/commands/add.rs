@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use pueue_lib::task::Task;
+
+use crate::client::display::{format_json, JsonKind};
+
+/// Read the commands of a task list file that's passed via `pueue add --from-file`.
+///
+/// Each line is a separate command. Surrounding whitespace is trimmed.
+/// Blank lines and lines that start with `#` are ignored, so the file can be documented.
+/// Trailing comments aren't supported, as a `#` may very well be part of a command,
+/// e.g. `echo '#1'`.
+pub fn read_command_file(path: &Path) -> Result<Vec<String>> {
+    let content = read_to_string(path).context(format!("Failed to read command file {path:?}"))?;
+
+    let commands = parse_command_file(&content);
+    if commands.is_empty() {
+        bail!("The command file {path:?} doesn't contain any commands.");
+    }
+
+    Ok(commands)
+}
+
+/// Extract all commands from the content of a task list file.
+/// See [read_command_file] for the format.
+pub fn parse_command_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Print a task that has just been created via `pueue add --output-json`.
+/// `raw_json` prints the task without the json envelope.
+pub fn print_added_task(mut task: Task, raw_json: bool) -> Result<()> {
+    // The environment is just a copy of the user's current environment.
+    task.envs = HashMap::new();
+    println!("{}", format_json(JsonKind::Task, &task, raw_json, false));
+
+    Ok(())
+}
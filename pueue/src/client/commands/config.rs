@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+use std::io::{read_to_string, stdin};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use crossterm::style::Color;
+use serde_yaml::{Mapping, Value};
+
+use pueue_lib::network::protocol::{get_client_stream, handshake};
+use pueue_lib::network::secret::read_handshake_secret;
+use pueue_lib::settings::{
+    configuration_directories, expand_home, Settings, ShellCommand, CONFIG_FROM_STDIN,
+    PUEUE_CONFIG_PATH_ENV,
+};
+
+use super::get_state;
+use crate::client::cli::CliArguments;
+use crate::client::display::table_builder::is_column_name;
+use crate::client::display::OutputStyle;
+use crate::client::query::apply_query;
+
+/// A single problem that has been found in the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The dotted path of the offending setting, e.g. `daemon.callback`.
+    /// Empty for problems with the file as a whole.
+    pub key: String,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(key: impl ToString, message: impl ToString) -> Self {
+        Problem {
+            key: key.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Check a configuration file and print a report of all problems that have been found.
+///
+/// Just like `pueue doctor`, this has to work without a valid configuration, which is why
+/// the file is read and parsed by hand.
+///
+/// Returns `true` if no problems have been found.
+pub async fn check_config(opt: &CliArguments, config: &Option<PathBuf>) -> Result<bool> {
+    let style = OutputStyle::from_color_choice(&Settings::default(), &opt.color_choice());
+
+    let Some(path) = config_path(config.clone().or_else(|| opt.config.clone())) else {
+        print_problems(
+            &style,
+            "the configuration",
+            None,
+            &[Problem::new(
+                "",
+                "No configuration file could be found. Point to one via `--config`.",
+            )],
+        );
+        return Ok(false);
+    };
+
+    let content = if path == Path::new(CONFIG_FROM_STDIN) {
+        read_to_string(stdin()).context("Failed to read configuration from stdin")?
+    } else {
+        std::fs::read_to_string(&path).context(format!("Failed to read {path:?}"))?
+    };
+    let name = format!("{path:?}");
+
+    // Syntax errors and values of the wrong type prevent any further checks.
+    // The error of the yaml parser already contains the line and column.
+    let settings = match Settings::from_reader(content.as_bytes()) {
+        Ok(settings) => settings,
+        Err(err) => {
+            print_problems(&style, &name, None, &[Problem::new("", err)]);
+            return Ok(false);
+        }
+    };
+
+    let mut problems = unknown_keys(&content);
+    problems.extend(validate(&settings));
+
+    // Groups only exist in the daemon, which is why they can only be checked if it's running.
+    match get_groups(&settings).await {
+        Ok(groups) => problems.extend(validate_group_references(&settings, &groups)),
+        Err(_) => println!("The daemon isn't reachable. Skipping checks of group names."),
+    }
+
+    print_problems(&style, &name, Some(&content), &problems);
+
+    Ok(problems.is_empty())
+}
+
+/// Find the configuration file in the same way [Settings::read] does.
+fn config_path(from_file: Option<PathBuf>) -> Option<PathBuf> {
+    from_file
+        .or_else(|| std::env::var(PUEUE_CONFIG_PATH_ENV).map(PathBuf::from).ok())
+        .or_else(|| {
+            configuration_directories()
+                .into_iter()
+                .map(|directory| directory.join("pueue.yml"))
+                .find(|path| path.is_file())
+        })
+}
+
+/// Check the values of the given settings for problems that would only surface at runtime.
+pub fn validate(settings: &Settings) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    // Relative paths are resolved against the working directory, which differs between the
+    // daemon and clients.
+    let shared = &settings.shared;
+    let paths = [
+        ("pueue_directory", &shared.pueue_directory),
+        ("runtime_directory", &shared.runtime_directory),
+        ("alias_file", &shared.alias_file),
+        ("unix_socket_path", &shared.unix_socket_path),
+        ("pid_path", &shared.pid_path),
+        ("daemon_cert", &shared.daemon_cert),
+        ("daemon_key", &shared.daemon_key),
+        ("shared_secret_path", &shared.shared_secret_path),
+    ];
+    for (name, path) in paths {
+        let Some(path) = path else {
+            continue;
+        };
+        if expand_home(path).is_relative() {
+            problems.push(Problem::new(
+                format!("shared.{name}"),
+                format!("The path {path:?} has to be absolute."),
+            ));
+        }
+    }
+
+    if let Some(callback) = &settings.daemon.callback {
+        check_template(&mut problems, "daemon.callback", callback);
+    }
+
+    if let Some(shell_command) = &settings.daemon.shell_command {
+        check_shell_command(&mut problems, "daemon.shell_command", shell_command);
+    }
+    for (group, shell_command) in &settings.daemon.group_shell_commands {
+        let key = format!("daemon.group_shell_commands.{group}");
+        check_shell_command(&mut problems, &key, shell_command);
+    }
+
+    for (group, color) in &settings.client.group_colors {
+        if Color::try_from(color.as_str()).is_err() {
+            problems.push(Problem::new(
+                format!("client.group_colors.{group}"),
+                format!("Unknown color \"{color}\"."),
+            ));
+        }
+    }
+
+    for column in &settings.client.column_order {
+        if !is_column_name(column) {
+            problems.push(Problem::new(
+                "client.column_order",
+                format!("Unknown column \"{column}\"."),
+            ));
+        }
+    }
+
+    for (name, query) in &settings.client.views {
+        if let Err(err) = apply_query(query, None) {
+            problems.push(Problem::new(
+                format!("client.views.{name}"),
+                format!("Invalid query: {err}"),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Check that all group specific settings refer to one of the given groups.
+pub fn validate_group_references(settings: &Settings, groups: &[String]) -> Vec<Problem> {
+    let references = [
+        ("client.group_colors", keys(&settings.client.group_colors)),
+        (
+            "daemon.group_env_vars",
+            keys(&settings.daemon.group_env_vars),
+        ),
+        (
+            "daemon.group_shell_commands",
+            keys(&settings.daemon.group_shell_commands),
+        ),
+    ];
+
+    let mut problems = Vec::new();
+    for (setting, referenced) in references {
+        for group in referenced {
+            if !groups.contains(&group) {
+                problems.push(Problem::new(
+                    format!("{setting}.{group}"),
+                    format!("The group \"{group}\" doesn't exist."),
+                ));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Get the sorted keys of a map, so problems are reported in a stable order.
+fn keys<T>(map: &HashMap<String, T>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+/// Make sure that a template can be compiled by handlebars.
+fn check_template(problems: &mut Vec<Problem>, key: &str, template: &str) {
+    if let Err(err) = handlebars::Template::compile(template) {
+        problems.push(Problem::new(key, format!("Invalid template: {err}")));
+    }
+}
+
+/// Shell commands need to be valid templates and have to contain the task's command.
+fn check_shell_command(problems: &mut Vec<Problem>, key: &str, shell_command: &ShellCommand) {
+    let commands = match shell_command {
+        ShellCommand::All(command) => vec![(key.to_string(), command)],
+        ShellCommand::PerPlatform { unix, windows } => [("unix", unix), ("windows", windows)]
+            .into_iter()
+            .filter_map(|(platform, command)| {
+                command
+                    .as_ref()
+                    .map(|command| (format!("{key}.{platform}"), command))
+            })
+            .collect(),
+    };
+
+    for (key, command) in commands {
+        if command.is_empty() {
+            problems.push(Problem::new(&key, "The shell command mustn't be empty."));
+            continue;
+        }
+        for part in command {
+            check_template(problems, &key, part);
+        }
+        if !command
+            .iter()
+            .any(|part| part.contains("pueue_command_string"))
+        {
+            problems.push(Problem::new(
+                &key,
+                "The shell command doesn't contain the `{{ pueue_command_string }}` placeholder.",
+            ));
+        }
+    }
+}
+
+/// Find keys that aren't known to Pueue, which most likely are typos.
+/// Those would otherwise be silently ignored.
+fn unknown_keys(content: &str) -> Vec<Problem> {
+    let Ok(Value::Mapping(mut config)) = serde_yaml::from_str::<Value>(content) else {
+        return Vec::new();
+    };
+    let Ok(Value::Mapping(known)) = serde_yaml::to_value(Settings::default()) else {
+        return Vec::new();
+    };
+
+    let mut problems = Vec::new();
+    let profiles = config.remove("profiles");
+    unknown_section_keys(&mut problems, &known, "", &config);
+
+    // Each profile consists of the same sections as the main configuration.
+    if let Some(Value::Mapping(profiles)) = profiles {
+        for (profile, sections) in &profiles {
+            if let Value::Mapping(sections) = sections {
+                let prefix = format!("profiles.{}.", key_name(profile));
+                unknown_section_keys(&mut problems, &known, &prefix, sections);
+            }
+        }
+    }
+
+    problems
+}
+
+/// Check the keys of all sections, e.g. `client`, against those of the default configuration.
+fn unknown_section_keys(
+    problems: &mut Vec<Problem>,
+    known: &Mapping,
+    prefix: &str,
+    sections: &Mapping,
+) {
+    for (section, values) in sections {
+        let name = key_name(section);
+        let section = format!("{prefix}{name}");
+        let Some(Value::Mapping(known_keys)) = known.get(name.as_str()) else {
+            problems.push(Problem::new(&section, "Unknown key."));
+            continue;
+        };
+        let Value::Mapping(values) = values else {
+            continue;
+        };
+        for key in values.keys() {
+            let key = key_name(key);
+            if !known_keys.contains_key(key.as_str()) {
+                problems.push(Problem::new(format!("{section}.{key}"), "Unknown key."));
+            }
+        }
+    }
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(key) => key.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Find the line of a dotted key in the configuration file.
+///
+/// Each segment of the key is searched for after the line of the previous one.
+/// This is a best-effort heuristic, which is good enough to point users to the right place.
+fn find_line(content: &str, key: &str) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = 0;
+    for segment in key.split('.') {
+        let index = lines[start..].iter().position(|line| {
+            line.trim_start()
+                .trim_start_matches("- ")
+                .strip_prefix(segment)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })?;
+        start += index + 1;
+    }
+
+    Some(start)
+}
+
+/// Ask the daemon for the names of all existing groups.
+async fn get_groups(settings: &Settings) -> Result<Vec<String>> {
+    let mut stream = get_client_stream(&settings.shared).await?;
+    let secret = read_handshake_secret(&settings.shared)?;
+    handshake(&mut stream, secret.as_deref()).await?;
+    let state = get_state(&mut stream).await?;
+
+    Ok(state.groups.into_keys().collect())
+}
+
+fn print_problems(style: &OutputStyle, name: &str, content: Option<&str>, problems: &[Problem]) {
+    if problems.is_empty() {
+        println!("No problems found in {name}.");
+        return;
+    }
+
+    println!("Found {} problem(s) in {name}:", problems.len());
+    for problem in problems {
+        let marker = style.style_text("[error]", Some(Color::Red), None);
+        if problem.key.is_empty() {
+            println!("{marker} {}", problem.message);
+            continue;
+        }
+        let line = content
+            .and_then(|content| find_line(content, &problem.key))
+            .map(|line| format!(" (line {line})"))
+            .unwrap_or_default();
+        println!("{marker} {}{line}", problem.key);
+        println!("        {}", problem.message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Every broken value is reported with the key of its setting.
+    #[test]
+    fn validate_broken_settings() {
+        let mut settings = Settings::default();
+        settings.shared.pid_path = Some(PathBuf::from("relative/pueue.pid"));
+        settings.daemon.callback = Some("echo {{ id".to_string());
+        settings.daemon.shell_command = Some(vec!["bash".to_string(), "-c".to_string()].into());
+        settings.client.group_colors = HashMap::from([("build".to_string(), "blurple".into())]);
+        settings.client.column_order = vec!["id".to_string(), "nope".to_string()];
+        settings.client.views = HashMap::from([("broken".to_string(), "status=".to_string())]);
+        // The `on_complete_command` isn't a template, so braces are perfectly fine.
+        settings.client.on_complete_command = Some("awk '{{print $1}'".to_string());
+
+        let keys: Vec<String> = validate(&settings)
+            .into_iter()
+            .map(|problem| problem.key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "shared.pid_path",
+                "daemon.callback",
+                "daemon.shell_command",
+                "client.group_colors.build",
+                "client.column_order",
+                "client.views.broken",
+            ]
+        );
+    }
+
+    /// The default configuration doesn't have any problems.
+    #[test]
+    fn validate_default_settings() {
+        assert_eq!(validate(&Settings::default()), Vec::new());
+    }
+
+    /// Settings for groups that don't exist are reported.
+    #[test]
+    fn validate_missing_groups() {
+        let mut settings = Settings::default();
+        settings.daemon.group_env_vars = HashMap::from([
+            ("default".to_string(), HashMap::new()),
+            ("missing".to_string(), HashMap::new()),
+        ]);
+
+        let problems = validate_group_references(&settings, &["default".to_string()]);
+        assert_eq!(
+            problems,
+            vec![Problem::new(
+                "daemon.group_env_vars.missing",
+                "The group \"missing\" doesn't exist."
+            )]
+        );
+    }
+
+    /// Misspelled keys are reported, including those in profiles, and can be found in the file.
+    #[test]
+    fn unknown_keys_are_reported() {
+        let content = "client:\n  dark_mode: true\n  dark_mod: true\ndaemon:\n  callbak: ls\n\
+            profiles:\n  remote:\n    shared:\n      hots: example.org\nsharred: {}\n";
+
+        let keys: Vec<String> = unknown_keys(content)
+            .into_iter()
+            .map(|problem| problem.key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "client.dark_mod",
+                "daemon.callbak",
+                "sharred",
+                "profiles.remote.shared.hots",
+            ]
+        );
+
+        assert_eq!(find_line(content, "client.dark_mod"), Some(3));
+        assert_eq!(find_line(content, "profiles.remote.shared.hots"), Some(9));
+        assert_eq!(find_line(content, "client.missing"), None);
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::crate_version;
+use crossterm::style::Color;
+
+use pueue_lib::network::protocol::*;
+use pueue_lib::network::secret::{read_handshake_secret, read_shared_secret};
+use pueue_lib::process_helper::process_exists;
+use pueue_lib::settings::Settings;
+
+use crate::client::cli::CliArguments;
+use crate::client::display::OutputStyle;
+
+/// The result of a single check of `pueue doctor`.
+struct Check {
+    description: String,
+    /// `None` if the check passed, otherwise the reason for the failure.
+    failure: Option<String>,
+    /// An optional hint on how to fix a failed check.
+    hint: Option<String>,
+}
+
+impl Check {
+    fn ok(description: impl ToString) -> Self {
+        Check {
+            description: description.to_string(),
+            failure: None,
+            hint: None,
+        }
+    }
+
+    fn fail(description: impl ToString, failure: impl ToString, hint: impl ToString) -> Self {
+        Check {
+            description: description.to_string(),
+            failure: Some(failure.to_string()),
+            hint: Some(hint.to_string()),
+        }
+    }
+
+    fn from_result(description: impl ToString, result: Result<()>, hint: impl ToString) -> Self {
+        match result {
+            Ok(()) => Check::ok(description),
+            Err(err) => Check::fail(description, format!("{err:#}"), hint),
+        }
+    }
+}
+
+/// Run a set of diagnostic checks on the local Pueue setup and print the results as a checklist.
+///
+/// This is deliberately independent of [crate::client::client::Client], as the client
+/// bails on the first problem it encounters, while this command tries to report all of them.
+///
+/// Returns `true` if all checks passed.
+pub async fn doctor(opt: &CliArguments) -> Result<bool> {
+    let mut checks = Vec::new();
+
+    // Read the configuration. If no file exists, continue with the default settings, which are
+    // also used by the daemon in that case.
+    let mut settings = match Settings::read(&opt.config) {
+        Ok((settings, true)) => {
+            checks.push(Check::ok("Configuration file found"));
+            settings
+        }
+        Ok((settings, false)) => {
+            checks.push(Check::fail(
+                "Configuration file found",
+                "No configuration file could be found",
+                "The daemon creates a configuration file on its first start. \
+                Start it via `pueued` or point to an existing file via `--config`.",
+            ));
+            settings
+        }
+        Err(err) => {
+            // All other checks depend on the configuration, so there's nothing left to check.
+            checks.push(Check::fail(
+                "Configuration file is readable",
+                err,
+                "Make sure the configuration file is valid YAML. \
+                All other checks depend on the configuration and have been skipped.",
+            ));
+            // The default settings are only used to style the output.
+            let style = OutputStyle::from_color_choice(&Settings::default(), &opt.color_choice());
+            print_checks(&style, &checks);
+
+            return Ok(false);
+        }
+    };
+
+    if let Some(profile) = &opt.profile {
+        checks.push(Check::from_result(
+            format!("Profile '{profile}' exists"),
+            settings.load_profile(profile).map_err(Into::into),
+            "Check the `profiles` section of your configuration file.",
+        ));
+    }
+
+    let shared = &settings.shared;
+    checks.push(Check::from_result(
+        format!(
+            "Pueue directory is writable: {:?}",
+            shared.pueue_directory()
+        ),
+        check_writable_dir(&shared.pueue_directory()),
+        "The daemon creates this directory on startup. Check its permissions.",
+    ));
+    let log_directory = shared.pueue_directory().join("task_logs");
+    checks.push(Check::from_result(
+        format!("Log directory is writable: {log_directory:?}"),
+        check_writable_dir(&log_directory),
+        "The daemon creates this directory on startup. Check its permissions.",
+    ));
+    checks.push(Check::from_result(
+        "TLS certificates exist",
+        check_certificates(&settings),
+        "The daemon creates missing certificates on startup. \
+        Remove broken certificates and restart the daemon.",
+    ));
+    checks.push(Check::from_result(
+        format!(
+            "Shared secret is readable: {:?}",
+            shared.shared_secret_path()
+        ),
+        read_shared_secret(&shared.shared_secret_path())
+            .map(|_| ())
+            .map_err(Into::into),
+        "The daemon creates the secret on startup. Check the file's permissions.",
+    ));
+    checks.push(Check::from_result(
+        format!(
+            "Pid file matches a running process: {:?}",
+            shared.pid_path()
+        ),
+        check_pid_file(&shared.pid_path()),
+        "Start the daemon via `pueued`. If it crashed, remove the stale pid file.",
+    ));
+    checks.push(check_daemon(&settings).await);
+
+    // Determine whether we should color/style our output or not.
+    let style = OutputStyle::from_color_choice(&settings, &opt.color_choice());
+    print_checks(&style, &checks);
+
+    Ok(checks.iter().all(|check| check.failure.is_none()))
+}
+
+/// Ensure the directory exists and that we're allowed to create files in it.
+fn check_writable_dir(path: &Path) -> Result<()> {
+    if !path.is_dir() {
+        bail!("Directory doesn't exist");
+    }
+    tempfile::tempfile_in(path).context("Cannot create files in directory")?;
+
+    Ok(())
+}
+
+/// Check the TLS certificates, which are only needed for TCP connections.
+fn check_certificates(settings: &Settings) -> Result<()> {
+    for path in [settings.shared.daemon_cert(), settings.shared.daemon_key()] {
+        if !path.is_file() {
+            bail!("Missing file {path:?}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure that the pid file exists and that it points to an actual process.
+fn check_pid_file(path: &Path) -> Result<()> {
+    let content = read_to_string(path).context("Failed to read pid file")?;
+    let pid: u32 = content
+        .trim()
+        .parse()
+        .context(format!("Invalid pid in file: {content:?}"))?;
+
+    if !process_exists(pid) {
+        bail!("No process with pid {pid} exists. The pid file is stale.");
+    }
+
+    Ok(())
+}
+
+/// Try to connect to the daemon, do the handshake and compare the daemon's version.
+async fn check_daemon(settings: &Settings) -> Check {
+    let description = "Daemon is reachable";
+    let mut stream = match get_client_stream(&settings.shared).await {
+        Ok(stream) => stream,
+        Err(err) => {
+            let hint = "Start the daemon via `pueued` and make sure the client and daemon use \
+                the same socket or host and port.";
+            return Check::fail(description, err, hint);
+        }
+    };
+
+    let handshake_result = match read_handshake_secret(&settings.shared) {
+        Ok(secret) => handshake(&mut stream, secret.as_deref()).await,
+        Err(err) => Err(err),
+    };
+    let version = match handshake_result {
+        Ok(version) => version,
+        Err(err) => {
+            return Check::fail(
+                description,
+                err,
+                "Make sure the client and the daemon use the same shared secret.",
+            )
+        }
+    };
+
+    if version != crate_version!() {
+        return Check::fail(
+            "Daemon and client versions match",
+            format!(
+                "Client version {} differs from daemon version {version}",
+                crate_version!()
+            ),
+            "Restart the daemon after updating Pueue.",
+        );
+    }
+
+    Check::ok(format!("Daemon is reachable and runs version {version}"))
+}
+
+fn print_checks(style: &OutputStyle, checks: &[Check]) {
+    for check in checks {
+        match &check.failure {
+            None => {
+                let marker = style.style_text("[ok]", Some(Color::Green), None);
+                println!("{marker}   {}", check.description);
+            }
+            Some(failure) => {
+                let marker = style.style_text("[fail]", Some(Color::Red), None);
+                println!("{marker} {}", check.description);
+                println!("       {failure}");
+                if let Some(hint) = &check.hint {
+                    println!("       Hint: {hint}");
+                }
+            }
+        }
+    }
+}
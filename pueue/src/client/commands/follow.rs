@@ -1,24 +1,28 @@
-use std::path::Path;
-
 use anyhow::{bail, Result};
 
 use pueue_lib::network::protocol::GenericStream;
+use pueue_lib::settings::Settings;
 
-use crate::client::commands::get_state;
-use crate::client::display::follow_local_task_logs;
+use crate::client::commands::{get_state, get_task, run_on_complete_command};
+use crate::client::display::{follow_local_task_logs, follow_remote_task_logs, OutputStyle};
 
-/// This function reads a log file from the filesystem and streams it to `stdout`.
-/// This is the default behavior of `pueue`'s log reading logic, which is only possible
-/// if `pueued` runs on the same environment.
+/// Follow the output of a task until it finishes.
+///
+/// The log files are read directly, if `pueued` runs on the same environment and the client is
+/// configured to do so. Otherwise, the daemon streams the output to us.
 ///
 /// `pueue follow` can be called without a `task_id`, in which case we check whether there's a
 /// single running task. If that's the case, we default to it.
 /// If there are multiple tasks, the user has to specify which task they want to follow.
-pub async fn local_follow(
+///
+/// Once the followed task finishes, the `on_complete_command` is run for it.
+pub async fn follow(
     stream: &mut GenericStream,
-    pueue_directory: &Path,
+    settings: &Settings,
     task_id: &Option<usize>,
     lines: Option<usize>,
+    no_timestamps: bool,
+    style: &OutputStyle,
 ) -> Result<()> {
     let task_id = match task_id {
         Some(task_id) => *task_id,
@@ -51,7 +55,25 @@ pub async fn local_follow(
         }
     };
 
-    follow_local_task_logs(stream, pueue_directory, task_id, lines).await?;
+    if settings.client.read_local_logs {
+        let pueue_directory = settings.shared.pueue_directory();
+        follow_local_task_logs(
+            stream,
+            &pueue_directory,
+            task_id,
+            lines,
+            no_timestamps,
+            style,
+        )
+        .await?;
+    } else {
+        follow_remote_task_logs(stream, task_id, lines, no_timestamps, style).await?;
+    }
+
+    // The task might also have been removed in the meantime.
+    if let Some(task) = get_task(stream, task_id).await? {
+        run_on_complete_command(settings, &task).await;
+    }
 
     Ok(())
 }
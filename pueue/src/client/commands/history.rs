@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+use std::env::vars;
+
+use anyhow::{bail, Result};
+
+use pueue_lib::network::message::*;
+use pueue_lib::network::protocol::*;
+
+/// Add the command with the given index of the history as a new task.
+/// The new task is added to the same group and uses the same working directory as the original
+/// command, while the environment is taken from the current shell, just like `pueue add`.
+///
+/// Returns the daemon's response to the add request.
+pub async fn rerun_history(
+    stream: &mut GenericStream,
+    group: Option<String>,
+    index: usize,
+) -> Result<Message> {
+    send_message(HistoryRequestMessage { group }, stream).await?;
+    let entries = match receive_message(stream).await? {
+        Message::HistoryResponse(entries) => entries,
+        Message::Failure(text) => bail!(text),
+        _ => unreachable!(),
+    };
+
+    let Some(entry) = entries.into_iter().nth(index) else {
+        bail!("There's no entry with index {index} in the history.");
+    };
+
+    let message = AddMessage {
+        command: entry.command,
+        path: entry.path,
+        envs: HashMap::from_iter(vars()),
+        group: entry.group,
+        ..Default::default()
+    };
+    send_message(message, stream).await?;
+
+    Ok(receive_message(stream).await?)
+}
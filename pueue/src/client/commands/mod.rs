@@ -10,17 +10,29 @@ use pueue_lib::network::protocol::*;
 use pueue_lib::state::State;
 use pueue_lib::{network::message::Message, task::Task};
 
+use crate::client::query::apply_query;
+
+mod add;
+mod config;
+mod doctor;
 mod edit;
+mod follow;
 mod format_state;
-mod local_follow;
+mod history;
+mod on_complete;
 mod restart;
 mod wait;
 
+pub use add::{print_added_task, read_command_file};
+pub use config::check_config;
+pub use doctor::doctor;
 pub use edit::edit;
+pub use follow::follow;
 pub use format_state::format_state;
-pub use local_follow::local_follow;
+pub use history::rerun_history;
+pub use on_complete::run_on_complete_command;
 pub use restart::restart;
-pub use wait::{wait, WaitTargetStatus};
+pub use wait::wait;
 
 // This is a helper function for easy retrieval of the current daemon state.
 // The current daemon state is often needed in more complex commands.
@@ -52,3 +64,16 @@ pub async fn get_task(stream: &mut GenericStream, task_id: usize) -> Result<Opti
 
     Ok(state.tasks.get(&task_id).cloned())
 }
+
+/// Resolve a `pueue status`-style query, e.g. `label=batch-7`, to the ids of all matching tasks.
+pub async fn get_task_ids_for_query(stream: &mut GenericStream, query: &str) -> Result<Vec<usize>> {
+    let query_result = apply_query(query, None)?;
+    let state = get_state(stream).await?;
+
+    let mut tasks: Vec<Task> = state.tasks.into_values().collect();
+    tasks = query_result.apply_filters(tasks);
+    tasks = query_result.order_tasks(tasks);
+    tasks = query_result.limit_tasks(tasks);
+
+    Ok(tasks.iter().map(|task| task.id).collect())
+}
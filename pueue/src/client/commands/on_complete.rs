@@ -0,0 +1,48 @@
+use tokio::process::Command;
+
+use pueue_lib::process_helper::compile_shell_command;
+use pueue_lib::settings::Settings;
+use pueue_lib::task::{Task, TaskStatus};
+
+/// Run the user's `on_complete_command` for a task that finished while we were waiting on it.
+///
+/// The command is executed locally by the client, with information about the task exposed via
+/// environment variables. This is purely a convenience for the user, which is why any errors are
+/// only printed and never abort the current command.
+pub async fn run_on_complete_command(settings: &Settings, task: &Task) {
+    let Some(command) = &settings.client.on_complete_command else {
+        return;
+    };
+    let TaskStatus::Done { result, .. } = &task.status else {
+        return;
+    };
+
+    let exit_code = task
+        .exit_code()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "None".to_string());
+
+    let mut command = Command::from(compile_shell_command(settings, command));
+    command
+        .env("PUEUE_TASK_ID", task.id.to_string())
+        .env("PUEUE_TASK_GROUP", &task.group)
+        .env("PUEUE_TASK_COMMAND", &task.command)
+        .env("PUEUE_TASK_RESULT", result.to_string())
+        .env("PUEUE_TASK_EXIT_CODE", exit_code);
+
+    match command.status().await {
+        Ok(status) if !status.success() => {
+            eprintln!(
+                "The on_complete_command for task {} failed with {status}",
+                task.id
+            );
+        }
+        Ok(_) => (),
+        Err(err) => {
+            eprintln!(
+                "Failed to run the on_complete_command for task {}: {err}",
+                task.id
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use anyhow::{bail, Result};
 
 use chrono::Local;
@@ -5,7 +7,7 @@ use pueue_lib::network::message::*;
 use pueue_lib::network::protocol::*;
 use pueue_lib::settings::Settings;
 use pueue_lib::state::FilteredTasks;
-use pueue_lib::task::{Task, TaskResult, TaskStatus};
+use pueue_lib::task::{Task, TaskStatus};
 
 use crate::client::commands::get_state;
 
@@ -23,6 +25,7 @@ pub async fn restart(
     task_ids: Vec<usize>,
     all_failed: bool,
     failed_in_group: Option<String>,
+    failed_only: bool,
     start_immediately: bool,
     stashed: bool,
     in_place: bool,
@@ -59,16 +62,7 @@ pub async fn restart(
         let failed = filtered_tasks
             .matching_ids
             .into_iter()
-            .filter(|task_id| {
-                let task = state.tasks.get(task_id).unwrap();
-                !matches!(
-                    task.status,
-                    TaskStatus::Done {
-                        result: TaskResult::Success,
-                        ..
-                    }
-                )
-            })
+            .filter(|task_id| state.tasks.get(task_id).unwrap().failed())
             .collect();
 
         // We return an empty vec for the mismatching tasks, since there shouldn't be any.
@@ -79,6 +73,14 @@ pub async fn restart(
         }
     } else if task_ids.is_empty() {
         bail!("Please provide the ids of the tasks you want to restart.");
+    } else if failed_only {
+        // Only keep the failed tasks among the given ids.
+        // Successful tasks are skipped on purpose, which is why they aren't reported.
+        let mut filtered_tasks = state.filter_tasks(done_filter, Some(task_ids));
+        filtered_tasks
+            .matching_ids
+            .retain(|task_id| state.tasks.get(task_id).unwrap().failed());
+        filtered_tasks
     } else {
         state.filter_tasks(done_filter, Some(task_ids))
     };
@@ -98,18 +100,22 @@ pub async fn restart(
         .map(|task_id| state.tasks.get(task_id).unwrap().clone())
         .collect();
 
-    // If the tasks should be edited, edit them in one go.
+    // If the tasks should be edited, edit them all in one go.
     if edit {
-        let mut editable_tasks: Vec<EditableTask> = tasks.iter().map(EditableTask::from).collect();
-        editable_tasks = edit_tasks(settings, editable_tasks)?;
+        let editable_tasks: Vec<EditableTask> = tasks.iter().map(EditableTask::from).collect();
+        let mut edited_tasks: HashMap<usize, EditableTask> = edit_tasks(settings, editable_tasks)?
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
 
         // Now merge the edited properties back into the tasks.
-        // We simply zip the task and editable task vectors, as we know that they have the same
-        // order.
-        tasks
-            .iter_mut()
-            .zip(editable_tasks.into_iter())
-            .for_each(|(task, edited)| edited.into_task(task));
+        // The edited tasks are matched by their id, as the editing modes don't necessarily
+        // preserve the order of the tasks.
+        for task in tasks.iter_mut() {
+            if let Some(edited) = edited_tasks.remove(&task.id) {
+                edited.into_task(task);
+            }
+        }
     }
 
     // Go through all restartable commands we found and process them.
@@ -131,34 +137,17 @@ pub async fn restart(
         }
 
         // In case we don't do in-place restarts, we have to add a new task.
-        // Create a AddMessage to send the task to the daemon from the updated info and the old task.
-        let add_task_message = AddMessage {
-            command: task.command,
-            path: task.path,
-            envs: task.envs.clone(),
-            start_immediately,
-            stashed,
-            group: task.group.clone(),
-            enqueue_at: None,
-            dependencies: Vec::new(),
-            priority: Some(task.priority),
-            label: task.label,
-            print_task_id: false,
-        };
+        let add_task_message = clone_for_restart(task, start_immediately, stashed);
 
-        // Send the cloned task to the daemon and abort on any failure messages.
+        // Send the cloned task to the daemon and abort on anything but a success.
         send_message(add_task_message, stream).await?;
-        if let Message::Failure(message) = receive_message(stream).await? {
-            bail!(message);
-        };
+        ensure_success(receive_message(stream).await?)?;
     }
 
     // Send the singular in-place restart message to the daemon.
     if in_place {
         send_message(restart_message, stream).await?;
-        if let Message::Failure(message) = receive_message(stream).await? {
-            bail!(message);
-        };
+        ensure_success(receive_message(stream).await?)?;
     }
 
     if !filtered_tasks.matching_ids.is_empty() {
@@ -173,3 +162,51 @@ pub async fn restart(
 
     Ok(())
 }
+
+/// Bail on any response of the daemon that isn't a success, e.g. if a group doesn't exist.
+fn ensure_success(response: Message) -> Result<()> {
+    match response {
+        Message::Success(_) => Ok(()),
+        Message::Failure(message) => bail!(message),
+        Message::GroupNotFound(message) => bail!(
+            "Group \"{}\" doesn't exist. Use one of these: {:?}",
+            message.group,
+            message.existing_groups
+        ),
+        other => bail!("Received unexpected response from the daemon: {other:?}"),
+    }
+}
+
+/// Create a [AddMessage] from the updated info and the old task.
+/// The daemon adds it as a new task with a new id, the original task is left untouched.
+fn clone_for_restart(task: Task, start_immediately: bool, stashed: bool) -> AddMessage {
+    AddMessage {
+        command: task.command,
+        path: task.path,
+        create_workdir: task.create_workdir,
+        envs: task.envs,
+        start_immediately,
+        stashed,
+        group: task.group,
+        enqueue_at: None,
+        dependencies: Vec::new(),
+        priority: Some(task.priority),
+        label: task.label,
+        user: task.user,
+        raw_command: task.raw_command,
+        memory_limit: task.memory_limit,
+        // The expiry time refers to the original task and has most likely already passed.
+        expire_at: None,
+        timeout: task.timeout,
+        start_group: false,
+        key: None,
+        replace: false,
+        on_success: task.on_success,
+        on_failure: task.on_failure,
+        success_signals: task.success_signals,
+        print_task_id: false,
+        return_task: false,
+        // The group might have been removed in the meantime, which should be noticed.
+        create_group: false,
+    }
+}
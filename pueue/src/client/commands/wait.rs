@@ -6,27 +6,14 @@ use chrono::Local;
 use crossterm::style::{Attribute, Color};
 use pueue_lib::network::message::TaskSelection;
 use pueue_lib::state::State;
-use strum::{Display, EnumString};
 use tokio::time::sleep;
 
 use pueue_lib::network::protocol::GenericStream;
-use pueue_lib::task::{Task, TaskResult, TaskStatus};
+use pueue_lib::settings::Settings;
+use pueue_lib::task::{Task, TaskResult, TaskStatus, WaitTargetStatus};
 
-use crate::client::{commands::get_state, display::OutputStyle};
-
-/// The `wait` subcommand can wait for these specific stati.
-#[derive(Default, Debug, Clone, PartialEq, Display, EnumString)]
-pub enum WaitTargetStatus {
-    #[default]
-    #[strum(serialize = "done", serialize = "Done")]
-    Done,
-    #[strum(serialize = "success", serialize = "Success")]
-    Success,
-    #[strum(serialize = "queued", serialize = "Queued")]
-    Queued,
-    #[strum(serialize = "running", serialize = "Running")]
-    Running,
-}
+use crate::client::commands::{get_state, run_on_complete_command};
+use crate::client::display::OutputStyle;
 
 /// Wait until tasks are done.
 /// Tasks can be specified by:
@@ -37,9 +24,16 @@ pub enum WaitTargetStatus {
 ///
 /// By default, this will output status changes of tasks to `stdout`.
 /// Pass `quiet == true` to suppress any logging.
+///
+/// If a task finishes without ever reaching the target status, e.g. a task that's waited on to
+/// be `Running` fails due to its dependencies, the target status can no longer be reached.
+/// In that case, we exit with a `1`.
+///
+/// The `on_complete_command` is run for every task that finishes while we're waiting.
 pub async fn wait(
     stream: &mut GenericStream,
     style: &OutputStyle,
+    settings: &Settings,
     selection: TaskSelection,
     quiet: bool,
     target_status: &Option<WaitTargetStatus>,
@@ -93,6 +87,9 @@ pub async fn wait(
             if !quiet {
                 log_status_change(previous_status, task, style);
             }
+            if task.is_done() {
+                run_on_complete_command(settings, task).await;
+            }
         }
 
         // We can stop waiting, if every task reached its the target state.
@@ -106,14 +103,21 @@ pub async fn wait(
             };
 
             // Check if the task hit the target status.
-            if reached_target_status(task, &target_status) {
+            if target_status.is_reached_by(task) {
                 watched_tasks.remove(&task_id);
                 finished_tasks.insert(task_id);
+                continue;
             }
 
-            // If we're waiting for `Success`ful tasks, check if any of the tasks failed.
+            // Finished tasks won't change their status anymore, so the target status can no
+            // longer be reached. This also covers failed tasks when waiting for `Success`.
             // If so, exit with a `1`.
-            if target_status == WaitTargetStatus::Success && task.failed() {
+            if task.is_done() {
+                if !quiet {
+                    eprintln!(
+                        "Task {task_id} finished without reaching the target status {target_status}"
+                    );
+                }
                 std::process::exit(1);
             }
         }
@@ -135,35 +139,6 @@ pub async fn wait(
     Ok(())
 }
 
-/// Check if a task reached the target status.
-/// Other stati that can only occur after that status will also qualify.
-fn reached_target_status(task: &Task, target_status: &WaitTargetStatus) -> bool {
-    match target_status {
-        WaitTargetStatus::Queued => {
-            matches!(
-                task.status,
-                TaskStatus::Queued { .. } | TaskStatus::Running { .. } | TaskStatus::Done { .. }
-            )
-        }
-        WaitTargetStatus::Running => {
-            matches!(
-                task.status,
-                TaskStatus::Running { .. } | TaskStatus::Done { .. }
-            )
-        }
-        WaitTargetStatus::Done => matches!(task.status, TaskStatus::Done { .. }),
-        WaitTargetStatus::Success => {
-            matches!(
-                task.status,
-                TaskStatus::Done {
-                    result: TaskResult::Success,
-                    ..
-                }
-            )
-        }
-    }
-}
-
 /// Get the correct tasks depending on a given TaskSelection.
 fn get_tasks(state: &State, selection: &TaskSelection) -> Vec<Task> {
     match selection {
@@ -240,6 +215,10 @@ fn log_status_change(previous_status: TaskStatus, task: &Task, style: &OutputSty
                 let status = style.style_text("killed", Some(Color::Red), None);
                 format!("Task {task_id} has been {status}")
             }
+            TaskResult::Expired => {
+                let status = style.style_text("expired", Some(Color::Red), None);
+                format!("Task {task_id} {status} before it was started")
+            }
         };
         println!("{current_time} - {text}");
 
@@ -2,15 +2,19 @@ use std::io::{self, Write};
 use std::path::Path;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use tokio::time::sleep;
 
 use pueue_lib::{
-    log::{get_log_file_handle, get_log_path, seek_to_last_lines},
-    network::protocol::GenericStream,
+    log::{get_log_file_handle, get_log_path, seek_to_last_lines, TimestampStripper},
+    network::{
+        message::{Message, StreamRequestMessage},
+        protocol::{receive_message, send_message, GenericStream},
+    },
 };
 
 use crate::client::commands::get_task;
+use crate::client::display::{print_error, print_success, OutputStyle};
 
 /// Follow the log output of running task.
 ///
@@ -24,6 +28,8 @@ pub async fn follow_local_task_logs(
     pueue_directory: &Path,
     task_id: usize,
     lines: Option<usize>,
+    no_timestamps: bool,
+    style: &OutputStyle,
 ) -> Result<()> {
     // It might be that the task is not yet running.
     // Ensure that it exists and is started.
@@ -50,7 +56,7 @@ pub async fn follow_local_task_logs(
 
     // Stdout handle to directly stream log file output to `io::stdout`.
     // This prevents us from allocating any large amounts of memory.
-    let mut stdout = io::stdout();
+    let mut stdout = TimestampStripper::new(style.task_output_writer(io::stdout()), no_timestamps);
 
     // If `lines` is passed as an option, we only want to show the last `X` lines.
     // To achieve this, we seek the file handle to the start of the `Xth` line
@@ -108,3 +114,48 @@ pub async fn follow_local_task_logs(
         sleep(timeout).await;
     }
 }
+
+/// Follow the log output of a task by letting the daemon stream it to us.
+/// This is used if the client cannot read the daemon's log files directly.
+pub async fn follow_remote_task_logs(
+    stream: &mut GenericStream,
+    task_id: usize,
+    lines: Option<usize>,
+    no_timestamps: bool,
+    style: &OutputStyle,
+) -> Result<()> {
+    let message = StreamRequestMessage {
+        task_id: Some(task_id),
+        lines,
+        offset: None,
+    };
+    send_message(message, stream).await?;
+
+    // The same writer is used for all chunks, as a timestamp might be split between two of them.
+    let mut stdout = TimestampStripper::new(style.task_output_writer(io::stdout()), no_timestamps);
+
+    // The daemon explicitly ends the stream via a `Close` message, so a connection that breaks
+    // in the meantime is an error.
+    loop {
+        let message = receive_message(stream)
+            .await
+            .context("The connection to the daemon broke before the stream ended.")?;
+
+        match message {
+            Message::Stream(chunk) => {
+                stdout.write_all(chunk.text.as_bytes())?;
+                stdout.flush()?;
+            }
+            Message::Close => return Ok(()),
+            Message::Success(text) => {
+                print_success(style, &text);
+                return Ok(());
+            }
+            Message::Failure(text) => {
+                print_error(style, &text);
+                std::process::exit(1);
+            }
+            _ => bail!("Received unexpected message while following the task's output."),
+        }
+    }
+}
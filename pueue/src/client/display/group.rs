@@ -1,12 +1,16 @@
+use std::collections::BTreeMap;
+
 use crossterm::style::{Attribute, Color};
+use serde::Serialize;
 
 use pueue_lib::{
-    network::message::GroupResponseMessage,
+    network::message::{GroupResponseMessage, GroupStats},
     state::{Group, GroupStatus},
 };
 
 use crate::client::cli::SubCommand;
 
+use super::json::{format_json, JsonKind};
 use super::OutputStyle;
 
 /// Print some info about the daemon's current groups.
@@ -17,20 +21,28 @@ pub fn format_groups(
     style: &OutputStyle,
 ) -> String {
     // Get commandline options to check whether we should return the groups as json.
-    let json = match cli_command {
-        SubCommand::Group { json, .. } => *json,
+    let (json, raw_json) = match cli_command {
+        SubCommand::Group { json, raw_json, .. } => (*json, *raw_json),
         // If `parallel` is called without an argument, the group info is shown.
         SubCommand::Parallel {
             parallel_tasks: None,
             group: None,
-        } => false,
+        } => (false, false),
         _ => {
             panic!("Got wrong Subcommand {cli_command:?} in format_groups. This shouldn't happen.")
         }
     };
 
     if json {
-        return serde_json::to_string(&message.groups).unwrap();
+        let groups: BTreeMap<&String, GroupWithStats> = message
+            .groups
+            .iter()
+            .map(|(name, group)| {
+                let stats = message.stats.get(name);
+                (name, GroupWithStats { group, stats })
+            })
+            .collect();
+        return format_json(JsonKind::Group, &groups, raw_json, false);
     }
 
     let mut text = String::new();
@@ -39,6 +51,14 @@ pub fn format_groups(
         let styled = get_group_headline(name, group, style);
 
         text.push_str(&styled);
+        // Only show the throughput of groups that actually did something recently.
+        if let Some(stats) = message
+            .stats
+            .get(name)
+            .filter(|stats| stats.finished_tasks > 0)
+        {
+            text.push_str(&format_group_stats(stats));
+        }
         if group_iter.peek().is_some() {
             text.push('\n');
         }
@@ -47,11 +67,37 @@ pub fn format_groups(
     text
 }
 
+/// The json representation of a group, which includes the group's throughput.
+#[derive(Serialize)]
+struct GroupWithStats<'a> {
+    #[serde(flatten)]
+    group: &'a Group,
+    stats: Option<&'a GroupStats>,
+}
+
+/// Format the throughput of a group, e.g. ` (3 finished in the last 3600s, avg. runtime 1.5s)`.
+fn format_group_stats(stats: &GroupStats) -> String {
+    let mut text = format!(
+        " ({} finished in the last {}s",
+        stats.finished_tasks, stats.window
+    );
+    if let Some(average_runtime) = stats.average_runtime {
+        text.push_str(&format!(
+            ", avg. runtime {:.1}s",
+            average_runtime as f64 / 1000.0
+        ));
+    }
+    text.push(')');
+
+    text
+}
+
 /// Return some nicely formatted info about a given group.
 /// This is also used as a headline that's displayed above group's task tables.
 pub fn get_group_headline(name: &str, group: &Group, style: &OutputStyle) -> String {
-    // Style group name
-    let name = style.style_text(format!("Group \"{name}\""), None, Some(Attribute::Bold));
+    // Style group name. Use the group's color, if the user configured one.
+    let color = style.group_colors.get(name).copied();
+    let name = style.style_text(format!("Group \"{name}\""), color, Some(Attribute::Bold));
 
     // Print the current state of the group.
     let status = match group.status {
@@ -60,5 +106,10 @@ pub fn get_group_headline(name: &str, group: &Group, style: &OutputStyle) -> Str
         GroupStatus::Reset => style.style_text("resetting", Some(Color::Red), None),
     };
 
-    format!("{} ({} parallel): {}", name, group.parallel_tasks, status)
+    let mut headline = format!("{} ({} parallel): {}", name, group.parallel_tasks, status);
+    if let Some(description) = &group.description {
+        headline.push_str(&format!(" - {description}"));
+    }
+
+    headline
 }
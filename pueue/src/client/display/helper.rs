@@ -1,8 +1,10 @@
 use std::collections::BTreeMap;
 
 use chrono::{DateTime, Local, LocalResult};
+use crossterm::style::Color;
 
-use pueue_lib::{settings::Settings, task::Task};
+use pueue_lib::settings::Settings;
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 /// Try to get the start of the current date to the best of our abilities.
 /// Throw an error, if we can't.
@@ -37,11 +39,37 @@ pub fn sort_tasks_by_group(tasks: Vec<Task>) -> BTreeMap<String, Vec<Task>> {
     sorted_task_groups
 }
 
+/// Returns the human readable representation of a task's status and the color it's displayed in.
+pub fn status_text_and_color(task: &Task) -> (String, Color) {
+    let status_string = task.status.to_string();
+    match &task.status {
+        TaskStatus::Running { .. } => (status_string, Color::Green),
+        TaskStatus::Paused { .. } => (status_string, Color::White),
+        // Locked tasks are currently being edited by a user.
+        TaskStatus::Locked { .. } => ("Locked (editing)".to_string(), Color::Cyan),
+        TaskStatus::Done { result, .. } => result_text_and_color(result),
+        _ => (status_string, Color::Yellow),
+    }
+}
+
+/// Returns the human readable representation of a task's result and the color it's displayed in.
+pub fn result_text_and_color(result: &TaskResult) -> (String, Color) {
+    match result {
+        TaskResult::Success => (TaskResult::Success.to_string(), Color::Green),
+        TaskResult::DependencyFailed => ("Dependency failed".to_string(), Color::Red),
+        TaskResult::FailedToSpawn(_) => ("Failed to spawn".to_string(), Color::Red),
+        TaskResult::Failed(code) => (format!("Failed ({code})"), Color::Red),
+        _ => (result.to_string(), Color::Red),
+    }
+}
+
 /// Returns the formatted `start` and `end` text for a given task.
 ///
 /// 1. If the start || end is today, skip the date.
 /// 2. Otherwise show the date in both.
 ///
+/// If `client.relative_times` is enabled, both are shown relative to now instead.
+///
 /// If the task doesn't have a start and/or end yet, an empty string will be returned
 /// for the respective field.
 pub fn formatted_start_end(task: &Task, settings: &Settings) -> (String, String) {
@@ -53,18 +81,18 @@ pub fn formatted_start_end(task: &Task, settings: &Settings) -> (String, String)
         None => return ("".into(), "".into()),
     };
 
+    if settings.client.relative_times {
+        let now = Local::now();
+        let end = end
+            .map(|end| formatted_relative_time(end, now))
+            .unwrap_or_default();
+        return (formatted_relative_time(start, now), end);
+    }
+
     // If the task started today, just show the time.
     // Otherwise show the full date and time.
     let started_today = start >= start_of_today();
-    let formatted_start = if started_today {
-        start
-            .format(&settings.client.status_time_format)
-            .to_string()
-    } else {
-        start
-            .format(&settings.client.status_datetime_format)
-            .to_string()
-    };
+    let formatted_start = settings.client.format_timestamp(&start, !started_today);
 
     // If the task didn't finish yet, only return the formatted start.
     let end = match end {
@@ -75,12 +103,58 @@ pub fn formatted_start_end(task: &Task, settings: &Settings) -> (String, String)
     // If the task ended today we only show the time.
     // In all other circumstances, we show the full date.
     let finished_today = end >= start_of_today();
-    let formatted_end = if finished_today {
-        end.format(&settings.client.status_time_format).to_string()
+    let formatted_end = settings.client.format_timestamp(&end, !finished_today);
+
+    (formatted_start, formatted_end)
+}
+
+/// Returns a human readable representation of a point in time relative to `now`, using the
+/// largest fitting unit, e.g. `5m ago` or `in 2h`.
+pub fn formatted_relative_time(time: DateTime<Local>, now: DateTime<Local>) -> String {
+    let seconds = (time - now).num_seconds();
+
+    let absolute = seconds.abs();
+    let duration = if absolute >= 86400 {
+        format!("{}d", absolute / 86400)
+    } else if absolute >= 3600 {
+        format!("{}h", absolute / 3600)
+    } else if absolute >= 60 {
+        format!("{}m", absolute / 60)
     } else {
-        end.format(&settings.client.status_datetime_format)
-            .to_string()
+        format!("{absolute}s")
     };
 
-    (formatted_start, formatted_end)
+    if seconds > 0 {
+        format!("in {duration}")
+    } else {
+        format!("{duration} ago")
+    }
+}
+
+/// Returns a human readable representation of the time left until the given point in time,
+/// e.g. `1h 5m 30s`. Points in time that already passed are shown as `now`.
+pub fn formatted_time_left(until: DateTime<Local>) -> String {
+    let seconds_left = (until - Local::now()).num_seconds();
+    if seconds_left <= 0 {
+        return "now".into();
+    }
+
+    let days = seconds_left / 86400;
+    let hours = seconds_left % 86400 / 3600;
+    let minutes = seconds_left % 3600 / 60;
+    let seconds = seconds_left % 60;
+
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{days}d"));
+    }
+    if days > 0 || hours > 0 {
+        parts.push(format!("{hours}h"));
+    }
+    if days > 0 || hours > 0 || minutes > 0 {
+        parts.push(format!("{minutes}m"));
+    }
+    parts.push(format!("{seconds}s"));
+
+    parts.join(" ")
 }
@@ -0,0 +1,45 @@
+use pueue_lib::network::message::HistoryEntry;
+use pueue_lib::settings::Settings;
+
+use crate::client::cli::SubCommand;
+
+use super::helper::result_text_and_color;
+use super::json::{format_json, JsonKind};
+use super::OutputStyle;
+
+/// Print the history of finished commands, one entry per line.
+/// The index in front of each entry can be passed to `pueue history --rerun`.
+pub fn format_history(
+    entries: Vec<HistoryEntry>,
+    cli_command: &SubCommand,
+    style: &OutputStyle,
+    settings: &Settings,
+) -> String {
+    let (json, raw_json) = match cli_command {
+        SubCommand::History { json, raw_json, .. } => (*json, *raw_json),
+        _ => {
+            panic!("Got wrong Subcommand {cli_command:?} in format_history. This shouldn't happen.")
+        }
+    };
+
+    if json {
+        return format_json(JsonKind::History, &entries, raw_json, false);
+    }
+
+    if entries.is_empty() {
+        return "The history is empty.".to_string();
+    }
+
+    let index_width = (entries.len() - 1).to_string().len();
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let end = settings.client.format_timestamp(&entry.end, true);
+            let (result, color) = result_text_and_color(&entry.result);
+            let result = style.style_text(result, Some(color), None);
+            format!("{index:>index_width$} {end} [{result}] {}", entry.command)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,49 @@
+//! All json output of the client is wrapped in a versioned envelope, e.g.
+//! `{"schema_version": 1, "kind": "status", "data": {...}}`.
+//! This allows consumers to detect the kind and version of the data before parsing it.
+use serde::Serialize;
+
+/// The version of the envelope's schema.
+/// This has to be bumped whenever the wrapped data changes in an incompatible way.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// The kind of data that's wrapped in the envelope.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonKind {
+    Status,
+    Log,
+    Group,
+    History,
+    Task,
+}
+
+#[derive(Serialize)]
+struct JsonEnvelope<'a, T: Serialize> {
+    schema_version: u32,
+    kind: JsonKind,
+    data: &'a T,
+}
+
+/// Serialize some data to json.
+/// The data is wrapped in the envelope, unless the raw data is requested via `--raw-json`.
+pub fn format_json<T: Serialize>(kind: JsonKind, data: &T, raw: bool, pretty: bool) -> String {
+    let serialized = match (raw, pretty) {
+        (true, false) => serde_json::to_string(data),
+        (true, true) => serde_json::to_string_pretty(data),
+        (false, pretty) => {
+            let envelope = JsonEnvelope {
+                schema_version: JSON_SCHEMA_VERSION,
+                kind,
+                data,
+            };
+            if pretty {
+                serde_json::to_string_pretty(&envelope)
+            } else {
+                serde_json::to_string(&envelope)
+            }
+        }
+    };
+
+    serialized.unwrap()
+}
@@ -0,0 +1,40 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use snap::read::FrameDecoder;
+
+use pueue_lib::log::{get_log_file_handle, seek_to_last_lines};
+use pueue_lib::network::message::TaskLogMessage;
+use pueue_lib::settings::Settings;
+
+/// Write the raw log output of a single task to the given file.
+///
+/// The output is written as is, i.e. without any task information or styling.
+pub fn write_log_to_file(
+    task_log: &TaskLogMessage,
+    path: &Path,
+    settings: &Settings,
+    lines: Option<usize>,
+) -> Result<()> {
+    let mut destination =
+        File::create(path).context(format!("Failed to create log file at {path:?}"))?;
+
+    if settings.client.read_local_logs {
+        let mut file = get_log_file_handle(task_log.task.id, &settings.shared.pueue_directory())
+            .context("Failed to get log file handle")?;
+        if let Some(lines) = lines {
+            seek_to_last_lines(&mut file, lines).context("Failed reading local log file")?;
+        }
+        io::copy(&mut file, &mut destination).context("Failed to write log file")?;
+    } else if let Some(bytes) = task_log.output.as_ref() {
+        // Remote logs are sent compressed by the daemon.
+        let mut decompressor = FrameDecoder::new(&bytes[..]);
+        io::copy(&mut decompressor, &mut destination).context("Failed to write log file")?;
+    } else {
+        bail!("Logs requested from pueue daemon, but none received. Please report this bug.");
+    }
+
+    Ok(())
+}
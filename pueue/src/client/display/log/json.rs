@@ -9,6 +9,8 @@ use pueue_lib::network::message::TaskLogMessage;
 use pueue_lib::settings::Settings;
 use pueue_lib::task::Task;
 
+use crate::client::display::json::{format_json, JsonKind};
+
 /// This is the output struct used for
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TaskLog {
@@ -20,6 +22,7 @@ pub fn print_log_json(
     task_log_messages: BTreeMap<usize, TaskLogMessage>,
     settings: &Settings,
     lines: Option<usize>,
+    raw_json: bool,
 ) {
     let mut tasks: BTreeMap<usize, Task> = BTreeMap::new();
     let mut task_log: BTreeMap<usize, String> = BTreeMap::new();
@@ -47,7 +50,7 @@ pub fn print_log_json(
         json.insert(id, TaskLog { task, output });
     }
 
-    println!("{}", serde_json::to_string(&json).unwrap());
+    println!("{}", format_json(JsonKind::Log, &json, raw_json, false));
 }
 
 /// Read logs directly from local files for a specific task.
@@ -3,10 +3,10 @@ use std::io::{self, Stdout};
 
 use crossterm::style::{Attribute, Color};
 
-use pueue_lib::log::{get_log_file_handle, seek_to_last_lines};
+use pueue_lib::log::{get_log_file_handle, seek_to_last_lines, TimestampStripper};
 use pueue_lib::settings::Settings;
 
-use crate::client::display::OutputStyle;
+use crate::client::display::{OutputStyle, TaskOutputWriter};
 
 /// The daemon didn't send any log output, thereby we didn't request any.
 /// If that's the case, read the log file from the local pueue directory.
@@ -15,6 +15,7 @@ pub fn print_local_log(
     style: &OutputStyle,
     settings: &Settings,
     lines: Option<usize>,
+    no_timestamps: bool,
 ) {
     let mut file = match get_log_file_handle(task_id, &settings.shared.pueue_directory()) {
         Ok(file) => file,
@@ -25,7 +26,7 @@ pub fn print_local_log(
     };
     // Stdout handler to directly write log file output to io::stdout
     // without having to load anything into memory.
-    let mut stdout = io::stdout();
+    let mut stdout = TimestampStripper::new(style.task_output_writer(io::stdout()), no_timestamps);
 
     print_local_file(
         &mut stdout,
@@ -36,7 +37,12 @@ pub fn print_local_log(
 }
 
 /// Print a local log file of a task.
-fn print_local_file(stdout: &mut Stdout, file: &mut File, lines: &Option<usize>, header: String) {
+fn print_local_file(
+    stdout: &mut TimestampStripper<TaskOutputWriter<Stdout>>,
+    file: &mut File,
+    lines: &Option<usize>,
+    header: String,
+) {
     if let Ok(metadata) = file.metadata() {
         if metadata.len() != 0 {
             // Indicates whether the full log output is shown or just the last part of it.
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use anyhow::{bail, Result};
 use comfy_table::{Attribute as ComfyAttribute, Cell, CellAlignment, Table};
 use crossterm::style::Color;
 
@@ -11,10 +12,12 @@ use super::OutputStyle;
 use crate::client::cli::SubCommand;
 use crate::client::client::selection_from_params;
 
+mod file;
 mod json;
 mod local;
 mod remote;
 
+use file::*;
 use json::*;
 use local::*;
 use remote::*;
@@ -47,16 +50,19 @@ pub fn print_logs(
     cli_command: &SubCommand,
     style: &OutputStyle,
     settings: &Settings,
-) {
+) -> Result<()> {
     // Get actual commandline options.
     // This is necessary to know how we should display/return the log information.
     let SubCommand::Log {
         json,
+        raw_json,
         task_ids,
         group,
         lines,
         full,
         all,
+        out_file,
+        no_timestamps,
     } = cli_command
     else {
         panic!("Got wrong Subcommand {cli_command:?} in print_log. This shouldn't happen");
@@ -66,8 +72,19 @@ pub fn print_logs(
 
     // Return the server response in json representation.
     if *json {
-        print_log_json(task_logs, settings, lines);
-        return;
+        print_log_json(task_logs, settings, lines, *raw_json);
+        return Ok(());
+    }
+
+    // Write the output of a single task to a file.
+    if let Some(path) = out_file {
+        let Some((_, task_log)) = task_logs.first_key_value() else {
+            bail!("There's no task for your specified id");
+        };
+        if task_logs.len() > 1 {
+            bail!("The log can only be written to a file for a single task");
+        }
+        return write_log_to_file(task_log, path, settings, lines);
     }
 
     let selection = selection_from_params(*all, group, task_ids);
@@ -75,15 +92,15 @@ pub fn print_logs(
         match selection {
             TaskSelection::TaskIds(_) => {
                 println!("There are no finished tasks for your specified ids");
-                return;
+                return Ok(());
             }
             TaskSelection::Group(group) => {
                 println!("There are no finished tasks for group '{group}'");
-                return;
+                return Ok(());
             }
             TaskSelection::All => {
                 println!("There are no finished tasks");
-                return;
+                return Ok(());
             }
         }
     }
@@ -91,18 +108,17 @@ pub fn print_logs(
     // Iterate over each task and print the respective log.
     let mut task_iter = task_logs.iter_mut().peekable();
     while let Some((_, task_log)) = task_iter.next() {
-        print_log(task_log, style, settings, lines);
+        print_log(task_log, style, settings, lines, *no_timestamps);
 
         // Add a newline if there is another task that's going to be printed.
         if let Some((_, task_log)) = task_iter.peek() {
-            if matches!(
-                &task_log.task.status,
-                TaskStatus::Done { .. } | TaskStatus::Running { .. } | TaskStatus::Paused { .. }
-            ) {
+            if task_log.task.is_terminal() || task_log.task.is_active() {
                 println!();
             }
         }
     }
+
+    Ok(())
 }
 
 /// Print the log of a single task.
@@ -112,27 +128,26 @@ pub fn print_logs(
 /// lines: Whether we should reduce the log output of each task to a specific number of lines.
 ///         `None` implicates that everything should be printed.
 ///         This is only important, if we read local lines.
+/// no_timestamps: Whether the timestamps at the start of each line should be removed.
 fn print_log(
     message: &mut TaskLogMessage,
     style: &OutputStyle,
     settings: &Settings,
     lines: Option<usize>,
+    no_timestamps: bool,
 ) {
     let task = &message.task;
     // We only show logs of finished or running tasks.
-    if !matches!(
-        task.status,
-        TaskStatus::Done { .. } | TaskStatus::Running { .. } | TaskStatus::Paused { .. }
-    ) {
+    if task.is_waiting() {
         return;
     }
 
     print_task_info(task, style);
 
     if settings.client.read_local_logs {
-        print_local_log(message.task.id, style, settings, lines);
+        print_local_log(message.task.id, style, settings, lines, no_timestamps);
     } else if message.output.is_some() {
-        print_remote_log(message, style, lines);
+        print_remote_log(message, style, lines, no_timestamps);
     } else {
         println!("Logs requested from pueue daemon, but none received. Please report this bug.");
     }
@@ -150,6 +165,7 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
     let (exit_status, color) = match &task.status {
         TaskStatus::Paused { .. } => ("paused".into(), Color::White),
         TaskStatus::Running { .. } => ("running".into(), Color::Yellow),
+        TaskStatus::Locked { .. } => ("locked while being edited".into(), Color::Cyan),
         TaskStatus::Done { result, .. } => match result {
             TaskResult::Success => ("completed successfully".into(), Color::Green),
             TaskResult::Failed(exit_code) => {
@@ -159,6 +175,7 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
             TaskResult::Killed => ("killed by system or user".into(), Color::Red),
             TaskResult::Errored => ("some IO error.\n Check daemon log.".into(), Color::Red),
             TaskResult::DependencyFailed => ("dependency failed".into(), Color::Red),
+            TaskResult::Expired => ("expired before it was started".into(), Color::Red),
         },
         _ => (task.status.to_string(), Color::White),
     };
@@ -196,6 +213,12 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
             Cell::new(label),
         ]);
     }
+    if let Some(note) = &task.note {
+        table.add_row(vec![
+            style.styled_cell("Note:", None, Some(ComfyAttribute::Bold)),
+            Cell::new(note),
+        ]);
+    }
 
     let (start, end) = task.start_and_end();
 
@@ -213,6 +236,20 @@ fn print_task_info(task: &Task, style: &OutputStyle) {
         ]);
     }
 
+    // The timeline of status changes, if the daemon records them.
+    if !task.status_history.is_empty() {
+        let history = task
+            .status_history
+            .iter()
+            .map(|(time, status)| format!("{} {status}", time.to_rfc2822()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        table.add_row(vec![
+            style.styled_cell("History:", None, Some(ComfyAttribute::Bold)),
+            Cell::new(history),
+        ]);
+    }
+
     // Set the padding of the left column to 0 align the keys to the right
     let first_column = table.column_mut(0).unwrap();
     first_column.set_cell_alignment(CellAlignment::Right);
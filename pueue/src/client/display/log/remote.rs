@@ -4,6 +4,7 @@ use anyhow::Result;
 use crossterm::style::{Attribute, Color};
 use snap::read::FrameDecoder;
 
+use pueue_lib::log::TimestampStripper;
 use pueue_lib::network::message::TaskLogMessage;
 
 use super::OutputStyle;
@@ -11,7 +12,12 @@ use super::OutputStyle;
 /// Prints log output received from the daemon.
 /// We can safely call .unwrap() on output in here, since this
 /// branch is always called after ensuring that it is `Some`.
-pub fn print_remote_log(task_log: &TaskLogMessage, style: &OutputStyle, lines: Option<usize>) {
+pub fn print_remote_log(
+    task_log: &TaskLogMessage,
+    style: &OutputStyle,
+    lines: Option<usize>,
+    no_timestamps: bool,
+) {
     if let Some(bytes) = task_log.output.as_ref() {
         if !bytes.is_empty() {
             // Add a hint if we should limit the output to X lines **and** there are actually more
@@ -25,7 +31,7 @@ pub fn print_remote_log(task_log: &TaskLogMessage, style: &OutputStyle, lines: O
             let header = style.style_text("output:", Some(Color::Green), Some(Attribute::Bold));
             println!("\n{header}{line_info}");
 
-            if let Err(err) = decompress_and_print_remote_log(bytes) {
+            if let Err(err) = decompress_and_print_remote_log(bytes, style, no_timestamps) {
                 println!("Error while parsing stdout: {err}");
             }
         }
@@ -35,11 +41,15 @@ pub fn print_remote_log(task_log: &TaskLogMessage, style: &OutputStyle, lines: O
 /// We cannot easily stream log output from the client to the daemon (yet).
 /// Right now, the output is compressed in the daemon and sent as a single payload to the
 /// client. In here, we take that payload, decompress it and stream it it directly to stdout.
-fn decompress_and_print_remote_log(bytes: &[u8]) -> Result<()> {
+fn decompress_and_print_remote_log(
+    bytes: &[u8],
+    style: &OutputStyle,
+    no_timestamps: bool,
+) -> Result<()> {
     let mut decompressor = FrameDecoder::new(bytes);
 
     let stdout = io::stdout();
-    let mut write = stdout.lock();
+    let mut write = TimestampStripper::new(style.task_output_writer(stdout.lock()), no_timestamps);
     io::copy(&mut decompressor, &mut write)?;
 
     Ok(())
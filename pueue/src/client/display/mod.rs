@@ -5,19 +5,26 @@
 mod follow;
 mod group;
 pub mod helper;
+mod history;
+mod json;
 mod log;
 mod state;
 pub mod style;
 pub mod table_builder;
+mod task_output;
+mod tree;
 
 use crossterm::style::Color;
 
 // Re-exports
-pub use self::follow::follow_local_task_logs;
+pub use self::follow::{follow_local_task_logs, follow_remote_task_logs};
 pub use self::group::format_groups;
+pub use self::history::format_history;
+pub use self::json::{format_json, JsonKind};
 pub use self::log::{determine_log_line_amount, print_logs};
 pub use self::state::print_state;
 pub use self::style::OutputStyle;
+pub use self::task_output::TaskOutputWriter;
 
 /// Used to style any generic success message from the daemon.
 pub fn print_success(_style: &OutputStyle, message: &str) {
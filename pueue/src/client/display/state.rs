@@ -1,13 +1,17 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 
 use pueue_lib::settings::Settings;
 use pueue_lib::state::{State, PUEUE_DEFAULT_GROUP};
-use pueue_lib::task::Task;
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 use super::{helper::*, table_builder::TableBuilder, OutputStyle};
 use crate::client::cli::SubCommand;
 use crate::client::display::group::get_group_headline;
-use crate::client::query::apply_query;
+use crate::client::display::json::{format_json, JsonKind};
+use crate::client::display::tree::format_task_tree;
+use crate::client::query::{apply_query, apply_sort};
 
 /// Get the output for the state of the daemon in a nicely formatted table.
 /// If there are multiple groups, each group with a task will have its own table.
@@ -23,46 +27,194 @@ pub fn print_state(
 ) -> Result<String> {
     let mut output = String::new();
 
-    let (json, group_only, query) = match cli_command {
-        SubCommand::Status { json, group, query } => (*json, group.clone(), Some(query)),
-        SubCommand::FormatStatus { group } => (false, group.clone(), None),
-        _ => panic!("Got wrong Subcommand {cli_command:?} in print_state. This shouldn't happen!"),
-    };
+    let (json, json_pretty, group_only, mut groups, query, sort, scheduled, tree, total) =
+        match cli_command {
+            SubCommand::Status {
+                json,
+                json_pretty,
+                group,
+                groups,
+                query,
+                sort,
+                scheduled,
+                tree,
+                total,
+                ..
+            } => (
+                *json,
+                *json_pretty,
+                group.clone(),
+                groups.clone(),
+                Some(query),
+                sort.clone(),
+                *scheduled,
+                *tree,
+                *total,
+            ),
+            SubCommand::FormatStatus { group } => (
+                false,
+                false,
+                group.clone(),
+                Vec::new(),
+                None,
+                None,
+                false,
+                false,
+                false,
+            ),
+            _ => panic!(
+                "Got wrong Subcommand {cli_command:?} in print_state. This shouldn't happen!"
+            ),
+        };
 
     let mut table_builder = TableBuilder::new(settings, style);
 
+    // Only show scheduled tasks and the time that's left until they're enqueued.
+    if scheduled {
+        tasks.retain(Task::is_scheduled);
+        table_builder.show_enqueue_in();
+    }
+
+    // Only show the tasks of the requested groups.
+    // Unknown groups are ignored, but the user is warned about them.
+    if !groups.is_empty() {
+        groups.retain(|group| {
+            let exists = state.groups.contains_key(group);
+            if !exists {
+                eprintln!("There exists no group \"{group}\"");
+            }
+            exists
+        });
+        tasks.retain(|task| groups.contains(&task.group));
+        state.groups.retain(|name, _| groups.contains(name));
+    }
+
+    // The filters above happen before the query is applied, so its ordering and limits only see
+    // the remaining tasks.
     if let Some(query) = query {
-        let query_result = apply_query(&query.join(" "), &group_only)?;
+        let mut query_result = apply_query(&query.join(" "), group_only.as_deref())?;
+        if let Some(sort) = &sort {
+            apply_sort(sort, &mut query_result)?;
+        }
         table_builder.set_visibility_by_rules(&query_result.selected_columns);
         tasks = query_result.apply_filters(tasks);
         tasks = query_result.order_tasks(tasks);
         tasks = query_result.limit_tasks(tasks);
     }
 
+    // Print each of the remaining tasks as a separate line of json and exit.
+    if matches!(
+        cli_command,
+        SubCommand::Status {
+            json_stream: true,
+            ..
+        }
+    ) {
+        let lines: Vec<String> = tasks
+            .iter()
+            .map(|task| serde_json::to_string(task).unwrap())
+            .collect();
+        output.push_str(&lines.join("\n"));
+        return Ok(output);
+    }
+
     // If the json flag is specified, print the state as json and exit.
-    if json {
-        if query.is_some() {
+    if json || json_pretty {
+        if query.is_some() || scheduled || !groups.is_empty() {
             state.tasks = tasks.into_iter().map(|task| (task.id, task)).collect();
         }
-        output.push_str(&serde_json::to_string(&state).unwrap());
+        let raw_json = matches!(cli_command, SubCommand::Status { raw_json: true, .. });
+        output.push_str(&format_json(
+            JsonKind::Status,
+            &state,
+            raw_json,
+            json_pretty,
+        ));
         return Ok(output);
     }
 
-    if let Some(group) = group_only {
-        print_single_group(state, tasks, style, group, table_builder, &mut output);
-        return Ok(output);
+    // The summary is computed before the tasks are handed over to the table printing logic.
+    let totals = (total && !tasks.is_empty()).then(|| format_totals(&tasks));
+
+    if tree {
+        // Show the dependency hierarchy of the tasks instead of tables.
+        if tasks.is_empty() {
+            output.push_str("Task list is empty. Add tasks with `pueue add -- [cmd]`");
+        } else {
+            output.push_str(&format_task_tree(&tasks, style, settings));
+        }
+    } else if let Some(group) = group_only {
+        print_single_group(&state, tasks, style, group, table_builder, &mut output);
+    } else if !groups.is_empty() {
+        // Print the requested groups in the given order, separated by an empty line.
+        let tables: Vec<String> = groups
+            .into_iter()
+            .map(|group| {
+                let mut table = String::new();
+                let builder = table_builder.clone();
+                print_single_group(&state, tasks.clone(), style, group, builder, &mut table);
+                table
+            })
+            .collect();
+        output.push_str(&tables.join("\n\n"));
+    } else {
+        print_all_groups(state, tasks, style, table_builder, &mut output);
     }
 
-    print_all_groups(state, tasks, style, table_builder, &mut output);
+    if let Some(totals) = totals {
+        output.push_str(&format!("\n{totals}"));
+    }
 
     Ok(output)
 }
 
+/// Summarize the given tasks in a single line, e.g. `2 running, 3 queued, 1 failed (6 total)`.
+/// If the tasks belong to multiple groups, the amount of tasks per group is included as well.
+fn format_totals(tasks: &[Task]) -> String {
+    let mut per_status: BTreeMap<usize, (&str, usize)> = BTreeMap::new();
+    let mut per_group: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in tasks {
+        // The key determines the order in which the stati are listed.
+        let status = match &task.status {
+            TaskStatus::Running { .. } => (0, "running"),
+            TaskStatus::Paused { .. } => (1, "paused"),
+            TaskStatus::Queued { .. } => (2, "queued"),
+            TaskStatus::Stashed { .. } => (3, "stashed"),
+            TaskStatus::Locked { .. } => (4, "locked"),
+            TaskStatus::Done {
+                result: TaskResult::Success,
+                ..
+            } => (5, "succeeded"),
+            TaskStatus::Done { .. } => (6, "failed"),
+        };
+        per_status.entry(status.0).or_insert((status.1, 0)).1 += 1;
+        *per_group.entry(&task.group).or_default() += 1;
+    }
+
+    let statuses = per_status
+        .values()
+        .map(|(name, count)| format!("{count} {name}"))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let mut total = format!("{} total", tasks.len());
+    if per_group.len() > 1 {
+        let groups = per_group
+            .iter()
+            .map(|(group, count)| format!("{group}: {count}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        total.push_str(&format!("; {groups}"));
+    }
+
+    format!("{statuses} ({total})")
+}
+
 /// The user requested only a single group to be displayed.
 ///
 /// Print this group or show an error if this group doesn't exist.
 fn print_single_group(
-    state: State,
+    state: &State,
     tasks: Vec<Task>,
     style: &OutputStyle,
     group_name: String,
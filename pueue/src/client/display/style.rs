@@ -1,10 +1,19 @@
+use std::collections::HashMap;
+use std::env::var_os;
+use std::io::{stdout, Write};
+
 use pueue_lib::settings::Settings;
 
 use comfy_table::{Attribute as ComfyAttribute, Cell, Color as ComfyColor};
 use crossterm::style::{style, Attribute, Color, Stylize};
+use crossterm::tty::IsTty;
+
+use super::TaskOutputWriter;
+use crate::client::cli::{no_color_requested, ColorChoice};
 
 /// OutputStyle wrapper for actual colors depending on settings
-/// - Enables styles if color mode is 'always', or if color mode is 'auto' and output is a tty.
+/// - Enables styles if color mode is 'always', or if color mode is 'auto', `NO_COLOR` isn't set
+///   and output is a tty.
 /// - Using dark colors if dark_mode is enabled
 #[derive(Debug, Clone)]
 pub struct OutputStyle {
@@ -12,17 +21,39 @@ pub struct OutputStyle {
     pub enabled: bool,
     /// Whether dark mode is enabled.
     pub dark_mode: bool,
+    /// The configured headline colors of groups.
+    pub group_colors: HashMap<String, Color>,
 }
 
 impl OutputStyle {
     /// init color-scheme depending on settings
-    pub const fn new(settings: &Settings, enabled: bool) -> Self {
+    pub fn new(settings: &Settings, enabled: bool) -> Self {
+        // Ignore any colors that cannot be parsed, so we fall back to the default styling.
+        let group_colors = settings
+            .client
+            .group_colors
+            .iter()
+            .filter_map(|(group, color)| {
+                Color::try_from(color.as_str())
+                    .ok()
+                    .map(|color| (group.clone(), color))
+            })
+            .collect();
+
         Self {
             enabled,
             dark_mode: settings.client.dark_mode,
+            group_colors,
         }
     }
 
+    /// Create the output style for the user's color choice.
+    /// See [ColorChoice::enabled] for how `NO_COLOR` and tty detection are taken into account.
+    pub fn from_color_choice(settings: &Settings, color: &ColorChoice) -> Self {
+        let enabled = color.enabled(no_color_requested(var_os("NO_COLOR")), stdout().is_tty());
+        Self::new(settings, enabled)
+    }
+
     /// Return the desired crossterm color depending on whether we're in dark mode or not.
     fn map_color(&self, color: Color) -> Color {
         if self.dark_mode {
@@ -30,6 +61,7 @@ impl OutputStyle {
                 Color::Green => Color::DarkGreen,
                 Color::Red => Color::DarkRed,
                 Color::Yellow => Color::DarkYellow,
+                Color::Cyan => Color::DarkCyan,
                 _ => color,
             }
         } else {
@@ -44,6 +76,7 @@ impl OutputStyle {
                 Color::Green => ComfyColor::DarkGreen,
                 Color::Red => ComfyColor::DarkRed,
                 Color::Yellow => ComfyColor::DarkYellow,
+                Color::Cyan => ComfyColor::DarkCyan,
                 _ => ComfyColor::White,
             };
         }
@@ -52,6 +85,7 @@ impl OutputStyle {
             Color::Green => ComfyColor::Green,
             Color::Red => ComfyColor::Red,
             Color::Yellow => ComfyColor::Yellow,
+            Color::Cyan => ComfyColor::Cyan,
             _ => ComfyColor::White,
         }
     }
@@ -82,6 +116,12 @@ impl OutputStyle {
         styled.to_string()
     }
 
+    /// Wrap a writer that's used to print the raw output of tasks.
+    /// ANSI escape sequences of the task are only passed through if styling is enabled.
+    pub fn task_output_writer<W: Write>(&self, writer: W) -> TaskOutputWriter<W> {
+        TaskOutputWriter::new(writer, !self.enabled)
+    }
+
     /// A helper method to produce styled Comfy-table cells.
     /// Use this anywhere you need to create Comfy-table cells, so that the correct
     /// colors are used depending on the current color mode and dark-mode preset.
@@ -1,12 +1,14 @@
-use chrono::TimeDelta;
+use chrono::{Local, TimeDelta};
 use comfy_table::presets::UTF8_HORIZONTAL_ONLY;
 use comfy_table::{Cell, ContentArrangement, Row, Table};
-use crossterm::style::Color;
 
 use pueue_lib::settings::Settings;
-use pueue_lib::task::{Task, TaskResult, TaskStatus};
+use pueue_lib::task::{Task, TaskStatus};
 
-use super::helper::{formatted_start_end, start_of_today};
+use super::helper::{
+    formatted_relative_time, formatted_start_end, formatted_time_left, start_of_today,
+    status_text_and_color,
+};
 use super::OutputStyle;
 use crate::client::query::Rule;
 
@@ -28,12 +30,18 @@ pub struct TableBuilder<'a> {
     status: bool,
     priority: bool,
     enqueue_at: bool,
+    /// The time left until a scheduled task is enqueued.
+    /// This column can't be selected via queries and is only shown for `status --scheduled`.
+    enqueue_in: bool,
     dependencies: bool,
     label: bool,
     command: bool,
     path: bool,
     start: bool,
     end: bool,
+    /// The worker slot of started tasks.
+    /// This column is only shown, if it's explicitly selected.
+    worker: bool,
 }
 
 impl<'a> TableBuilder<'a> {
@@ -48,12 +56,14 @@ impl<'a> TableBuilder<'a> {
             status: true,
             priority: false,
             enqueue_at: false,
+            enqueue_in: false,
             dependencies: false,
             label: false,
             command: true,
             path: true,
             start: true,
             end: true,
+            worker: false,
         }
     }
 
@@ -75,6 +85,11 @@ impl<'a> TableBuilder<'a> {
         table
     }
 
+    /// Show the time that's left until scheduled tasks are enqueued.
+    pub fn show_enqueue_in(&mut self) {
+        self.enqueue_in = true;
+    }
+
     /// By default, several columns aren't shown until there's at least one task with relevant data.
     /// This function determines whether any of those columns should be shown.
     fn determine_special_columns(&mut self, tasks: &[Task]) {
@@ -111,7 +126,7 @@ impl<'a> TableBuilder<'a> {
         }
     }
 
-    /// Take a list of given [pest] rules from our `crate::client::query::column_selection::apply` logic.
+    /// Take the list of columns that have been selected via the `columns=` section of a query.
     /// Set the column visibility based on these rules.
     pub fn set_visibility_by_rules(&mut self, rules: &[Rule]) {
         // Don't change anything, if there're no rules
@@ -130,6 +145,7 @@ impl<'a> TableBuilder<'a> {
         self.path = false;
         self.start = false;
         self.end = false;
+        self.worker = false;
 
         // Make sure we don't do any default column visibility checks of our own.
         self.selected_columns = true;
@@ -146,51 +162,63 @@ impl<'a> TableBuilder<'a> {
                 Rule::column_path => self.path = true,
                 Rule::column_start => self.start = true,
                 Rule::column_end => self.end = true,
+                Rule::column_worker => self.worker = true,
                 _ => (),
             }
         }
     }
 
-    /// Build a header row based on the current selection of columns.
-    fn build_header(&self) -> Row {
-        let mut header = Vec::new();
-
-        // Create table header row
-        if self.id {
-            header.push(Cell::new("Id"));
-        }
-        if self.status {
-            header.push(Cell::new("Status"));
-        }
-        if self.priority {
-            header.push(Cell::new("Prio"));
-        }
-        if self.enqueue_at {
-            header.push(Cell::new("Enqueue At"));
-        }
-        if self.dependencies {
-            header.push(Cell::new("Deps"));
-        }
-        if self.label {
-            header.push(Cell::new("Label"));
-        }
-        if self.command {
-            header.push(Cell::new("Command"));
-        }
-        if self.path {
-            header.push(Cell::new("Path"));
-        }
-        if self.start {
-            header.push(Cell::new("Start"));
+    /// Determine the visible columns in the order in which they should be displayed.
+    ///
+    /// Columns listed in `client.column_order` come first, unknown names are ignored.
+    /// All other visible columns are appended in their default order.
+    fn visible_columns(&self) -> Vec<Column> {
+        let mut columns: Vec<Column> = Vec::new();
+        let configured = self
+            .settings
+            .client
+            .column_order
+            .iter()
+            .filter_map(|name| Column::from_name(name));
+        for column in configured.chain(Column::ALL) {
+            if self.is_visible(column) && !columns.contains(&column) {
+                columns.push(column);
+            }
         }
-        if self.end {
-            header.push(Cell::new("End"));
+
+        columns
+    }
+
+    fn is_visible(&self, column: Column) -> bool {
+        match column {
+            Column::Id => self.id,
+            Column::Status => self.status,
+            Column::Priority => self.priority,
+            Column::EnqueueAt => self.enqueue_at,
+            Column::EnqueueIn => self.enqueue_in,
+            Column::Dependencies => self.dependencies,
+            Column::Label => self.label,
+            Column::Command => self.command,
+            Column::Path => self.path,
+            Column::Start => self.start,
+            Column::End => self.end,
+            Column::Worker => self.worker,
         }
+    }
+
+    /// Build a header row based on the current selection of columns.
+    fn build_header(&self) -> Row {
+        let header: Vec<Cell> = self
+            .visible_columns()
+            .into_iter()
+            .map(|column| Cell::new(column.title()))
+            .collect();
 
         Row::from(header)
     }
 
     fn build_task_rows(&self, tasks: &[Task]) -> Vec<Row> {
+        let columns = self.visible_columns();
         let mut rows = Vec::new();
         // Add rows one by one.
         for task in tasks.iter() {
@@ -200,94 +228,163 @@ impl<'a> TableBuilder<'a> {
                 row.max_height(height);
             }
 
-            if self.id {
-                row.add_cell(Cell::new(task.id));
+            for column in columns.iter() {
+                row.add_cell(self.build_cell(task, *column));
             }
 
-            if self.status {
+            rows.push(row);
+        }
+
+        rows
+    }
+
+    /// Build the cell of a single column for a given task.
+    fn build_cell(&self, task: &Task, column: Column) -> Cell {
+        match column {
+            Column::Id => Cell::new(task.id),
+            Column::Status => {
                 // Determine the human readable task status representation and the respective color.
-                let status_string = task.status.to_string();
-                let (status_text, color) = match &task.status {
-                    TaskStatus::Running { .. } => (status_string, Color::Green),
-                    TaskStatus::Paused { .. } | TaskStatus::Locked { .. } => {
-                        (status_string, Color::White)
-                    }
-                    TaskStatus::Done { result, .. } => match result {
-                        TaskResult::Success => (TaskResult::Success.to_string(), Color::Green),
-                        TaskResult::DependencyFailed => {
-                            ("Dependency failed".to_string(), Color::Red)
-                        }
-                        TaskResult::FailedToSpawn(_) => ("Failed to spawn".to_string(), Color::Red),
-                        TaskResult::Failed(code) => (format!("Failed ({code})"), Color::Red),
-                        _ => (result.to_string(), Color::Red),
-                    },
-                    _ => (status_string, Color::Yellow),
-                };
-                row.add_cell(self.style.styled_cell(status_text, Some(color), None));
+                let (status_text, color) = status_text_and_color(task);
+                self.style.styled_cell(status_text, Some(color), None)
             }
+            Column::Priority => Cell::new(task.priority.to_string()),
+            Column::EnqueueAt => {
+                let TaskStatus::Stashed {
+                    enqueue_at: Some(enqueue_at),
+                } = task.status
+                else {
+                    return Cell::new("");
+                };
 
-            if self.priority {
-                row.add_cell(Cell::new(task.priority.to_string()));
-            }
+                if self.settings.client.relative_times {
+                    return Cell::new(formatted_relative_time(enqueue_at, Local::now()));
+                }
 
-            if self.enqueue_at {
+                // Only show the date if the task is not supposed to be enqueued today.
+                let enqueue_today =
+                    enqueue_at <= start_of_today() + TimeDelta::try_days(1).unwrap();
+                Cell::new(
+                    self.settings
+                        .client
+                        .format_timestamp(&enqueue_at, !enqueue_today),
+                )
+            }
+            Column::EnqueueIn => {
                 if let TaskStatus::Stashed {
                     enqueue_at: Some(enqueue_at),
                 } = task.status
                 {
-                    // Only show the date if the task is not supposed to be enqueued today.
-                    let enqueue_today =
-                        enqueue_at <= start_of_today() + TimeDelta::try_days(1).unwrap();
-                    let formatted_enqueue_at = if enqueue_today {
-                        enqueue_at.format(&self.settings.client.status_time_format)
-                    } else {
-                        enqueue_at.format(&self.settings.client.status_datetime_format)
-                    };
-                    row.add_cell(Cell::new(formatted_enqueue_at));
+                    Cell::new(formatted_time_left(enqueue_at))
                 } else {
-                    row.add_cell(Cell::new(""));
+                    Cell::new("")
                 }
             }
-
-            if self.dependencies {
+            Column::Dependencies => {
                 let text = task
                     .dependencies
                     .iter()
                     .map(|id| id.to_string())
                     .collect::<Vec<String>>()
                     .join(", ");
-                row.add_cell(Cell::new(text));
-            }
-
-            if self.label {
-                row.add_cell(Cell::new(task.label.as_deref().unwrap_or_default()));
+                Cell::new(text)
             }
-
-            // Add command and path.
-            if self.command {
+            Column::Label => Cell::new(task.label.as_deref().unwrap_or_default()),
+            Column::Command => {
                 if self.settings.client.show_expanded_aliases {
-                    row.add_cell(Cell::new(&task.command));
+                    Cell::new(&task.command)
                 } else {
-                    row.add_cell(Cell::new(&task.original_command));
+                    Cell::new(&task.original_command)
                 }
             }
-
-            if self.path {
-                row.add_cell(Cell::new(task.path.to_string_lossy()));
+            Column::Path => Cell::new(task.path.to_string_lossy()),
+            Column::Start => Cell::new(formatted_start_end(task, self.settings).0),
+            Column::End => Cell::new(formatted_start_end(task, self.settings).1),
+            Column::Worker => {
+                // Tasks that are waiting to be started again might still carry their old worker.
+                match task.worker_id {
+                    Some(worker_id) if !task.is_waiting() => Cell::new(worker_id),
+                    _ => Cell::new(""),
+                }
             }
+        }
+    }
+}
 
-            // Add start and end info
-            let (start, end) = formatted_start_end(task, self.settings);
-            if self.start {
-                row.add_cell(Cell::new(start));
-            }
-            if self.end {
-                row.add_cell(Cell::new(end));
-            }
+/// Check whether a name can be used in the `client.column_order` setting.
+pub fn is_column_name(name: &str) -> bool {
+    Column::from_name(name).is_some()
+}
 
-            rows.push(row);
-        }
+/// All columns of the status table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Id,
+    Status,
+    Priority,
+    EnqueueAt,
+    EnqueueIn,
+    Dependencies,
+    Label,
+    Command,
+    Path,
+    Start,
+    End,
+    Worker,
+}
 
-        rows
+impl Column {
+    /// All columns in their default order.
+    const ALL: [Column; 12] = [
+        Column::Id,
+        Column::Status,
+        Column::Priority,
+        Column::EnqueueAt,
+        Column::EnqueueIn,
+        Column::Dependencies,
+        Column::Label,
+        Column::Command,
+        Column::Path,
+        Column::Start,
+        Column::End,
+        Column::Worker,
+    ];
+
+    /// Get a column by the name that's used in the `client.column_order` setting.
+    /// The names are the same that're used for column selection in status queries.
+    fn from_name(name: &str) -> Option<Column> {
+        let column = match name.to_lowercase().as_str() {
+            "id" => Column::Id,
+            "status" => Column::Status,
+            "priority" => Column::Priority,
+            "enqueue_at" => Column::EnqueueAt,
+            "enqueue_in" => Column::EnqueueIn,
+            "dependencies" => Column::Dependencies,
+            "label" => Column::Label,
+            "command" => Column::Command,
+            "path" => Column::Path,
+            "start" => Column::Start,
+            "end" => Column::End,
+            "worker" => Column::Worker,
+            _ => return None,
+        };
+
+        Some(column)
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Id => "Id",
+            Column::Status => "Status",
+            Column::Priority => "Prio",
+            Column::EnqueueAt => "Enqueue At",
+            Column::EnqueueIn => "Enqueue In",
+            Column::Dependencies => "Deps",
+            Column::Label => "Label",
+            Column::Command => "Command",
+            Column::Path => "Path",
+            Column::Start => "Start",
+            Column::End => "End",
+            Column::Worker => "Worker",
+        }
     }
 }
@@ -0,0 +1,91 @@
+use std::io::{self, Write};
+
+/// The position inside of an ANSI escape sequence, while stripping those sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EscapeState {
+    /// We're in normal text.
+    Text,
+    /// We just read an `ESC`.
+    Escape,
+    /// Control sequence, e.g. colors (`ESC [ ... m`).
+    Csi,
+    /// Operating system command or a similar string sequence (`ESC ] ... BEL`).
+    Osc,
+    /// We just read an `ESC` inside of an operating system command.
+    OscEscape,
+}
+
+/// A writer for the raw output of tasks.
+///
+/// Tasks might emit their own ANSI escape sequences, e.g. to color their output.
+/// If styling is enabled, this output is passed through verbatim.
+/// Otherwise, all escape sequences are stripped, so piped output or `--color=never`
+/// results in plain text.
+///
+/// Escape sequences may span several writes, which is why the parsing state is kept around.
+pub struct TaskOutputWriter<W: Write> {
+    inner: W,
+    strip_ansi: bool,
+    state: EscapeState,
+}
+
+impl<W: Write> TaskOutputWriter<W> {
+    pub fn new(inner: W, strip_ansi: bool) -> Self {
+        Self {
+            inner,
+            strip_ansi,
+            state: EscapeState::Text,
+        }
+    }
+
+    /// Advance the escape sequence state machine by a single byte.
+    /// Returns whether the byte is part of the visible text.
+    fn is_text(&mut self, byte: u8) -> bool {
+        const ESC: u8 = 0x1b;
+        const BEL: u8 = 0x07;
+
+        let (next_state, is_text) = match self.state {
+            EscapeState::Text if byte == ESC => (EscapeState::Escape, false),
+            EscapeState::Text => (EscapeState::Text, true),
+            EscapeState::Escape => match byte {
+                b'[' => (EscapeState::Csi, false),
+                b']' | b'P' | b'X' | b'^' | b'_' => (EscapeState::Osc, false),
+                // Intermediate bytes, the sequence continues.
+                0x20..=0x2f => (EscapeState::Escape, false),
+                _ => (EscapeState::Text, false),
+            },
+            // Final bytes of a control sequence.
+            EscapeState::Csi if (0x40..=0x7e).contains(&byte) => (EscapeState::Text, false),
+            EscapeState::Csi => (EscapeState::Csi, false),
+            EscapeState::Osc if byte == BEL => (EscapeState::Text, false),
+            EscapeState::Osc if byte == ESC => (EscapeState::OscEscape, false),
+            EscapeState::Osc => (EscapeState::Osc, false),
+            EscapeState::OscEscape => (EscapeState::Text, false),
+        };
+
+        self.state = next_state;
+        is_text
+    }
+}
+
+impl<W: Write> Write for TaskOutputWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.strip_ansi {
+            return self.inner.write(buf);
+        }
+
+        let mut text = Vec::with_capacity(buf.len());
+        for byte in buf {
+            if self.is_text(*byte) {
+                text.push(*byte);
+            }
+        }
+        self.inner.write_all(&text)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
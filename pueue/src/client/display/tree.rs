@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use pueue_lib::settings::Settings;
+use pueue_lib::task::Task;
+
+use super::helper::status_text_and_color;
+use super::OutputStyle;
+
+/// Render the given tasks as a forest, in which tasks are nested below the tasks they depend on.
+///
+/// Tasks without dependencies (or whose dependencies aren't part of the given tasks) are roots.
+/// A task that depends on multiple tasks is shown below each of them, but its dependants are
+/// only expanded on its first appearance. This also prevents endless recursion in case of cycles.
+pub fn format_task_tree(tasks: &[Task], style: &OutputStyle, settings: &Settings) -> String {
+    let tasks: BTreeMap<usize, &Task> = tasks.iter().map(|task| (task.id, task)).collect();
+
+    // Map each task to the tasks that depend on it.
+    let mut dependants: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for task in tasks.values() {
+        for dependency in task.dependencies.iter() {
+            if tasks.contains_key(dependency) {
+                dependants.entry(*dependency).or_default().push(task.id);
+            }
+        }
+    }
+
+    let roots = tasks.values().filter(|task| {
+        !task
+            .dependencies
+            .iter()
+            .any(|dependency| tasks.contains_key(dependency))
+    });
+
+    let renderer = TreeRenderer {
+        tasks: &tasks,
+        dependants: &dependants,
+        style,
+        settings,
+    };
+    let mut lines = Vec::new();
+    let mut expanded = BTreeSet::new();
+    for root in roots {
+        renderer.render(root.id, "", None, &mut expanded, &mut lines);
+    }
+
+    // Tasks that're part of a dependency cycle don't have a root.
+    // Render them anyway, starting at the lowest id.
+    for task_id in tasks.keys() {
+        if !expanded.contains(task_id) {
+            renderer.render(*task_id, "", None, &mut expanded, &mut lines);
+        }
+    }
+
+    lines.join("\n")
+}
+
+struct TreeRenderer<'a> {
+    tasks: &'a BTreeMap<usize, &'a Task>,
+    dependants: &'a BTreeMap<usize, Vec<usize>>,
+    style: &'a OutputStyle,
+    settings: &'a Settings,
+}
+
+impl TreeRenderer<'_> {
+    /// Render a task and all of its dependants.
+    ///
+    /// `prefix` is the indentation of the current depth, `is_last` is `None` for roots and
+    /// otherwise indicates whether this task is the last child of its parent.
+    fn render(
+        &self,
+        task_id: usize,
+        prefix: &str,
+        is_last: Option<bool>,
+        expanded: &mut BTreeSet<usize>,
+        lines: &mut Vec<String>,
+    ) {
+        let task = self.tasks[&task_id];
+        let (connector, child_prefix) = match is_last {
+            None => ("", String::new()),
+            Some(false) => ("├─ ", format!("{prefix}│  ")),
+            Some(true) => ("└─ ", format!("{prefix}   ")),
+        };
+
+        let (status, color) = status_text_and_color(task);
+        let status = self.style.style_text(status, Some(color), None);
+        let command = if self.settings.client.show_expanded_aliases {
+            &task.command
+        } else {
+            &task.original_command
+        };
+
+        // Only expand the dependants of a task once.
+        let first_appearance = expanded.insert(task_id);
+        let children = self.dependants.get(&task_id).filter(|_| first_appearance);
+        let mut line = format!("{prefix}{connector}{task_id} [{status}] {command}");
+        if !first_appearance && self.dependants.contains_key(&task_id) {
+            line.push_str(" (see above)");
+        }
+        lines.push(line);
+
+        let Some(children) = children else {
+            return;
+        };
+        for (index, child) in children.iter().enumerate() {
+            let is_last = index + 1 == children.len();
+            self.render(*child, &child_prefix, Some(is_last), expanded, lines);
+        }
+    }
+}
@@ -4,3 +4,5 @@ pub mod client;
 mod commands;
 pub(crate) mod display;
 pub mod query;
+
+pub use commands::{check_config, doctor};
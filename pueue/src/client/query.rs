@@ -0,0 +1,3 @@
+//! The query syntax of `pueue status` is part of [pueue_lib::query], so other frontends can
+//! apply the exact same queries as the CLI.
+pub use pueue_lib::query::*;
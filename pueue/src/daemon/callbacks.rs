@@ -5,16 +5,23 @@ use handlebars::{Handlebars, RenderError};
 use log::{debug, error, info};
 use pueue_lib::{
     log::{get_log_path, read_last_log_file_lines},
-    process_helper::compile_shell_command,
+    process_helper::{compile_shell_command, run_as_user},
     settings::Settings,
     task::{Task, TaskResult, TaskStatus},
 };
 
 use super::state_helper::LockedState;
 
+/// Spawn the global callback and the task's own hook, if any, once a task finishes.
+/// Both are independent of each other and may run at the same time.
+pub fn spawn_callback(settings: &Settings, state: &mut LockedState, task: &Task) {
+    spawn_global_callback(settings, state, task);
+    spawn_task_hook(settings, state, task);
+}
+
 /// Users can specify a callback that's fired whenever a task finishes.
 /// The callback is performed by spawning a new subprocess.
-pub fn spawn_callback(settings: &Settings, state: &mut LockedState, task: &Task) {
+fn spawn_global_callback(settings: &Settings, state: &mut LockedState, task: &Task) {
     // Return early, if there's no callback specified
     let Some(template_string) = &settings.daemon.callback else {
         return;
@@ -45,6 +52,63 @@ pub fn spawn_callback(settings: &Settings, state: &mut LockedState, task: &Task)
     state.callbacks.push(child);
 }
 
+/// Tasks can have their own `on_success` and `on_failure` hooks, which are fired depending on
+/// the task's result. In contrast to the global callback, the hooks aren't templated.
+/// Instead, information about the task is passed via environment variables.
+fn spawn_task_hook(settings: &Settings, state: &mut LockedState, task: &Task) {
+    let TaskStatus::Done { result, .. } = &task.status else {
+        return;
+    };
+    let hook = if matches!(result, TaskResult::Success) {
+        &task.on_success
+    } else {
+        &task.on_failure
+    };
+    let Some(hook) = hook else {
+        return;
+    };
+
+    let exit_code = task
+        .exit_code()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "None".into());
+    let out_path = get_log_path(task.id, &settings.shared.pueue_directory());
+
+    let mut command = compile_shell_command(settings, hook);
+    command
+        .current_dir(&task.path)
+        .env("PUEUE_TASK_ID", task.id.to_string())
+        .env("PUEUE_TASK_GROUP", &task.group)
+        .env("PUEUE_TASK_RESULT", result.to_string())
+        .env("PUEUE_TASK_EXIT_CODE", exit_code)
+        .env("PUEUE_TASK_OUTPUT_PATH", out_path);
+
+    // Hooks are part of the task, so they run as the same user.
+    if let Some(user) = &task.user {
+        if let Err(err) = run_as_user(&mut command, user) {
+            error!(
+                "Failed to run hook of task {} as user '{user}': {err:#}",
+                task.id
+            );
+            return;
+        }
+    }
+
+    let child = match command.spawn() {
+        Err(error) => {
+            error!(
+                "Failed to spawn hook of task {} with error: {error}",
+                task.id
+            );
+            return;
+        }
+        Ok(child) => child,
+    };
+
+    debug!("Spawned hook for task {}", task.id);
+    state.callbacks.push(child);
+}
+
 /// Take the callback template string from the configuration and insert all parameters from the
 /// finished task.
 pub fn build_callback_command(
@@ -111,15 +175,11 @@ pub fn build_callback_command(
     parameters.insert("output_path", out_path.display().to_string());
 
     // Get the exit code
-    if let TaskStatus::Done { result, .. } = &task.status {
-        match result {
-            TaskResult::Success => parameters.insert("exit_code", "0".into()),
-            TaskResult::Failed(code) => parameters.insert("exit_code", code.to_string()),
-            _ => parameters.insert("exit_code", "None".into()),
-        };
-    } else {
-        parameters.insert("exit_code", "None".into());
-    }
+    let exit_code = task
+        .exit_code()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "None".into());
+    parameters.insert("exit_code", exit_code);
 
     handlebars.render_template(template_string, &parameters)
 }
@@ -4,6 +4,8 @@ use std::path::PathBuf;
 use clap::Subcommand;
 use clap::{ArgAction, Parser, ValueHint};
 
+use super::logging::LogTarget;
+
 #[derive(Parser, Debug)]
 #[command(name = "pueued", about = "Start the Pueue daemon", author, version)]
 pub struct CliArguments {
@@ -11,6 +13,12 @@ pub struct CliArguments {
     #[arg(short, long, action = ArgAction::Count)]
     pub verbose: u8,
 
+    /// Where the daemon's log output should be written to.
+    ///
+    /// `journald` is only available on Linux, if Pueue has been built with the `journald` feature.
+    #[arg(long, value_enum, default_value_t = LogTarget::Stderr)]
+    pub log_target: LogTarget,
+
     /// If this flag is set, the daemon will start and fork itself into the background.
     ///
     /// Beware: Closing the terminal won't kill the daemon any longer.
@@ -22,6 +30,8 @@ pub struct CliArguments {
     ///
     /// This path can also be set via the $PUEUE_CONFIG_PATH environment variable.
     /// The commandline option overwrites the environment variable!
+    ///
+    /// Use `-` to read the config from stdin. No default config file is created in that case.
     #[arg(short, long, value_hint = ValueHint::FilePath)]
     pub config: Option<PathBuf>,
 
@@ -29,6 +39,13 @@ pub struct CliArguments {
     #[arg(short, long)]
     pub profile: Option<String>,
 
+    /// Don't create a default config file if none can be found.
+    ///
+    /// By default, the daemon writes a default config file on its first start.
+    /// With this flag, a missing config file is treated as an error instead.
+    #[arg(long)]
+    pub no_create_config: bool,
+
     #[cfg(target_os = "windows")]
     #[command(subcommand)]
     pub service: Option<ServiceSubcommandEntry>,
@@ -0,0 +1,107 @@
+//! Logger initialization of the daemon.
+use anyhow::Result;
+use clap::ValueEnum;
+use log::warn;
+use simplelog::{Config, ConfigBuilder, LevelFilter, SimpleLogger, TermLogger, TerminalMode};
+
+/// The destination of the daemon's log output.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogTarget {
+    /// Log to stderr.
+    #[default]
+    Stderr,
+    /// Log directly to the systemd journal.
+    /// Only available on Linux, if Pueue has been built with the `journald` feature.
+    Journald,
+}
+
+impl LogTarget {
+    /// The name of this target as it's passed on the commandline.
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            LogTarget::Stderr => "stderr",
+            LogTarget::Journald => "journald",
+        }
+    }
+}
+
+/// Initialize the global logger for the given target.
+pub fn init_logger(level: LevelFilter, target: LogTarget) -> Result<()> {
+    match target {
+        LogTarget::Stderr => {
+            init_terminal_logger(level);
+            Ok(())
+        }
+        LogTarget::Journald => init_journald_logger(level),
+    }
+}
+
+/// Initialize a terminal logger that writes to stderr.
+fn init_terminal_logger(level: LevelFilter) {
+    // Try to initialize the logger with the timezone set to the Local time of the machine.
+    let mut builder = ConfigBuilder::new();
+    let logger_config = match builder.set_time_offset_to_local() {
+        Err(_) => {
+            warn!("Failed to determine the local time of this machine. Fallback to UTC.");
+            Config::default()
+        }
+        Ok(builder) => builder.build(),
+    };
+
+    // Init a terminal logger. If this fails for some reason, try fallback to a SimpleLogger
+    if TermLogger::init(
+        level,
+        logger_config.clone(),
+        TerminalMode::Stderr,
+        simplelog::ColorChoice::Auto,
+    )
+    .is_err()
+    {
+        SimpleLogger::init(level, logger_config).unwrap();
+    }
+}
+
+/// Create a logger that sends its messages to the systemd journal.
+///
+/// Log levels are mapped to their respective journal priorities, i.e. `error` becomes `err`,
+/// `warn` becomes `warning` and so on. Timestamps are added by the journal itself.
+#[cfg(all(feature = "journald", target_os = "linux"))]
+fn journald_logger() -> Result<systemd_journal_logger::JournalLog> {
+    use anyhow::Context;
+
+    let logger = systemd_journal_logger::JournalLog::new()
+        .context("Failed to create journald logger")?
+        .with_syslog_identifier("pueued".to_string());
+
+    Ok(logger)
+}
+
+#[cfg(all(feature = "journald", target_os = "linux"))]
+fn init_journald_logger(level: LevelFilter) -> Result<()> {
+    use anyhow::Context;
+
+    journald_logger()?
+        .install()
+        .context("Failed to install journald logger")?;
+    log::set_max_level(level);
+
+    Ok(())
+}
+
+#[cfg(not(all(feature = "journald", target_os = "linux")))]
+fn init_journald_logger(_level: LevelFilter) -> Result<()> {
+    anyhow::bail!(
+        "Logging to journald is only supported on Linux, if Pueue is built with the `journald` feature."
+    )
+}
+
+#[cfg(all(test, feature = "journald", target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// Make sure that the journald logger can be set up.
+    #[test]
+    fn journald_logger_initializes() {
+        assert!(journald_logger().is_ok());
+    }
+}
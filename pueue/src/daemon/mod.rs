@@ -13,6 +13,7 @@ use pueue_lib::network::protocol::socket_cleanup;
 use pueue_lib::network::secret::init_shared_secret;
 use pueue_lib::settings::Settings;
 use pueue_lib::state::{SharedState, State};
+use tokio::sync::watch;
 use tokio::try_join;
 
 use self::state_helper::{restore_state, save_state};
@@ -20,9 +21,12 @@ use crate::daemon::network::socket::accept_incoming;
 
 mod callbacks;
 pub mod cli;
+pub mod logging;
 mod network;
 mod pid;
 mod process_handler;
+/// Reloading of the configuration while the daemon is running.
+pub mod reload;
 #[cfg(target_os = "windows")]
 pub mod service;
 /// Contains re-usable helper functions, that operate on the pueue-lib state.
@@ -37,15 +41,26 @@ pub mod task_handler;
 /// There are some global operations that crash during tests, such as the ctlc handler.
 /// This is due to the fact, that tests in the same file are executed in multiple threads.
 /// Since the threads own the same global space, this would crash.
-pub async fn run(config_path: Option<PathBuf>, profile: Option<String>, test: bool) -> Result<()> {
+pub async fn run(
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+    create_config: bool,
+    test: bool,
+) -> Result<()> {
     // Try to read settings from the configuration file.
     let (mut settings, config_found) =
         Settings::read(&config_path).context("Error while reading configuration.")?;
 
     // We couldn't find a configuration file.
     // This probably means that Pueue has been started for the first time and we have to create a
-    // default config file once.
+    // default config file once, unless the user explicitly asked us not to.
     if !config_found {
+        if !create_config {
+            bail!(
+                "No configuration file could be found and creating a default one has been \
+                disabled via --no-create-config."
+            );
+        }
         if let Err(error) = settings.save(&config_path) {
             bail!("Failed saving config file: {error:?}.");
         }
@@ -67,7 +82,7 @@ pub async fn run(config_path: Option<PathBuf>, profile: Option<String>, test: bo
     // Restore the previous state and save any changes that might have happened during this
     // process. If no previous state exists, just create a new one.
     // Create a new empty state if any errors occur, but print the error message.
-    let state = match restore_state(&settings.shared.pueue_directory()) {
+    let state = match restore_state(&settings) {
         Ok(Some(state)) => state,
         Ok(None) => State::new(),
         Err(error) => {
@@ -88,10 +103,19 @@ pub async fn run(config_path: Option<PathBuf>, profile: Option<String>, test: bo
         setup_signal_panic_handling(&settings, state.clone())?;
     }
 
+    // Settings that are reloaded on SIGHUP are published via this channel.
+    let (settings_sender, settings_receiver) = watch::channel(settings.clone());
+    #[cfg(unix)]
+    if !test {
+        reload::setup_reload_handling(config_path, profile, settings_sender)?;
+    }
+    #[cfg(not(unix))]
+    drop(settings_sender);
+
     // Run both the task handler and the message handler in the same tokio task.
     // If any of them fails, return an error immediately.
-    let task_handler = task_handler::run(state.clone(), settings.clone());
-    let message_handler = accept_incoming(settings.clone(), state.clone());
+    let task_handler = task_handler::run(state.clone(), settings_receiver.clone());
+    let message_handler = accept_incoming(settings_receiver, state.clone());
     try_join!(task_handler, message_handler).map(|_| ())
 }
 
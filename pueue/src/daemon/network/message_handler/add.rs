@@ -1,22 +1,31 @@
+use std::collections::BTreeMap;
+
 use chrono::Local;
 use pueue_lib::aliasing::insert_alias;
 use pueue_lib::failure_msg;
+use pueue_lib::log::clean_log_handles;
 use pueue_lib::network::message::*;
-use pueue_lib::state::{GroupStatus, SharedState};
+use pueue_lib::process_helper::ensure_user_permission;
+use pueue_lib::state::GroupStatus;
 use pueue_lib::task::{Task, TaskStatus};
 
 use super::*;
 use crate::daemon::process_handler;
-use crate::daemon::state_helper::save_state;
+use crate::daemon::state_helper::{mark_tasks_changed, record_status, save_state, LockedState};
 use crate::ok_or_save_state_failure;
 
 /// Invoked when calling `pueue add`.
 /// Queues a new task to the state.
 /// If the start_immediately flag is set, send a StartMessage to the task handler.
-pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -> Message {
-    let mut state = state.lock().unwrap();
-    if let Err(message) = ensure_group_exists(&mut state, &message.group) {
-        return message;
+pub fn add_task(settings: &Settings, state: &mut LockedState, message: AddMessage) -> Message {
+    // The group is only created, once all other checks passed.
+    let create_group = !state.groups.contains_key(&message.group);
+    if create_group && !message.create_group {
+        return GroupNotFoundMessage {
+            group: message.group,
+            existing_groups: state.groups.keys().cloned().collect(),
+        }
+        .into();
     }
 
     // Ensure that specified dependencies actually exist.
@@ -29,6 +38,71 @@ pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -
         return failure_msg!("Unable to setup dependencies : task(s) {not_found:?} not found",);
     }
 
+    // Check whether there's already a task with the same key.
+    // That task is either replaced by the new task, which then takes over its id, or adding the
+    // new task fails.
+    let existing_id = message
+        .key
+        .as_deref()
+        .and_then(|key| state.task_id_by_key(key));
+    let replaced_id = match existing_id {
+        None => None,
+        Some(id) if !message.replace => {
+            return failure_msg!(
+                "A task with the key \"{}\" already exists (id {id})",
+                message.key.unwrap_or_default()
+            );
+        }
+        Some(id) => {
+            let status = &state.tasks.get(&id).expect("The task exists.").status;
+            if !matches!(
+                status,
+                TaskStatus::Queued { .. } | TaskStatus::Stashed { .. } | TaskStatus::Done { .. }
+            ) {
+                return failure_msg!("Task {id} cannot be replaced, as it's currently {status}");
+            }
+            Some(id)
+        }
+    };
+
+    // Ensure that the new task doesn't end up being one of its own ancestors.
+    let new_id = replaced_id.unwrap_or_else(|| state.next_task_id());
+    if state.would_create_cycle(new_id, &message.dependencies) {
+        return failure_msg!(
+            "Unable to setup dependencies : task(s) {:?} would create a dependency cycle",
+            message.dependencies
+        );
+    }
+
+    // The systemd scope that enforces the memory limit belongs to the daemon's user.
+    // Switching to another user inside of that scope isn't supported.
+    if message.user.is_some() && message.memory_limit.is_some() {
+        return failure_msg!("Memory limits can't be combined with a different user.");
+    }
+
+    // The working directory is created by the daemon, so it would end up belonging to the
+    // daemon's user instead of the task's user.
+    if message.create_workdir {
+        if message.user.is_some() {
+            return failure_msg!(
+                "Creating the working directory can't be combined with a different user."
+            );
+        }
+        if !message.path.is_absolute() {
+            return failure_msg!(
+                "The working directory {:?} has to be an absolute path to be created.",
+                message.path
+            );
+        }
+    }
+
+    // Make sure the task can actually be executed as the requested user.
+    if let Some(user) = &message.user {
+        if let Err(err) = ensure_user_permission(user) {
+            return failure_msg!("{err:#}");
+        }
+    }
+
     // Create a new task and add it to the state.
     let mut task = Task::new(
         message.command,
@@ -42,6 +116,16 @@ pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -
         message.priority.unwrap_or(0),
         message.label,
     );
+    task.create_workdir = message.create_workdir;
+    task.user = message.user;
+    task.raw_command = message.raw_command;
+    task.memory_limit = message.memory_limit;
+    task.expire_at = message.expire_at;
+    task.timeout = message.timeout;
+    task.key = message.key;
+    task.on_success = message.on_success;
+    task.on_failure = message.on_failure;
+    task.success_signals = message.success_signals;
 
     // Handle if the command is to be stashed and/or automatically enqueued later.
     if message.stashed || message.enqueue_at.is_some() {
@@ -49,6 +133,7 @@ pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -
             enqueue_at: message.enqueue_at,
         };
     }
+    record_status(settings, &mut task);
 
     // Check if there're any aliases that should be applied.
     // If one is found, we expand the command, otherwise we just take the original command.
@@ -56,12 +141,27 @@ pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -
     //
     // This gives us better debugging capabilities and the user can opt to either show the
     // original command or the expanded command in their `status` view.
-    task.command = insert_alias(settings, task.original_command.clone());
+    //
+    // Raw commands aren't interpreted in any way, which is why aliases don't apply to them.
+    if task.raw_command.is_none() {
+        task.command = insert_alias(settings, task.original_command.clone());
+    }
+
+    // Reject commands that're forbidden by the daemon's configuration.
+    if let Err(failure) = check_command_denylist(settings, &task.original_command, &task.command) {
+        return failure;
+    }
 
     // Sort and deduplicate dependency ids.
     task.dependencies.sort_unstable();
     task.dependencies.dedup();
 
+    // Create the requested group with its worker pool.
+    if create_group {
+        state.create_group(&task.group);
+        state.children.0.insert(task.group.clone(), BTreeMap::new());
+    }
+
     // Check if the task's group is paused before we pass it to the state
     let group_status = state
         .groups
@@ -69,30 +169,272 @@ pub fn add_task(settings: &Settings, state: &SharedState, message: AddMessage) -
         .expect("We ensured that the group exists.")
         .status;
     let group_is_paused = matches!(group_status, GroupStatus::Paused);
+    let group = task.group.clone();
 
     // Add the task and persist the state.
-    let task_id = state.add_task(task);
-    ok_or_save_state_failure!(save_state(&state, settings));
+    // A replaced task is overwritten, including its log.
+    let task_id = match replaced_id {
+        Some(id) => {
+            task.id = id;
+            state.tasks.insert(id, task);
+            clean_log_handles(id, &settings.shared.pueue_directory());
+            id
+        }
+        None => state.add_task(task),
+    };
+    mark_tasks_changed(settings, [task_id]);
+    ok_or_save_state_failure!(save_state(state, settings));
+
+    // Resume the task's group, if the client explicitly asked for it.
+    let group_is_resumed = group_is_paused && message.start_group;
+    if group_is_resumed {
+        process_handler::start::start(settings, state, TaskSelection::Group(group));
+    }
 
     // Notify the task handler, in case the client wants to start the task immediately.
     if message.start_immediately {
-        process_handler::start::start(settings, &mut state, TaskSelection::TaskIds(vec![task_id]));
+        process_handler::start::start(settings, state, TaskSelection::TaskIds(vec![task_id]));
+    }
+
+    // The client wants to work with the created task itself.
+    if message.return_task {
+        let task = state
+            .tasks
+            .get(&task_id)
+            .expect("The task has just been added.");
+        return Message::AddResponse(Box::new(task.clone()));
     }
 
     // Create the customized response for the client.
+    let added = if replaced_id.is_some() {
+        format!("Task {task_id} has been replaced")
+    } else {
+        format!("New task added (id {task_id})")
+    };
     let mut response = if message.print_task_id {
         task_id.to_string()
     } else if let Some(enqueue_at) = message.enqueue_at {
         let enqueue_at = format_datetime(settings, &enqueue_at);
-        format!("New task added (id {task_id}). It will be enqueued at {enqueue_at}")
+        format!("{added}. It will be enqueued at {enqueue_at}")
     } else {
-        format!("New task added (id {task_id}).")
+        format!("{added}.")
     };
 
-    // Notify the user if the task's group is paused
-    if !message.print_task_id && group_is_paused {
-        response.push_str("\nThe group of this task is currently paused!")
+    // Notify the user if the task's group is paused or has just been resumed.
+    if !message.print_task_id && group_is_resumed {
+        response.push_str("\nThe group of this task has been resumed.")
+    } else if !message.print_task_id && group_is_paused {
+        response.push_str(
+            "\nThe group of this task is currently paused! Use `--start-group` to resume it.",
+        )
     }
 
     create_success_message(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use pueue_lib::settings::CommandDenylist;
+
+    use super::super::fixtures::*;
+    use super::*;
+
+    fn add_message(dependencies: Vec<usize>) -> AddMessage {
+        AddMessage {
+            command: "ls".to_string(),
+            path: std::env::temp_dir(),
+            group: PUEUE_DEFAULT_GROUP.to_string(),
+            dependencies,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reject_direct_dependency_cycle() {
+        let (state, settings, _tempdir) = get_stub_state();
+        // Task 4 depends on the id that'll be assigned to the next task.
+        state
+            .lock()
+            .unwrap()
+            .tasks
+            .get_mut(&4)
+            .unwrap()
+            .dependencies = vec![5];
+
+        let message = add_task(&settings, &mut state.lock().unwrap(), add_message(vec![4]));
+        assert!(matches!(message, Message::Failure(_)), "{message:?}");
+        assert_eq!(state.lock().unwrap().tasks.len(), 5);
+    }
+
+    #[test]
+    fn reject_transitive_dependency_cycle() {
+        let (state, settings, _tempdir) = get_stub_state();
+        {
+            // 2 -> 5, 3 -> 2, 4 -> 3. Adding 5 -> 4 closes the cycle.
+            let mut state = state.lock().unwrap();
+            state.tasks.get_mut(&2).unwrap().dependencies = vec![5];
+            state.tasks.get_mut(&3).unwrap().dependencies = vec![2];
+            state.tasks.get_mut(&4).unwrap().dependencies = vec![3];
+        }
+
+        let message = add_task(&settings, &mut state.lock().unwrap(), add_message(vec![4]));
+        assert!(matches!(message, Message::Failure(_)), "{message:?}");
+
+        // Depending on tasks outside of the cycle is fine.
+        let message = add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message(vec![0, 1]),
+        );
+        assert!(matches!(message, Message::Success(_)), "{message:?}");
+    }
+
+    fn add_message_with_key(command: &str, key: &str, replace: bool) -> AddMessage {
+        AddMessage {
+            command: command.to_string(),
+            key: Some(key.to_string()),
+            replace,
+            ..add_message(Vec::new())
+        }
+    }
+
+    #[test]
+    fn add_with_key() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        let message = add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("ls", "job", false),
+        );
+        assert!(matches!(message, Message::Success(_)), "{message:?}");
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.task_id_by_key("job"), Some(5));
+        assert_eq!(state.tasks[&5].key, Some("job".to_string()));
+    }
+
+    #[test]
+    fn reject_duplicate_key() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("ls", "job", false),
+        );
+        let message = add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("pwd", "job", false),
+        );
+        assert!(matches!(message, Message::Failure(_)), "{message:?}");
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.tasks.len(), 6);
+        assert_eq!(state.tasks[&5].command, "ls");
+    }
+
+    #[test]
+    fn replace_duplicate_key() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("ls", "job", false),
+        );
+        let message = add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("pwd", "job", true),
+        );
+        assert!(matches!(message, Message::Success(_)), "{message:?}");
+
+        // The new task took over the id of the replaced task.
+        let state = state.lock().unwrap();
+        assert_eq!(state.tasks.len(), 6);
+        assert_eq!(state.task_id_by_key("job"), Some(5));
+        assert_eq!(state.tasks[&5].command, "pwd");
+    }
+
+    #[test]
+    fn reject_replacing_running_task() {
+        let (state, settings, _tempdir) = get_stub_state();
+        // Task 3 is running.
+        state.lock().unwrap().tasks.get_mut(&3).unwrap().key = Some("job".to_string());
+
+        let message = add_task(
+            &settings,
+            &mut state.lock().unwrap(),
+            add_message_with_key("pwd", "job", true),
+        );
+        assert!(matches!(message, Message::Failure(_)), "{message:?}");
+        assert_eq!(state.lock().unwrap().tasks.len(), 5);
+    }
+
+    #[test]
+    fn reject_unknown_group() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        let message = AddMessage {
+            group: "unknown".to_string(),
+            ..add_message(Vec::new())
+        };
+        let response = add_task(&settings, &mut state.lock().unwrap(), message);
+        let Message::GroupNotFound(response) = response else {
+            panic!("Expected GroupNotFound, got {response:?}");
+        };
+        assert_eq!(response.group, "unknown");
+        assert_eq!(
+            response.existing_groups,
+            vec![PUEUE_DEFAULT_GROUP.to_string()]
+        );
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.tasks.len(), 5);
+        assert!(!state.groups.contains_key("unknown"));
+    }
+
+    #[test]
+    fn create_unknown_group() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        let message = AddMessage {
+            group: "unknown".to_string(),
+            create_group: true,
+            ..add_message(Vec::new())
+        };
+        let response = add_task(&settings, &mut state.lock().unwrap(), message);
+        assert!(matches!(response, Message::Success(_)), "{response:?}");
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.tasks[&5].group, "unknown");
+        assert!(state.groups.contains_key("unknown"));
+        assert!(state.children.0.contains_key("unknown"));
+    }
+    #[test]
+    fn reject_denied_command() {
+        let (state, mut settings, _tempdir) = get_stub_state();
+        settings.daemon.command_denylist = CommandDenylist::new(&["^rm -rf /$"]).unwrap();
+
+        let message = AddMessage {
+            command: "rm -rf /".to_string(),
+            ..add_message(Vec::new())
+        };
+        let response = add_task(&settings, &mut state.lock().unwrap(), message);
+        let Message::Failure(text) = response else {
+            panic!("Expected the command to be denied, got {response:?}");
+        };
+        assert!(text.contains("command_denylist"), "{text}");
+        assert_eq!(state.lock().unwrap().tasks.len(), 5);
+
+        // Commands that don't match any pattern are added as usual.
+        let message = AddMessage {
+            command: "rm -rf /tmp/test".to_string(),
+            ..add_message(Vec::new())
+        };
+        let response = add_task(&settings, &mut state.lock().unwrap(), message);
+        assert!(matches!(response, Message::Success(_)), "{response:?}");
+    }
+}
@@ -0,0 +1,86 @@
+use pueue_lib::{network::message::*, settings::Settings};
+
+use crate::{
+    daemon::{
+        network::message_handler::ok_or_failure_message,
+        state_helper::{mark_tasks_changed, save_state, LockedState},
+    },
+    ok_or_save_state_failure,
+};
+
+/// Invoked on `pueue annotate`.
+/// Set or remove the note of a task.
+/// Notes can be attached to tasks in any state, as they're mostly useful for finished tasks.
+pub fn annotate(settings: &Settings, state: &mut LockedState, message: AnnotateMessage) -> Message {
+    let task_id = message.task_id;
+    let Some(task) = state.tasks.get_mut(&task_id) else {
+        return create_failure_message(format!("No task with id {task_id}"));
+    };
+
+    let response = match message.note {
+        Some(note) => {
+            task.note = Some(note);
+            create_success_message(format!("Note of task {task_id} has been set."))
+        }
+        None => {
+            if task.note.take().is_none() {
+                return create_failure_message(format!("Task {task_id} doesn't have a note."));
+            }
+            create_success_message(format!("Note of task {task_id} has been removed."))
+        }
+    };
+
+    mark_tasks_changed(settings, [task_id]);
+    ok_or_save_state_failure!(save_state(state, settings));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn set_and_remove_note() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        // Notes can be attached to finished tasks.
+        let message = AnnotateMessage {
+            task_id: 1,
+            note: Some("Flaky, rerun on monday".to_string()),
+        };
+        let response = annotate(&settings, &mut state.lock().unwrap(), message);
+        assert!(matches!(response, Message::Success(_)));
+        {
+            let state = state.lock().unwrap();
+            assert_eq!(
+                state.tasks[&1].note,
+                Some("Flaky, rerun on monday".to_string())
+            );
+        }
+
+        let message = AnnotateMessage {
+            task_id: 1,
+            note: None,
+        };
+        let response = annotate(&settings, &mut state.lock().unwrap(), message);
+        assert!(matches!(response, Message::Success(_)));
+        let state = state.lock().unwrap();
+        assert_eq!(state.tasks[&1].note, None);
+    }
+
+    #[test]
+    fn annotate_missing_task() {
+        let (state, settings, _tempdir) = get_stub_state();
+
+        let message = AnnotateMessage {
+            task_id: 10,
+            note: Some("note".to_string()),
+        };
+        let response = annotate(&settings, &mut state.lock().unwrap(), message);
+        assert!(matches!(response, Message::Failure(_)));
+    }
+}
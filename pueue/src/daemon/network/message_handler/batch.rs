@@ -0,0 +1,35 @@
+use pueue_lib::network::message::*;
+use pueue_lib::settings::Settings;
+
+use super::handle_locked_message;
+use crate::daemon::state_helper::LockedState;
+
+/// Invoked when a client sends multiple messages in one go.
+/// All messages are executed in the given order and their responses are returned in that
+/// same order.
+/// The state stays locked for the whole batch, so no other client or the task handler can
+/// interfere in between.
+///
+/// Nested batches and messages that need special handling of the connection, such as log
+/// streams or daemon shutdowns, aren't allowed in a batch.
+/// If a batch contains any of those, the whole batch is rejected and nothing is executed.
+pub fn batch(settings: &Settings, state: &mut LockedState, messages: Vec<Message>) -> Message {
+    let contains_forbidden_message = messages.iter().any(|message| {
+        matches!(
+            message,
+            Message::Batch(_) | Message::StreamRequest(_) | Message::DaemonShutdown(_)
+        )
+    });
+    if contains_forbidden_message {
+        return create_failure_message(
+            "Batches must not contain other batches, stream requests or shutdown requests.",
+        );
+    }
+
+    let responses = messages
+        .into_iter()
+        .map(|message| handle_locked_message(message, state, settings))
+        .collect();
+
+    Message::BatchResponse(responses)
+}
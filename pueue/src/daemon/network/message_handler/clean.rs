@@ -1,10 +1,11 @@
 use pueue_lib::log::clean_log_handles;
 use pueue_lib::network::message::*;
-use pueue_lib::state::SharedState;
 use pueue_lib::task::{TaskResult, TaskStatus};
 
 use super::*;
-use crate::daemon::state_helper::{is_task_removable, save_state};
+use crate::daemon::state_helper::{
+    append_history, is_task_removable, mark_tasks_changed, save_state, LockedState,
+};
 use crate::ok_or_save_state_failure;
 
 fn construct_success_clean_message(message: CleanMessage) -> String {
@@ -24,15 +25,16 @@ fn construct_success_clean_message(message: CleanMessage) -> String {
 
 /// Invoked when calling `pueue clean`.
 /// Remove all failed or done tasks from the state.
-pub fn clean(settings: &Settings, state: &SharedState, message: CleanMessage) -> Message {
-    let mut state = state.lock().unwrap();
+/// The removed tasks are added to the persisted task history.
+pub fn clean(settings: &Settings, state: &mut LockedState, message: CleanMessage) -> Message {
+    let mut history = Vec::new();
 
     let filtered_tasks =
         state.filter_tasks(|task| matches!(task.status, TaskStatus::Done { .. }), None);
 
     for task_id in &filtered_tasks.matching_ids {
         // Ensure the task is removable, i.e. there are no dependant tasks.
-        if !is_task_removable(&state, task_id, &[]) {
+        if !is_task_removable(state, task_id, &[]) {
             continue;
         }
 
@@ -58,11 +60,16 @@ pub fn clean(settings: &Settings, state: &SharedState, message: CleanMessage) ->
                 }
             }
         }
-        let _ = state.tasks.remove(task_id).unwrap();
+        let task = state.tasks.remove(task_id).unwrap();
+        mark_tasks_changed(settings, [*task_id]);
+        history.extend(HistoryEntry::from_task(&task));
         clean_log_handles(*task_id, &settings.shared.pueue_directory());
     }
 
-    ok_or_save_state_failure!(save_state(&state, settings));
+    ok_or_save_state_failure!(save_state(state, settings));
+    if let Err(error) = append_history(&history, settings) {
+        return failure_msg!("Tasks have been removed, but the history couldn't be saved: {error}");
+    }
 
     create_success_message(construct_success_clean_message(message))
 }
@@ -122,7 +129,11 @@ mod tests {
         let (state, settings, _tempdir) = get_stub_state();
 
         // Only task 1 will be removed, since it's the only TaskStatus with `Done`.
-        let message = clean(&settings, &state, get_message(false, None));
+        let message = clean(
+            &settings,
+            &mut state.lock().unwrap(),
+            get_message(false, None),
+        );
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -139,7 +150,11 @@ mod tests {
         let (state, settings, _tempdir) = get_clean_test_state(&[PUEUE_DEFAULT_GROUP]);
 
         // All finished tasks should removed when calling default `clean`.
-        let message = clean(&settings, &state, get_message(false, None));
+        let message = clean(
+            &settings,
+            &mut state.lock().unwrap(),
+            get_message(false, None),
+        );
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -157,7 +172,11 @@ mod tests {
 
         // Only successfully finished tasks should get removed when
         // calling `clean` with the `successful_only` flag.
-        let message = clean(&settings, &state, get_message(true, None));
+        let message = clean(
+            &settings,
+            &mut state.lock().unwrap(),
+            get_message(true, None),
+        );
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -176,7 +195,11 @@ mod tests {
         let (state, settings, _tempdir) = get_clean_test_state(&[PUEUE_DEFAULT_GROUP, "other"]);
 
         // All finished tasks should removed in selected group (other)
-        let message = clean(&settings, &state, get_message(false, Some("other".into())));
+        let message = clean(
+            &settings,
+            &mut state.lock().unwrap(),
+            get_message(false, Some("other".into())),
+        );
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -199,7 +222,11 @@ mod tests {
         let (state, settings, _tempdir) = get_clean_test_state(&[PUEUE_DEFAULT_GROUP, "other"]);
 
         // Only successfully finished tasks should removed in the 'other' group
-        let message = clean(&settings, &state, get_message(true, Some("other".into())));
+        let message = clean(
+            &settings,
+            &mut state.lock().unwrap(),
+            get_message(true, Some("other".into())),
+        );
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
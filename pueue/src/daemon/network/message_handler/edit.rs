@@ -1,19 +1,17 @@
 use pueue_lib::aliasing::insert_alias;
 use pueue_lib::network::message::*;
-use pueue_lib::state::SharedState;
 use pueue_lib::task::TaskStatus;
 use pueue_lib::{failure_msg, success_msg};
 
 use super::*;
-use crate::daemon::state_helper::save_state;
+use crate::daemon::state_helper::{mark_tasks_changed, save_state, set_status, LockedState};
 use crate::ok_or_save_state_failure;
 
 /// Invoked when calling `pueue edit`.
 /// If a user wants to edit a message, we need to send him the current command.
 /// Lock the task to prevent execution, before the user has finished editing the command.
-pub fn edit_request(state: &SharedState, task_ids: Vec<usize>) -> Message {
+pub fn edit_request(settings: &Settings, state: &mut LockedState, task_ids: Vec<usize>) -> Message {
     // Check whether the task exists and is queued/stashed. Abort if that's not the case.
-    let mut state = state.lock().unwrap();
     let mut editable_tasks: Vec<EditableTask> = Vec::new();
     for task_id in task_ids {
         match state.tasks.get_mut(&task_id) {
@@ -21,9 +19,9 @@ pub fn edit_request(state: &SharedState, task_ids: Vec<usize>) -> Message {
                 if !task.is_queued() && !task.is_stashed() {
                     return create_failure_message("You can only edit a queued/stashed task");
                 }
-                task.status = TaskStatus::Locked {
-                    previous_status: Box::new(task.status.clone()),
-                };
+                let previous_status = Box::new(task.status.clone());
+                set_status(settings, task, TaskStatus::Locked { previous_status });
+                mark_tasks_changed(settings, [task_id]);
 
                 editable_tasks.push(EditableTask::from(&*task));
             }
@@ -38,11 +36,10 @@ pub fn edit_request(state: &SharedState, task_ids: Vec<usize>) -> Message {
 /// Now we actually update the message with the updated command from the client.
 pub fn edit(
     settings: &Settings,
-    state: &SharedState,
+    state: &mut LockedState,
     editable_tasks: Vec<EditableTask>,
 ) -> Message {
     // Check whether the task exists and is locked. Abort if that's not the case.
-    let mut state = state.lock().unwrap();
     for editable_task in editable_tasks {
         match state.tasks.get_mut(&editable_task.id) {
             Some(task) => {
@@ -54,16 +51,34 @@ pub fn edit(
                 };
 
                 // Restore the task to its previous state.
-                task.status = *previous_status.clone();
+                let previous_status = *previous_status.clone();
+                set_status(settings, task, previous_status);
+                mark_tasks_changed(settings, [editable_task.id]);
+
+                // Reject edited commands that're forbidden by the daemon's configuration.
+                // The task keeps its previous command in that case.
+                let command = insert_alias(settings, editable_task.command.clone());
+                if let Err(failure) =
+                    check_command_denylist(settings, &editable_task.command, &command)
+                {
+                    ok_or_save_state_failure!(save_state(state, settings));
+                    return failure;
+                }
+
+                // A raw command no longer matches an edited command.
+                // The edited command is executed via the shell instead.
+                if task.command != editable_task.command {
+                    task.raw_command = None;
+                }
 
                 // Update all properties to the edited values.
-                task.original_command = editable_task.command.clone();
-                task.command = insert_alias(settings, editable_task.command);
+                task.original_command = editable_task.command;
+                task.command = command;
                 task.path = editable_task.path;
                 task.label = editable_task.label;
                 task.priority = editable_task.priority;
 
-                ok_or_save_state_failure!(save_state(&state, settings));
+                ok_or_save_state_failure!(save_state(state, settings));
             }
             None => return failure_msg!("Task to edit has gone away: {}", editable_task.id),
         }
@@ -73,9 +88,8 @@ pub fn edit(
 }
 
 /// Invoked if a client fails to edit a task and asks the daemon to restore the task's status.
-pub fn edit_restore(state: &SharedState, task_ids: Vec<usize>) -> Message {
+pub fn edit_restore(settings: &Settings, state: &mut LockedState, task_ids: Vec<usize>) -> Message {
     // Check whether the task exists and is queued/stashed. Abort if that's not the case.
-    let mut state = state.lock().unwrap();
     let mut failed_tasks = Vec::new();
     for task_id in &task_ids {
         match state.tasks.get_mut(task_id) {
@@ -86,7 +100,9 @@ pub fn edit_restore(state: &SharedState, task_ids: Vec<usize>) -> Message {
                 };
 
                 // Restore the task to its previous state.
-                task.status = *previous_status.clone();
+                let previous_status = *previous_status.clone();
+                set_status(settings, task, previous_status);
+                mark_tasks_changed(settings, [*task_id]);
             }
             None => failed_tasks.push(format!("No task with id {}! Cannot be unlocked.", task_id)),
         }
@@ -2,19 +2,23 @@ use chrono::Local;
 use pueue_lib::{
     network::message::*,
     settings::Settings,
-    state::SharedState,
     success_msg,
     task::{Task, TaskStatus},
 };
 
 use crate::daemon::network::response_helper::*;
+use crate::daemon::process_handler;
+use crate::daemon::state_helper::{mark_tasks_changed, set_status, LockedState};
 
 use super::format_datetime;
 
 /// Invoked when calling `pueue enqueue`.
 /// Enqueue specific stashed tasks.
-pub fn enqueue(settings: &Settings, state: &SharedState, message: EnqueueMessage) -> Message {
-    let mut state = state.lock().unwrap();
+///
+/// If `start_immediately` is set, the tasks are started right away, just like `pueue add
+/// --immediate`. This ignores the parallel limit of the group, meaning that a full group
+/// exceeds its limit by the amount of started tasks.
+pub fn enqueue(settings: &Settings, state: &mut LockedState, message: EnqueueMessage) -> Message {
     // Get the affected task ids, based on the task selection.
     let selected_task_ids = match message.tasks {
         TaskSelection::TaskIds(ref task_ids) => state
@@ -66,16 +70,18 @@ pub fn enqueue(settings: &Settings, state: &SharedState, message: EnqueueMessage
 
         // Either specify the point of time the task should be enqueued or enqueue the task
         // immediately.
-        if message.enqueue_at.is_some() {
-            task.status = TaskStatus::Stashed {
+        let status = if message.enqueue_at.is_some() {
+            TaskStatus::Stashed {
                 enqueue_at: message.enqueue_at,
-            };
+            }
         } else {
-            task.status = TaskStatus::Queued {
+            TaskStatus::Queued {
                 enqueued_at: Local::now(),
-            };
-        }
+            }
+        };
+        set_status(settings, task, status);
     }
+    mark_tasks_changed(settings, selected_task_ids.iter().copied());
 
     let matching_function = if message.enqueue_at.is_some() {
         |task: &Task| {
@@ -91,7 +97,8 @@ pub fn enqueue(settings: &Settings, state: &SharedState, message: EnqueueMessage
     };
 
     // Construct a response depending on the selected tasks.
-    if let Some(enqueue_at) = &message.enqueue_at {
+    let start_immediately = message.start_immediately && message.enqueue_at.is_none();
+    let response = if let Some(enqueue_at) = &message.enqueue_at {
         let enqueue_at = format_datetime(settings, enqueue_at);
 
         match &message.tasks {
@@ -99,7 +106,7 @@ pub fn enqueue(settings: &Settings, state: &SharedState, message: EnqueueMessage
                 &format!("Stashed tasks will be enqueued at {enqueue_at}"),
                 task_ids.clone(),
                 matching_function,
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => {
                 success_msg!("Enqueue stashed tasks of group {group} at {enqueue_at}.",)
@@ -109,19 +116,32 @@ pub fn enqueue(settings: &Settings, state: &SharedState, message: EnqueueMessage
             }
         }
     } else {
+        let action = if start_immediately {
+            "enqueued and started"
+        } else {
+            "enqueued"
+        };
+
         match &message.tasks {
             TaskSelection::TaskIds(task_ids) => task_action_response_helper(
-                "Stashed tasks have been enqueued",
+                &format!("Stashed tasks have been {action}"),
                 task_ids.clone(),
                 matching_function,
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => {
-                success_msg!("All stashed tasks of group \"{group}\" have been enqueued.")
+                success_msg!("All stashed tasks of group \"{group}\" have been {action}.")
             }
             TaskSelection::All => {
-                success_msg!("All stashed tasks have been enqueued.")
+                success_msg!("All stashed tasks have been {action}.")
             }
         }
+    };
+
+    // Force-start the freshly enqueued tasks, if requested.
+    if start_immediately {
+        process_handler::start::start(settings, state, TaskSelection::TaskIds(selected_task_ids));
     }
+
+    response
 }
@@ -1,7 +1,10 @@
-use pueue_lib::{network::message::*, settings::Settings, state::SharedState};
+use pueue_lib::{network::message::*, settings::Settings};
 
 use crate::{
-    daemon::{network::message_handler::ok_or_failure_message, state_helper::save_state},
+    daemon::{
+        network::message_handler::ok_or_failure_message,
+        state_helper::{mark_tasks_changed, save_state, LockedState},
+    },
     ok_or_save_state_failure,
 };
 
@@ -9,9 +12,7 @@ use crate::{
 /// Manage environment variables for tasks.
 /// - Set environment variables
 /// - Unset environment variables
-pub fn env(settings: &Settings, state: &SharedState, message: EnvMessage) -> Message {
-    let mut state = state.lock().unwrap();
-
+pub fn env(settings: &Settings, state: &mut LockedState, message: EnvMessage) -> Message {
     let message = match message {
         EnvMessage::Set {
             task_id,
@@ -27,6 +28,7 @@ pub fn env(settings: &Settings, state: &SharedState, message: EnvMessage) -> Mes
             }
 
             task.envs.insert(key, value);
+            mark_tasks_changed(settings, [task_id]);
 
             create_success_message("Environment variable set.")
         }
@@ -40,7 +42,10 @@ pub fn env(settings: &Settings, state: &SharedState, message: EnvMessage) -> Mes
             }
 
             match task.envs.remove(&key) {
-                Some(_) => create_success_message("Environment variable unset."),
+                Some(_) => {
+                    mark_tasks_changed(settings, [task_id]);
+                    create_success_message("Environment variable unset.")
+                }
                 None => create_failure_message(format!(
                     "No environment variable with key '{key}' found."
                 )),
@@ -50,7 +55,7 @@ pub fn env(settings: &Settings, state: &SharedState, message: EnvMessage) -> Mes
 
     // Save the state if there were any changes.
     if let Message::Success(_) = message {
-        ok_or_save_state_failure!(save_state(&state, settings));
+        ok_or_save_state_failure!(save_state(state, settings));
     }
 
     message
@@ -1,18 +1,21 @@
 use std::collections::BTreeMap;
 
+use chrono::{DateTime, Local, TimeDelta};
 use pueue_lib::{
     failure_msg,
     network::message::*,
     settings::Settings,
-    state::{SharedState, PUEUE_DEFAULT_GROUP},
+    state::{State, PUEUE_DEFAULT_GROUP},
     success_msg,
+    task::{TaskResult, TaskStatus},
 };
 
 use crate::daemon::network::message_handler::ok_or_failure_message;
 use crate::{
     daemon::{
-        network::response_helper::ensure_group_exists, process_handler::initiate_shutdown,
-        state_helper::save_state,
+        network::response_helper::ensure_group_exists,
+        process_handler::initiate_shutdown,
+        state_helper::{save_state, LockedState},
     },
     ok_or_save_state_failure,
 };
@@ -22,20 +25,21 @@ use crate::{
 /// - Show groups
 /// - Add group
 /// - Remove group
-pub fn group(settings: &Settings, state: &SharedState, message: GroupMessage) -> Message {
-    let mut state = state.lock().unwrap();
-
+/// - Set the parallel tasks of new groups
+pub fn group(settings: &Settings, state: &mut LockedState, message: GroupMessage) -> Message {
     match message {
         GroupMessage::List => {
             // Return information about all groups to the client.
             GroupResponseMessage {
                 groups: state.groups.clone(),
+                stats: group_stats(state, settings.daemon.group_stats_window, Local::now()),
             }
             .into()
         }
         GroupMessage::Add {
             name,
             parallel_tasks,
+            description,
         } => {
             if state.groups.contains_key(&name) {
                 return failure_msg!("Group \"{name}\" already exists");
@@ -45,16 +49,23 @@ pub fn group(settings: &Settings, state: &SharedState, message: GroupMessage) ->
             if let Some(parallel_tasks) = parallel_tasks {
                 group.parallel_tasks = parallel_tasks;
             }
+            group.description = description;
             // Create the worker pool.
             state.children.0.insert(name.clone(), BTreeMap::new());
 
             // Persist the state.
-            ok_or_save_state_failure!(save_state(&state, settings));
+            ok_or_save_state_failure!(save_state(state, settings));
 
             success_msg!("New group \"{name}\" has been created")
         }
+        GroupMessage::SetDefaultParallel(parallel_tasks) => {
+            // This only affects groups that're created from now on.
+            state.default_parallel_tasks = Some(parallel_tasks);
+
+            success_msg!("New groups will be created with {parallel_tasks} parallel tasks")
+        }
         GroupMessage::Remove(group) => {
-            if let Err(message) = ensure_group_exists(&mut state, &group) {
+            if let Err(message) = ensure_group_exists(state, &group) {
                 return message;
             }
 
@@ -73,11 +84,11 @@ pub fn group(settings: &Settings, state: &SharedState, message: GroupMessage) ->
             // internal datastructures, which is really bad.
             if let Some(pool) = state.children.0.get(&group) {
                 if !pool.is_empty() {
-                    initiate_shutdown(settings, &mut state, Shutdown::Emergency);
+                    initiate_shutdown(settings, state, Shutdown::Emergency);
                     return failure_msg!("Encountered a non-empty worker pool, while removing a group. This is a critical error. Please report this bug.");
                 }
             } else {
-                initiate_shutdown(settings, &mut state, Shutdown::Emergency);
+                initiate_shutdown(settings, state, Shutdown::Emergency);
                 return failure_msg!("Encountered an group without an worker pool, while removing a group. This is a critical error. Please report this bug.");
             }
 
@@ -89,9 +100,117 @@ pub fn group(settings: &Settings, state: &SharedState, message: GroupMessage) ->
             state.children.0.remove(&group);
 
             // Persist the state.
-            ok_or_save_state_failure!(save_state(&state, settings));
+            ok_or_save_state_failure!(save_state(state, settings));
 
             success_msg!("Group \"{group}\" has been removed")
         }
     }
 }
+
+/// Compute the throughput of all groups.
+/// Only tasks that finished in the `window` seconds before `now` are taken into account.
+/// Tasks that never actually ran, e.g. because they expired or failed to spawn, are ignored.
+fn group_stats(state: &State, window: u64, now: DateTime<Local>) -> BTreeMap<String, GroupStats> {
+    let window_start = i64::try_from(window)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|window| now.checked_sub_signed(window));
+
+    let mut stats: BTreeMap<String, GroupStats> = state
+        .groups
+        .keys()
+        .map(|name| {
+            let stats = GroupStats {
+                window,
+                ..Default::default()
+            };
+            (name.clone(), stats)
+        })
+        .collect();
+
+    // Sum up the runtime of all tasks that finished inside the window, per group.
+    let mut runtimes: BTreeMap<&str, i64> = BTreeMap::new();
+    for task in state.tasks.values() {
+        let TaskStatus::Done {
+            start, end, result, ..
+        } = &task.status
+        else {
+            continue;
+        };
+        if matches!(
+            result,
+            TaskResult::FailedToSpawn(_) | TaskResult::DependencyFailed | TaskResult::Expired
+        ) {
+            continue;
+        }
+        let (start, end) = (*start, *end);
+        if window_start.is_some_and(|window_start| end < window_start) {
+            continue;
+        }
+        let Some(group_stats) = stats.get_mut(&task.group) else {
+            continue;
+        };
+
+        group_stats.finished_tasks += 1;
+        *runtimes.entry(&task.group).or_default() += (end - start).num_milliseconds().max(0);
+    }
+
+    for (group, runtime) in runtimes {
+        if let Some(group_stats) = stats.get_mut(group) {
+            group_stats.average_runtime = Some(runtime as u64 / group_stats.finished_tasks as u64);
+        }
+    }
+
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn compute_group_stats() {
+        let (state, _settings, _tempdir) = get_stub_state();
+        let mut state = state.lock().unwrap();
+        let now = Local::now();
+
+        // Finish the first three tasks at different points in time with different runtimes.
+        // Only tasks 0 and 1 finished inside the last hour.
+        let finished = [
+            (0, TimeDelta::minutes(10), TimeDelta::seconds(30)),
+            (1, TimeDelta::minutes(50), TimeDelta::seconds(90)),
+            (2, TimeDelta::hours(2), TimeDelta::seconds(10)),
+        ];
+        for (id, ago, runtime) in finished {
+            let task = state.tasks.get_mut(&id).unwrap();
+            task.status = TaskStatus::Done {
+                enqueued_at: now - ago - runtime,
+                start: now - ago - runtime,
+                end: now - ago,
+                result: TaskResult::Success,
+            };
+        }
+
+        // Tasks that never ran aren't counted, even though they finished inside the window.
+        let task = state.tasks.get_mut(&4).unwrap();
+        task.status = TaskStatus::Done {
+            enqueued_at: now - TimeDelta::minutes(5),
+            start: now - TimeDelta::minutes(5),
+            end: now - TimeDelta::minutes(5),
+            result: TaskResult::Expired,
+        };
+
+        let stats = group_stats(&state, 3600, now);
+        let default_stats = stats.get(PUEUE_DEFAULT_GROUP).unwrap();
+        assert_eq!(default_stats.window, 3600);
+        assert_eq!(default_stats.finished_tasks, 2);
+        assert_eq!(default_stats.average_runtime, Some(60_000));
+
+        // A larger window includes the older task as well.
+        let stats = group_stats(&state, 3 * 3600, now);
+        let default_stats = stats.get(PUEUE_DEFAULT_GROUP).unwrap();
+        assert_eq!(default_stats.finished_tasks, 3);
+        assert_eq!(default_stats.average_runtime, Some(130_000 / 3));
+    }
+}
@@ -0,0 +1,25 @@
+use pueue_lib::failure_msg;
+use pueue_lib::network::message::*;
+use pueue_lib::settings::Settings;
+use pueue_lib::state::State;
+
+use crate::daemon::state_helper::read_history;
+
+/// Invoked when calling `pueue history`.
+/// Return the persisted history together with all finished tasks that're still in the state,
+/// ordered by the time they finished.
+pub fn get_history(settings: &Settings, state: &State, message: HistoryRequestMessage) -> Message {
+    let mut entries = match read_history(settings) {
+        Ok(entries) => entries,
+        Err(error) => return failure_msg!("Failed to read history: {error:?}"),
+    };
+
+    entries.extend(state.tasks.values().filter_map(HistoryEntry::from_task));
+
+    if let Some(group) = &message.group {
+        entries.retain(|entry| &entry.group == group);
+    }
+    entries.sort_by_key(|entry| entry.end);
+
+    Message::HistoryResponse(entries)
+}
@@ -1,20 +1,17 @@
-use pueue_lib::{
-    network::message::*, settings::Settings, state::SharedState, success_msg, task::Task,
-};
+use pueue_lib::{network::message::*, settings::Settings, success_msg, task::Task};
 
 use crate::daemon::{
     network::response_helper::{ensure_group_exists, task_action_response_helper},
     process_handler,
+    state_helper::LockedState,
 };
 
 /// Invoked when calling `pueue kill`.
 /// Forward the kill message to the task handler, which then kills the process.
-pub fn kill(settings: &Settings, state: &SharedState, message: KillMessage) -> Message {
-    let mut state = state.lock().unwrap();
-
+pub fn kill(settings: &Settings, state: &mut LockedState, message: KillMessage) -> Message {
     // If a group is selected, make sure it exists.
     if let TaskSelection::Group(group) = &message.tasks {
-        if let Err(message) = ensure_group_exists(&mut state, group) {
+        if let Err(message) = ensure_group_exists(state, group) {
             return message;
         }
     }
@@ -26,7 +23,7 @@ pub fn kill(settings: &Settings, state: &SharedState, message: KillMessage) -> M
                 "Tasks are being killed",
                 task_ids.clone(),
                 Task::is_running,
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => {
                 success_msg!("Sending signal {signal} to all running tasks of group {group}.",)
@@ -41,20 +38,29 @@ pub fn kill(settings: &Settings, state: &SharedState, message: KillMessage) -> M
                 "Tasks are being killed",
                 task_ids.clone(),
                 Task::is_running,
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => success_msg!(
                 "All tasks of group \"{group}\" are being killed. The group will also be paused!!!"
             ),
-            TaskSelection::All => {
+            TaskSelection::All if message.pause_groups => {
                 success_msg!("All tasks are being killed. All groups will be paused!!!")
             }
+            TaskSelection::All => {
+                success_msg!("All running tasks are being killed. Groups won't be paused.")
+            }
         }
     };
 
     // Actually execute the command
     if let Message::Success(_) = response {
-        process_handler::kill::kill(settings, &mut state, message.tasks, true, message.signal);
+        process_handler::kill::kill(
+            settings,
+            state,
+            message.tasks,
+            message.pause_groups,
+            message.signal,
+        );
     }
 
     response
@@ -1,9 +1,11 @@
 use std::collections::BTreeMap;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use std::time::Duration;
 
 use anyhow::Result;
+use log::debug;
+use tokio::io::AsyncReadExt;
 
 use pueue_lib::failure_msg;
 use pueue_lib::log::read_and_compress_log_file;
@@ -11,13 +13,13 @@ use pueue_lib::log::*;
 use pueue_lib::network::message::*;
 use pueue_lib::network::protocol::{send_message, GenericStream};
 use pueue_lib::settings::Settings;
-use pueue_lib::state::SharedState;
+use pueue_lib::state::{SharedState, State};
+
+use crate::daemon::state_helper::with_state;
 
 /// Invoked when calling `pueue log`.
 /// Return tasks and their output to the client.
-pub fn get_log(settings: &Settings, state: &SharedState, message: LogRequestMessage) -> Message {
-    let state = { state.lock().unwrap().clone() };
-
+pub fn get_log(settings: &Settings, state: &State, message: LogRequestMessage) -> Message {
     let task_ids = match message.tasks {
         TaskSelection::All => state.tasks.keys().cloned().collect(),
         TaskSelection::TaskIds(task_ids) => task_ids,
@@ -105,19 +107,27 @@ pub async fn follow_log(
     // It might be that the task is not yet running.
     // Ensure that it exists and is started.
     loop {
-        {
-            let state = state.lock().unwrap();
-            let Some(task) = state.tasks.get(&task_id) else {
+        let task_started = with_state(state, |state| {
+            state
+                .tasks
+                .get(&task_id)
+                .map(|task| task.is_running() || task.is_done())
+        });
+
+        match task_started {
+            None => {
                 return Ok(create_failure_message(
                     "Pueue: The task to be followed doesn't exist.",
                 ));
-            };
+            }
             // The task is running or finished, we can start to follow.
-            if task.is_running() || task.is_done() {
-                break;
+            Some(true) => break,
+            Some(false) => {
+                if wait_or_cancelled(stream, Duration::from_millis(1000)).await {
+                    return Ok(Message::Close);
+                }
             }
         }
-        tokio::time::sleep(Duration::from_millis(1000)).await;
     }
 
     let mut handle = match get_log_file_handle(task_id, pueue_directory) {
@@ -134,16 +144,30 @@ pub async fn follow_log(
     // since the file can go away (e.g. due to finishing a task).
     let path = get_log_path(task_id, pueue_directory);
 
-    // If `lines` is passed as an option, we only want to show the last `X` lines.
+    // If an `offset` is passed, the client wants to continue at a specific position of the log.
+    // Otherwise, if `lines` is passed as an option, we only want to show the last `X` lines.
     // To achieve this, we seek the file handle to the start of the `Xth` line
     // from the end of the file.
     // The loop following this section will then only copy those last lines to stdout.
-    if let Some(lines) = message.lines {
+    if let Some(offset) = message.offset {
+        if let Err(err) = handle.seek(SeekFrom::Start(offset)) {
+            return Ok(failure_msg!(
+                "Pueue: Couldn't seek to offset {offset}: {err}"
+            ));
+        }
+    } else if let Some(lines) = message.lines {
         if let Err(err) = seek_to_last_lines(&mut handle, lines) {
             println!("Error seeking to last lines from log: {err}");
         }
     }
 
+    // Keep track of our position in the log file, so every chunk can tell the client where
+    // exactly it's located.
+    let mut offset = match handle.stream_position() {
+        Ok(offset) => offset,
+        Err(err) => return Ok(failure_msg!("Pueue Error: {err}")),
+    };
+
     loop {
         // Check whether the file still exists. Exit if it doesn't.
         if !path.exists() {
@@ -151,41 +175,68 @@ pub async fn follow_log(
                 "Pueue: Log file has gone away. Has the task been removed?",
             ));
         }
+
+        // Check if the task in question does:
+        // 1. Still exist
+        // 2. Is still running
+        //
+        // This is checked before reading the next chunk. That way, any output that's been
+        // written right before the task finished is still sent, before the stream is closed.
+        let task_running = with_state(state, |state| {
+            state.tasks.get(&task_id).map(|task| task.is_running())
+        });
+
         // Read the next chunk of text from the last position.
         let mut buffer = Vec::new();
 
         if let Err(err) = handle.read_to_end(&mut buffer) {
             return Ok(create_failure_message(format!("Pueue Error: {err}")));
         };
-        let text = String::from_utf8_lossy(&buffer).to_string();
 
         // Only send a message, if there's actual new content.
-        if !text.is_empty() {
+        if !buffer.is_empty() {
+            let next_offset = offset + buffer.len() as u64;
             // Send the next chunk.
-            let response = Message::Stream(text);
+            let response = StreamResponseMessage {
+                offset,
+                next_offset,
+                text: String::from_utf8_lossy(&buffer).to_string(),
+            };
             send_message(response, stream).await?;
+            offset = next_offset;
         }
 
-        // Check if the task in question does:
-        // 1. Still exist
-        // 2. Is still running
-        //
-        // In case it's not, close the stream.
-        {
-            let state = state.lock().unwrap();
-            let Some(task) = state.tasks.get(&task_id) else {
+        match task_running {
+            None => {
                 return Ok(create_failure_message(
                     "Pueue: The followed task has been removed.",
                 ));
-            };
-
-            // The task is done, just close the stream.
-            if !task.is_running() {
-                return Ok(Message::Close);
             }
+            // The task is done and all of its output has been sent.
+            // Explicitly tell the client that the stream ended.
+            Some(false) => return Ok(Message::Close),
+            Some(true) => (),
         }
 
         // Wait for 1 second before sending the next chunk.
-        tokio::time::sleep(Duration::from_millis(1000)).await;
+        if wait_or_cancelled(stream, Duration::from_millis(1000)).await {
+            return Ok(Message::Close);
+        }
+    }
+}
+
+/// Sleep for the given duration, while watching the stream for a cancellation by the client.
+///
+/// The client isn't supposed to send anything while streaming, so any received data, a closed
+/// connection or an error is considered a cancellation.
+/// Returns `true`, if the stream has been cancelled.
+async fn wait_or_cancelled(stream: &mut GenericStream, duration: Duration) -> bool {
+    let mut buffer = [0; 64];
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => false,
+        _ = stream.read(&mut buffer) => {
+            debug!("Client cancelled the log stream");
+            true
+        }
     }
 }
@@ -4,16 +4,20 @@ use chrono::{DateTime, Local};
 use pueue_lib::failure_msg;
 use pueue_lib::network::message::*;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::SharedState;
+use pueue_lib::state::{SharedState, State};
 
 use crate::daemon::network::response_helper::*;
+use crate::daemon::state_helper::{with_state, LockedState};
 
 mod add;
+mod annotate;
+mod batch;
 mod clean;
 mod edit;
 mod enqueue;
 mod env;
 mod group;
+mod history;
 mod kill;
 mod log;
 mod parallel;
@@ -25,19 +29,42 @@ mod send;
 mod start;
 mod stash;
 mod switch;
+mod unschedule;
 
 pub use log::follow_log;
 
 pub fn handle_message(message: Message, state: &SharedState, settings: &Settings) -> Message {
+    // Logs are read from disk and might be large, which is why the state isn't kept locked in
+    // the meantime.
+    if let Message::Log(message) = message {
+        let state = with_state(state, |state| state.clone());
+        return log::get_log(settings, &state, message);
+    }
+
+    with_state(state, |state| {
+        handle_locked_message(message, state, settings)
+    })
+}
+
+/// Handle a message on the already locked state. \
+/// This allows to handle several messages without releasing the lock in between.
+fn handle_locked_message(
+    message: Message,
+    state: &mut LockedState,
+    settings: &Settings,
+) -> Message {
     match message {
         Message::Add(message) => add::add_task(settings, state, message),
+        Message::Annotate(message) => annotate::annotate(settings, state, message),
+        Message::Batch(messages) => batch::batch(settings, state, messages),
         Message::Clean(message) => clean::clean(settings, state, message),
         Message::Edit(editable_tasks) => edit::edit(settings, state, editable_tasks),
-        Message::EditRequest(task_ids) => edit::edit_request(state, task_ids),
-        Message::EditRestore(task_ids) => edit::edit_restore(state, task_ids),
+        Message::EditRequest(task_ids) => edit::edit_request(settings, state, task_ids),
+        Message::EditRestore(task_ids) => edit::edit_restore(settings, state, task_ids),
         Message::Env(message) => env::env(settings, state, message),
         Message::Enqueue(message) => enqueue::enqueue(settings, state, message),
         Message::Group(message) => group::group(settings, state, message),
+        Message::History(message) => history::get_history(settings, state, message),
         Message::Kill(message) => kill::kill(settings, state, message),
         Message::Log(message) => log::get_log(settings, state, message),
         Message::Parallel(message) => parallel::set_parallel_tasks(message, state),
@@ -49,6 +76,7 @@ pub fn handle_message(message: Message, state: &SharedState, settings: &Settings
         Message::Start(message) => start::start(settings, state, message),
         Message::Stash(message) => stash::stash(settings, state, message),
         Message::Switch(message) => switch::switch(settings, state, message),
+        Message::Unschedule(message) => unschedule::unschedule(settings, state, message),
         Message::Status => get_status(state),
         _ => create_failure_message("Not yet implemented"),
     }
@@ -56,19 +84,32 @@ pub fn handle_message(message: Message, state: &SharedState, settings: &Settings
 
 /// Invoked when calling `pueue status`.
 /// Return the current state.
-fn get_status(state: &SharedState) -> Message {
-    let state = state.lock().unwrap().clone();
-    Message::StatusResponse(Box::new(state))
+fn get_status(state: &State) -> Message {
+    Message::StatusResponse(Box::new(state.clone()))
 }
 
 // If the enqueue at time is today, only show the time. Otherwise, include the date.
 fn format_datetime(settings: &Settings, enqueue_at: &DateTime<Local>) -> String {
-    let format_string = if enqueue_at.date_naive() == Local::now().date_naive() {
-        &settings.client.status_time_format
-    } else {
-        &settings.client.status_datetime_format
-    };
-    enqueue_at.format(format_string).to_string()
+    let today = enqueue_at.date_naive() == Local::now().date_naive();
+    settings.client.format_timestamp(enqueue_at, !today)
+}
+
+/// Reject commands that're forbidden by the daemon's `command_denylist`.
+/// The expanded alias is checked as well, so aliases cannot be used to sidestep the denylist.
+fn check_command_denylist(
+    settings: &Settings,
+    original_command: &str,
+    command: &str,
+) -> Result<(), Message> {
+    for command in [original_command, command] {
+        if let Some(pattern) = settings.daemon.command_denylist.find_match(command) {
+            return Err(failure_msg!(
+                "The command \"{command}\" is denied by the pattern \"{pattern}\" of the daemon's command_denylist"
+            ));
+        }
+    }
+
+    Ok(())
 }
 
 fn ok_or_failure_message<T, E: Display>(result: Result<T, E>) -> Result<T, Message> {
@@ -1,13 +1,12 @@
 use pueue_lib::network::message::*;
-use pueue_lib::state::SharedState;
 use pueue_lib::success_msg;
 
 use crate::daemon::network::response_helper::*;
+use crate::daemon::state_helper::LockedState;
 
 /// Set the parallel tasks for a specific group.
-pub fn set_parallel_tasks(message: ParallelMessage, state: &SharedState) -> Message {
-    let mut state = state.lock().unwrap();
-    let group = match ensure_group_exists(&mut state, &message.group) {
+pub fn set_parallel_tasks(message: ParallelMessage, state: &mut LockedState) -> Message {
+    let group = match ensure_group_exists(state, &message.group) {
         Ok(group) => group,
         Err(message) => return message,
     };
@@ -1,20 +1,17 @@
 use pueue_lib::log::clean_log_handles;
 use pueue_lib::network::message::*;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::SharedState;
 use pueue_lib::task::{Task, TaskStatus};
 
 use super::ok_or_failure_message;
 use crate::daemon::network::response_helper::*;
-use crate::daemon::state_helper::{is_task_removable, save_state};
+use crate::daemon::state_helper::{is_task_removable, mark_tasks_changed, save_state, LockedState};
 use crate::ok_or_save_state_failure;
 
 /// Invoked when calling `pueue remove`.
 /// Remove tasks from the queue.
 /// We have to ensure that those tasks aren't running!
-pub fn remove(settings: &Settings, state: &SharedState, task_ids: Vec<usize>) -> Message {
-    let mut state = state.lock().unwrap();
-
+pub fn remove(settings: &Settings, state: &mut LockedState, task_ids: Vec<usize>) -> Message {
     // Filter all running tasks, since we cannot remove them.
     let filter = |task: &Task| {
         matches!(
@@ -30,7 +27,7 @@ pub fn remove(settings: &Settings, state: &SharedState, task_ids: Vec<usize>) ->
     // Don't delete tasks, if there are other tasks that depend on this one.
     // However, we allow to delete those tasks, if they're supposed to be deleted as well.
     for task_id in filtered_tasks.matching_ids.clone() {
-        if !is_task_removable(&state, &task_id, &filtered_tasks.matching_ids) {
+        if !is_task_removable(state, &task_id, &filtered_tasks.matching_ids) {
             filtered_tasks.non_matching_ids.push(task_id);
             filtered_tasks.matching_ids.retain(|id| id != &task_id);
         };
@@ -38,11 +35,12 @@ pub fn remove(settings: &Settings, state: &SharedState, task_ids: Vec<usize>) ->
 
     for task_id in &filtered_tasks.matching_ids {
         state.tasks.remove(task_id);
+        mark_tasks_changed(settings, [*task_id]);
 
         clean_log_handles(*task_id, &settings.shared.pueue_directory());
     }
 
-    ok_or_save_state_failure!(save_state(&state, settings));
+    ok_or_save_state_failure!(save_state(state, settings));
 
     compile_task_response("Tasks removed from list", filtered_tasks)
 }
@@ -60,7 +58,7 @@ mod tests {
 
         // 3 and 4 aren't allowed to be removed, since they're running.
         // The rest will succeed.
-        let message = remove(&settings, &state, vec![0, 1, 2, 3, 4]);
+        let message = remove(&settings, &mut state.lock().unwrap(), vec![0, 1, 2, 3, 4]);
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -93,7 +91,7 @@ mod tests {
         }
 
         // Make sure we cannot remove a task with dependencies.
-        let message = remove(&settings, &state, vec![1]);
+        let message = remove(&settings, &mut state.lock().unwrap(), vec![1]);
 
         // Return message is correct
         assert!(matches!(message, Message::Failure(_)));
@@ -107,7 +105,7 @@ mod tests {
         }
 
         // Make sure we cannot remove a task with recursive dependencies.
-        let message = remove(&settings, &state, vec![1, 5]);
+        let message = remove(&settings, &mut state.lock().unwrap(), vec![1, 5]);
 
         // Return message is correct
         assert!(matches!(message, Message::Failure(_)));
@@ -121,7 +119,7 @@ mod tests {
         }
 
         // Make sure we can remove tasks with dependencies if all dependencies are specified.
-        let message = remove(&settings, &state, vec![1, 5, 6]);
+        let message = remove(&settings, &mut state.lock().unwrap(), vec![1, 5, 6]);
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -1,22 +1,21 @@
 use pueue_lib::failure_msg;
-use pueue_lib::state::{GroupStatus, SharedState};
+use pueue_lib::state::GroupStatus;
 use pueue_lib::{network::message::*, settings::Settings};
 
 use crate::daemon::process_handler;
+use crate::daemon::state_helper::LockedState;
 
 /// Invoked when calling `pueue reset`.
 /// Kill all children by using the `kill` function.
 /// Set the full_reset flag, which will prevent new tasks from being spawned.
-pub fn reset(settings: &Settings, state: &SharedState, message: ResetMessage) -> Message {
-    let mut state = state.lock().unwrap();
-
+pub fn reset(settings: &Settings, state: &mut LockedState, message: ResetMessage) -> Message {
     match message.target {
         ResetTarget::All => {
             // Mark all groups to be reset and kill all tasks
             for (_name, group) in state.groups.iter_mut() {
                 group.status = GroupStatus::Reset;
             }
-            process_handler::kill::kill(settings, &mut state, TaskSelection::All, false, None);
+            process_handler::kill::kill(settings, state, TaskSelection::All, false, None);
         }
         ResetTarget::Groups(groups) => {
             // First up, check whether we actually have all requested groups.
@@ -34,7 +33,7 @@ pub fn reset(settings: &Settings, state: &SharedState, message: ResetMessage) ->
 
                 process_handler::kill::kill(
                     settings,
-                    &mut state,
+                    state,
                     TaskSelection::Group(name.to_string()),
                     false,
                     None,
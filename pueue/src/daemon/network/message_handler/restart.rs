@@ -1,17 +1,16 @@
 use chrono::Local;
-use std::sync::MutexGuard;
 
 use pueue_lib::{
     aliasing::insert_alias,
     network::message::*,
     settings::Settings,
-    state::{SharedState, State},
     task::{Task, TaskStatus},
 };
 
 use crate::daemon::process_handler;
+use crate::daemon::state_helper::{mark_tasks_changed, set_status, LockedState};
 
-use super::task_action_response_helper;
+use super::{check_command_denylist, task_action_response_helper};
 
 /// This is a small wrapper around the actual in-place task `restart` functionality.
 ///
@@ -19,11 +18,19 @@ use super::task_action_response_helper;
 /// new task, which is completely handled on the client-side.
 pub fn restart_multiple(
     settings: &Settings,
-    state: &SharedState,
+    state: &mut LockedState,
     message: RestartMessage,
 ) -> Message {
     let task_ids: Vec<usize> = message.tasks.iter().map(|task| task.task_id).collect();
-    let mut state = state.lock().unwrap();
+
+    // Reject edited commands that're forbidden by the daemon's configuration.
+    // None of the tasks are restarted in that case.
+    for task in &message.tasks {
+        let command = insert_alias(settings, task.command.clone());
+        if let Err(failure) = check_command_denylist(settings, &task.command, &command) {
+            return failure;
+        }
+    }
 
     // We have to compile the response beforehand.
     // Otherwise we no longer know which tasks, were actually capable of being being restarted.
@@ -31,17 +38,17 @@ pub fn restart_multiple(
         "Tasks has restarted",
         task_ids.clone(),
         Task::is_done,
-        &state,
+        state,
     );
 
     // Restart a tasks in-place
     for task in message.tasks {
-        restart(&mut state, task, message.stashed, settings);
+        restart(state, task, message.stashed, settings);
     }
 
     // Actually start the processes if we should do so.
     if message.start_immediately {
-        process_handler::start::start(settings, &mut state, TaskSelection::TaskIds(task_ids));
+        process_handler::start::start(settings, state, TaskSelection::TaskIds(task_ids));
     }
 
     response
@@ -52,12 +59,7 @@ pub fn restart_multiple(
 ///
 /// The "not in-place" restart functionality is actually just a copy the finished task + create a
 /// new task, which is completely handled on the client-side.
-fn restart(
-    state: &mut MutexGuard<State>,
-    to_restart: TaskToRestart,
-    stashed: bool,
-    settings: &Settings,
-) {
+fn restart(state: &mut LockedState, to_restart: TaskToRestart, stashed: bool, settings: &Settings) {
     // Check if we actually know this task.
     let Some(task) = state.tasks.get_mut(&to_restart.task_id) else {
         return;
@@ -69,18 +71,24 @@ fn restart(
     }
 
     // Either enqueue the task or stash it.
-    if stashed {
-        task.status = TaskStatus::Stashed { enqueue_at: None };
+    let status = if stashed {
+        TaskStatus::Stashed { enqueue_at: None }
     } else {
-        task.status = TaskStatus::Queued {
+        TaskStatus::Queued {
             enqueued_at: Local::now(),
-        };
+        }
     };
+    set_status(settings, task, status);
 
     // Update task properties in case they've been edited.
+    // A raw command no longer matches an edited command, which is then executed via the shell.
+    if task.command != to_restart.command {
+        task.raw_command = None;
+    }
     task.original_command = to_restart.command.clone();
     task.command = insert_alias(settings, to_restart.command);
     task.path = to_restart.path;
     task.label = to_restart.label.clone();
     task.priority = to_restart.priority;
+    mark_tasks_changed(settings, [to_restart.task_id]);
 }
@@ -1,28 +1,35 @@
 use std::io::Write;
 
-use pueue_lib::state::SharedState;
-use pueue_lib::{failure_msg, network::message::*};
+use log::warn;
+
+use pueue_lib::network::message::*;
+use pueue_lib::state::FilteredTasks;
+
+use crate::daemon::network::response_helper::*;
+use crate::daemon::state_helper::LockedState;
 
 /// Invoked when calling `pueue send`.
-/// The message will be forwarded to the task handler, which then sends the user input to the process.
-/// In here we only do some error handling.
-pub fn send(state: &SharedState, message: SendMessage) -> Message {
-    let task_id = message.task_id;
-    let mut state = state.lock().unwrap();
+/// The input is written to the stdin of all given tasks.
+/// Tasks that aren't running or whose stdin cannot be written to are reported as failed.
+pub fn send(state: &mut LockedState, message: SendMessage) -> Message {
+    let input = message.input.into_bytes();
+
+    let mut filtered_tasks = FilteredTasks::default();
+    for task_id in message.task_ids {
+        // Only running processes can receive input.
+        let Some(child) = state.children.get_child_mut(task_id) else {
+            filtered_tasks.non_matching_ids.push(task_id);
+            continue;
+        };
 
-    // Check whether the task exists and is running. Abort if that's not the case.
-    let child = match state.children.get_child_mut(task_id) {
-        Some(child) => child,
-        None => {
-            return failure_msg!("You can only send input to a running process.");
-        }
-    };
-    {
         let child_stdin = child.inner().stdin.as_mut().unwrap();
-        if let Err(err) = child_stdin.write_all(&message.input.into_bytes()) {
-            return failure_msg!("Failed to send input to task {task_id} with err {err:?}");
+        if let Err(err) = child_stdin.write_all(&input) {
+            warn!("Failed to send input to task {task_id} with err {err:?}");
+            filtered_tasks.non_matching_ids.push(task_id);
+            continue;
         };
+        filtered_tasks.matching_ids.push(task_id);
     }
 
-    create_success_message("Message is being send to the process.")
+    compile_task_response("Message is being send to tasks", filtered_tasks)
 }
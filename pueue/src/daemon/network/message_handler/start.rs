@@ -1,19 +1,18 @@
 use pueue_lib::network::message::*;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::SharedState;
 use pueue_lib::success_msg;
 use pueue_lib::task::TaskStatus;
 
 use crate::daemon::network::response_helper::*;
 use crate::daemon::process_handler;
+use crate::daemon::state_helper::LockedState;
 
 /// Invoked when calling `pueue start`.
 /// Forward the start message to the task handler, which then starts the process(es).
-pub fn start(settings: &Settings, state: &SharedState, message: StartMessage) -> Message {
-    let mut state = state.lock().unwrap();
+pub fn start(settings: &Settings, state: &mut LockedState, message: StartMessage) -> Message {
     // If a group is selected, make sure it exists.
     if let TaskSelection::Group(group) = &message.tasks {
-        if let Err(message) = ensure_group_exists(&mut state, group) {
+        if let Err(message) = ensure_group_exists(state, group) {
             return message;
         }
     }
@@ -30,7 +29,7 @@ pub fn start(settings: &Settings, state: &SharedState, message: StartMessage) ->
                         | TaskStatus::Stashed { .. }
                 )
             },
-            &state,
+            state,
         ),
         TaskSelection::Group(group) => {
             success_msg!("Group \"{group}\" is being resumed.")
@@ -39,7 +38,7 @@ pub fn start(settings: &Settings, state: &SharedState, message: StartMessage) ->
     };
 
     if let Message::Success(_) = response {
-        process_handler::start::start(settings, &mut state, message.tasks);
+        process_handler::start::start(settings, state, message.tasks);
     }
 
     // Return a response depending on the selected tasks.
@@ -1,16 +1,14 @@
-use pueue_lib::{
-    network::message::*, settings::Settings, state::SharedState, success_msg, task::TaskStatus,
-};
+use pueue_lib::{network::message::*, settings::Settings, success_msg, task::TaskStatus};
 
 use crate::daemon::network::response_helper::*;
+use crate::daemon::state_helper::{mark_tasks_changed, set_status, LockedState};
 
 use super::format_datetime;
 
 /// Invoked when calling `pueue stash`.
 /// Stash specific queued tasks.
 /// They won't be executed until they're enqueued or explicitly started.
-pub fn stash(settings: &Settings, state: &SharedState, message: StashMessage) -> Message {
-    let mut state = state.lock().unwrap();
+pub fn stash(settings: &Settings, state: &mut LockedState, message: StashMessage) -> Message {
     // Get the affected task ids, based on the task selection.
     let selected_task_ids = match message.tasks {
         TaskSelection::TaskIds(ref task_ids) => state
@@ -60,10 +58,15 @@ pub fn stash(settings: &Settings, state: &SharedState, message: StashMessage) ->
         // We just checked that they're there and the state is locked. It's safe to unwrap.
         let task = state.tasks.get_mut(task_id).expect("Task should be there.");
 
-        task.status = TaskStatus::Stashed {
-            enqueue_at: message.enqueue_at,
-        };
+        set_status(
+            settings,
+            task,
+            TaskStatus::Stashed {
+                enqueue_at: message.enqueue_at,
+            },
+        );
     }
+    mark_tasks_changed(settings, selected_task_ids.iter().copied());
 
     // Construct a response depending on the selected tasks.
     if let Some(enqueue_at) = &message.enqueue_at {
@@ -79,7 +82,7 @@ pub fn stash(settings: &Settings, state: &SharedState, message: StashMessage) ->
                         TaskStatus::Stashed { .. } | TaskStatus::Locked { .. }
                     )
                 },
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => {
                 success_msg!("Enqueue stashed tasks of group {group} at {enqueue_at}.",)
@@ -99,7 +102,7 @@ pub fn stash(settings: &Settings, state: &SharedState, message: StashMessage) ->
                         TaskStatus::Stashed { .. } | TaskStatus::Locked { .. }
                     )
                 },
-                &state,
+                state,
             ),
             TaskSelection::Group(group) => {
                 success_msg!("All queued tasks of group \"{group}\" have been stashd.")
@@ -1,19 +1,16 @@
 use pueue_lib::failure_msg;
 use pueue_lib::network::message::*;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::SharedState;
 use pueue_lib::task::TaskStatus;
 
 use super::ok_or_failure_message;
-use crate::daemon::state_helper::save_state;
+use crate::daemon::state_helper::{mark_tasks_changed, save_state, LockedState};
 use crate::ok_or_save_state_failure;
 
 /// Invoked when calling `pueue switch`.
 /// Switch the position of two tasks in the upcoming queue.
 /// We have to ensure that those tasks are either `Queued` or `Stashed`
-pub fn switch(settings: &Settings, state: &SharedState, message: SwitchMessage) -> Message {
-    let mut state = state.lock().unwrap();
-
+pub fn switch(settings: &Settings, state: &mut LockedState, message: SwitchMessage) -> Message {
     let task_ids = [message.task_id_1, message.task_id_2];
     let filtered_tasks = state.filter_tasks(
         |task| {
@@ -44,6 +41,7 @@ pub fn switch(settings: &Settings, state: &SharedState, message: SwitchMessage)
     // Put tasks back in again
     state.tasks.insert(first_task.id, first_task);
     state.tasks.insert(second_task.id, second_task);
+    mark_tasks_changed(settings, [first_id, second_id]);
 
     for (_, task) in state.tasks.iter_mut() {
         // If the task depends on both, we can just keep it as it is.
@@ -55,13 +53,15 @@ pub fn switch(settings: &Settings, state: &SharedState, message: SwitchMessage)
         if let Some(old_id) = task.dependencies.iter_mut().find(|id| *id == &first_id) {
             *old_id = second_id;
             task.dependencies.sort_unstable();
+            mark_tasks_changed(settings, [task.id]);
         } else if let Some(old_id) = task.dependencies.iter_mut().find(|id| *id == &second_id) {
             *old_id = first_id;
             task.dependencies.sort_unstable();
+            mark_tasks_changed(settings, [task.id]);
         }
     }
 
-    ok_or_save_state_failure!(save_state(&state, settings));
+    ok_or_save_state_failure!(save_state(state, settings));
     create_success_message("Tasks have been switched")
 }
 
@@ -118,7 +118,7 @@ mod tests {
     fn switch_normal() {
         let (state, settings, _tempdir) = get_test_state();
 
-        let message = switch(&settings, &state, get_message(1, 2));
+        let message = switch(&settings, &mut state.lock().unwrap(), get_message(1, 2));
 
         // Return message is correct
         assert!(matches!(message, Message::Success(_)));
@@ -136,7 +136,7 @@ mod tests {
     fn switch_task_with_itself() {
         let (state, settings, _tempdir) = get_test_state();
 
-        let message = switch(&settings, &state, get_message(1, 1));
+        let message = switch(&settings, &mut state.lock().unwrap(), get_message(1, 1));
 
         // Return message is correct
         assert!(matches!(message, Message::Failure(_)));
@@ -151,7 +151,7 @@ mod tests {
     fn switch_task_with_dependant() {
         let (state, settings, _tempdir) = get_test_state();
 
-        switch(&settings, &state, get_message(0, 3));
+        switch(&settings, &mut state.lock().unwrap(), get_message(0, 3));
 
         let state = state.lock().unwrap();
         assert_eq!(state.tasks.get(&4).unwrap().dependencies, vec![0, 3]);
@@ -163,7 +163,7 @@ mod tests {
     fn switch_double_dependency() {
         let (state, settings, _tempdir) = get_test_state();
 
-        switch(&settings, &state, get_message(1, 2));
+        switch(&settings, &mut state.lock().unwrap(), get_message(1, 2));
 
         let state = state.lock().unwrap();
         assert_eq!(state.tasks.get(&5).unwrap().dependencies, vec![2]);
@@ -188,7 +188,11 @@ mod tests {
         ];
 
         for ids in combinations {
-            let message = switch(&settings, &state, get_message(ids.0, ids.1));
+            let message = switch(
+                &settings,
+                &mut state.lock().unwrap(),
+                get_message(ids.0, ids.1),
+            );
 
             // Assert, that we get a Failure message with the correct text.
             assert!(matches!(message, Message::Failure(_)));
@@ -0,0 +1,28 @@
+use pueue_lib::network::message::*;
+use pueue_lib::settings::Settings;
+use pueue_lib::task::{Task, TaskStatus};
+
+use crate::daemon::network::response_helper::*;
+use crate::daemon::state_helper::{mark_tasks_changed, save_state, set_status, LockedState};
+use crate::ok_or_save_state_failure;
+
+/// Invoked when calling `pueue unschedule`.
+/// Remove the enqueue time of scheduled tasks.
+/// The tasks stay stashed and have to be enqueued manually afterwards.
+pub fn unschedule(
+    settings: &Settings,
+    state: &mut LockedState,
+    message: UnscheduleMessage,
+) -> Message {
+    let filtered_tasks = state.filter_tasks(Task::is_scheduled, Some(message.task_ids));
+    for task_id in &filtered_tasks.matching_ids {
+        // We just checked that they're there and the state is locked. It's safe to unwrap.
+        let task = state.tasks.get_mut(task_id).expect("Task should be there.");
+        set_status(settings, task, TaskStatus::Stashed { enqueue_at: None });
+    }
+    mark_tasks_changed(settings, filtered_tasks.matching_ids.iter().copied());
+
+    ok_or_save_state_failure!(save_state(state, settings));
+
+    compile_task_response("Tasks have been unscheduled", filtered_tasks)
+}
@@ -1,9 +1,12 @@
-use std::time::{Duration, SystemTime};
+use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::{bail, Context, Result};
+use anyhow::Result;
 use clap::crate_version;
-use log::{debug, info, warn};
-use tokio::time::sleep;
+use log::{debug, warn};
+use tokio::sync::watch::Receiver;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
 
 use pueue_lib::error::Error;
 use pueue_lib::network::message::*;
@@ -14,16 +17,37 @@ use pueue_lib::state::SharedState;
 
 use crate::daemon::network::message_handler::handle_message;
 use crate::daemon::process_handler::initiate_shutdown;
+use crate::daemon::state_helper::with_state;
 
 use super::message_handler::follow_log;
 
+/// The maximum amount of connections that're rejected with an error message at the same time.
+/// Any further connections beyond `shared.max_connections` are closed right away, so a flood of
+/// connections cannot pile up tasks in the daemon.
+const MAX_PENDING_REJECTIONS: usize = 10;
+
 /// Listen for new connections on the socket.
 /// On a new connection, the connected stream will be handled in a separate tokio task.
 /// See [handle_incoming] for the actual connection handler function.
-pub async fn accept_incoming(settings: Settings, state: SharedState) -> Result<()> {
+///
+/// The settings are received via a channel, as some of them can be reloaded while the daemon is
+/// running. The listener, the secret and the connection limit are only set up once, as they
+/// require a restart.
+///
+/// Connections beyond `shared.max_connections` are rejected with an error message.
+/// See [MAX_PENDING_REJECTIONS] for the limit of concurrent rejections.
+pub async fn accept_incoming(
+    settings_receiver: Receiver<Settings>,
+    state: SharedState,
+) -> Result<()> {
+    let settings = settings_receiver.borrow().clone();
     let listener = get_listener(&settings.shared).await?;
     // Read secret once to prevent multiple disk reads.
     let secret = read_shared_secret(&settings.shared.shared_secret_path())?;
+    // Each connection holds a permit until it's closed.
+    let max_connections = settings.shared.max_connections;
+    let connections = Arc::new(Semaphore::new(max_connections));
+    let rejections = Arc::new(Semaphore::new(MAX_PENDING_REJECTIONS));
 
     loop {
         // Poll incoming connections.
@@ -35,24 +59,76 @@ pub async fn accept_incoming(settings: Settings, state: SharedState) -> Result<(
             }
         };
 
+        let secret_clone = secret.clone();
+        let settings_clone = settings_receiver.borrow().clone();
+
+        // The permits are released, once the connection has been handled.
+        let Ok(permit) = connections.clone().try_acquire_owned() else {
+            let Ok(rejection_permit) = rejections.clone().try_acquire_owned() else {
+                warn!("Closing connection, too many connections are being rejected already");
+                continue;
+            };
+
+            warn!("Rejecting connection, the limit of {max_connections} connections is reached");
+            tokio::spawn(async move {
+                let _permit = rejection_permit;
+                let rejection = reject_incoming(stream, settings_clone, secret_clone);
+                let _result = timeout(Duration::from_secs(5), rejection).await;
+            });
+            continue;
+        };
+
         // Start a new task for the request
         let state_clone = state.clone();
-        let secret_clone = secret.clone();
-        let settings_clone = settings.clone();
         tokio::spawn(async move {
+            let _permit = permit;
             let _result = handle_incoming(stream, state_clone, settings_clone, secret_clone).await;
         });
     }
 }
 
+/// Reject a connection, as the daemon already handles the maximum amount of connections.
+///
+/// The handshake is still performed as usual, so the client receives a proper error message as
+/// the response to its instruction.
+async fn reject_incoming(
+    mut stream: GenericStream,
+    settings: Settings,
+    secret: Vec<u8>,
+) -> Result<()> {
+    let secret = (!settings.shared.skip_secret_handshake()).then_some(secret.as_slice());
+    if !accept_handshake(&mut stream, secret, crate_version!()).await? {
+        return Ok(());
+    }
+
+    // Wait for the client's instruction, which is answered with the rejection.
+    let payload = receive_bytes(&mut stream).await?;
+    if let Some(offered) = capability_offer(&payload) {
+        stream = accept_capabilities(stream, offered).await?;
+        receive_message(&mut stream).await?;
+    } else {
+        deserialize_message(&payload)?;
+    }
+    let response = create_failure_message(format!(
+        "The daemon is busy, it already handles the maximum amount of {} connections. \
+        Please try again later.",
+        settings.shared.max_connections
+    ));
+    send_message(response, &mut stream).await?;
+
+    Ok(())
+}
+
 /// Handle a new connection from a client.
 ///
 /// Pueue has a very simple protocol that needs to be followed.
-/// 1. Client sends secret for authentication
+/// 1. Client sends secret for authentication.
+///    This is skipped for unix sockets, if `unix_socket_trust_permissions` is set.
 /// 2. If secret is valid, the daemon sends its own version to the client.
-/// 3. The Client sends the instruction message.
-/// 4. The Daemon reads the instruction and acts upon it.
-/// 5. The Daemon sends a response
+/// 3. The Client may offer its capabilities, which are answered with the supported ones.
+/// 4. The Client sends the instruction message.
+/// 5. The Daemon reads the instruction and acts upon it.
+/// 6. The Daemon sends a response
 ///
 /// There're two edge-cases where this pattern is not valid:
 /// 1. Shutdown. In that case the message is sent first and the daemon shuts down afterwards.
@@ -64,40 +140,26 @@ async fn handle_incoming(
     settings: Settings,
     secret: Vec<u8>,
 ) -> Result<()> {
-    // Receive the secret once and check, whether the client is allowed to connect
-    let payload_bytes = receive_bytes(&mut stream).await?;
-
-    // Didn't receive any bytes. The client disconnected.
-    if payload_bytes.is_empty() {
-        info!("Client went away");
+    // Clients connecting via a trusted unix socket are already authenticated by the socket's
+    // filesystem permissions. Everybody else has to send the secret first.
+    //
+    // The daemon confirms a valid secret by sending its current version, so the client can
+    // inform the user if the daemon needs a restart in case of a version mismatch.
+    let secret = (!settings.shared.skip_secret_handshake()).then_some(secret.as_slice());
+    if !accept_handshake(&mut stream, secret, crate_version!()).await? {
         return Ok(());
     }
 
-    let start = SystemTime::now();
-
-    // Return if we got a wrong secret from the client.
-    if payload_bytes != secret {
-        let received_secret = String::from_utf8(payload_bytes)?;
-        warn!("Received invalid secret: {received_secret}");
-
-        // Wait for 1 second before closing the socket, when getting a invalid secret.
-        // This invalidates any timing attacks.
-        let remaining_sleep_time = Duration::from_secs(1)
-            - SystemTime::now()
-                .duration_since(start)
-                .context("Couldn't calculate duration. Did the system time change?")?;
-        sleep(remaining_sleep_time).await;
-        bail!("Received invalid secret");
-    }
-
-    // Send confirmation to the client, that the secret was valid.
-    // This is also the current version of the daemon, so the client can inform user if the
-    // daemon needs a restart in case of a version mismatch.
-    send_bytes(crate_version!().as_bytes(), &mut stream).await?;
-
     loop {
         // Receive the actual instruction from the client
-        let message_result = receive_message(&mut stream).await;
+        let payload = receive_bytes(&mut stream).await?;
+
+        // Newer clients offer their capabilities before sending their first instruction.
+        if let Some(offered) = capability_offer(&payload) {
+            stream = accept_capabilities(stream, offered).await?;
+            continue;
+        }
+        let message_result = deserialize_message(&payload);
 
         if let Err(Error::EmptyPayload) = message_result {
             debug!("Client went away");
@@ -133,8 +195,9 @@ async fn handle_incoming(
                 let response = create_success_message("Daemon is shutting down");
                 send_message(response, &mut stream).await?;
 
-                let mut state = state.lock().unwrap();
-                initiate_shutdown(&settings, &mut state, shutdown_type);
+                with_state(&state, |state| {
+                    initiate_shutdown(&settings, state, shutdown_type)
+                });
 
                 return Ok(());
             }
@@ -1,26 +1,47 @@
+use std::collections::HashMap;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
 use anyhow::Context;
 use chrono::Local;
-use log::info;
+use log::{info, warn};
 use pueue_lib::log::clean_log_handles;
+use pueue_lib::process_helper::terminated_by_signal;
 use pueue_lib::state::GroupStatus;
 use pueue_lib::task::{TaskResult, TaskStatus};
 
 use super::*;
 
 use crate::daemon::callbacks::spawn_callback;
-use crate::daemon::state_helper::{pause_on_failure, save_state, LockedState};
+use crate::daemon::state_helper::{
+    mark_tasks_changed, pause_on_failure, save_state, set_status, LockedState,
+};
 use crate::ok_or_shutdown;
 
+/// How long we wait for the log writers of a finished task to write the remaining output.
+const LOG_WRITER_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Check whether there are any finished processes
 /// In case there are, handle them and update the shared state
-pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
+///
+/// `exited_since` contains the point in time at which a task's process has been seen exiting,
+/// while its log writers were still busy.
+pub fn handle_finished_tasks(
+    settings: &Settings,
+    state: &mut LockedState,
+    exited_since: &mut HashMap<usize, Instant>,
+) {
     // Clone the state ref, so we don't have two mutable borrows later on.
-    let finished = get_finished(state);
+    let finished = get_finished(state, exited_since);
 
     // Nothing to do. Early return
     if finished.is_empty() {
         return;
     }
+    mark_tasks_changed(
+        settings,
+        finished.iter().map(|((task_id, _, _), _)| *task_id),
+    );
 
     for ((task_id, group, worker_id), error) in finished.iter() {
         let (enqueued_at, start) = {
@@ -40,28 +61,35 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
         // Handle std::io errors on child processes.
         // I have never seen something like this, but it might happen.
         if let Some(error) = error {
-            let (_taks_id, _child) = state
+            let (_taks_id, _child, log_writers) = state
                 .children
                 .0
                 .get_mut(group)
                 .expect("Worker group must exist when handling finished tasks.")
                 .remove(worker_id)
                 .expect("Errored child went missing while handling finished task.");
+            join_log_writers(*task_id, log_writers);
 
             // Update the tasks's state and return a clone for callback handling.
             let task = {
                 let task = state.tasks.get_mut(task_id).unwrap();
 
-                task.status = TaskStatus::Done {
-                    enqueued_at,
-                    start,
-                    end: Local::now(),
-                    result: TaskResult::Errored,
-                };
+                set_status(
+                    settings,
+                    task,
+                    TaskStatus::Done {
+                        enqueued_at,
+                        start,
+                        end: Local::now(),
+                        result: TaskResult::Errored,
+                    },
+                );
 
                 task.clone()
             };
 
+            // Errored tasks aren't stashed again, as they didn't exit by being killed.
+            state.stash_on_exit.remove(task_id);
             spawn_callback(settings, state, &task);
             error!("Child {} failed with io::Error: {:?}", task_id, error);
 
@@ -70,7 +98,7 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
         }
 
         // Handle any tasks that exited with some kind of exit code
-        let (_task_id, mut child) = state
+        let (_task_id, mut child, log_writers) = state
             .children
             .0
             .get_mut(group)
@@ -82,21 +110,45 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
         // Errors really shouldn't happen in here, since we already checked if it's finished
         // with try_wait() before.
         let exit_code_result = child.wait();
-        let exit_code = exit_code_result
+        let exit_status = exit_code_result
             .context(format!(
                 "Failed on wait() for finished task {task_id} with error: {error:?}"
             ))
-            .unwrap()
-            .code();
+            .unwrap();
+
+        // All output has been written to the log by now, see `get_finished`.
+        join_log_writers(*task_id, log_writers);
 
         // Processes with exit code 0 exited successfully
-        // Processes with `None` have been killed by a Signal
-        let result = match exit_code {
+        // Processes with `None` have been killed by a Signal, which might count as a success
+        // for this specific task.
+        let result = match exit_status.code() {
             Some(0) => TaskResult::Success,
             Some(exit_code) => TaskResult::Failed(exit_code),
-            None => TaskResult::Killed,
+            None => {
+                let success_signals = &state.tasks.get(task_id).unwrap().success_signals;
+                if success_signals
+                    .iter()
+                    .any(|signal| terminated_by_signal(&exit_status, signal))
+                {
+                    TaskResult::Success
+                } else {
+                    TaskResult::Killed
+                }
+            }
         };
 
+        // The task has been killed by `pueue pause --restash` and is put back into the stash.
+        if state.stash_on_exit.remove(task_id) {
+            info!("Task {task_id} has been stashed again");
+            let task = state
+                .tasks
+                .get_mut(task_id)
+                .expect("Task was removed before child process has finished!");
+            set_status(settings, task, TaskStatus::Stashed { enqueue_at: None });
+            continue;
+        }
+
         info!("Task {task_id} finished with result: {result:?}");
 
         // Update the tasks's state and return a clone for callback handling.
@@ -106,12 +158,16 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
                 .get_mut(task_id)
                 .expect("Task was removed before child process has finished!");
 
-            task.status = TaskStatus::Done {
-                enqueued_at,
-                start,
-                end: Local::now(),
-                result: result.clone(),
-            };
+            set_status(
+                settings,
+                task,
+                TaskStatus::Done {
+                    enqueued_at,
+                    start,
+                    end: Local::now(),
+                    result: result.clone(),
+                },
+            );
 
             task.clone()
         };
@@ -138,23 +194,56 @@ pub fn handle_finished_tasks(settings: &Settings, state: &mut LockedState) {
 
 /// Gather all finished tasks and sort them by finished and errored.
 /// Returns a list of finished task ids and whether they errored or not.
-fn get_finished(state: &mut LockedState) -> Vec<((usize, String, usize), Option<std::io::Error>)> {
+///
+/// A task only counts as finished once all of its output has been written to the log.
+/// Processes that have been started in the background by the task might keep the output open
+/// though, which is why we only wait for a limited amount of time.
+/// We never block while waiting, the task is simply picked up again on one of the next ticks.
+fn get_finished(
+    state: &mut LockedState,
+    exited_since: &mut HashMap<usize, Instant>,
+) -> Vec<((usize, String, usize), Option<std::io::Error>)> {
+    // Forget about tasks whose children have been removed in the meantime.
+    exited_since.retain(|task_id, _| state.children.has_child(*task_id));
+
     let mut finished = Vec::new();
     for (group, children) in state.children.0.iter_mut() {
-        for (worker_id, (task_id, child)) in children.iter_mut() {
-            match child.try_wait() {
+        for (worker_id, (task_id, child, log_writers)) in children.iter_mut() {
+            let error = match child.try_wait() {
                 // Handle a child error.
-                Err(error) => {
-                    finished.push(((*task_id, group.clone(), *worker_id), Some(error)));
-                }
+                Err(error) => Some(error),
                 // Child process did not exit yet
                 Ok(None) => continue,
-                Ok(_exit_status) => {
-                    finished.push(((*task_id, group.clone(), *worker_id), None));
-                }
+                Ok(_exit_status) => None,
+            };
+
+            let exited_at = *exited_since.entry(*task_id).or_insert_with(Instant::now);
+            if log_writers.iter().any(|writer| !writer.is_finished())
+                && exited_at.elapsed() < LOG_WRITER_TIMEOUT
+            {
+                continue;
             }
+
+            exited_since.remove(task_id);
+            finished.push(((*task_id, group.clone(), *worker_id), error));
         }
     }
 
     finished
 }
+
+/// Join the threads that copied a finished child's output into its log file.
+///
+/// Threads that are still running at this point are left alone, as some other process keeps the
+/// output open. See [get_finished].
+fn join_log_writers(task_id: usize, log_writers: Vec<JoinHandle<()>>) {
+    for writer in log_writers {
+        if !writer.is_finished() {
+            warn!("The output of task {task_id} is still open, its log might be incomplete");
+            continue;
+        }
+        if writer.join().is_err() {
+            error!("The log writer of task {task_id} panicked");
+        }
+    }
+}
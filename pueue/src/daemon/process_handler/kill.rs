@@ -16,7 +16,8 @@ use crate::ok_or_shutdown;
 /// By default, this kills tasks with Rust's subprocess handling "kill" logic.
 /// However, the user can decide to send unix signals to the processes as well.
 ///
-/// `issued_by_user` This is `true` when a kill is issued by an actual user.
+/// `pause_groups` This is `true` when a kill is issued by an actual user, unless they
+///   explicitly asked to keep the groups running (`pueue kill --all-groups`).
 ///   It is `false`, if the daemon resets or during shutdown.
 ///
 ///   In case `true` is given and  a `group` or `all` are killed the affected groups should
@@ -29,7 +30,7 @@ pub fn kill(
     settings: &Settings,
     state: &mut LockedState,
     tasks: TaskSelection,
-    issued_by_user: bool,
+    pause_groups: bool,
     signal: Option<Signal>,
 ) {
     // Get the keys of all tasks that should be resumed
@@ -42,7 +43,7 @@ pub fn kill(
             };
 
             // Check whether the group should be paused before killing the tasks.
-            if should_pause_group(state, issued_by_user, &group_name) {
+            if should_pause_group(state, pause_groups, &group_name) {
                 let group = state.groups.get_mut(&group_name).unwrap();
                 group.status = GroupStatus::Paused;
             }
@@ -65,7 +66,7 @@ pub fn kill(
             // Pause all groups, if applicable
             let group_names: Vec<String> = state.groups.keys().cloned().collect();
             for group_name in group_names {
-                if should_pause_group(state, issued_by_user, &group_name) {
+                if should_pause_group(state, pause_groups, &group_name) {
                     state.set_status_for_all_groups(GroupStatus::Paused);
                 }
             }
@@ -119,10 +120,11 @@ pub fn kill_task(state: &mut LockedState, task_id: usize) {
 
 /// Determine, whether a group should be paused during a kill command.
 /// It should only be paused if:
-/// - The kill was issued by the user, i.e. it wasn't issued by a system during shutdown/reset.
+/// - Pausing was requested, i.e. the kill was issued by the user and wasn't issued by a system
+///   during shutdown/reset.
 /// - The group that's being killed must have queued or stashed-enqueued tasks.
-fn should_pause_group(state: &LockedState, issued_by_user: bool, group: &str) -> bool {
-    if !issued_by_user {
+fn should_pause_group(state: &LockedState, pause_groups: bool, group: &str) -> bool {
+    if !pause_groups {
         return false;
     }
 
@@ -6,15 +6,25 @@ use pueue_lib::settings::Settings;
 use pueue_lib::state::GroupStatus;
 use pueue_lib::task::TaskStatus;
 
-use crate::daemon::state_helper::{save_state, LockedState};
+use crate::daemon::state_helper::{mark_tasks_changed, save_state, set_status, LockedState};
 use crate::ok_or_shutdown;
 
+use super::kill::kill_task;
 use super::perform_action;
 
 /// Pause specific tasks or groups.
 ///
 /// `wait` decides, whether running tasks will kept running until they finish on their own.
-pub fn pause(settings: &Settings, state: &mut LockedState, selection: TaskSelection, wait: bool) {
+///
+/// `restash` kills the running and paused tasks instead. Once their processes exited, they're
+///   moved back to the `Stashed` state by the [finish](super::finish) handler.
+pub fn pause(
+    settings: &Settings,
+    state: &mut LockedState,
+    selection: TaskSelection,
+    wait: bool,
+    restash: bool,
+) {
     // Get the keys of all tasks that should be paused
     let keys: Vec<usize> = match selection {
         TaskSelection::TaskIds(task_ids) => task_ids,
@@ -30,7 +40,12 @@ pub fn pause(settings: &Settings, state: &mut LockedState, selection: TaskSelect
             info!("Pausing group {group_name}");
 
             let filtered_tasks = state.filter_tasks_of_group(
-                |task| matches!(task.status, TaskStatus::Running { .. }),
+                |task| match task.status {
+                    TaskStatus::Running { .. } => true,
+                    // Paused tasks still occupy a slot, so they're stashed as well.
+                    TaskStatus::Paused { .. } => restash,
+                    _ => false,
+                },
                 &group_name,
             );
 
@@ -45,8 +60,24 @@ pub fn pause(settings: &Settings, state: &mut LockedState, selection: TaskSelect
         }
     };
 
-    // Pause all tasks that were found.
-    if !wait {
+    // Kill all tasks that were found and remember to stash them, once they exited.
+    if restash {
+        for id in keys {
+            let Some(task) = state.tasks.get(&id) else {
+                continue;
+            };
+            if !matches!(
+                task.status,
+                TaskStatus::Running { .. } | TaskStatus::Paused { .. }
+            ) {
+                continue;
+            }
+
+            state.stash_on_exit.insert(id);
+            kill_task(state, id);
+        }
+    } else if !wait {
+        // Pause all tasks that were found.
         for id in keys {
             // Get the enqueued_at/start times from the current state.
             let (enqueued_at, start) = match state.tasks.get(&id).unwrap().status {
@@ -64,7 +95,10 @@ pub fn pause(settings: &Settings, state: &mut LockedState, selection: TaskSelect
             };
 
             if success {
-                state.change_status(id, TaskStatus::Paused { enqueued_at, start });
+                if let Some(task) = state.tasks.get_mut(&id) {
+                    set_status(settings, task, TaskStatus::Paused { enqueued_at, start });
+                }
+                mark_tasks_changed(settings, [id]);
             }
         }
     }
@@ -1,17 +1,24 @@
-use std::io::Write;
+use std::io::{self, Read, Write};
 use std::process::Stdio;
+use std::thread::{self, JoinHandle};
 
-use chrono::Local;
+use chrono::{DateTime, Local};
 use command_group::CommandGroup;
 use log::{error, info, warn};
-use pueue_lib::log::{create_log_file_handles, get_writable_log_file_handle};
-use pueue_lib::process_helper::compile_shell_command;
+use pueue_lib::log::{
+    create_log_file_handles, get_writable_log_file_handle, LineLengthLimiter, LineTimestamper,
+};
+use pueue_lib::process_helper::{
+    apply_memory_limit, compile_group_shell_command, compile_raw_command, run_as_user,
+};
 use pueue_lib::settings::Settings;
 use pueue_lib::state::GroupStatus;
 use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 use crate::daemon::callbacks::spawn_callback;
-use crate::daemon::state_helper::{pause_on_failure, save_state, LockedState};
+use crate::daemon::state_helper::{
+    mark_tasks_changed, pause_on_failure, save_state, set_status, LockedState,
+};
 use crate::ok_or_shutdown;
 
 /// See if we can start a new queued task.
@@ -141,18 +148,50 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
     };
 
     // Get all necessary info for starting the task
-    let (command, path, group, mut envs) = {
+    let (command, raw_command, memory_limit, path, create_workdir, group, mut envs, user) = {
         let task = state.tasks.get(&task_id).unwrap();
         (
             task.command.clone(),
+            task.raw_command.clone(),
+            task.memory_limit,
             task.path.clone(),
+            task.create_workdir,
             task.group.clone(),
             task.envs.clone(),
+            task.user.clone(),
         )
     };
 
-    // Build the shell command that should be executed.
-    let mut command = compile_shell_command(settings, &command);
+    // Create the working directory, if the user asked for it.
+    if create_workdir {
+        if let Err(err) = std::fs::create_dir_all(&path) {
+            let error =
+                format!("Failed to create working directory {path:?} of task {task_id}: {err}");
+            handle_spawn_failure(settings, state, task_id, enqueued_at, error);
+            return;
+        }
+    }
+
+    // Build the command that should be executed.
+    // Raw commands are executed directly, everything else is wrapped in a shell.
+    let mut command = match raw_command {
+        Some(args) if !args.is_empty() => compile_raw_command(settings, &args),
+        _ => compile_group_shell_command(settings, Some(&group), &command),
+    };
+
+    // Run the task in its own cgroup, if its memory usage should be limited.
+    if let Some(memory_limit) = memory_limit {
+        command = apply_memory_limit(command, memory_limit);
+    }
+
+    // Drop privileges to the task's user, if one has been specified.
+    if let Some(user) = user {
+        if let Err(err) = run_as_user(&mut command, &user) {
+            let error = format!("Failed to spawn child {task_id} as user '{user}': {err:#}");
+            handle_spawn_failure(settings, state, task_id, enqueued_at, error);
+            return;
+        }
+    }
 
     // Determine the worker's id depending on the current group.
     // Inject that info into the environment.
@@ -160,67 +199,166 @@ pub fn spawn_process(settings: &Settings, state: &mut LockedState, task_id: usiz
     envs.insert("PUEUE_GROUP".into(), group.clone());
     envs.insert("PUEUE_WORKER_ID".into(), worker_id.to_string());
 
+    // If the line length of the log is limited or the lines are timestamped, the output has to
+    // pass through the daemon. Otherwise, the process writes directly to the log file.
+    let max_line_length = settings.daemon.max_log_line_length;
+    let timestamp_logs = settings.daemon.timestamp_logs;
+    let (stdout, stderr, log_handles) = if max_line_length.is_some() || timestamp_logs {
+        (
+            Stdio::piped(),
+            Stdio::piped(),
+            Some((stdout_log, stderr_log)),
+        )
+    } else {
+        (Stdio::from(stdout_log), Stdio::from(stderr_log), None)
+    };
+
+    // The environment variables of the configuration are applied on top of the task's own
+    // environment. Those of the task's group take precedence over the global ones.
+    let group_env_vars = settings
+        .daemon
+        .group_env_vars
+        .get(&group)
+        .cloned()
+        .unwrap_or_default();
+
     // Spawn the actual subprocess
     let spawned_command = command
         .current_dir(path)
         .stdin(Stdio::piped())
         .env_clear()
         .envs(envs.clone())
-        .stdout(Stdio::from(stdout_log))
-        .stderr(Stdio::from(stderr_log))
+        .envs(&settings.daemon.env_vars)
+        .envs(group_env_vars)
+        .stdout(stdout)
+        .stderr(stderr)
         .group_spawn();
 
     // Check if the task managed to spawn
-    let child = match spawned_command {
+    let mut child = match spawned_command {
         Ok(child) => child,
         Err(err) => {
             let error = format!("Failed to spawn child {task_id} with err: {err:?}");
-            error!("{}", error);
-
-            // Write some debug log output to the task's log file.
-            // This should always work, but print a datailed error if it didn't work.
-            if let Ok(mut file) = get_writable_log_file_handle(task_id, &pueue_directory) {
-                let log_output =
-                    format!("Pueue error, failed to spawn task. Check your command.\n{error}");
-                let write_result = file.write_all(log_output.as_bytes());
-                if let Err(write_err) = write_result {
-                    error!("Failed to write spawn error to task log: {}", write_err);
-                }
-            }
-
-            // Update all necessary fields on the task.
-            let task = {
-                let task = state.tasks.get_mut(&task_id).unwrap();
-                task.status = TaskStatus::Done {
-                    enqueued_at,
-                    start: Local::now(),
-                    end: Local::now(),
-                    result: TaskResult::FailedToSpawn(error),
-                };
-                task.clone()
-            };
-
-            // Spawn any callback if necessary
-            spawn_callback(settings, state, &task);
-
-            pause_on_failure(state, settings, &task.group);
-            ok_or_shutdown!(settings, state, save_state(state, settings));
+            handle_spawn_failure(settings, state, task_id, enqueued_at, error);
             return;
         }
     };
 
+    // Copy the piped output into the log file.
+    let mut log_writers = Vec::new();
+    if let Some((stdout_log, stderr_log)) = log_handles {
+        // Both outputs are written to the same file, so their timestamps have to agree on where
+        // a line starts.
+        let (stdout_log, stderr_log): (LogWriter, LogWriter) = if timestamp_logs {
+            let stdout_log = LineTimestamper::new(stdout_log);
+            let stderr_log = LineTimestamper::sharing_line_with(stderr_log, &stdout_log);
+            (Box::new(stdout_log), Box::new(stderr_log))
+        } else {
+            (Box::new(stdout_log), Box::new(stderr_log))
+        };
+
+        if let Some(stdout) = child.inner().stdout.take() {
+            log_writers.push(spawn_log_writer(stdout, stdout_log, max_line_length));
+        }
+        if let Some(stderr) = child.inner().stderr.take() {
+            log_writers.push(spawn_log_writer(stderr, stderr_log, max_line_length));
+        }
+    }
+
     // Save the process handle in our self.children datastructure.
-    state.children.add_child(&group, worker_id, task_id, child);
+    state
+        .children
+        .add_child(&group, worker_id, task_id, child, log_writers);
 
     let task = state.tasks.get_mut(&task_id).unwrap();
-    task.status = TaskStatus::Running {
-        enqueued_at,
-        start: Local::now(),
-    };
+    set_status(
+        settings,
+        task,
+        TaskStatus::Running {
+            enqueued_at,
+            start: Local::now(),
+        },
+    );
+    task.worker_id = Some(worker_id);
     // Overwrite the task's environment variables with the new ones, containing the
     // PUEUE_WORKER_ID and PUEUE_GROUP variables.
     task.envs = envs;
 
     info!("Started task: {}", task.command);
+    mark_tasks_changed(settings, [task_id]);
+    ok_or_shutdown!(settings, state, save_state(state, settings));
+}
+
+/// Mark a task as failed, if its process couldn't be spawned.
+fn handle_spawn_failure(
+    settings: &Settings,
+    state: &mut LockedState,
+    task_id: usize,
+    enqueued_at: DateTime<Local>,
+    error: String,
+) {
+    error!("{}", error);
+
+    // Write some debug log output to the task's log file.
+    // This should always work, but print a datailed error if it didn't work.
+    let pueue_directory = settings.shared.pueue_directory();
+    if let Ok(mut file) = get_writable_log_file_handle(task_id, &pueue_directory) {
+        let log_output = format!("Pueue error, failed to spawn task. Check your command.\n{error}");
+        let write_result = file.write_all(log_output.as_bytes());
+        if let Err(write_err) = write_result {
+            error!("Failed to write spawn error to task log: {}", write_err);
+        }
+    }
+
+    // Update all necessary fields on the task.
+    let task = {
+        let task = state.tasks.get_mut(&task_id).unwrap();
+        set_status(
+            settings,
+            task,
+            TaskStatus::Done {
+                enqueued_at,
+                start: Local::now(),
+                end: Local::now(),
+                result: TaskResult::FailedToSpawn(error),
+            },
+        );
+        task.clone()
+    };
+    mark_tasks_changed(settings, [task_id]);
+
+    // Spawn any callback if necessary
+    spawn_callback(settings, state, &task);
+
+    pause_on_failure(state, settings, &task.group);
     ok_or_shutdown!(settings, state, save_state(state, settings));
 }
+
+/// A writer for the log file of a task, which might add timestamps to each line.
+type LogWriter = Box<dyn Write + Send>;
+
+/// Copy the output of a process to its log file in a separate thread.
+/// Lines that are longer than `max_line_length` bytes are truncated.
+///
+/// The thread stops as soon as the process closes its output, i.e. when it exits.
+/// It's joined once the process finished, so no output is missing when the task is done.
+fn spawn_log_writer<R: Read + Send + 'static>(
+    mut output: R,
+    log: LogWriter,
+    max_line_length: Option<usize>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        // The timestamps are added after truncating, so they don't count towards the line length.
+        let mut writer: Box<dyn Write> = match max_line_length {
+            Some(max_line_length) => Box::new(LineLengthLimiter::new(log, max_line_length)),
+            None => log,
+        };
+
+        if let Err(err) = io::copy(&mut output, &mut writer) {
+            error!("Failed to write task output to log file: {err}");
+        }
+        if let Err(err) = writer.flush() {
+            error!("Failed to flush task output to log file: {err}");
+        }
+    })
+}
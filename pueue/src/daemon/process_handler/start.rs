@@ -6,7 +6,7 @@ use pueue_lib::{
 };
 
 use crate::daemon::process_handler::spawn::spawn_process;
-use crate::daemon::state_helper::{save_state, LockedState};
+use crate::daemon::state_helper::{mark_tasks_changed, save_state, set_status, LockedState};
 use crate::ok_or_shutdown;
 
 use super::perform_action;
@@ -25,7 +25,7 @@ pub fn start(settings: &Settings, state: &mut LockedState, tasks: TaskSelection)
             for task_id in task_ids {
                 // Continue all children that are simply paused
                 if state.children.has_child(task_id) {
-                    continue_task(state, task_id);
+                    continue_task(settings, state, task_id);
                 } else {
                     // Start processes for all tasks that haven't been started yet
                     spawn_process(settings, state, task_id);
@@ -63,14 +63,14 @@ pub fn start(settings: &Settings, state: &mut LockedState, tasks: TaskSelection)
 
     // Resume all specified paused tasks
     for task_id in task_ids {
-        continue_task(state, task_id);
+        continue_task(settings, state, task_id);
     }
 
     ok_or_shutdown!(settings, state, save_state(state, settings));
 }
 
 /// Send a start signal to a paused task to continue execution.
-fn continue_task(state: &mut LockedState, task_id: usize) {
+fn continue_task(settings: &Settings, state: &mut LockedState, task_id: usize) {
     // Task doesn't exist
     if !state.children.has_child(task_id) {
         return;
@@ -106,6 +106,9 @@ fn continue_task(state: &mut LockedState, task_id: usize) {
     };
 
     if success {
-        state.change_status(task_id, TaskStatus::Running { enqueued_at, start });
+        if let Some(task) = state.tasks.get_mut(&task_id) {
+            set_status(settings, task, TaskStatus::Running { enqueued_at, start });
+        }
+        mark_tasks_changed(settings, [task_id]);
     }
 }
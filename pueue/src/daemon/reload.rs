@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use pueue_lib::settings::{Settings, CONFIG_FROM_STDIN};
+
+/// Read the configuration again and apply all settings that can be changed while the daemon is
+/// running. See [apply_reloadable_settings] for which settings are reloaded.
+pub fn reload_settings(
+    config_path: &Option<PathBuf>,
+    profile: &Option<String>,
+    current: &Settings,
+) -> Result<Settings> {
+    // The config has already been consumed from stdin on startup.
+    if config_path.as_deref() == Some(Path::new(CONFIG_FROM_STDIN)) {
+        bail!("The configuration has been read from stdin and cannot be reloaded.");
+    }
+
+    let (mut settings, config_found) =
+        Settings::read(config_path).context("Error while reading configuration.")?;
+    if !config_found {
+        bail!("Couldn't find a configuration file.");
+    }
+    if let Some(profile) = profile {
+        settings.load_profile(profile)?;
+    }
+
+    Ok(apply_reloadable_settings(current, settings))
+}
+
+/// Take over all settings from `new` that can safely be changed while the daemon is running.
+///
+/// These are all `daemon` settings, e.g. the callback, the environment variables that're
+/// injected into tasks, the maximum log line length, the shell command or the configured groups
+/// and their parallel tasks, as well as the `client` settings that are used by the daemon,
+/// e.g. for formatting timestamps.
+///
+/// The `shared` settings, such as the socket, the TLS certificates, the shared secret or the
+/// pueue directory, are kept as they are. Changing those requires a restart of the daemon.
+pub fn apply_reloadable_settings(current: &Settings, new: Settings) -> Settings {
+    Settings {
+        client: new.client,
+        daemon: new.daemon,
+        ..current.clone()
+    }
+}
+
+/// Reload the configuration, whenever the daemon receives a SIGHUP.
+/// The reloaded settings are published to the task handler and the message handler via the
+/// given channel.
+#[cfg(unix)]
+pub fn setup_reload_handling(
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+    sender: tokio::sync::watch::Sender<Settings>,
+) -> Result<()> {
+    use log::{info, warn};
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup()).context("Failed to listen for SIGHUP.")?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            info!("Received SIGHUP, reloading configuration.");
+            let reloaded = reload_settings(&config_path, &profile, &sender.borrow());
+            match reloaded {
+                Ok(settings) => {
+                    sender.send_replace(settings);
+                    info!("Configuration has been reloaded.");
+                }
+                Err(error) => warn!("Failed to reload configuration:\n{error:?}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn reload_applies_daemon_settings_only() -> Result<()> {
+        let tempdir = TempDir::new()?;
+        let config_path = tempdir.path().join("pueue.yml");
+
+        let mut current = Settings::default();
+        current.shared.host = "original".into();
+        current.daemon.callback = Some("echo original".to_string());
+
+        // Change both, a reloadable and a non-reloadable setting on disk.
+        let mut changed = current.clone();
+        changed.shared.host = "changed".into();
+        changed.daemon.callback = Some("echo changed".to_string());
+        changed.daemon.env_vars = HashMap::from([("FOO".to_string(), "bar".to_string())]);
+        changed.save(&Some(config_path.clone()))?;
+
+        let reloaded = reload_settings(&Some(config_path), &None, &current)?;
+        assert_eq!(reloaded.daemon, changed.daemon);
+        assert_eq!(reloaded.shared, current.shared);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reload_fails_for_stdin_config() {
+        let current = Settings::default();
+        let result = reload_settings(&Some(PathBuf::from("-")), &None, &current);
+        assert!(result.is_err());
+    }
+}
@@ -1,17 +1,32 @@
-use std::fs;
-use std::path::Path;
-use std::sync::MutexGuard;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 
 use anyhow::{Context, Result};
-use chrono::prelude::*;
-use log::{debug, info};
+use chrono::{prelude::*, TimeDelta};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
 
+use pueue_lib::network::message::HistoryEntry;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::{Group, GroupStatus, State, PUEUE_DEFAULT_GROUP};
-use pueue_lib::task::{TaskResult, TaskStatus};
+use pueue_lib::state::{Group, GroupStatus, SharedState, State, PUEUE_DEFAULT_GROUP};
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 pub type LockedState<'a> = MutexGuard<'a, State>;
 
+/// Lock the state and run the given closure on it. \
+/// The lock is released as soon as the closure returns.
+///
+/// Since the closure is synchronous, it's impossible to accidentally hold the lock across an
+/// `.await` point, which would block all other parts of the daemon in the meantime.
+/// Prefer this over locking the state by hand, especially in async code.
+pub fn with_state<R>(state: &SharedState, f: impl FnOnce(&mut LockedState) -> R) -> R {
+    let mut state = state.lock().unwrap();
+    f(&mut state)
+}
+
 /// Check if a task can be deleted. \
 /// We have to check all dependant tasks, that haven't finished yet.
 /// This is necessary to prevent deletion of tasks which are specified as a dependency.
@@ -61,19 +76,185 @@ pub fn pause_on_failure(state: &mut LockedState, settings: &Settings, group: &st
     }
 }
 
+/// Set the status of a task. \
+/// The change is recorded in the task's status history, if the user enabled it.
+pub fn set_status(settings: &Settings, task: &mut Task, status: TaskStatus) {
+    task.status = status;
+    record_status(settings, task);
+}
+
+/// Record the current status of a task in its status history, if the user enabled it.
+pub fn record_status(settings: &Settings, task: &mut Task) {
+    if settings.daemon.track_status_history {
+        task.record_status_change();
+    }
+}
+
+/// The file the full snapshot of the state is saved to.
+const STATE_FILE: &str = "state.json";
+/// The write-ahead log with all changes to the state since the last snapshot.
+const STATE_WAL_FILE: &str = "state.wal.jsonl";
+
+/// A single record of the state's write-ahead log, which is stored as JSON lines.
+///
+/// Each log starts with a [WalRecord::Generation] record. All other records describe a change
+/// to the state and are applied in order on top of the snapshot with the same generation.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum WalRecord {
+    /// The generation of the snapshot this log applies to.
+    Generation(u64),
+    /// A task has been added or changed.
+    Task(Box<Task>),
+    /// A task has been removed.
+    RemovedTask(usize),
+    /// Any of the groups changed.
+    Groups(BTreeMap<String, Group>),
+}
+
+/// Metadata that's saved next to the state in a snapshot.
+#[derive(Deserialize)]
+struct SnapshotMetadata {
+    /// The generation of the snapshot. Snapshots that are written without a write-ahead log
+    /// don't have a generation, which is why it defaults to `0`.
+    #[serde(default)]
+    wal_generation: u64,
+}
+
+/// The daemon's bookkeeping about the state as it has been persisted last.
+///
+/// If the daemon persists state changes in a write-ahead log, this is used to only append the
+/// parts of the state that actually changed since they've been persisted.
+#[derive(Debug, Default)]
+struct PersistedState {
+    /// The ids of all tasks that have been added, changed or removed since the last save.
+    changed_tasks: BTreeSet<usize>,
+    /// The persisted groups. `None`, if nothing has been persisted by this process yet.
+    /// Groups are few and small, which is why they're simply compared on each save.
+    groups: Option<BTreeMap<String, Group>>,
+    /// The highest generation of any snapshot or write-ahead log that has been seen or written.
+    /// The write-ahead log only applies to the snapshot of the same generation.
+    generation: u64,
+    /// The amount of records that have been appended to the write-ahead log since the last
+    /// snapshot.
+    wal_records: usize,
+}
+
+/// The bookkeeping of each state that's persisted, identified by its `pueue_directory`.
+///
+/// The state itself is shared with clients, which is why the bookkeeping is kept in here. \
+/// The daemons of the test suite run in the same process, hence the map.
+static PERSISTED_STATES: Mutex<BTreeMap<PathBuf, PersistedState>> = Mutex::new(BTreeMap::new());
+
+/// Run the given closure on the bookkeeping of the state in the given `pueue_directory`.
+fn with_persisted_state<R>(
+    pueue_directory: PathBuf,
+    f: impl FnOnce(&mut PersistedState) -> R,
+) -> R {
+    let mut persisted_states = PERSISTED_STATES.lock().unwrap();
+    f(persisted_states.entry(pueue_directory).or_default())
+}
+
+/// Remember that the given tasks have been added, changed or removed. \
+/// They're then written to the write-ahead log on the next save.
+///
+/// This has to be called whenever a task is touched, as the daemon doesn't compare the whole
+/// state on each save. It's a no-op, if no write-ahead log is used.
+pub fn mark_tasks_changed(settings: &Settings, task_ids: impl IntoIterator<Item = usize>) {
+    if settings.daemon.state_wal_max_records.is_none() {
+        return;
+    }
+
+    with_persisted_state(settings.shared.pueue_directory(), |persisted| {
+        persisted.changed_tasks.extend(task_ids)
+    });
+}
+
 /// Save the current state to disk. \
-/// We do this to restore in case of a crash. \
-/// If log == true, the file will be saved with a time stamp.
+/// We do this to restore in case of a crash.
+///
+/// By default, the full state is written on every call. \
+/// If `daemon.state_wal_max_records` is set, only the tasks that have been marked via
+/// [mark_tasks_changed] and the groups, if they changed, are appended to a write-ahead log
+/// instead. Once the log grows too large, it's compacted into a new snapshot.
 ///
 /// In comparison to the daemon -> client communication, the state is saved
 /// as JSON for readability and debugging purposes.
 pub fn save_state(state: &State, settings: &Settings) -> Result<()> {
-    let serialized = serde_json::to_string(&state).context("Failed to serialize state:");
+    let pueue_directory = settings.shared.pueue_directory();
+    let path = pueue_directory.join(STATE_WAL_FILE);
+
+    let Some(max_records) = settings.daemon.state_wal_max_records else {
+        write_snapshot(state, settings, None)?;
+
+        // A previous log doesn't belong to this snapshot and must never be replayed on top of it.
+        if path.exists() {
+            fs::remove_file(&path).context("Failed to remove write-ahead log of the state.")?;
+        }
+        return Ok(());
+    };
+
+    with_persisted_state(pueue_directory, |persisted| -> Result<()> {
+        let records = collect_changes(state, persisted);
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        // Compact the log into a new snapshot, if it grew too large, if it's missing or if
+        // nothing has been persisted by this process yet.
+        if persisted.groups.is_none()
+            || persisted.wal_records + records.len() > max_records
+            || !path.exists()
+        {
+            // Generations only ever increase, so a previous log never matches a newer snapshot.
+            // This includes logs of previous processes, e.g. if the snapshot has been removed.
+            let generation = persisted.generation.max(read_wal_generation(&path)) + 1;
+            write_snapshot(state, settings, Some(generation))?;
+            persisted.generation = generation;
+
+            // Start a new log for the new snapshot.
+            // Until this happened, the previous log is ignored due to its outdated generation.
+            let temp = path.with_extension("jsonl.partial");
+            let header = serialize_wal_records(&[WalRecord::Generation(generation)])?;
+            fs::write(&temp, header).context("Failed to write temp file while saving state.")?;
+            fs::rename(&temp, &path)
+                .context("Failed to overwrite old write-ahead log of the state.")?;
+
+            persisted.wal_records = 0;
+        } else {
+            let mut file = OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .context("Failed to open write-ahead log of the state.")?;
+            file.write_all(serialize_wal_records(&records)?.as_bytes())
+                .context("Failed to write write-ahead log of the state.")?;
+
+            persisted.wal_records += records.len();
+            debug!("Appended {} records to: {path:?}", records.len());
+        }
+
+        persisted.changed_tasks.clear();
+        persisted.groups = Some(state.groups.clone());
+
+        Ok(())
+    })
+}
+
+/// Write a full snapshot of the state. \
+/// Snapshots that belong to a write-ahead log contain the log's generation.
+fn write_snapshot(state: &State, settings: &Settings, generation: Option<u64>) -> Result<()> {
+    let serialized = match generation {
+        None => serde_json::to_string(&state),
+        Some(generation) => serde_json::to_value(state).and_then(|mut value| {
+            value["wal_generation"] = generation.into();
+            serde_json::to_string(&value)
+        }),
+    }
+    .context("Failed to serialize state:")?;
 
-    let serialized = serialized.unwrap();
     let path = settings.shared.pueue_directory();
     let temp = path.join("state.json.partial");
-    let real = path.join("state.json");
+    let real = path.join(STATE_FILE);
 
     // Write to temporary log file first, to prevent loss due to crashes.
     fs::write(&temp, serialized).context("Failed to write temp file while saving state.")?;
@@ -86,13 +267,161 @@ pub fn save_state(state: &State, settings: &Settings) -> Result<()> {
     Ok(())
 }
 
+/// Determine the write-ahead log records for all changes since the state has been persisted
+/// last.
+fn collect_changes(state: &State, persisted: &PersistedState) -> Vec<WalRecord> {
+    let mut records: Vec<WalRecord> = persisted
+        .changed_tasks
+        .iter()
+        .map(|task_id| match state.tasks.get(task_id) {
+            Some(task) => WalRecord::Task(Box::new(task.clone())),
+            None => WalRecord::RemovedTask(*task_id),
+        })
+        .collect();
+
+    if persisted.groups.as_ref() != Some(&state.groups) {
+        records.push(WalRecord::Groups(state.groups.clone()));
+    }
+
+    records
+}
+
+/// Read the generation of the write-ahead log at the given path. \
+/// Returns `0`, if there's no log or if it doesn't start with a generation.
+fn read_wal_generation(path: &Path) -> u64 {
+    let Ok(file) = File::open(path) else {
+        return 0;
+    };
+
+    let mut header = String::new();
+    if BufReader::new(file).read_line(&mut header).is_err() {
+        return 0;
+    }
+    match serde_json::from_str(&header) {
+        Ok(WalRecord::Generation(generation)) => generation,
+        _ => 0,
+    }
+}
+
+/// Serialize records as JSON lines.
+fn serialize_wal_records(records: &[WalRecord]) -> Result<String> {
+    let mut serialized = String::new();
+    for record in records {
+        serialized
+            .push_str(&serde_json::to_string(record).context("Failed to serialize state change.")?);
+        serialized.push('\n');
+    }
+
+    Ok(serialized)
+}
+
+/// Apply the write-ahead log to a restored snapshot of the given generation. \
+/// Logs of another generation are outdated, as their changes are already part of the snapshot.
+fn replay_wal(state: &mut State, pueue_directory: &Path, generation: u64) -> Result<()> {
+    let path = pueue_directory.join(STATE_WAL_FILE);
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let data = fs::read_to_string(&path).context("Failed to read write-ahead log of the state.")?;
+    let mut lines = data.lines();
+    match lines.next().map(serde_json::from_str) {
+        Some(Ok(WalRecord::Generation(wal_generation))) if wal_generation == generation => (),
+        _ => {
+            info!("Ignoring outdated write-ahead log of the state at: {path:?}");
+            return Ok(());
+        }
+    }
+
+    info!("Replaying write-ahead log of the state");
+    for line in lines {
+        // The last record might be incomplete, if the daemon crashed while writing it.
+        let record = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(err) => {
+                warn!("Stopping replay of the state's write-ahead log at invalid record: {err}");
+                break;
+            }
+        };
+
+        match record {
+            WalRecord::Generation(_) => (),
+            WalRecord::Task(task) => {
+                state.tasks.insert(task.id, *task);
+            }
+            WalRecord::RemovedTask(task_id) => {
+                state.tasks.remove(&task_id);
+            }
+            WalRecord::Groups(groups) => state.groups = groups,
+        }
+    }
+
+    Ok(())
+}
+
+/// Append entries to the task history, which is stored as JSON lines in the `pueue_directory`.
+///
+/// The history file is append-only and thereby survives `pueue clean`.
+pub fn append_history(entries: &[HistoryEntry], settings: &Settings) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let path = settings.shared.pueue_directory().join("history.jsonl");
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open history file.")?;
+
+    let mut serialized = String::new();
+    for entry in entries {
+        serialized
+            .push_str(&serde_json::to_string(entry).context("Failed to serialize history entry.")?);
+        serialized.push('\n');
+    }
+    file.write_all(serialized.as_bytes())
+        .context("Failed to write history file.")?;
+
+    Ok(())
+}
+
+/// Read all previously persisted history entries. \
+/// Lines that cannot be deserialized are skipped.
+pub fn read_history(settings: &Settings) -> Result<Vec<HistoryEntry>> {
+    let path = settings.shared.pueue_directory().join("history.jsonl");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let data = fs::read_to_string(&path).context("Failed to read history file.")?;
+    let entries = data
+        .lines()
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                warn!("Skipping invalid history entry: {err}");
+                None
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
 /// Restore the last state from a previous session. \
 /// The state is stored as json in the `pueue_directory`.
 ///
 /// If the state cannot be deserialized, an empty default state will be used instead. \
 /// All groups with queued tasks will be automatically paused to prevent unwanted execution.
-pub fn restore_state(pueue_directory: &Path) -> Result<Option<State>> {
-    let path = pueue_directory.join("state.json");
+pub fn restore_state(settings: &Settings) -> Result<Option<State>> {
+    let pueue_directory = settings.shared.pueue_directory();
+    let path = pueue_directory.join(STATE_FILE);
+
+    // Forget about anything a previous daemon in this process might have persisted.
+    with_persisted_state(pueue_directory.clone(), |persisted| {
+        *persisted = PersistedState::default()
+    });
 
     // Ignore if the file doesn't exist. It doesn't have to.
     if !path.exists() {
@@ -107,6 +436,14 @@ pub fn restore_state(pueue_directory: &Path) -> Result<Option<State>> {
     // Try to deserialize the state file.
     let mut state: State = serde_json::from_str(&data).context("Failed to deserialize state.")?;
 
+    // Apply all changes that have been made since the snapshot has been written.
+    let metadata: SnapshotMetadata =
+        serde_json::from_str(&data).context("Failed to deserialize state.")?;
+    replay_wal(&mut state, &pueue_directory, metadata.wal_generation)?;
+    with_persisted_state(pueue_directory, |persisted| {
+        persisted.generation = metadata.wal_generation
+    });
+
     // Restore all tasks.
     // While restoring the tasks, check for any invalid/broken stati.
     for (_, task) in state.tasks.iter_mut() {
@@ -120,17 +457,21 @@ pub fn restore_state(pueue_directory: &Path) -> Result<Option<State>> {
                 task.status,
                 TaskResult::Killed
             );
-            task.status = TaskStatus::Done {
-                start,
-                end: Local::now(),
-                enqueued_at,
-                result: TaskResult::Killed,
-            };
+            set_status(
+                settings,
+                task,
+                TaskStatus::Done {
+                    start,
+                    end: Local::now(),
+                    enqueued_at,
+                    result: TaskResult::Killed,
+                },
+            );
         }
 
         // Handle crash during editing of the task command.
         if matches!(task.status, TaskStatus::Locked { .. }) {
-            task.status = TaskStatus::Stashed { enqueue_at: None };
+            set_status(settings, task, TaskStatus::Stashed { enqueue_at: None });
         }
 
         // Go trough all tasks and set all groups that are no longer
@@ -145,6 +486,7 @@ pub fn restore_state(pueue_directory: &Path) -> Result<Option<State>> {
                     .or_insert(Group {
                         status: GroupStatus::Running,
                         parallel_tasks: 1,
+                        description: None,
                     })
             }
         };
@@ -162,3 +504,274 @@ pub fn restore_state(pueue_directory: &Path) -> Result<Option<State>> {
 
     Ok(Some(state))
 }
+
+/// Update the point in time since which each group has been idle and return all groups that
+/// have been idle for at least the given window.
+pub fn expired_idle_groups(
+    state: &State,
+    idle_since: &mut HashMap<String, DateTime<Local>>,
+    window: TimeDelta,
+    now: DateTime<Local>,
+) -> Vec<String> {
+    // Forget about groups that're in use again or that have been removed in the meantime.
+    idle_since.retain(|group, _| state.group_is_idle(group));
+
+    let mut expired = Vec::new();
+    for group in state.groups.keys() {
+        if !state.group_is_idle(group) {
+            continue;
+        }
+
+        let since = idle_since.entry(group.clone()).or_insert(now);
+        if now - *since >= window {
+            expired.push(group.clone());
+        }
+    }
+
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Settings that persist the state with a write-ahead log in a temporary directory.
+    fn wal_settings(max_records: usize) -> (Settings, TempDir) {
+        let tempdir = TempDir::new().unwrap();
+        let mut settings = Settings::default();
+        settings.shared.pueue_directory = Some(tempdir.path().to_owned());
+        settings.daemon.state_wal_max_records = Some(max_records);
+
+        (settings, tempdir)
+    }
+
+    fn stashed_task(command: &str) -> Task {
+        Task::new(
+            command.to_string(),
+            std::env::temp_dir(),
+            HashMap::new(),
+            PUEUE_DEFAULT_GROUP.to_string(),
+            TaskStatus::Stashed { enqueue_at: None },
+            Vec::new(),
+            0,
+            None,
+        )
+    }
+
+    /// The generation of the last snapshot that has been written for the given settings.
+    fn generation(settings: &Settings) -> u64 {
+        with_persisted_state(settings.shared.pueue_directory(), |persisted| {
+            persisted.generation
+        })
+    }
+
+    fn wal_lines(settings: &Settings) -> usize {
+        fs::read_to_string(settings.shared.pueue_directory().join(STATE_WAL_FILE))
+            .unwrap()
+            .lines()
+            .count()
+    }
+
+    /// Changes are appended to the write-ahead log and replayed on top of the last snapshot.
+    #[test]
+    fn replay_wal_over_snapshot() -> Result<()> {
+        let (settings, _tempdir) = wal_settings(100);
+        let mut state = State::new();
+        state.add_task(stashed_task("ls"));
+        state.add_task(stashed_task("pwd"));
+        // The first save writes a snapshot and starts a new log.
+        save_state(&state, &settings)?;
+        assert_eq!(wal_lines(&settings), 1);
+
+        // Change, add and remove a task and add a group.
+        state.tasks.get_mut(&0).unwrap().label = Some("changed".to_string());
+        mark_tasks_changed(&settings, [0]);
+        save_state(&state, &settings)?;
+        let task_id = state.add_task(stashed_task("whoami"));
+        state.tasks.remove(&1);
+        mark_tasks_changed(&settings, [task_id, 1]);
+        state.create_group("build");
+        save_state(&state, &settings)?;
+        // Saving without any changes doesn't write anything.
+        save_state(&state, &settings)?;
+        assert_eq!(wal_lines(&settings), 5);
+
+        // The snapshot alone doesn't contain the changes.
+        let snapshot: State = serde_json::from_str(&fs::read_to_string(
+            settings.shared.pueue_directory().join(STATE_FILE),
+        )?)?;
+        assert_eq!(snapshot.tasks.len(), 2);
+
+        let restored = restore_state(&settings)?.unwrap();
+        assert_eq!(restored, state);
+        assert_eq!(restored.tasks[&0].label, Some("changed".to_string()));
+
+        Ok(())
+    }
+
+    /// The log is compacted into a new snapshot once it grows too large.
+    #[test]
+    fn compact_wal() -> Result<()> {
+        let (settings, _tempdir) = wal_settings(2);
+        let mut state = State::new();
+        save_state(&state, &settings)?;
+
+        for command in ["ls", "pwd", "whoami", "date"] {
+            let task_id = state.add_task(stashed_task(command));
+            mark_tasks_changed(&settings, [task_id]);
+            save_state(&state, &settings)?;
+            assert!(
+                wal_lines(&settings) <= 3,
+                "The log should've been compacted"
+            );
+        }
+        assert!(generation(&settings) > 1);
+
+        let restored = restore_state(&settings)?.unwrap();
+        assert_eq!(restored, state);
+
+        Ok(())
+    }
+
+    /// A log that belongs to an older snapshot is ignored, as its changes are part of the snapshot.
+    #[test]
+    fn ignore_outdated_wal() -> Result<()> {
+        let (settings, _tempdir) = wal_settings(100);
+        let mut state = State::new();
+        state.add_task(stashed_task("ls"));
+        save_state(&state, &settings)?;
+
+        // Simulate a log of a previous generation.
+        let outdated = serialize_wal_records(&[
+            WalRecord::Generation(generation(&settings) - 1),
+            WalRecord::RemovedTask(0),
+        ])?;
+        fs::write(
+            settings.shared.pueue_directory().join(STATE_WAL_FILE),
+            outdated,
+        )?;
+
+        let restored = restore_state(&settings)?.unwrap();
+        assert_eq!(restored, state);
+
+        Ok(())
+    }
+
+    /// Saving the full state removes the log, as it doesn't belong to that snapshot.
+    #[test]
+    fn full_save_removes_wal() -> Result<()> {
+        let (mut settings, _tempdir) = wal_settings(100);
+        let mut state = State::new();
+        save_state(&state, &settings)?;
+        let wal_path = settings.shared.pueue_directory().join(STATE_WAL_FILE);
+        assert!(wal_path.exists());
+
+        settings.daemon.state_wal_max_records = None;
+        state.add_task(stashed_task("ls"));
+        save_state(&state, &settings)?;
+        assert!(!wal_path.exists(), "The log should've been removed");
+
+        let restored = restore_state(&settings)?.unwrap();
+        assert_eq!(restored, state);
+
+        Ok(())
+    }
+
+    /// New snapshots get a newer generation than any log on disk. \
+    /// Otherwise, a crash before the new log has been written would replay the stale log on
+    /// top of the new snapshot.
+    #[test]
+    fn generations_are_monotonic() -> Result<()> {
+        let (settings, _tempdir) = wal_settings(100);
+        let pueue_directory = settings.shared.pueue_directory();
+
+        // A stale log of a previous daemon, whose snapshot has been removed.
+        let stale = serialize_wal_records(&[WalRecord::Generation(1), WalRecord::RemovedTask(0)])?;
+        fs::write(pueue_directory.join(STATE_WAL_FILE), stale)?;
+
+        let mut state = State::new();
+        state.add_task(stashed_task("ls"));
+        save_state(&state, &settings)?;
+
+        let snapshot: SnapshotMetadata =
+            serde_json::from_str(&fs::read_to_string(pueue_directory.join(STATE_FILE))?)?;
+        assert_eq!(snapshot.wal_generation, 2);
+        assert_eq!(generation(&settings), 2);
+
+        Ok(())
+    }
+
+    /// The lock must be released as soon as the closure returns.
+    #[test]
+    fn with_state_releases_lock() {
+        let state: SharedState = Arc::new(Mutex::new(State::new()));
+
+        let group_count = with_state(&state, |state| {
+            // The state is locked while the closure is executed.
+            state.groups.len()
+        });
+        assert_eq!(group_count, 1);
+
+        assert!(
+            state.try_lock().is_ok(),
+            "The state should be unlocked after the closure returned."
+        );
+    }
+
+    #[test]
+    fn idle_groups_expire_after_window() {
+        let mut state = State::new();
+        state.create_group("empty");
+        let mut idle_since = HashMap::new();
+        let window = TimeDelta::try_seconds(60).unwrap();
+        let start = Local::now();
+
+        // The group just became idle.
+        let expired = expired_idle_groups(&state, &mut idle_since, window, start);
+        assert!(expired.is_empty());
+
+        // It's still inside the idle window.
+        let now = start + TimeDelta::try_seconds(30).unwrap();
+        let expired = expired_idle_groups(&state, &mut idle_since, window, now);
+        assert!(expired.is_empty());
+
+        // The idle window has passed.
+        let now = start + window;
+        let expired = expired_idle_groups(&state, &mut idle_since, window, now);
+        assert_eq!(expired, vec!["empty".to_string()]);
+    }
+
+    #[test]
+    fn idle_timer_resets_when_group_is_used() {
+        let mut state = State::new();
+        state.create_group("empty");
+        let mut idle_since = HashMap::new();
+        let window = TimeDelta::try_seconds(60).unwrap();
+        let start = Local::now();
+        expired_idle_groups(&state, &mut idle_since, window, start);
+
+        // A task is added and removed again, before the window passed.
+        let id = state.add_task(Task::new(
+            "ls".into(),
+            std::env::temp_dir(),
+            HashMap::new(),
+            "empty".into(),
+            TaskStatus::Stashed { enqueue_at: None },
+            Vec::new(),
+            0,
+            None,
+        ));
+        let now = start + TimeDelta::try_seconds(30).unwrap();
+        expired_idle_groups(&state, &mut idle_since, window, now);
+        state.tasks.remove(&id);
+
+        // The group has only been idle for 30 seconds since the task has been removed.
+        let now = start + window;
+        let expired = expired_idle_groups(&state, &mut idle_since, window, now);
+        assert!(expired.is_empty());
+    }
+}
@@ -1,23 +1,27 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Duration;
 
 use anyhow::Result;
-use chrono::prelude::*;
-use log::{error, info};
+use chrono::{prelude::*, TimeDelta};
+use log::{error, info, warn};
+use tokio::sync::watch::Receiver;
 
 use pueue_lib::children::Children;
 use pueue_lib::network::message::*;
 use pueue_lib::network::protocol::socket_cleanup;
 use pueue_lib::settings::Settings;
-use pueue_lib::state::{Group, GroupStatus, SharedState};
+use pueue_lib::state::{Group, GroupStatus, SharedState, State};
 use pueue_lib::task::{TaskResult, TaskStatus};
 
 use crate::daemon::pid::cleanup_pid_file;
-use crate::daemon::state_helper::save_state;
+use crate::daemon::state_helper::{
+    expired_idle_groups, mark_tasks_changed, save_state, set_status,
+};
 use crate::ok_or_shutdown;
 
 use super::callbacks::{check_callbacks, spawn_callback};
 use super::process_handler::finish::handle_finished_tasks;
+use super::process_handler::kill::kill_task;
 use super::process_handler::spawn::spawn_new;
 use super::state_helper::LockedState;
 
@@ -26,14 +30,21 @@ use super::state_helper::LockedState;
 ///
 /// - Handle finished tasks, i.e. cleanup processes, update statuses.
 /// - Callback handling logic. This is rather uncritical.
+/// - Kill running tasks that exceeded their timeout.
 /// - Enqueue any stashed processes which are ready for being queued.
 /// - Ensure tasks with dependencies have no failed ancestors
 /// - Handle shutdown logic (graceful & not graceful).
+/// - Remove groups that have been empty for too long, if the user enabled this.
 /// - If the client requested a reset: reset the state if all children have been killed and handled.
 /// - Check whether we can spawn new tasks.
 ///
 /// We also wait for 300ms to prevent this loop from running hot.
-pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
+///
+/// The settings are updated, whenever they're reloaded via the given channel.
+/// The groups of the `daemon.groups` setting are applied on startup and on every reload.
+pub async fn run(state: SharedState, mut settings_receiver: Receiver<Settings>) -> Result<()> {
+    let mut settings = settings_receiver.borrow_and_update().clone();
+
     // Initialize the subprocess management structure.
     {
         let mut state = state.lock().unwrap();
@@ -42,14 +53,31 @@ pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
             pools.insert(group.clone(), BTreeMap::new());
         }
         state.children = Children(pools);
+
+        apply_group_settings(&settings, &mut state, &HashMap::new());
     }
 
+    // The point in time since which each group has been idle.
+    let mut idle_since = HashMap::new();
+    // The point in time at which each task's process has exited, while its output was still being
+    // written to the log.
+    let mut exited_since = HashMap::new();
+    // The tasks that have already been killed due to their timeout.
+    let mut timed_out = HashSet::new();
+
     loop {
+        // Pick up any reloaded settings.
+        if settings_receiver.has_changed().unwrap_or(false) {
+            let previous_groups = std::mem::take(&mut settings.daemon.groups);
+            settings = settings_receiver.borrow_and_update().clone();
+            apply_group_settings(&settings, &mut state.lock().unwrap(), &previous_groups);
+        }
+
         'mutex_block: {
             let mut state = state.lock().unwrap();
 
             check_callbacks(&mut state);
-            handle_finished_tasks(&settings, &mut state);
+            handle_finished_tasks(&settings, &mut state, &mut exited_since);
 
             // Check if we're in shutdown.
             // If all tasks are killed, we do some cleanup and exit.
@@ -60,9 +88,12 @@ pub async fn run(state: SharedState, settings: Settings) -> Result<()> {
 
             // If we aren't in shutdown mode, do the usual stuff
             handle_group_resets(&settings, &mut state);
+            expire_tasks(&settings, &mut state);
+            kill_timed_out_tasks(&mut state, &mut timed_out);
             enqueue_delayed_tasks(&settings, &mut state);
             check_failed_dependencies(&settings, &mut state);
             spawn_new(&settings, &mut state);
+            remove_idle_groups(&settings, &mut state, &mut idle_since);
         }
 
         tokio::time::sleep(Duration::from_millis(300)).await;
@@ -103,7 +134,7 @@ fn handle_shutdown(settings: &Settings, state: &mut LockedState) {
 /// and no new tasks will be spawned.
 /// This function checks, if all killed children have been handled.
 /// If that's the case, completely reset the state
-fn handle_group_resets(_settings: &Settings, state: &mut LockedState) {
+fn handle_group_resets(settings: &Settings, state: &mut LockedState) {
     let groups_to_reset: Vec<String> = state
         .groups
         .iter()
@@ -118,7 +149,9 @@ fn handle_group_resets(_settings: &Settings, state: &mut LockedState) {
         }
 
         // Remove all tasks that belong to the group to reset
+        let task_ids = state.task_ids_in_group(name);
         state.tasks.retain(|_id, task| &task.group != name);
+        mark_tasks_changed(settings, task_ids);
 
         // Restart the group, now that it's devoid of tasks.
         if let Some(group) = state.groups.get_mut(name) {
@@ -130,21 +163,38 @@ fn handle_group_resets(_settings: &Settings, state: &mut LockedState) {
 /// As time passes, some delayed tasks may need to be enqueued.
 /// Gather all stashed tasks and enqueue them if it is after the task's enqueue_at
 fn enqueue_delayed_tasks(settings: &Settings, state: &mut LockedState) {
+    // Take a single snapshot of the current time, so all tasks of this iteration are compared
+    // against the same point in time, even if the system clock is adjusted in the meantime.
+    let now = Local::now();
     let mut changed = false;
     for (_, task) in state.tasks.iter_mut() {
-        if let TaskStatus::Stashed {
+        let TaskStatus::Stashed {
             enqueue_at: Some(time),
         } = task.status
-        {
-            if time <= Local::now() {
-                info!("Enqueuing delayed task : {}", task.id);
+        else {
+            continue;
+        };
 
-                task.status = TaskStatus::Queued {
-                    enqueued_at: Local::now(),
-                };
-                changed = true;
-            }
+        if time > now {
+            continue;
+        }
+
+        // The scheduled time might have passed a long time ago, e.g. if the daemon wasn't
+        // running at that time. Such tasks are enqueued right away.
+        let delay = now - time;
+        if delay > TimeDelta::try_seconds(1).unwrap() {
+            info!(
+                "Enqueuing delayed task {}, its scheduled time passed {}s ago",
+                task.id,
+                delay.num_seconds()
+            );
+        } else {
+            info!("Enqueuing delayed task : {}", task.id);
         }
+
+        set_status(settings, task, TaskStatus::Queued { enqueued_at: now });
+        mark_tasks_changed(settings, [task.id]);
+        changed = true;
     }
     // Save the state if a task has been enqueued
     if changed {
@@ -152,6 +202,192 @@ fn enqueue_delayed_tasks(settings: &Settings, state: &mut LockedState) {
     }
 }
 
+/// Apply the `daemon.groups` setting and persist the changes.
+/// `previous` are the groups of the settings before they've been reloaded.
+fn apply_group_settings(
+    settings: &Settings,
+    state: &mut LockedState,
+    previous: &HashMap<String, usize>,
+) {
+    if settings.daemon.groups.is_empty() && previous.is_empty() {
+        return;
+    }
+
+    sync_configured_groups(state, &settings.daemon.groups, previous);
+    ok_or_shutdown!(settings, state, save_state(state, settings));
+}
+
+/// Create all configured groups that don't exist yet and set their parallel tasks.
+///
+/// Groups that have been configured `previously`, but aren't anymore, are removed.
+/// This is refused for groups that still contain tasks, which also keeps running tasks intact.
+fn sync_configured_groups(
+    state: &mut State,
+    groups: &HashMap<String, usize>,
+    previous: &HashMap<String, usize>,
+) {
+    for (name, parallel_tasks) in groups {
+        if !state.groups.contains_key(name) {
+            info!("Creating group {name} from the configuration");
+            state.children.0.insert(name.clone(), BTreeMap::new());
+        }
+        state.create_group(name).parallel_tasks = *parallel_tasks;
+    }
+
+    for name in previous.keys().filter(|name| !groups.contains_key(*name)) {
+        if !state.groups.contains_key(name) {
+            continue;
+        }
+        if state.tasks.values().any(|task| &task.group == name) {
+            warn!("Not removing group {name}, as it still contains tasks");
+            continue;
+        }
+
+        info!("Removing group {name}, as it has been removed from the configuration");
+        if let Err(error) = state.remove_group(name) {
+            warn!("Failed to remove group {name}: {error}");
+            continue;
+        }
+        state.children.0.remove(name);
+    }
+}
+
+/// Remove all groups, that have been idle for longer than `daemon.auto_remove_empty_groups`.
+fn remove_idle_groups(
+    settings: &Settings,
+    state: &mut LockedState,
+    idle_since: &mut HashMap<String, DateTime<Local>>,
+) {
+    let Some(seconds) = settings.daemon.auto_remove_empty_groups else {
+        return;
+    };
+    let window = TimeDelta::try_seconds(seconds as i64).unwrap_or(TimeDelta::MAX);
+
+    let groups = expired_idle_groups(state, idle_since, window, Local::now());
+    if groups.is_empty() {
+        return;
+    }
+
+    for group in groups {
+        // Only remove the group, if there're no children left in its pool, which should always
+        // be the case for groups without tasks.
+        if state
+            .children
+            .0
+            .get(&group)
+            .is_some_and(|pool| !pool.is_empty())
+        {
+            continue;
+        }
+
+        info!("Removing group {group}, as it has been empty for {seconds}s");
+        if let Err(error) = state.remove_group(&group) {
+            error!("Failed to remove idle group {group}: {error}");
+            continue;
+        }
+        state.children.0.remove(&group);
+        idle_since.remove(&group);
+    }
+
+    ok_or_shutdown!(settings, state, save_state(state, settings));
+}
+
+/// Mark all queued or stashed tasks whose expiry time has passed as expired.
+/// These tasks won't be started anymore.
+fn expire_tasks(settings: &Settings, state: &mut LockedState) {
+    let now = Local::now();
+    let expired: Vec<usize> = state
+        .tasks
+        .iter()
+        .filter(|(_, task)| task.is_queued() || task.is_stashed())
+        .filter(|(_, task)| task.expire_at.is_some_and(|expire_at| expire_at <= now))
+        .map(|(id, _)| *id)
+        .collect();
+
+    if expired.is_empty() {
+        return;
+    }
+
+    for id in expired {
+        let task = {
+            let task = state.tasks.get_mut(&id).unwrap();
+            info!("Task {id} expired before it was started");
+
+            // Stashed tasks have never been enqueued.
+            let enqueued_at = match task.status {
+                TaskStatus::Queued { enqueued_at } => enqueued_at,
+                _ => now,
+            };
+            set_status(
+                settings,
+                task,
+                TaskStatus::Done {
+                    enqueued_at,
+                    start: now,
+                    end: now,
+                    result: TaskResult::Expired,
+                },
+            );
+            task.clone()
+        };
+        mark_tasks_changed(settings, [id]);
+
+        spawn_callback(settings, state, &task);
+    }
+
+    ok_or_shutdown!(settings, state, save_state(state, settings));
+}
+
+/// Kill all running tasks that have been running for longer than their timeout.
+/// Their processes are handled like any other killed process, i.e. they end up as
+/// [TaskResult::Killed].
+///
+/// `timed_out` contains the tasks that have already been killed. Their processes might take a
+/// while to exit, during which they must not be killed again.
+fn kill_timed_out_tasks(state: &mut LockedState, timed_out: &mut HashSet<usize>) {
+    for id in timed_out_tasks(state, Local::now(), timed_out) {
+        info!("Task {id} exceeded its timeout and is killed");
+        kill_task(state, id);
+    }
+}
+
+/// Get the ids of all running tasks whose timeout has passed at the given point in time.
+/// The runtime is measured from the start of the task, so time spent paused counts as well.
+///
+/// Tasks in `timed_out` are skipped, while the returned tasks are added to it.
+/// Tasks that are no longer running are removed from it.
+fn timed_out_tasks(
+    state: &State,
+    now: DateTime<Local>,
+    timed_out: &mut HashSet<usize>,
+) -> Vec<usize> {
+    timed_out.retain(|id| {
+        state.tasks.get(id).is_some_and(|task| {
+            matches!(
+                task.status,
+                TaskStatus::Running { .. } | TaskStatus::Paused { .. }
+            )
+        })
+    });
+
+    let ids: Vec<usize> = state
+        .tasks
+        .iter()
+        .filter(|(id, _)| !timed_out.contains(id))
+        .filter(|(_, task)| matches!(task.status, TaskStatus::Running { .. }))
+        .filter(|(_, task)| {
+            let (Some(timeout), Some(duration)) = (task.timeout, task.duration_at(now)) else {
+                return false;
+            };
+            TimeDelta::from_std(timeout).is_ok_and(|timeout| duration >= timeout)
+        })
+        .map(|(id, _)| *id)
+        .collect();
+
+    timed_out.extend(&ids);
+    ids
+}
+
 /// Ensure that no `Queued` tasks have any failed dependencies.
 /// Otherwise set their status to `Done` and result to `DependencyFailed`.
 fn check_failed_dependencies(settings: &Settings, state: &mut LockedState) {
@@ -205,15 +441,152 @@ fn check_failed_dependencies(settings: &Settings, state: &mut LockedState) {
                 continue;
             };
 
-            task.status = TaskStatus::Done {
-                enqueued_at,
-                start: Local::now(),
-                end: Local::now(),
-                result: TaskResult::DependencyFailed,
-            };
+            set_status(
+                settings,
+                task,
+                TaskStatus::Done {
+                    enqueued_at,
+                    start: Local::now(),
+                    end: Local::now(),
+                    result: TaskResult::DependencyFailed,
+                },
+            );
             task.clone()
         };
+        mark_tasks_changed(settings, [id]);
 
         spawn_callback(settings, state, &task);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pueue_lib::state::PUEUE_DEFAULT_GROUP;
+    use pueue_lib::task::Task;
+
+    use super::*;
+
+    #[test]
+    fn running_tasks_time_out() {
+        let mut state = State::new();
+        let start = Local::now();
+        let mut task = Task::new(
+            "sleep 60".into(),
+            std::env::temp_dir(),
+            HashMap::new(),
+            PUEUE_DEFAULT_GROUP.into(),
+            TaskStatus::Running {
+                enqueued_at: start,
+                start,
+            },
+            Vec::new(),
+            0,
+            None,
+        );
+        state.add_task(task.clone());
+        task.timeout = Some(Duration::from_secs(60));
+        let with_timeout = state.add_task(task.clone());
+        task.status = TaskStatus::Paused {
+            enqueued_at: start,
+            start,
+        };
+        let paused = state.add_task(task);
+
+        // The timeout didn't pass yet.
+        let now = start + TimeDelta::try_seconds(30).unwrap();
+        assert!(timed_out_tasks(&state, now, &mut HashSet::new()).is_empty());
+
+        // Only the running task with a timeout is killed, paused tasks are left alone.
+        let now = start + TimeDelta::try_seconds(60).unwrap();
+        assert_eq!(
+            timed_out_tasks(&state, now, &mut HashSet::new()),
+            vec![with_timeout]
+        );
+
+        // The time a task spent paused counts towards its timeout.
+        // Once it's resumed, it's killed right away.
+        state.tasks.get_mut(&paused).unwrap().status = TaskStatus::Running {
+            enqueued_at: start,
+            start,
+        };
+        assert_eq!(
+            timed_out_tasks(&state, now, &mut HashSet::new()),
+            vec![with_timeout, paused]
+        );
+    }
+
+    /// Tasks are only killed once, even if their process takes a while to exit.
+    /// Once they're restarted, their timeout applies again.
+    #[test]
+    fn timed_out_tasks_are_killed_once() {
+        let mut state = State::new();
+        let start = Local::now();
+        let mut task = Task::new(
+            "sleep 60".into(),
+            std::env::temp_dir(),
+            HashMap::new(),
+            PUEUE_DEFAULT_GROUP.into(),
+            TaskStatus::Running {
+                enqueued_at: start,
+                start,
+            },
+            Vec::new(),
+            0,
+            None,
+        );
+        task.timeout = Some(Duration::from_secs(60));
+        let id = state.add_task(task);
+
+        let now = start + TimeDelta::try_seconds(60).unwrap();
+        let mut timed_out = HashSet::new();
+        assert_eq!(timed_out_tasks(&state, now, &mut timed_out), vec![id]);
+        // The process is still running, as it didn't react to the kill yet.
+        assert!(timed_out_tasks(&state, now, &mut timed_out).is_empty());
+        assert!(timed_out_tasks(&state, now, &mut timed_out).is_empty());
+
+        // The task finished and is restarted in place.
+        state.tasks.get_mut(&id).unwrap().status = TaskStatus::Done {
+            enqueued_at: start,
+            start,
+            end: now,
+            result: TaskResult::Killed,
+        };
+        assert!(timed_out_tasks(&state, now, &mut timed_out).is_empty());
+        state.tasks.get_mut(&id).unwrap().status = TaskStatus::Running {
+            enqueued_at: now,
+            start: now,
+        };
+        let later = now + TimeDelta::try_seconds(60).unwrap();
+        assert_eq!(timed_out_tasks(&state, later, &mut timed_out), vec![id]);
+    }
+
+    /// Configured groups are created and updated. Removed groups are only dropped, if they
+    /// don't contain any tasks.
+    #[test]
+    fn configured_groups_are_synced() {
+        let mut state = State::new();
+        state.create_group("busy");
+        state.add_task(Task::new(
+            "sleep 60".into(),
+            std::env::temp_dir(),
+            HashMap::new(),
+            "busy".into(),
+            TaskStatus::Stashed { enqueue_at: None },
+            Vec::new(),
+            0,
+            None,
+        ));
+
+        let previous = HashMap::from([("busy".to_string(), 1), ("empty".to_string(), 1)]);
+        sync_configured_groups(&mut state, &previous, &HashMap::new());
+        assert!(state.children.0.contains_key("empty"));
+
+        let groups = HashMap::from([(PUEUE_DEFAULT_GROUP.to_string(), 4)]);
+        sync_configured_groups(&mut state, &groups, &previous);
+
+        assert_eq!(state.groups[PUEUE_DEFAULT_GROUP].parallel_tasks, 4);
+        assert!(state.groups.contains_key("busy"));
+        assert!(!state.groups.contains_key("empty"));
+        assert!(!state.children.0.contains_key("empty"));
+    }
+}
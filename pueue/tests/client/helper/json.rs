@@ -0,0 +1,21 @@
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Parse the json output of a client command and return the data inside of its envelope.
+/// The schema version and the kind of the envelope are checked along the way.
+pub fn parse_json_envelope<T: DeserializeOwned>(stdout: &[u8], kind: &str) -> Result<T> {
+    let mut envelope: Value = serde_json::from_slice(stdout).context(format!(
+        "Failed to deserialize json envelope: \n{}",
+        String::from_utf8_lossy(stdout)
+    ))?;
+
+    if envelope["schema_version"] != 1 {
+        bail!("Unexpected schema version in envelope: {envelope}");
+    }
+    if envelope["kind"] != kind {
+        bail!("Expected envelope of kind {kind}: {envelope}");
+    }
+
+    serde_json::from_value(envelope["data"].take()).context("Failed to deserialize json data")
+}
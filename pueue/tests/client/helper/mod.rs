@@ -1,7 +1,9 @@
 mod compare_output;
+mod json;
 mod run;
 
 // Re-export all helper functions for this test as a convenience.
 pub use crate::helper::*;
 pub use compare_output::*;
+pub use json::*;
 pub use run::*;
@@ -0,0 +1,232 @@
+use anyhow::{Context, Result};
+use pretty_assertions::assert_eq;
+
+use pueue_lib::network::message::TaskSelection;
+use pueue_lib::state::GroupStatus;
+use pueue_lib::task::{Task, TaskStatus};
+
+use crate::client::helper::*;
+
+/// Tasks added with `--front` get a priority just above all currently queued tasks of the
+/// group and are thereby started before any of them.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_in_front() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the default group, so we can queue up a few tasks.
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    // Add some tasks with different priorities.
+    assert_success(add_task(shared, "sleep 60").await?);
+    assert_success(add_task_with_priority(shared, "sleep 60", 3).await?);
+    assert_success(add_task_with_priority(shared, "sleep 60", 1).await?);
+
+    // Add a task that should be put in front of all other tasks.
+    let output = run_client_command(shared, &["add", "--front", "sleep 60"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let state = get_state(shared).await?;
+    let task = state.tasks.get(&3).unwrap();
+    assert_eq!(
+        task.priority, 4,
+        "The task should outrank the highest priority."
+    );
+
+    // Resume the group and make sure the new task is started first.
+    assert_success(start_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_task_condition(shared, 3, Task::is_running).await?;
+    for id in 0..3 {
+        wait_for_task_condition(shared, id, Task::is_queued).await?;
+    }
+
+    Ok(())
+}
+
+/// If there're no queued tasks in the group, `--front` falls back to the default priority.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_in_front_of_empty_group() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["add", "--front", "--stashed", "ls"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let state = get_state(shared).await?;
+    assert_eq!(state.tasks.get(&0).unwrap().priority, 0);
+
+    Ok(())
+}
+
+/// Tasks added with `--working-directory-from` use the working directory of the given task.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_with_working_directory_from() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task with a working directory that differs from the client's working directory.
+    let path = shared.pueue_directory().join("task_logs");
+    let path_arg = path.to_string_lossy();
+    let output = run_client_command(shared, &["add", "--stashed", "-w", &path_arg, "ls"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let output = run_client_command(
+        shared,
+        &["add", "--stashed", "--working-directory-from", "0", "ls"],
+    )?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let state = get_state(shared).await?;
+    assert_eq!(state.tasks.get(&1).unwrap().path, path);
+
+    // Referencing a non-existing task is an error.
+    let output = run_client_command(shared, &["add", "--cwd-from", "5", "ls"])?;
+    assert!(!output.status.success(), "Adding the task should fail");
+    assert_eq!(get_state(shared).await?.tasks.len(), 2);
+
+    Ok(())
+}
+
+/// Adding a task to a paused group shows a warning, as the task won't be started.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_to_paused_group() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    let output = run_client_command(shared, &["add", "ls"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("The group of this task is currently paused!"),
+        "The user should be warned about the paused group. Got: {stdout}"
+    );
+
+    let state = get_state(shared).await?;
+    assert_eq!(
+        state.groups.get(PUEUE_DEFAULT_GROUP).unwrap().status,
+        GroupStatus::Paused
+    );
+
+    Ok(())
+}
+
+/// Tasks added with `--start-group` resume their paused group and are thereby started.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_with_start_group() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    let output = run_client_command(shared, &["add", "--start-group", "ls"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("The group of this task has been resumed."),
+        "The user should be told that the group has been resumed. Got: {stdout}"
+    );
+
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Running).await?;
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    Ok(())
+}
+
+/// `add --from-file` adds a task for each line of the file.
+/// Comments and blank lines are skipped, while a `#` inside of a command is preserved.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_from_file() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let path = daemon.tempdir.path().join("commands.txt");
+    std::fs::write(
+        &path,
+        "# Build everything\necho first\n\n   \n  echo '#2'  \n# echo skipped\necho third # kept\n",
+    )?;
+
+    let output = run_client_command(
+        shared,
+        &["add", "--stashed", "--from-file", path.to_str().unwrap()],
+    )?;
+    assert!(output.status.success(), "Adding the tasks should succeed");
+
+    let state = get_state(shared).await?;
+    let commands: Vec<&str> = state
+        .tasks
+        .values()
+        .map(|task| task.command.as_str())
+        .collect();
+    assert_eq!(
+        commands,
+        vec!["echo first", "echo '#2'", "echo third # kept"]
+    );
+    assert!(state.tasks.values().all(|task| task.is_stashed()));
+
+    Ok(())
+}
+
+/// `add --output-json` prints the full created task, as it has been added by the daemon.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_with_json_output() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the default group, so the task stays queued.
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    let output = run_client_command(
+        shared,
+        &["add", "--output-json", "--priority", "3", "--", "ls"],
+    )?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let task: Task = parse_json_envelope(&output.stdout, "task")?;
+    assert_eq!(task.id, 0);
+    assert_eq!(task.command, "ls");
+    assert_eq!(task.group, PUEUE_DEFAULT_GROUP);
+    assert_eq!(task.priority, 3);
+    assert!(matches!(task.status, TaskStatus::Queued { .. }));
+    assert!(task.envs.is_empty(), "The environment shouldn't be printed");
+
+    // The printed task is the task that's known to the daemon.
+    let state = get_state(shared).await?;
+    let mut expected = state.tasks.get(&0).unwrap().clone();
+    expected.envs.clear();
+    assert_eq!(task, expected);
+
+    // The raw task can be printed without the envelope.
+    let output = run_client_command(shared, &["add", "--output-json", "--raw-json", "--", "ls"])?;
+    let task: Task =
+        serde_json::from_slice(&output.stdout).context("Failed to deserialize task")?;
+    assert_eq!(task.id, 1);
+
+    Ok(())
+}
+
+/// Adding a task to an unknown group fails, unless the group should be created.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn add_to_unknown_group() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["add", "--group", "typo", "ls"])?;
+    assert!(!output.status.success(), "Adding the task should fail");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--create-group"), "Got output: {stdout}");
+
+    let output = run_client_command(shared, &["add", "--group", "typo", "--create-group", "ls"])?;
+    assert!(output.status.success(), "Adding the task should succeed");
+
+    let state = get_state(shared).await?;
+    assert!(state.groups.contains_key("typo"));
+    assert_eq!(state.tasks.get(&0).unwrap().group, "typo");
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use pueue_lib::log::get_log_path;
+use pueue_lib::task::Task;
+
+use crate::client::helper::*;
+
+/// `clean --all --successful-only` removes the successful tasks and their logs across all groups.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn clean_all_groups_successful_only() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    add_group_with_slots(shared, "other", 1).await?;
+
+    // Tasks 0 and 2 succeed, tasks 1 and 3 fail.
+    for (command, group) in [
+        ("ls", PUEUE_DEFAULT_GROUP),
+        ("failing", PUEUE_DEFAULT_GROUP),
+        ("ls", "other"),
+        ("failing", "other"),
+    ] {
+        let mut message = create_add_message(shared, command);
+        message.group = group.to_string();
+        assert_success(send_message(shared, message).await?);
+    }
+    for id in 0..4 {
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+
+    let output = run_client_command(shared, &["clean", "--all", "--successful-only"])?;
+    assert!(output.status.success(), "Cleaning should succeed");
+
+    let state = get_state(shared).await?;
+    let remaining: Vec<usize> = state.tasks.keys().copied().collect();
+    assert_eq!(remaining, vec![1, 3], "Only the failed tasks should remain");
+
+    // The logs of the removed tasks have been deleted as well.
+    let pueue_dir = shared.pueue_directory();
+    for id in [0, 2] {
+        assert!(!get_log_path(id, &pueue_dir).exists());
+    }
+    for id in [1, 3] {
+        assert!(get_log_path(id, &pueue_dir).exists());
+    }
+
+    Ok(())
+}
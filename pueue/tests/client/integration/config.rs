@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::client::helper::*;
+
+/// The configuration of a properly set up daemon doesn't have any problems.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn config_check_valid() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["config", "check"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "Check failed:\n{stdout}");
+    assert!(
+        stdout.contains("No problems found"),
+        "Expected no problems:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// Typos and broken values are reported with their line and a non-zero exit code.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn config_check_broken_file() -> Result<()> {
+    let (settings, tempdir) = daemon_base_setup()?;
+    let path = tempdir.path().join("broken.yml");
+    std::fs::write(
+        &path,
+        "client:\n  dark_mode: true\ndaemon:\n  callbak: 'echo {{ id }}'\n  \
+        on_complete: true\n  shell_command: [sh, -c, 'echo']\n",
+    )?;
+
+    let output = run_client_command(
+        &settings.shared,
+        &["config", "check", "--config", path.to_str().unwrap()],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "Check succeeded:\n{stdout}");
+    for expected in [
+        "[error] daemon.callbak (line 4)",
+        "[error] daemon.on_complete (line 5)",
+        "[error] daemon.shell_command (line 6)",
+    ] {
+        assert!(stdout.contains(expected), "Expected {expected}:\n{stdout}");
+    }
+
+    Ok(())
+}
+
+/// Settings of groups that don't exist on the daemon are reported.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn config_check_missing_group() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.group_env_vars = HashMap::from([("missing".to_string(), HashMap::new())]);
+    settings.save(&Some(tempdir.path().join("pueue.yml")))?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["config", "check"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "Check succeeded:\n{stdout}");
+    assert!(
+        stdout.contains("[error] daemon.group_env_vars.missing"),
+        "Expected missing group:\n{stdout}"
+    );
+
+    Ok(())
+}
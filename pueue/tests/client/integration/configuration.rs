@@ -11,7 +11,7 @@ use pueue_lib::{
     state::State,
 };
 
-use crate::helper::*;
+use crate::client::helper::*;
 
 /// Spawn the daemon by calling the actual pueued binary.
 /// This is basically the same as the `standalone_daemon` logic, but it uses the
@@ -81,11 +81,89 @@ async fn run_with_env_config_path() -> Result<()> {
         .context("Failed to execute pueue with env config variable".to_string())?;
 
     // Deserialize the message and make sure it's a status response.
-    let response = String::from_utf8_lossy(&output.stdout);
-    let state: State = serde_json::from_str(&response)?;
+    let state: State = parse_json_envelope(&output.stdout, "status")?;
 
     assert!(state.tasks.is_empty(), "State must have no tasks");
 
     child.kill()?;
     Ok(())
 }
+
+/// The daemon refuses to start if no config file exists and `--no-create-config` is set.
+/// No default config file must be written in that case.
+#[test]
+fn no_create_config_without_config() -> Result<()> {
+    let tempdir = tempfile::Builder::new().prefix("pueue-").tempdir()?;
+    let path = tempdir.path();
+
+    // Make sure that none of the default config locations point to an existing config.
+    let output = Command::cargo_bin("pueued")?
+        .arg("--no-create-config")
+        .env_remove(PUEUE_CONFIG_PATH_ENV)
+        .env("HOME", path)
+        .env("XDG_CONFIG_HOME", path)
+        .current_dir(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to execute pueued")?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "Daemon started without config");
+    assert!(
+        stderr.contains("--no-create-config"),
+        "Unexpected error output:\n{stderr}"
+    );
+    assert!(!path.join("pueue").join("pueue.yml").exists());
+    assert!(!path.join("pueue.yml").exists());
+
+    Ok(())
+}
+
+/// The client refuses to connect, if the pid file points to a daemon that no longer exists.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stale_pid_file() -> Result<()> {
+    // Only set up the directory, but don't start a daemon.
+    let (settings, _tempdir) = daemon_base_setup()?;
+    let shared = &settings.shared;
+
+    // Pretend that a daemon died without removing its pid file.
+    std::fs::write(shared.pid_path(), i32::MAX.to_string())?;
+
+    let output = run_client_command(shared, &["status"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success(), "Client succeeded: {stderr}");
+    assert!(
+        stderr.contains("Daemon not running (stale pid file/socket)"),
+        "Expected stale daemon error: {stderr}"
+    );
+
+    Ok(())
+}
+
+/// The pid file isn't checked for daemons that're reached via TCP, as they might run on another
+/// machine.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stale_pid_file_ignored_for_tcp() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.shared.use_unix_socket = false;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let shared = &settings.shared;
+
+    std::fs::write(shared.pid_path(), i32::MAX.to_string())?;
+
+    // There's no daemon, so the client still fails, but not due to the pid file.
+    let output = run_client_command(shared, &["status"])?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success(), "Client succeeded: {stderr}");
+    assert!(
+        !stderr.contains("stale pid file"),
+        "Unexpected stale daemon error: {stderr}"
+    );
+
+    Ok(())
+}
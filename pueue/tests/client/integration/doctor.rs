@@ -0,0 +1,90 @@
+use std::fs::{remove_file, write};
+
+use anyhow::Result;
+
+use crate::client::helper::*;
+
+/// All checks pass for a properly set up daemon.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn doctor_healthy_daemon() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["doctor"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "Doctor failed:\n{stdout}");
+    assert!(!stdout.contains("[fail]"), "Found failed check:\n{stdout}");
+    assert!(stdout.contains("Daemon is reachable"));
+
+    Ok(())
+}
+
+/// The doctor reports an unreachable daemon and exits with a non-zero exit code.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn doctor_unreachable_daemon() -> Result<()> {
+    // Only set up the directory, but don't start a daemon.
+    let (settings, _tempdir) = daemon_base_setup()?;
+
+    let output = run_client_command(&settings.shared, &["doctor"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "Doctor succeeded:\n{stdout}");
+    assert!(
+        stdout.contains("[fail] Daemon is reachable"),
+        "Expected unreachable daemon:\n{stdout}"
+    );
+    assert!(
+        stdout.contains("[ok]   Configuration file found"),
+        "Expected config to be found:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// The doctor still runs all other checks if the configuration file is missing.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn doctor_missing_config() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    remove_file(shared.pueue_directory().join("pueue.yml"))?;
+
+    let output = run_client_command(shared, &["doctor"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "Doctor succeeded:\n{stdout}");
+    assert!(
+        stdout.contains("[fail] Configuration file found"),
+        "Expected missing config:\n{stdout}"
+    );
+
+    Ok(())
+}
+
+/// An unreadable configuration file is reported and all checks that depend on it are skipped.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn doctor_invalid_config() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    write(
+        shared.pueue_directory().join("pueue.yml"),
+        "shared: [invalid",
+    )?;
+
+    let output = run_client_command(shared, &["doctor"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "Doctor succeeded:\n{stdout}");
+    assert!(
+        stdout.contains("[fail] Configuration file is readable"),
+        "Expected unreadable config:\n{stdout}"
+    );
+    assert!(
+        !stdout.contains("Daemon is reachable"),
+        "Settings-dependent checks should be skipped:\n{stdout}"
+    );
+
+    Ok(())
+}
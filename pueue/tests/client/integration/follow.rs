@@ -84,6 +84,40 @@ async fn wait_for_task(#[case] read_local_logs: bool) -> Result<()> {
     Ok(())
 }
 
+/// `follow --no-timestamps` hides the timestamps, which are added if `daemon.timestamp_logs` is
+/// enabled.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn no_timestamps(#[case] read_local_logs: bool) -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.timestamp_logs = true;
+    settings.client.read_local_logs = read_local_logs;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // The timestamps are shown by default.
+    let output = run_client_command(shared, &["follow", "0"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().count() == 2 && stdout.lines().all(|line| line.starts_with('[')),
+        "Expected timestamped output:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["follow", "0", "--no-timestamps"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "first\nsecond\n");
+
+    Ok(())
+}
+
 /// Fail when following a non-existing task
 #[rstest]
 #[case(true)]
@@ -148,3 +182,31 @@ async fn fail_on_non_existing(#[case] read_local_logs: bool) -> Result<()> {
 //
 //     Ok(())
 // }
+
+/// The `on_complete_command` is run once the followed task finishes, regardless of whether the
+/// log is read locally or streamed by the daemon.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_complete_command(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let hook_output = daemon.tempdir.path().join("on_complete");
+    daemon.settings.client.on_complete_command = Some(format!(
+        "echo \"$PUEUE_TASK_ID $PUEUE_TASK_RESULT\" > {hook_output:?}"
+    ));
+    set_read_local_logs(&mut daemon, read_local_logs)?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let output = run_client_command(shared, &["follow"])?;
+    assert!(output.status.success(), "Follow failed");
+
+    let content = std::fs::read_to_string(&hook_output)
+        .context("The on_complete_command didn't write its output")?;
+    assert_eq!(content, "0 Success\n");
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::{Context, Result};
+use crossterm::style::{style, Attribute, Color, Stylize};
 use pueue_lib::network::message::*;
 use pueue_lib::state::{Group, GroupStatus};
+use pueue_lib::task::Task;
 
 use crate::client::helper::*;
 
@@ -38,6 +40,7 @@ async fn colored() -> Result<()> {
     let message = PauseMessage {
         tasks: TaskSelection::Group(PUEUE_DEFAULT_GROUP.into()),
         wait: true,
+        restash: false,
     };
     send_message(shared, message)
         .await
@@ -53,6 +56,74 @@ async fn colored() -> Result<()> {
     Ok(())
 }
 
+/// `NO_COLOR` disables colors in auto mode, but an explicit `--color always` still wins.
+/// `--no-color` always disables colors.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn no_color() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    run_client_command(shared, &["group", "add", "testgroup", "--parallel=2"])?;
+    wait_for_group(shared, "testgroup").await?;
+
+    let envs = HashMap::from([("NO_COLOR", "1")]);
+    let output = run_client_command_with_env(shared, &["group"], envs.clone())?;
+    assert_snapshot_matches_stdout("group__default", output.stdout)?;
+
+    let output = run_client_command_with_env(shared, &["--color", "always", "group"], envs)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains('\u{1b}'),
+        "Explicitly requested colors should be used:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["--no-color", "group"])?;
+    assert_snapshot_matches_stdout("group__default", output.stdout)?;
+
+    Ok(())
+}
+
+/// A configured group color is applied to the group's headline.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn configured_color() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings
+        .client
+        .group_colors
+        .insert("testgroup".into(), "blue".into());
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    run_client_command(shared, &["group", "add", "testgroup", "--parallel=2"])?;
+    wait_for_group(shared, "testgroup").await?;
+
+    let output = run_client_command(shared, &["--color", "always", "group"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let colored_name = style("Group \"testgroup\"")
+        .with(Color::Blue)
+        .attribute(Attribute::Bold)
+        .to_string();
+    assert!(
+        stdout.contains(&colored_name),
+        "Group headline isn't colored: {stdout:?}"
+    );
+
+    // Groups without a configured color keep the default styling.
+    let default_name = style("Group \"default\"")
+        .attribute(Attribute::Bold)
+        .to_string();
+    assert!(
+        stdout.contains(&default_name),
+        "Default group headline has unexpected styling: {stdout:?}"
+    );
+
+    Ok(())
+}
+
 /// Make sure that getting the list of groups as json works.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn json() -> Result<()> {
@@ -61,12 +132,10 @@ async fn json() -> Result<()> {
 
     // Get the group status output
     let output = run_client_command(shared, &["group", "--json"])?;
-    let json = String::from_utf8_lossy(&output.stdout);
-    println!("{json}");
 
     let state = get_state(shared).await?;
     let deserialized_groups: BTreeMap<String, Group> =
-        serde_json::from_str(&json).context("Failed to deserialize json state")?;
+        parse_json_envelope(&output.stdout, "group")?;
 
     assert_eq!(
         deserialized_groups, state.groups,
@@ -75,3 +144,62 @@ async fn json() -> Result<()> {
 
     Ok(())
 }
+
+/// A group's description is stored by the daemon, shown in the headline and part of the json.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn description() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    run_client_command(
+        shared,
+        &["group", "add", "build", "--description", "CI builds"],
+    )?;
+    wait_for_group(shared, "build").await?;
+
+    let state = get_state(shared).await?;
+    assert_eq!(
+        state.groups["build"].description,
+        Some("CI builds".to_string())
+    );
+
+    let output = run_client_command(shared, &["group"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Group \"build\" (1 parallel): running - CI builds"),
+        "Description isn't shown: {stdout}"
+    );
+
+    let output = run_client_command(shared, &["group", "--json"])?;
+    let groups: BTreeMap<String, Group> = parse_json_envelope(&output.stdout, "group")?;
+    assert_eq!(groups["build"].description, Some("CI builds".to_string()));
+
+    Ok(())
+}
+
+/// The throughput of groups is part of the json and shown in the overview, once tasks finished.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stats() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "ls").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["group", "--json"])?;
+    let groups: serde_json::Value = parse_json_envelope(&output.stdout, "group")?;
+    let stats: GroupStats = serde_json::from_value(groups["default"]["stats"].clone())
+        .context("Failed to deserialize group stats")?;
+    assert_eq!(stats.window, 3600);
+    assert_eq!(stats.finished_tasks, 1);
+    assert!(stats.average_runtime.is_some());
+
+    let output = run_client_command(shared, &["group"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("(1 finished in the last 3600s, avg. runtime"),
+        "Throughput isn't shown: {stdout}"
+    );
+
+    Ok(())
+}
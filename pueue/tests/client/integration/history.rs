@@ -0,0 +1,67 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use pueue_lib::network::message::HistoryEntry;
+use pueue_lib::task::{Task, TaskResult};
+
+use crate::client::helper::*;
+
+/// Finished tasks are still part of the history after they have been cleaned.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn list_history() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "ls").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    run_client_command(shared, &["clean"])?;
+
+    assert_success(add_task(shared, "failing_command").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["history", "--json"])?;
+    let entries: Vec<HistoryEntry> = parse_json_envelope(&output.stdout, "history")?;
+    let commands: Vec<&str> = entries.iter().map(|entry| entry.command.as_str()).collect();
+    assert_eq!(commands, vec!["ls", "failing_command"]);
+    assert_eq!(entries[0].result, TaskResult::Success);
+
+    let output = run_client_command(shared, &["history", "--json", "--raw-json"])?;
+    let raw_entries: Vec<HistoryEntry> = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(raw_entries, entries);
+
+    let output = run_client_command(shared, &["history"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "Unexpected history:\n{stdout}");
+    assert!(lines[0].starts_with("0 ") && lines[0].ends_with("[Success] ls"));
+    assert!(lines[1].starts_with("1 ") && lines[1].ends_with("failing_command"));
+
+    Ok(())
+}
+
+/// A command from the history can be added as a new task.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn rerun_from_history() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo from history").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    run_client_command(shared, &["clean"])?;
+
+    let output = run_client_command(shared, &["history", "--rerun", "0"])?;
+    assert!(
+        output.status.success(),
+        "Rerunning the command should succeed"
+    );
+
+    let task = wait_for_task_condition(shared, 1, Task::is_done).await?;
+    assert_eq!(task.original_command, "echo from history");
+    assert_eq!(task.path, shared.pueue_directory());
+
+    // Unknown indices are an error.
+    let output = run_client_command(shared, &["history", "--rerun", "5"])?;
+    assert!(!output.status.success(), "Rerunning should fail");
+
+    Ok(())
+}
@@ -88,6 +88,78 @@ async fn task_with_label() -> Result<()> {
     Ok(())
 }
 
+/// A note that has been attached via `pueue annotate` is shown in the log output and the json.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn task_with_note() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Add a task and wait until it finishes.
+    assert_success(add_task(shared, "echo test").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["annotate", "0", "Flaky, rerun on monday"])?;
+    assert!(output.status.success(), "Annotating should succeed");
+
+    let output = run_client_command(shared, &["log"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Note:") && stdout.contains("Flaky, rerun on monday"),
+        "The note should be shown in the task info:\n{stdout}"
+    );
+
+    let output = run_client_command(shared, &["log", "--json"])?;
+    let task_logs: BTreeMap<usize, TaskLog> = parse_json_envelope(&output.stdout, "log")?;
+    assert_eq!(
+        task_logs[&0].task.note,
+        Some("Flaky, rerun on monday".to_string())
+    );
+
+    Ok(())
+}
+
+/// Each line of the output is timestamped, if `daemon.timestamp_logs` is enabled.
+/// `log --no-timestamps` hides these timestamps again.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timestamped_output(#[case] read_local_logs: bool) -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.timestamp_logs = true;
+    settings.client.read_local_logs = read_local_logs;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    // Each line is prefixed with a timestamp like `[2024-01-10 10:00:00.123] `.
+    let output = run_client_command(shared, &["log", "0"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for text in ["first", "second"] {
+        let line = stdout
+            .lines()
+            .find(|line| line.ends_with(&format!("] {text}")))
+            .unwrap_or_else(|| panic!("Expected a timestamped line for '{text}':\n{stdout}"));
+        assert!(line.starts_with('['), "Unexpected line: {line}");
+        assert_eq!(line.len(), "[2024-01-10 10:00:00.123] ".len() + text.len());
+    }
+
+    // The timestamps are removed for clean viewing.
+    let output = run_client_command(shared, &["log", "0", "--no-timestamps"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().any(|line| line == "first") && stdout.lines().any(|line| line == "second"),
+        "Expected the output without timestamps:\n{stdout}"
+    );
+
+    Ok(())
+}
+
 /// Calling `log` with the `--color=always` flag, colors the output as expected.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn colored() -> Result<()> {
@@ -128,9 +200,7 @@ async fn json() -> Result<()> {
     let output = run_client_command(shared, &["log", "--json"])?;
 
     // Deserialize the json back to the original task BTreeMap.
-    let json = String::from_utf8_lossy(&output.stdout);
-    let mut task_logs: BTreeMap<usize, TaskLog> = serde_json::from_str(&json)
-        .context(format!("Failed to deserialize json tasks: \n{json}"))?;
+    let mut task_logs: BTreeMap<usize, TaskLog> = parse_json_envelope(&output.stdout, "log")?;
 
     // Get the actual BTreeMap from the daemon
     let mut state = get_state(shared).await?;
@@ -150,3 +220,87 @@ async fn json() -> Result<()> {
 
     Ok(())
 }
+
+/// ANSI escape sequences of a task's output are passed through verbatim if colors are enabled.
+/// They're stripped if colors are disabled.
+#[rstest]
+#[case(true, "always")]
+#[case(true, "never")]
+#[case(false, "always")]
+#[case(false, "never")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn colored_output(#[case] read_local_logs: bool, #[case] color: &str) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    // Persist the change, so it can be seen by the client.
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    // Add a task with colored output and wait until it finishes.
+    assert_success(add_task(shared, r"printf '\033[31mred\033[0m text\n'").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["--color", color, "log", "0"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if color == "always" {
+        assert!(
+            stdout.contains("\x1b[31mred\x1b[0m text"),
+            "Task colors should be passed through: {stdout:?}"
+        );
+    } else {
+        assert!(
+            stdout.contains("red text"),
+            "Task output should be printed: {stdout:?}"
+        );
+        assert!(
+            !stdout.contains('\x1b'),
+            "Escape sequences should be stripped: {stdout:?}"
+        );
+    }
+
+    Ok(())
+}
+
+/// `pueue log --out-file` writes the raw log of a task to the given file for both:
+/// - The log being streamed by the daemon.
+/// - The log being read from the local files.
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn out_file(#[case] read_local_logs: bool) -> Result<()> {
+    let mut daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    daemon.settings.client.read_local_logs = read_local_logs;
+    daemon
+        .settings
+        .save(&Some(daemon.tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    assert_success(add_task(shared, "echo first && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let path = daemon.tempdir.path().join("run0.log");
+    let output = run_client_command(
+        shared,
+        &["log", "0", "--full", "--out-file", path.to_str().unwrap()],
+    )?;
+    assert!(
+        output.status.success(),
+        "Log failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let written = std::fs::read_to_string(&path).context("Failed to read written log")?;
+    let stored = get_task_log(shared, 0, None).await?;
+    assert_eq!(written, stored);
+    assert_eq!(written, "first\nsecond\n");
+
+    Ok(())
+}
@@ -1,10 +1,17 @@
+mod add;
+mod clean;
 mod completions;
+mod config;
 mod configuration;
+mod doctor;
 mod edit;
 mod env;
 mod follow;
 mod group;
+mod history;
 mod log;
+mod pause;
 mod restart;
+mod stash;
 mod status;
 mod wait;
@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use pueue_lib::settings::Shared;
+use pueue_lib::task::Task;
+
+use crate::client::helper::*;
+
+/// Add five running tasks to a separate group, two of which are labeled as a batch.
+async fn add_running_tasks(shared: &Shared) -> Result<()> {
+    add_group_with_slots(shared, "testgroup", 5).await?;
+    for label in ["batch-1", "other", "batch-2", "other", "other"] {
+        let output = run_client_command(
+            shared,
+            &["add", "--group", "testgroup", "--label", label, "sleep 60"],
+        )?;
+        assert!(output.status.success(), "Adding a task should succeed");
+    }
+
+    for task_id in 0..5 {
+        wait_for_task_condition(shared, task_id, Task::is_running).await?;
+    }
+
+    Ok(())
+}
+
+/// Only the tasks that match the query of `pause --query` are paused.
+/// They can be resumed via `start --query`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pause_and_start_by_query() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    add_running_tasks(shared).await?;
+
+    let output = run_client_command(
+        shared,
+        &["pause", "--query", "status=running and label%=batch"],
+    )?;
+    assert!(output.status.success(), "Pausing the batch should succeed");
+
+    for task_id in [0, 2] {
+        wait_for_task_condition(shared, task_id, Task::is_paused).await?;
+    }
+    for task_id in [1, 3, 4] {
+        assert_task_condition(
+            shared,
+            task_id,
+            |task| task.is_running() && !task.is_paused(),
+            "Tasks outside of the batch should keep running",
+        )
+        .await?;
+    }
+
+    let output = run_client_command(
+        shared,
+        &["start", "--query", "status=paused and label%=batch"],
+    )?;
+    assert!(output.status.success(), "Resuming the batch should succeed");
+    for task_id in [0, 2] {
+        wait_for_task_condition(shared, task_id, |task| {
+            task.is_running() && !task.is_paused()
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Only the tasks that match the query of `kill --query` are killed.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn kill_by_query() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    add_running_tasks(shared).await?;
+
+    let output = run_client_command(shared, &["kill", "--query", "label%=batch"])?;
+    assert!(output.status.success(), "Killing the batch should succeed");
+
+    for task_id in [0, 2] {
+        wait_for_task_condition(shared, task_id, Task::is_done).await?;
+    }
+    for task_id in [1, 3, 4] {
+        assert_task_condition(
+            shared,
+            task_id,
+            Task::is_running,
+            "Tasks outside of the batch should keep running",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Queries that don't match any task are a no-op with a notice.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pause_by_query_without_matches() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    add_running_tasks(shared).await?;
+
+    let output = run_client_command(shared, &["pause", "--query", "label=nonexistent"])?;
+    assert!(
+        output.status.success(),
+        "An empty match shouldn't be an error"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("No tasks match the filter"),
+        "Expected a notice about the empty match, got: {stdout}"
+    );
+
+    // Nothing has been paused, not even the group.
+    for task_id in 0..5 {
+        assert_task_condition(
+            shared,
+            task_id,
+            |task| !task.is_paused(),
+            "No task should be paused",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use assert_matches::assert_matches;
 
+use pueue_lib::settings::EditMode;
 use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 use crate::client::helper::*;
@@ -180,3 +181,129 @@ async fn normal_restart_with_edit() -> Result<()> {
 
     Ok(())
 }
+
+/// Test that restarting multiple tasks edits all of them in a single session and that the edits
+/// are applied to the correct tasks.
+/// The toml editing mode orders tasks by the string representation of their ids, which is why
+/// more than ten tasks are used.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn restart_and_edit_multiple_tasks() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.client.edit_mode = EditMode::Toml;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    for id in 0..11 {
+        assert_success(add_task(shared, &format!("echo {id}")).await?);
+    }
+    for id in 0..11 {
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+
+    // Set the editor to a command which edits all commands in the toml file.
+    let mut envs = HashMap::new();
+    envs.insert("EDITOR", "sed -i 's/echo/printf/' ${PUEUE_EDIT_PATH} ||");
+
+    let ids: Vec<String> = (0..11).map(|id| id.to_string()).collect();
+    let mut args = vec!["restart", "--in-place", "--stashed", "--edit-all"];
+    args.extend(ids.iter().map(String::as_str));
+    run_client_command_with_env(shared, &args, envs)?;
+
+    let state = get_state(shared).await?;
+    for id in 0..11 {
+        let task = state.tasks.get(&id).unwrap();
+        assert_eq!(task.command, format!("printf {id}"));
+        assert_matches!(task.status, TaskStatus::Stashed { .. });
+    }
+
+    Ok(())
+}
+
+/// Restarting a range of tasks with `--failed-only` only restarts the failed tasks inside that
+/// range.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn restart_failed_only_in_range() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Tasks 0 and 2 fail, while 1 and 3 succeed.
+    for command in ["failing", "ls", "failing", "ls"] {
+        assert_success(add_task(shared, command).await?);
+    }
+    for id in 0..4 {
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+    let previous_state = get_state(shared).await?;
+
+    let output = run_client_command(shared, &["restart", "--in-place", "--failed-only", "1-3"])?;
+    assert!(output.status.success(), "Restarting should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Restarted tasks: [2]"),
+        "Only task 2 should be restarted. Got: {stdout}"
+    );
+
+    // Task 2 is restarted and fails again, while task 0 is outside of the range.
+    wait_for_task_condition(shared, 2, |task| {
+        task.is_done() && task.status != previous_state.tasks[&2].status
+    })
+    .await?;
+    let state = get_state(shared).await?;
+    assert_eq!(
+        state.tasks.len(),
+        4,
+        "No new tasks should have been created"
+    );
+    for id in [0, 1, 3] {
+        assert_eq!(
+            state.tasks[&id].status, previous_state.tasks[&id].status,
+            "Task {id} shouldn't have been restarted"
+        );
+    }
+
+    Ok(())
+}
+
+/// Restarting a task with `--as-new` adds a copy of it as a new task, even if in-place restarts
+/// are configured. The original task is left untouched.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn restart_as_new_task() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.client.restart_in_place = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "ls").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    let original = get_state(shared).await?.tasks.get(&0).unwrap().clone();
+
+    run_client_command(shared, &["restart", "--as-new", "--stashed", "0"])?;
+
+    let state = get_state(shared).await?;
+    assert_eq!(state.tasks.len(), 2, "A new task should have been created");
+    assert_eq!(state.tasks.get(&0).unwrap(), &original);
+
+    let copy = state.tasks.get(&1).unwrap();
+    assert_eq!(copy.command, original.command);
+    assert_matches!(copy.status, TaskStatus::Stashed { .. });
+
+    Ok(())
+}
+
+/// `--as-new` and `--in-place` contradict each other.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn restart_as_new_conflicts_with_in_place() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["restart", "--as-new", "--in-place", "0"])?;
+    assert!(!output.status.success(), "The flags should be rejected");
+
+    Ok(())
+}
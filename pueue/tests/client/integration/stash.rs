@@ -0,0 +1,127 @@
+use anyhow::{bail, Result};
+use chrono::{Local, TimeDelta};
+use rstest::rstest;
+
+use pueue_lib::network::message::TaskSelection;
+use pueue_lib::state::GroupStatus;
+use pueue_lib::task::TaskStatus;
+
+use crate::client::helper::*;
+
+/// Stashing a task with `--for` schedules it to be enqueued again after the given duration.
+#[rstest]
+#[case("--for", "30m", TimeDelta::minutes(30))]
+#[case("--duration", "1h30m", TimeDelta::minutes(90))]
+#[case("--for", "120", TimeDelta::seconds(120))]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stash_for_duration(
+    #[case] flag: &str,
+    #[case] duration: &str,
+    #[case] expected_delta: TimeDelta,
+) -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the default group, so the task stays queued.
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+    assert_success(add_task(shared, "ls").await?);
+
+    let output = run_client_command(shared, &["stash", "0", flag, duration])?;
+    assert!(output.status.success(), "Stashing the task should succeed");
+
+    let expected = Local::now() + expected_delta;
+    let TaskStatus::Stashed {
+        enqueue_at: Some(enqueue_at),
+    } = get_task_status(shared, 0).await?
+    else {
+        bail!("Task should be stashed with an enqueue date");
+    };
+
+    // Allow some tolerance for the time that passes between the client call and our check.
+    let difference = (expected - enqueue_at).abs();
+    assert!(
+        difference < TimeDelta::seconds(10),
+        "enqueue_at {enqueue_at} should be close to {expected}"
+    );
+
+    Ok(())
+}
+
+/// Durations without a unit for every number are rejected.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stash_for_invalid_duration() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["stash", "0", "--for", "1h30"])?;
+    assert!(!output.status.success(), "Invalid durations should fail");
+
+    Ok(())
+}
+
+/// A labeled batch of tasks can be stashed and enqueued in one shot via `--filter`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stash_and_enqueue_by_filter() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the default group, so the tasks stay queued.
+    assert_success(pause_tasks(shared, TaskSelection::Group(PUEUE_DEFAULT_GROUP.into())).await?);
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+    for label in ["batch-7", "batch-7", "batch-8"] {
+        let output = run_client_command(shared, &["add", "--label", label, "ls"])?;
+        assert!(output.status.success(), "Adding a task should succeed");
+    }
+
+    let output = run_client_command(shared, &["stash", "--filter", "label=batch-7"])?;
+    assert!(output.status.success(), "Stashing the batch should succeed");
+    for task_id in [0, 1] {
+        let status = get_task_status(shared, task_id).await?;
+        assert!(
+            matches!(status, TaskStatus::Stashed { enqueue_at: None }),
+            "Task {task_id} should be stashed, got {status:?}"
+        );
+    }
+    let status = get_task_status(shared, 2).await?;
+    assert!(
+        matches!(status, TaskStatus::Queued { .. }),
+        "Task 2 shouldn't be touched, got {status:?}"
+    );
+
+    let output = run_client_command(shared, &["enqueue", "--filter", "label=batch-7"])?;
+    assert!(
+        output.status.success(),
+        "Enqueuing the batch should succeed"
+    );
+    for task_id in [0, 1] {
+        let status = get_task_status(shared, task_id).await?;
+        assert!(
+            matches!(status, TaskStatus::Queued { .. }),
+            "Task {task_id} should be queued, got {status:?}"
+        );
+    }
+
+    Ok(())
+}
+
+/// Filters that don't match any task are a no-op with a notice.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stash_by_filter_without_matches() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["stash", "--filter", "label=nonexistent"])?;
+    assert!(
+        output.status.success(),
+        "An empty match shouldn't be an error"
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("No tasks match the filter"),
+        "Expected a notice about the empty match, got: {stdout}"
+    );
+
+    Ok(())
+}
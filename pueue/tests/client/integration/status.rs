@@ -1,6 +1,9 @@
 use anyhow::{Context, Result};
+use assert_cmd::Command;
+use chrono::{Local, TimeDelta};
 
-use pueue_lib::{state::State, task::Task};
+use pueue_lib::network::message::Message;
+use pueue_lib::{settings::Settings, state::State, task::Task};
 
 use crate::client::helper::*;
 
@@ -105,6 +108,70 @@ async fn multiple_groups() -> Result<()> {
     Ok(())
 }
 
+/// `pueue status --groups` only shows the tables of the given groups.
+/// Unknown groups result in a warning.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn multiple_selected_groups() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    add_group_with_slots(shared, "build", 1).await?;
+    add_group_with_slots(shared, "deploy", 1).await?;
+
+    run_client_command(shared, &["add", "--group", "build", "echo build"])?;
+    run_client_command(shared, &["add", "--group", "deploy", "echo deploy"])?;
+    run_client_command(shared, &["add", "echo default"])?;
+    for task_id in 0..3 {
+        wait_for_task_condition(shared, task_id, Task::is_done).await?;
+    }
+
+    let output = run_client_command(shared, &["status", "--groups", "build,deploy,unknown"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "Status failed: {stderr}");
+    assert!(stdout.contains("Group \"build\""), "{stdout}");
+    assert!(stdout.contains("Group \"deploy\""), "{stdout}");
+    assert!(!stdout.contains("Group \"default\""), "{stdout}");
+    assert!(!stdout.contains("echo default"), "{stdout}");
+    assert!(
+        stderr.contains("There exists no group \"unknown\""),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+/// The limits of a query only apply to the tasks of the groups given via `--groups`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn selected_groups_with_limit() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    add_group_with_slots(shared, "build", 1).await?;
+
+    assert_success(create_stashed_task(shared, "echo default", None).await?);
+    run_client_command(
+        shared,
+        &["add", "--stashed", "--group", "build", "echo build"],
+    )?;
+
+    let output = run_client_command(
+        shared,
+        &["status", "--json", "--groups", "build", "first", "1"],
+    )?;
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
+
+    let task_ids: Vec<usize> = deserialized_state.tasks.keys().cloned().collect();
+    assert_eq!(
+        task_ids,
+        vec![1],
+        "Only the task of the build group should be listed."
+    );
+
+    Ok(())
+}
+
 /// Calling `pueue status --json` will result in the current state being printed to the cli.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn json() -> Result<()> {
@@ -116,10 +183,7 @@ async fn json() -> Result<()> {
     wait_for_task_condition(shared, 0, Task::is_done).await?;
 
     let output = run_client_command(shared, &["status", "--json"])?;
-
-    let json = String::from_utf8_lossy(&output.stdout);
-    let deserialized_state: State =
-        serde_json::from_str(&json).context("Failed to deserialize json state")?;
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
 
     let state = get_state(shared).await?;
     assert_eq!(
@@ -129,3 +193,410 @@ async fn json() -> Result<()> {
 
     Ok(())
 }
+
+/// The json output is wrapped in a versioned envelope, unless the raw data is requested.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_envelope() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["status", "--json"])?;
+    let envelope: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(envelope["schema_version"], 1);
+    assert_eq!(envelope["kind"], "status");
+    assert!(
+        envelope["data"]["groups"].is_object(),
+        "Missing data: {envelope}"
+    );
+
+    let output = run_client_command(shared, &["status", "--json", "--raw-json"])?;
+    let deserialized_state: State =
+        serde_json::from_slice(&output.stdout).context("Failed to deserialize raw json state")?;
+    let state = get_state(shared).await?;
+    assert_eq!(deserialized_state, *state);
+
+    let output = run_client_command(shared, &["status", "--json-pretty", "--raw-json"])?;
+    let deserialized_state: State =
+        serde_json::from_slice(&output.stdout).context("Failed to deserialize raw json state")?;
+    assert_eq!(deserialized_state, *state);
+
+    // The raw json has to be requested together with the json output.
+    let output = run_client_command(shared, &["status", "--raw-json"])?;
+    assert!(
+        !output.status.success(),
+        "`--raw-json` without `--json` should be rejected"
+    );
+
+    Ok(())
+}
+
+/// `pueue status --json-pretty` prints indented json, while `--json` stays compact.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_pretty() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "ls").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["status", "--json-pretty"])?;
+    let pretty = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        pretty.contains("\n  \""),
+        "Expected indented json:\n{pretty}"
+    );
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
+    let state = get_state(shared).await?;
+    assert_eq!(deserialized_state, *state);
+
+    let output = run_client_command(shared, &["status", "--json"])?;
+    let compact = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !compact.trim_end().contains('\n'),
+        "Expected compact json:\n{compact}"
+    );
+
+    Ok(())
+}
+
+/// `pueue status --json-stream` prints one task per line, filtered and ordered by the query.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn json_stream() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    for _ in 0..3 {
+        assert_success(add_task(shared, "ls").await?);
+    }
+    assert_success(add_task(shared, "sleep 60").await?);
+    for task_id in 0..3 {
+        wait_for_task_condition(shared, task_id, Task::is_done).await?;
+    }
+
+    let output = run_client_command(
+        shared,
+        &[
+            "status",
+            "--json-stream",
+            "command=ls",
+            "order_by",
+            "id",
+            "desc",
+        ],
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let tasks = stdout
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<Task>(line)
+                .with_context(|| format!("Failed to deserialize line: {line}"))
+        })
+        .collect::<Result<Vec<Task>>>()?;
+    let ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![2, 1, 0]);
+
+    let state = get_state(shared).await?;
+    for task in tasks {
+        assert_eq!(&task, state.tasks.get(&task.id).unwrap());
+    }
+
+    Ok(())
+}
+
+/// `pueue status --scheduled` only shows stashed tasks with a pending enqueue time.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scheduled() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let enqueue_at = Local::now() + TimeDelta::try_hours(1).unwrap();
+    assert_success(create_stashed_task(shared, "ls", Some(enqueue_at)).await?);
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    assert_success(add_task(shared, "ls").await?);
+
+    let output = run_client_command(shared, &["status", "--scheduled", "--json"])?;
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
+
+    let task_ids: Vec<usize> = deserialized_state.tasks.keys().cloned().collect();
+    assert_eq!(
+        task_ids,
+        vec![0],
+        "Only the scheduled task should be listed."
+    );
+
+    // The table view shows the time that's left until the task is enqueued.
+    let output = run_client_command(shared, &["status", "--scheduled"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Enqueue In"), "Missing column in: {stdout}");
+
+    Ok(())
+}
+
+/// The limits of a query only apply to the scheduled tasks when using `--scheduled`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scheduled_with_limit() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let enqueue_at = Local::now() + TimeDelta::try_hours(1).unwrap();
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    assert_success(create_stashed_task(shared, "ls", Some(enqueue_at)).await?);
+
+    let output = run_client_command(shared, &["status", "--scheduled", "--json", "first", "1"])?;
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
+
+    let task_ids: Vec<usize> = deserialized_state.tasks.keys().cloned().collect();
+    assert_eq!(task_ids, vec![1], "The scheduled task should be listed.");
+
+    Ok(())
+}
+
+/// Tasks that're locked for editing get their own label and can be filtered via `status=locked`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn locked() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+
+    // Requesting an edit locks the task until the edit is finished.
+    send_message(shared, Message::EditRequest(vec![0])).await?;
+
+    let output = run_client_command(shared, &["status"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Locked (editing)"),
+        "Missing label in: {stdout}"
+    );
+
+    let output = run_client_command(shared, &["status", "status=locked", "--json"])?;
+    let deserialized_state: State = parse_json_envelope(&output.stdout, "status")?;
+
+    let task_ids: Vec<usize> = deserialized_state.tasks.keys().cloned().collect();
+    assert_eq!(task_ids, vec![0], "Only the locked task should be listed.");
+
+    Ok(())
+}
+
+/// The columns of the status table are ordered as configured in `client.column_order`.
+/// Unknown columns are ignored and visible columns that aren't listed are appended.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn custom_column_order() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.client.column_order = vec!["command".into(), "unknown".into(), "status".into()];
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+
+    let output = run_client_command(shared, &["status"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let header = stdout
+        .lines()
+        .find(|line| line.contains("Command"))
+        .context(format!("Couldn't find header in: {stdout}"))?;
+
+    let headers: Vec<&str> = header.split_whitespace().collect();
+    assert_eq!(
+        headers,
+        vec!["Command", "Status", "Id", "Path", "Start", "End"],
+        "Unexpected column order"
+    );
+
+    Ok(())
+}
+
+/// `pueue status --tree` nests tasks below the tasks they depend on.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tree() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Stash the root task, so none of its dependants are started.
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    run_client_command(shared, &["add", "--after", "0", "--", "echo one"])?;
+    run_client_command(shared, &["add", "--after", "1", "--", "echo two"])?;
+    run_client_command(shared, &["add", "--after", "0", "--", "echo three"])?;
+
+    let output = run_client_command(shared, &["status", "--tree"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let expected = [
+        "0 [Stashed] ls",
+        "├─ 1 [Queued] echo one",
+        "│  └─ 2 [Queued] echo two",
+        "└─ 3 [Queued] echo three",
+    ]
+    .join("\n");
+    assert_eq!(stdout.trim_end(), expected);
+
+    Ok(())
+}
+
+/// The `--total` footer summarizes the displayed tasks after filtering.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn total() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    assert_success(add_task(shared, "ls").await?);
+    assert_success(add_task(shared, "failing_command").await?);
+    wait_for_task_condition(shared, 2, Task::is_done).await?;
+    wait_for_task_condition(shared, 3, Task::is_done).await?;
+
+    let output = run_client_command(shared, &["status", "--total"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim_end().lines().last(),
+        Some("2 stashed, 1 succeeded, 1 failed (4 total)")
+    );
+
+    let output = run_client_command(shared, &["status", "--total", "status=stashed"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim_end().lines().last(),
+        Some("2 stashed (2 total)")
+    );
+
+    Ok(())
+}
+
+/// `--relative` shows the start of running tasks and the enqueue time of scheduled tasks
+/// relative to now.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn relative_times() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 60").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    // Add some slack, so the task is scheduled in 10 minutes, even if the test is slow.
+    let enqueue_at = Local::now() + TimeDelta::try_seconds(630).unwrap();
+    assert_success(create_stashed_task(shared, "ls", Some(enqueue_at)).await?);
+
+    let output = run_client_command(shared, &["status", "--relative"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let running = stdout
+        .lines()
+        .find(|line| line.contains("sleep 60"))
+        .context("Running task is missing")?;
+    assert!(
+        running.contains("s ago"),
+        "Expected relative start: {running}"
+    );
+    assert!(
+        stdout.contains("in 10m"),
+        "Expected relative enqueue time: {stdout}"
+    );
+
+    // Absolute times stay the default.
+    let output = run_client_command(shared, &["status"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        !stdout.contains(" ago"),
+        "Unexpected relative time: {stdout}"
+    );
+
+    Ok(())
+}
+
+/// A query can be saved as a named view via `--save-view` and recalled via `--view`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn saved_view() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "ls").await?);
+    assert_success(add_task(shared, "echo view").await?);
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+
+    let output = run_client_command(
+        shared,
+        &[
+            "status",
+            "--save-view",
+            "echo",
+            "command=echo",
+            "columns=id,command",
+        ],
+    )?;
+    assert!(output.status.success(), "Saving the view failed");
+
+    // The view has been persisted in the configuration file.
+    let (settings, _) = Settings::read(&Some(shared.pueue_directory().join("pueue.yml")))?;
+    assert_eq!(
+        settings.client.views.get("echo").map(String::as_str),
+        Some("command=echo columns=id,command")
+    );
+
+    let output = run_client_command(shared, &["status", "--view", "echo", "--json"])?;
+    let state: State = parse_json_envelope(&output.stdout, "status")?;
+    assert_eq!(state.tasks.keys().copied().collect::<Vec<_>>(), vec![1]);
+
+    Ok(())
+}
+
+/// Views can't be saved, if the configuration has been read from stdin.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn save_view_with_config_from_stdin() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    let config_path = shared.pueue_directory().join("pueue.yml");
+    let config = std::fs::read_to_string(&config_path)?;
+
+    let output = Command::cargo_bin("pueue")?
+        .args([
+            "--config",
+            "-",
+            "status",
+            "--save-view",
+            "echo",
+            "command=echo",
+        ])
+        .write_stdin(config)
+        .output()?;
+    assert!(!output.status.success(), "Saving the view should fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Views can't be saved"),
+        "Unexpected error: {stderr}"
+    );
+
+    // The configuration file is left untouched.
+    let (settings, _) = Settings::read(&Some(config_path))?;
+    assert!(settings.client.views.is_empty());
+
+    Ok(())
+}
+
+/// Recalling an unknown view fails and lists the available views.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unknown_view() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings
+        .client
+        .views
+        .insert("running".into(), "status=running".into());
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    let output = run_client_command(shared, &["status", "--view", "missing"])?;
+    assert!(!output.status.success(), "Unknown view should fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("There's no view \"missing\". Available views: running"),
+        "Unexpected error: {stderr}"
+    );
+
+    Ok(())
+}
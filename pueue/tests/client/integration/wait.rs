@@ -3,7 +3,7 @@ use std::{
     thread::{self, JoinHandle},
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use tokio::time::sleep;
 
 use crate::client::helper::*;
@@ -161,3 +161,79 @@ async fn success_failure() -> Result<()> {
 
     Ok(())
 }
+
+/// Test that the `on_complete_command` is run for tasks that finish while waiting.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn on_complete_command() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    let hook_output = tempdir.path().join("on_complete");
+    settings.client.on_complete_command = Some(format!(
+        "echo \"$PUEUE_TASK_ID $PUEUE_TASK_RESULT $PUEUE_TASK_EXIT_CODE\" > {hook_output:?}"
+    ));
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    run_client_command(shared, &["add", "--delay", "1 seconds", "sleep 1"])?;
+
+    let wait_handle = spawn_wait_client(shared, vec!["wait", "--quiet"]).await;
+    let output = wait_handle.join().unwrap()?;
+    assert!(output.status.success(), "Got non-zero exit code on wait.");
+
+    let content = std::fs::read_to_string(&hook_output)
+        .context("The on_complete_command didn't write its output")?;
+    assert_eq!(content, "0 Success 0\n");
+
+    Ok(())
+}
+
+/// Test that `wait --status running` returns as soon as the task has been started.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn running_target_status() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // The task is started after a short delay and keeps running for a long time.
+    run_client_command(shared, &["add", "--delay", "1 seconds", "sleep 60"])?;
+
+    let wait_handle = spawn_wait_client(shared, vec!["wait", "0", "--status", "running"]).await;
+
+    let output = wait_handle.join().unwrap()?;
+    assert!(output.status.success(), "Got non-zero exit code on wait.");
+
+    // The task is still running, as `wait` didn't wait for it to finish.
+    let task = get_task(shared, 0).await?;
+    assert!(task.is_running(), "Task should still be running");
+
+    Ok(())
+}
+
+/// Test that `wait --status running` fails with exitcode 1, if the task finishes without ever
+/// being started.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unreachable_target_status() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Task 1 depends on the failing task 0 and will thereby never be started.
+    run_client_command(shared, &["add", "--delay", "1 seconds", "failing_command"])?;
+    run_client_command(shared, &["add", "--after", "0", "sleep 60"])?;
+
+    let wait_handle = spawn_wait_client(shared, vec!["wait", "1", "--status", "running"]).await;
+
+    let output = wait_handle.join().unwrap()?;
+    assert!(
+        !output.status.success(),
+        "Got unexpected zero exit code on wait."
+    );
+    // The status might be displayed in either case.
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    assert!(
+        stderr.contains("task 1 finished without reaching the target status running"),
+        "Got unexpected error output: {stderr}"
+    );
+
+    Ok(())
+}
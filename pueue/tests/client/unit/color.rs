@@ -0,0 +1,59 @@
+use std::ffi::OsString;
+
+use anyhow::Result;
+use clap::Parser;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+use pueue::client::cli::{no_color_requested, CliArguments, ColorChoice};
+
+/// An explicit color choice always wins. On `auto`, `NO_COLOR` disables styling and
+/// otherwise styling depends on whether we're writing to a terminal.
+#[rstest]
+#[case(ColorChoice::Always, false, false, true)]
+#[case(ColorChoice::Always, true, false, true)]
+#[case(ColorChoice::Always, true, true, true)]
+#[case(ColorChoice::Never, false, true, false)]
+#[case(ColorChoice::Never, false, false, false)]
+#[case(ColorChoice::Auto, false, true, true)]
+#[case(ColorChoice::Auto, false, false, false)]
+#[case(ColorChoice::Auto, true, true, false)]
+#[case(ColorChoice::Auto, true, false, false)]
+fn color_enabled(
+    #[case] choice: ColorChoice,
+    #[case] no_color: bool,
+    #[case] is_tty: bool,
+    #[case] expected: bool,
+) {
+    assert_eq!(choice.enabled(no_color, is_tty), expected);
+}
+
+/// `NO_COLOR` only counts if it's set to a non-empty value.
+#[rstest]
+#[case(None, false)]
+#[case(Some(""), false)]
+#[case(Some("1"), true)]
+#[case(Some("false"), true)]
+fn no_color_env(#[case] value: Option<&str>, #[case] expected: bool) {
+    assert_eq!(no_color_requested(value.map(OsString::from)), expected);
+}
+
+/// `--no-color` is a shorthand for `--color never`.
+#[rstest]
+#[case(&["pueue"], ColorChoice::Auto)]
+#[case(&["pueue", "--color", "always"], ColorChoice::Always)]
+#[case(&["pueue", "--color", "never"], ColorChoice::Never)]
+#[case(&["pueue", "--no-color"], ColorChoice::Never)]
+fn color_choice(#[case] args: &[&str], #[case] expected: ColorChoice) -> Result<()> {
+    let cli = CliArguments::try_parse_from(args)?;
+    assert_eq!(cli.color_choice(), expected);
+
+    Ok(())
+}
+
+/// `--no-color` and an explicit `--color` cannot be combined.
+#[test]
+fn no_color_conflicts_with_color() {
+    let result = CliArguments::try_parse_from(["pueue", "--no-color", "--color", "always"]);
+    assert!(result.is_err());
+}
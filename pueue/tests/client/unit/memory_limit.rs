@@ -0,0 +1,61 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+use pueue::client::cli::{CliArguments, SubCommand};
+
+/// Parse the given `pueue add` arguments and return the memory limit in bytes.
+fn memory_limit(args: &[&str]) -> Result<Option<u64>> {
+    let mut arguments = vec!["pueue", "add"];
+    arguments.extend(args);
+    arguments.push("ls");
+
+    let cli = CliArguments::try_parse_from(arguments)?;
+    let Some(SubCommand::Add { memory_limit, .. }) = cli.cmd else {
+        bail!("Parsed unexpected subcommand");
+    };
+
+    Ok(memory_limit)
+}
+
+/// Memory sizes are parsed as bytes, with optional binary units.
+#[rstest]
+#[case("1024", 1024)]
+#[case("512K", 512 * 1024)]
+#[case("512k", 512 * 1024)]
+#[case("256M", 256 * 1024 * 1024)]
+#[case("2G", 2 * 1024 * 1024 * 1024)]
+#[case("1T", 1024 * 1024 * 1024 * 1024)]
+#[case(" 64M ", 64 * 1024 * 1024)]
+fn memory_limit_units(#[case] limit: &str, #[case] expected: u64) -> Result<()> {
+    assert_eq!(memory_limit(&["--memory-limit", limit])?, Some(expected));
+
+    Ok(())
+}
+
+/// Malformed, empty and overflowing sizes are rejected.
+#[rstest]
+#[case("")]
+#[case("0")]
+#[case("0M")]
+#[case("M")]
+#[case("-5M")]
+#[case("1.5G")]
+#[case("5X")]
+#[case("5MB")]
+#[case("18446744073709551616")]
+#[case("17179869184G")]
+fn invalid_memory_limit(#[case] limit: &str) {
+    assert!(
+        memory_limit(&["--memory-limit", limit]).is_err(),
+        "Expected {limit:?} to be rejected"
+    );
+}
+
+/// Memory limits can't be combined with a different user, as the task's scope is created in the
+/// systemd user instance of the daemon's user.
+#[test]
+fn memory_limit_conflicts_with_user() {
+    assert!(memory_limit(&["--memory-limit", "1G", "--user", "nobody"]).is_err());
+}
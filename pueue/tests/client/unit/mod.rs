@@ -1 +1,4 @@
+mod color;
+mod memory_limit;
 mod status_query;
+mod task_ids;
@@ -6,7 +6,7 @@ use chrono::{Local, TimeZone};
 use pretty_assertions::assert_eq;
 use rstest::rstest;
 
-use pueue::client::query::{apply_query, Rule};
+use pueue::client::query::{apply_query, apply_sort, Rule};
 use pueue_lib::state::PUEUE_DEFAULT_GROUP;
 use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
@@ -55,6 +55,7 @@ pub fn test_tasks() -> Vec<Task> {
         end: Local.with_ymd_and_hms(2022, 1, 8, 10, 10, 0).unwrap(),
     };
     successful.label = Some("label-10-1".to_string());
+    successful.priority = 2;
     tasks.insert(successful.id, successful);
 
     // Stashed task
@@ -80,6 +81,7 @@ pub fn test_tasks() -> Vec<Task> {
         start: Local.with_ymd_and_hms(2022, 1, 2, 12, 0, 0).unwrap(),
     };
     running.id = 4;
+    running.priority = 5;
     tasks.insert(running.id, running);
 
     // Add two queued tasks with different command
@@ -91,6 +93,7 @@ pub fn test_tasks() -> Vec<Task> {
     // Task 6 depends on task 5
     queued.id = 6;
     queued.dependencies.push(5);
+    queued.priority = -3;
     tasks.insert(queued.id, queued);
 
     tasks
@@ -99,7 +102,7 @@ pub fn test_tasks() -> Vec<Task> {
 fn test_tasks_with_query(query: &str, group: &Option<String>) -> Result<Vec<Task>> {
     let mut tasks = test_tasks();
 
-    let query_result = apply_query(query, group)?;
+    let query_result = apply_query(query, group.as_deref())?;
     tasks = query_result.apply_filters(tasks);
     tasks = query_result.order_tasks(tasks);
     tasks = query_result.limit_tasks(tasks);
@@ -110,7 +113,7 @@ fn test_tasks_with_query(query: &str, group: &Option<String>) -> Result<Vec<Task
 /// Select only specific columns for printing
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn column_selection() -> Result<()> {
-    let result = apply_query("columns=id,status,command", &None)?;
+    let result = apply_query("columns=id,status,command", None)?;
     assert_eq!(
         result.selected_columns,
         [Rule::column_id, Rule::column_status, Rule::column_command]
@@ -143,6 +146,32 @@ async fn limit_last() -> Result<()> {
     Ok(())
 }
 
+/// Skip some entries from the respective end of the list, e.g. for pagination.
+/// Offsets at or past the end of the list result in an empty list.
+#[rstest]
+#[case("first 2 offset 0", vec![0, 1])]
+#[case("first 2 offset 3", vec![3, 4])]
+#[case("first 4 offset 5", vec![5, 6])]
+#[case("first 2 offset 7", vec![])]
+#[case("first 2 offset 100", vec![])]
+#[case("last 2 offset 1", vec![4, 5])]
+#[case("last 3 offset 5", vec![0, 1])]
+#[case("last 2 offset 7", vec![])]
+#[case("last 2 offset 100", vec![])]
+#[case("order_by id desc first 2 offset 2", vec![4, 3])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn limit_offset(#[case] query: &'static str, #[case] ids: Vec<usize>) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got different tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
 /// Filter by start date
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn filter_start() -> Result<()> {
@@ -245,6 +274,29 @@ async fn filter_status(#[case] status_filter: &str, #[case] match_count: usize)
     Ok(())
 }
 
+/// Tasks that're locked for editing can be filtered via `status=locked`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_locked() -> Result<()> {
+    let mut tasks = test_tasks();
+    let mut locked = build_task();
+    locked.id = 7;
+    locked.status = TaskStatus::Locked {
+        previous_status: Box::new(locked.status.clone()),
+    };
+    tasks.push(locked);
+
+    let query_result = apply_query("status=locked", None)?;
+    let filtered = query_result.apply_filters(tasks.clone());
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0].id, 7);
+
+    let query_result = apply_query("status!=locked", None)?;
+    let filtered = query_result.apply_filters(tasks);
+    assert!(filtered.iter().all(|task| task.id != 7));
+
+    Ok(())
+}
+
 /// Order the test state by task status.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn order_by_status() -> Result<()> {
@@ -285,6 +337,32 @@ async fn order_by_status() -> Result<()> {
     Ok(())
 }
 
+/// Order the tasks by multiple columns.
+/// Further columns break ties of the previous ones, e.g. tasks with the same status.
+#[rstest]
+#[case("order_by status", vec![2, 3, 5, 6, 4, 0, 1])]
+#[case("order_by status, id", vec![2, 3, 5, 6, 4, 0, 1])]
+#[case("order_by status asc, id desc", vec![3, 2, 6, 5, 4, 0, 1])]
+#[case("order_by status desc,id desc", vec![1, 0, 4, 6, 5, 3, 2])]
+#[case("order_by label desc, id asc first 4", vec![2, 1, 0, 3])]
+#[case("order_by runtime", vec![2, 3, 5, 6, 0, 1, 4])]
+#[case("order_by runtime desc, id desc", vec![4, 1, 0, 6, 5, 3, 2])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn order_by_multiple_columns(
+    #[case] query: &'static str,
+    #[case] ids: Vec<usize>,
+) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got a different order than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
 /// Order the tasks by enqueue(d) date.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn order_by_enqueue_at() -> Result<()> {
@@ -427,3 +505,311 @@ async fn filter_command(
 
     Ok(())
 }
+
+/// Filter tasks by label or command with the "matches" `~=` regex filter.
+#[rstest]
+#[case("label~=^label-10-[01]$", 2)]
+#[case("label~=label", 3)]
+#[case("label~=()", 3)]
+#[case("command~=^sleep 60$", 5)]
+#[case("command~=^(echo|printf) ", 2)]
+#[case("command~=()", 7)]
+#[case("command~=^$", 0)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_regex(#[case] query: &'static str, #[case] match_count: usize) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+
+    assert_eq!(
+        tasks.len(),
+        match_count,
+        "Got a different amount of tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
+/// Filter tasks by label or command with the case-insensitive "contains" `~%=` filter.
+/// The plain "contains" `%=` filter keeps matching case-sensitively.
+#[rstest]
+#[case("label~%=build", vec![0, 1])]
+#[case("label~%=BUILD", vec![0, 1])]
+#[case("label%=build", vec![1])]
+#[case("label%=Build", vec![0])]
+#[case("command~%=cargo", vec![0, 1])]
+#[case("command%=cargo", vec![1])]
+#[case("command~%=nonexist", vec![])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_contains_ignore_case(
+    #[case] query: &'static str,
+    #[case] expected_ids: Vec<usize>,
+) -> Result<()> {
+    let mut frontend = build_task();
+    frontend.id = 0;
+    frontend.label = Some("Build-Frontend".to_string());
+    frontend.command = "Cargo build".to_string();
+
+    let mut backend = build_task();
+    backend.id = 1;
+    backend.label = Some("build-backend".to_string());
+    backend.command = "cargo build".to_string();
+
+    let mut unlabeled = build_task();
+    unlabeled.id = 2;
+
+    let query_result = apply_query(query, None)?;
+    let tasks = query_result.apply_filters(vec![frontend, backend, unlabeled]);
+
+    let actual_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+    assert_eq!(actual_ids, expected_ids);
+
+    Ok(())
+}
+
+/// Invalid regexes result in a proper error.
+#[rstest]
+#[case("label~=label-[")]
+#[case("command~=[sleep")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_invalid_regex(#[case] query: &'static str) -> Result<()> {
+    let Err(err) = apply_query(query, None) else {
+        panic!("Expected the query to fail: {query}");
+    };
+    assert!(
+        format!("{err:#}").contains("Invalid regex"),
+        "Got unexpected error: {err:#}"
+    );
+
+    Ok(())
+}
+
+/// Filter tasks by their id, either with a comparison or with an inclusive-exclusive range.
+#[rstest]
+#[case("id=3", vec![3])]
+#[case("id!=3", vec![0, 1, 2, 4, 5, 6])]
+#[case("id<2", vec![0, 1])]
+#[case("id>4", vec![5, 6])]
+#[case("id>=2 and id<5", vec![2, 3, 4])]
+#[case("id>=2 id<=5", vec![2, 3, 4, 5])]
+#[case("id in 2..5", vec![2, 3, 4])]
+#[case("id in 0..1", vec![0])]
+#[case("id in 3..3", vec![])]
+#[case("id in 5..100", vec![5, 6])]
+#[case("id in 0..2 or id in 5..7", vec![0, 1, 5, 6])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_id(#[case] query: &'static str, #[case] ids: Vec<usize>) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got different tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
+/// Reversed ranges and invalid ids result in a proper error.
+#[rstest]
+#[case("id in 5..2")]
+#[case("id in 5..")]
+#[case("id=-1")]
+#[case("id=abc")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_invalid_id(#[case] query: &'static str) -> Result<()> {
+    let Err(err) = apply_query(query, None) else {
+        panic!("Expected the query to fail: {query}");
+    };
+    assert!(
+        format!("{err:#}").contains("Failed to parse query"),
+        "Got unexpected error: {err:#}"
+    );
+
+    Ok(())
+}
+
+/// Filter tasks by their runtime.
+/// The running task has been started years ago, the finished tasks ran for five minutes.
+/// Tasks that haven't been started never match.
+#[rstest]
+#[case("runtime>1h", vec![4])]
+#[case("runtime>1d", vec![4])]
+#[case("runtime<10m", vec![0, 1])]
+#[case("runtime<1000h", vec![0, 1])]
+#[case("runtime=5m", vec![0, 1])]
+#[case("runtime=300s", vec![0, 1])]
+#[case("runtime!=5m", vec![4])]
+#[case("runtime>=5m", vec![0, 1, 4])]
+#[case("runtime<=4m", vec![])]
+#[case("runtime>1h or status=queued", vec![4, 5, 6])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_runtime(#[case] query: &'static str, #[case] ids: Vec<usize>) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got different tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
+/// Durations without a valid unit or out of range result in a proper error.
+#[rstest]
+#[case("runtime>5")]
+#[case("runtime>5w")]
+#[case("runtime>-5m")]
+#[case("runtime>99999999999999999999h")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_invalid_runtime(#[case] query: &'static str) -> Result<()> {
+    let Err(err) = apply_query(query, None) else {
+        panic!("Expected the query to fail: {query}");
+    };
+    assert!(
+        format!("{err:#}").contains("Failed to parse query"),
+        "Got unexpected error: {err:#}"
+    );
+
+    Ok(())
+}
+
+/// Filter tasks by their priority.
+#[rstest]
+#[case("priority=0", vec![0, 2, 3, 5])]
+#[case("priority=-3", vec![6])]
+#[case("priority!=0", vec![1, 4, 6])]
+#[case("priority>0", vec![1, 4])]
+#[case("priority>-5", vec![0, 1, 2, 3, 4, 5, 6])]
+#[case("priority<0", vec![6])]
+#[case("priority>=2", vec![1, 4])]
+#[case("priority >= 5", vec![4])]
+#[case("priority<=0", vec![0, 2, 3, 5, 6])]
+#[case("priority<=-4", vec![])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_priority(#[case] query: &'static str, #[case] ids: Vec<usize>) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got different tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
+/// Non-integer priorities result in a proper error.
+#[rstest]
+#[case("priority=abc")]
+#[case("priority=1.5")]
+#[case("priority=-")]
+#[case("priority=99999999999")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_invalid_priority(#[case] query: &'static str) -> Result<()> {
+    let Err(err) = apply_query(query, None) else {
+        panic!("Expected the query to fail: {query}");
+    };
+    assert!(
+        format!("{err:#}").contains("Failed to parse query"),
+        "Got unexpected error: {err:#}"
+    );
+
+    Ok(())
+}
+
+/// Filters can be combined with `and`/`or` and grouped with parenthesis.
+/// `and` binds stronger than `or` and is implied if no keyword is given.
+#[rstest]
+#[case("status=failed or status=running", vec![0, 4])]
+#[case("status=queued command%=echo", vec![5, 6])]
+#[case("status=queued and command%=echo", vec![5, 6])]
+#[case("(label%=10-0 or label%=10-1) and status=success", vec![1])]
+#[case("label%=10-0 or label%=10-1 and status=success", vec![0, 1])]
+#[case("status=failed or status=success and label=label-10-0", vec![0])]
+#[case(
+    "(status=failed or status=success) and (label=label-10-1 or label=label-10-2)",
+    vec![1]
+)]
+#[case("status=running or (status=queued and command~=^(echo|printf))", vec![4, 5, 6])]
+#[case("label=label-10-0 status=failed", vec![0])]
+#[case("label%=10 id in 1..3", vec![1, 2])]
+#[case("command%=echo id>5", vec![6])]
+#[case("command~=^(echo|printf) priority < 0", vec![6])]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_expression(#[case] query: &'static str, #[case] ids: Vec<usize>) -> Result<()> {
+    let tasks = test_tasks_with_query(query, &None)?;
+    let task_ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+
+    assert_eq!(
+        task_ids, ids,
+        "Got different tasks than expected for the query: {query}."
+    );
+
+    Ok(())
+}
+
+/// Incomplete filter expressions result in a proper error.
+/// Unbalanced parenthesis aren't part of label or command values.
+#[rstest]
+#[case("status=failed or")]
+#[case("(status=failed or status=running")]
+#[case("status=failed and ()")]
+#[case("label~=label-(")]
+#[case("command=echo ) status=failed")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn filter_expression_invalid(#[case] query: &'static str) -> Result<()> {
+    assert!(
+        apply_query(query, None).is_err(),
+        "Expected the query to fail: {query}"
+    );
+
+    Ok(())
+}
+
+/// The `--sort` shortcut results in the same order as the respective `order_by` condition.
+#[rstest]
+#[case("id:desc", "order_by id desc")]
+#[case("status", "order_by status")]
+#[case("enqueue_at:ASC", "order_by enqueue_at asc")]
+#[case("start:desc", "order_by start desc")]
+#[case("runtime:desc", "order_by runtime desc")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sort_shortcut(#[case] sort: &str, #[case] query: &str) -> Result<()> {
+    let mut query_result = apply_query("", None)?;
+    apply_sort(sort, &mut query_result)?;
+    let sorted: Vec<usize> = query_result
+        .order_tasks(test_tasks())
+        .iter()
+        .map(|task| task.id)
+        .collect();
+
+    let expected: Vec<usize> = test_tasks_with_query(query, &None)?
+        .iter()
+        .map(|task| task.id)
+        .collect();
+    assert_eq!(sorted, expected);
+
+    Ok(())
+}
+
+/// The `--sort` shortcut cannot be combined with an `order_by` condition in the query.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sort_shortcut_conflicts_with_order_by() -> Result<()> {
+    let mut query_result = apply_query("order_by id", None)?;
+    assert!(apply_sort("status:desc", &mut query_result).is_err());
+
+    Ok(())
+}
+
+/// Unknown columns and directions are rejected.
+#[rstest]
+#[case("runtimes")]
+#[case("idx")]
+#[case("id:down")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sort_shortcut_invalid(#[case] sort: &str) -> Result<()> {
+    let mut query_result = apply_query("", None)?;
+    assert!(apply_sort(sort, &mut query_result).is_err());
+
+    Ok(())
+}
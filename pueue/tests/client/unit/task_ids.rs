@@ -0,0 +1,78 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+use pueue::client::cli::{expand_task_ids, CliArguments, SubCommand};
+
+/// Parse the given `pueue kill` arguments and return the expanded task ids.
+fn kill_task_ids(args: &[&str]) -> Result<Vec<usize>> {
+    let mut arguments = vec!["pueue", "kill"];
+    arguments.extend(args);
+
+    let cli = CliArguments::try_parse_from(arguments)?;
+    let Some(SubCommand::Kill { task_ids, .. }) = cli.cmd else {
+        bail!("Parsed unexpected subcommand");
+    };
+
+    Ok(expand_task_ids(&task_ids))
+}
+
+/// Single ids, ranges and comma separated lists are all expanded to single task ids.
+#[rstest]
+#[case(&["5"], vec![5])]
+#[case(&["5", "6"], vec![5, 6])]
+#[case(&["5-10"], vec![5, 6, 7, 8, 9, 10])]
+#[case(&["3-3"], vec![3])]
+#[case(&["5,8,12"], vec![5, 8, 12])]
+#[case(&["1-3,7", "9"], vec![1, 2, 3, 7, 9])]
+#[case(&["0", "4-5,8-9"], vec![0, 4, 5, 8, 9])]
+#[case(&["1-10000"], (1..=10_000).collect())]
+fn task_id_expansion(#[case] args: &[&str], #[case] expected: Vec<usize>) -> Result<()> {
+    assert_eq!(kill_task_ids(args)?, expected);
+
+    Ok(())
+}
+
+/// Empty, malformed or overly large ranges are rejected.
+#[rstest]
+#[case(&["10-5"])]
+#[case(&["5-"])]
+#[case(&["-5"])]
+#[case(&["1-2-3"])]
+#[case(&["a-b"])]
+#[case(&["5,,6"])]
+#[case(&["1-10001"])]
+#[case(&["0-18446744073709551615"])]
+fn invalid_task_ids(#[case] args: &[&str]) {
+    assert!(
+        kill_task_ids(args).is_err(),
+        "Expected {args:?} to be rejected"
+    );
+}
+
+/// All commands that select tasks by a status query accept it via `--filter` and `--query`.
+#[rstest]
+#[case("stash")]
+#[case("enqueue")]
+#[case("start")]
+#[case("pause")]
+#[case("kill")]
+fn filter_query_alias(#[case] command: &str) -> Result<()> {
+    for flag in ["--filter", "--query"] {
+        let cli = CliArguments::try_parse_from(["pueue", command, flag, "label=batch"])?;
+        let filter = match cli.cmd {
+            Some(
+                SubCommand::Stash { filter, .. }
+                | SubCommand::Enqueue { filter, .. }
+                | SubCommand::Start { filter, .. }
+                | SubCommand::Pause { filter, .. }
+                | SubCommand::Kill { filter, .. },
+            ) => filter,
+            _ => bail!("Parsed unexpected subcommand"),
+        };
+        assert_eq!(filter.as_deref(), Some("label=batch"), "{command} {flag}");
+    }
+
+    Ok(())
+}
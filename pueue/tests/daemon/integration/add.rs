@@ -1,8 +1,14 @@
-use anyhow::Result;
+use std::process::Command;
+
+use anyhow::{Context, Result};
 use assert_matches::assert_matches;
 use chrono::Local;
 
-use pueue_lib::{network::message::TaskSelection, task::*};
+use pueue_lib::{
+    network::client::Client,
+    network::message::{AddMessage, TaskSelection},
+    task::*,
+};
 
 use crate::helper::*;
 
@@ -80,3 +86,98 @@ async fn test_add_with_immediate_start() -> Result<()> {
 
     Ok(())
 }
+
+/// Get the uid of a user via the `id` command.
+fn get_uid(user: Option<&str>) -> Result<String> {
+    let output = Command::new("id")
+        .arg("-u")
+        .args(user)
+        .output()
+        .context("Failed to run `id`")?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A task with a user runs as that user.
+/// Switching users requires privileges, hence this test only runs if the tests run as root.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_add_as_user() -> Result<()> {
+    if get_uid(None)? != "0" {
+        return Ok(());
+    }
+
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "id -u");
+    message.user = Some("nobody".into());
+    // The unprivileged user isn't allowed to enter the daemon's temporary directory.
+    message.path = std::env::temp_dir();
+    assert_success(send_message(shared, message).await?);
+
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_eq!(task.user, Some("nobody".to_string()));
+    assert_matches!(
+        task.status,
+        TaskStatus::Done {
+            result: TaskResult::Success,
+            ..
+        },
+        "Task should finish successfully",
+    );
+
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log.trim(), get_uid(Some("nobody"))?);
+
+    Ok(())
+}
+
+/// Adding a task for another user is rejected, if the daemon isn't allowed to switch users.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_add_as_user_without_permission() -> Result<()> {
+    if get_uid(None)? == "0" {
+        return Ok(());
+    }
+
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "id -u");
+    message.user = Some("root".into());
+    assert_failure(send_message(shared, message).await?);
+
+    // Unknown users are always rejected.
+    let mut message = create_add_message(shared, "id -u");
+    message.user = Some("pueue-user-that-does-not-exist".into());
+    assert_failure(send_message(shared, message).await?);
+
+    let state = get_state(shared).await?;
+    assert!(state.tasks.is_empty(), "No task should have been added.");
+
+    Ok(())
+}
+
+/// The library client adds tasks via the builder and returns their ids.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_add_task() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut client = Client::connect(shared).await?;
+    let builder = AddMessage::builder("sleep 60")
+        .path(shared.pueue_directory())
+        .label("library")
+        .stashed(true);
+    assert_eq!(client.add_task(builder.clone()).await?, 0);
+    assert_eq!(client.add_task(builder).await?, 1);
+
+    let state = client.status().await?;
+    assert_eq!(state.tasks.len(), 2);
+    assert_eq!(state.tasks[&1].label.as_deref(), Some("library"));
+
+    // Adding a task to a missing group results in an error.
+    let builder = AddMessage::builder("ls").group("doesnt_exist");
+    assert!(client.add_task(builder).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::{bail, Result};
+use pretty_assertions::assert_eq;
+
+use pueue_lib::network::message::*;
+
+use crate::helper::*;
+
+/// Messages in a batch are executed in order.
+/// The status at the end of the batch should thereby already contain the added task.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_add_and_status() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut add_message = create_add_message(shared, "ls");
+    add_message.stashed = true;
+    let batch = Message::Batch(vec![add_message.into(), Message::Status]);
+
+    let Message::BatchResponse(responses) = send_message(shared, batch).await? else {
+        bail!("Expected a batch response");
+    };
+    assert_eq!(
+        responses.len(),
+        2,
+        "There should be a response for each message."
+    );
+
+    let mut responses = responses.into_iter();
+    assert_success(responses.next().unwrap());
+    let Some(Message::StatusResponse(state)) = responses.next() else {
+        bail!("Expected a status response as second response");
+    };
+    assert_eq!(
+        state.tasks.len(),
+        1,
+        "The status should contain the added task."
+    );
+    assert_eq!(state.tasks.get(&0).unwrap().command, "ls");
+
+    Ok(())
+}
+
+/// Batches must neither be nested nor contain stream requests.
+/// Nothing of such a batch should be executed.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_rejects_forbidden_messages() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let nested = Message::Batch(vec![
+        create_add_message(shared, "ls").into(),
+        Message::Batch(vec![Message::Status]),
+    ]);
+    assert_failure(send_message(shared, nested).await?);
+
+    let stream = Message::Batch(vec![
+        create_add_message(shared, "ls").into(),
+        StreamRequestMessage {
+            task_id: Some(0),
+            lines: None,
+            offset: None,
+        }
+        .into(),
+    ]);
+    assert_failure(send_message(shared, stream).await?);
+
+    let state = get_state(shared).await?;
+    assert!(state.tasks.is_empty(), "No task should have been added.");
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use std::fs::read_to_string;
+use std::process::Command;
 
 use anyhow::{Context, Result};
+use pueue_lib::task::Task;
 
 use crate::helper::*;
 
@@ -41,3 +43,68 @@ async fn test_callback_variables() -> Result<()> {
 
     Ok(())
 }
+
+/// Tasks execute their `on_success` or `on_failure` hook, depending on their result.
+/// Information about the task is passed to the hook via environment variables.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_task_hooks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    let tempdir_path = daemon.tempdir.path().to_path_buf();
+
+    // Both tasks write into a separate file for each hook.
+    for (id, command) in [(0, "ls"), (1, "failing")] {
+        let mut message = create_add_message(shared, command);
+        message.on_success = Some(format!(
+            "echo \"$PUEUE_TASK_ID $PUEUE_TASK_RESULT\" > {}/success_{id}",
+            tempdir_path.to_string_lossy()
+        ));
+        message.on_failure = Some(format!(
+            "echo \"$PUEUE_TASK_ID $PUEUE_TASK_RESULT\" > {}/failure_{id}",
+            tempdir_path.to_string_lossy()
+        ));
+        assert_success(send_message(shared, message).await?);
+        wait_for_task_condition(shared, id, Task::is_done).await?;
+    }
+
+    assert_eq!(
+        wait_for_file_content(&tempdir_path.join("success_0")).await?,
+        "0 Success\n"
+    );
+    assert!(wait_for_file_content(&tempdir_path.join("failure_1"))
+        .await?
+        .starts_with("1 Failed"));
+
+    // Both hooks have been executed by now, so the other hooks would've been as well.
+    assert!(!tempdir_path.join("failure_0").exists());
+    assert!(!tempdir_path.join("success_1").exists());
+
+    Ok(())
+}
+
+/// The hooks of a task run as the task's user.
+/// Switching users requires privileges, hence this test only runs if the tests run as root.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_task_hook_as_user() -> Result<()> {
+    let uid = Command::new("id").arg("-u").output()?;
+    if String::from_utf8_lossy(&uid.stdout).trim() != "0" {
+        return Ok(());
+    }
+
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // The unprivileged user isn't allowed to enter the daemon's temporary directory.
+    let hook_output = std::env::temp_dir().join(format!("pueue_hook_user_{}", std::process::id()));
+    let mut message = create_add_message(shared, "ls");
+    message.user = Some("nobody".into());
+    message.path = std::env::temp_dir();
+    message.on_success = Some(format!("id -un > {}", hook_output.to_string_lossy()));
+    assert_success(send_message(shared, message).await?);
+
+    let content = wait_for_file_content(&hook_output).await;
+    let _ = std::fs::remove_file(&hook_output);
+    assert_eq!(content?, "nobody\n");
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use pueue_lib::error::Error;
+use pueue_lib::network::client::Client;
+use pueue_lib::network::message::Message;
+use pueue_lib::network::protocol::{receive_message, send_message as send_request};
+
+use crate::helper::*;
+
+/// Connections beyond `shared.max_connections` are rejected with a proper error message.
+/// As soon as a connection is closed, new clients can connect again.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_connection_limit() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.shared.max_connections = 2;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Occupy all available connections.
+    let first = get_authenticated_stream(shared).await?;
+    let _second = get_authenticated_stream(shared).await?;
+
+    // The next client still gets through the handshake, but its request is rejected.
+    let mut stream = get_authenticated_stream(shared).await?;
+    send_request(Message::Status, &mut stream).await?;
+    let response = receive_message(&mut stream).await?;
+    let Message::Failure(text) = response else {
+        panic!("Expected the connection to be rejected, got {response:?}");
+    };
+    assert!(
+        text.contains("maximum amount of 2 connections"),
+        "Unexpected rejection message: {text}"
+    );
+
+    // Close one of the connections, after which requests are handled again.
+    drop(first);
+    let mut tries = 0;
+    loop {
+        let response = send_message(shared, Message::Status).await?;
+        if matches!(response, Message::StatusResponse(_)) {
+            break;
+        }
+        tries += 1;
+        if tries > TIMEOUT / 50 {
+            panic!("Connection hasn't been released in time, last response: {response:?}");
+        }
+        sleep_ms(50).await;
+    }
+
+    Ok(())
+}
+
+/// The library client keeps retrying to connect until the daemon is up.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_connect_before_daemon_started() -> Result<()> {
+    let (settings, _tempdir) = daemon_base_setup()?;
+
+    // Start connecting, while there's no daemon yet.
+    let shared = settings.shared.clone();
+    let connect = tokio::spawn(async move {
+        Client::connect_with_timeout(&shared, Duration::from_secs(10), Duration::from_millis(50))
+            .await
+    });
+    sleep_ms(500).await;
+    assert!(
+        !connect.is_finished(),
+        "The client shouldn't be connected yet"
+    );
+
+    let mut child = standalone_daemon(&settings.shared).await?;
+    let mut client = connect.await??;
+    assert!(client.status().await?.tasks.is_empty());
+
+    child.kill()?;
+    Ok(())
+}
+
+/// Connecting fails with a dedicated error, if the daemon doesn't show up in time.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_connect_timeout() -> Result<()> {
+    let (settings, _tempdir) = daemon_base_setup()?;
+
+    let result = Client::connect_with_timeout(
+        &settings.shared,
+        Duration::from_millis(300),
+        Duration::from_millis(50),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(Error::ConnectionTimeout(..))),
+        "Expected a timeout"
+    );
+
+    Ok(())
+}
@@ -57,6 +57,7 @@ async fn test_failing_dependency() -> Result<()> {
         KillMessage {
             tasks: TaskSelection::TaskIds(vec![0]),
             signal: None,
+            pause_groups: true,
         },
     )
     .await?;
@@ -1,11 +1,11 @@
 use std::path::PathBuf;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use assert_matches::assert_matches;
 use test_log::test;
 
 use pueue_lib::network::message::*;
-use pueue_lib::settings::Shared;
+use pueue_lib::settings::{CommandDenylist, Shared};
 use pueue_lib::state::GroupStatus;
 use pueue_lib::task::*;
 
@@ -86,3 +86,64 @@ async fn test_edit_flow() -> Result<()> {
 
     Ok(())
 }
+
+/// Editing the command of a raw task drops the raw command.
+/// Otherwise, the edited command would be silently ignored.
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+async fn test_edit_raw_task() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    pause_tasks(shared, TaskSelection::All).await?;
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    let mut message = create_add_message(shared, "echo original");
+    message.raw_command = Some(vec!["echo".to_string(), "original".to_string()]);
+    assert_success(send_message(shared, message).await?);
+
+    let response = send_message(shared, Message::EditRequest(vec![0])).await?;
+    let Message::EditResponse(mut editable_tasks) = response else {
+        bail!("Didn't receive EditResponse after requesting edit.")
+    };
+    let mut editable_task = editable_tasks.remove(0);
+    editable_task.command = "echo edited".to_string();
+    assert_success(send_message(shared, Message::Edit(vec![editable_task])).await?);
+
+    let task = get_task(shared, 0).await?;
+    assert_eq!(task.raw_command, None);
+
+    // The edited command is the one that's executed.
+    start_tasks(shared, TaskSelection::All).await?;
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_eq!(get_task_log(shared, 0, None).await?, "edited\n");
+
+    Ok(())
+}
+
+/// Edited commands are checked against the daemon's `command_denylist`.
+/// Denied commands are rejected and the task keeps its previous command.
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+async fn test_edit_denied_command() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.command_denylist = CommandDenylist::new(&["^rm -rf /$"])?;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    pause_tasks(shared, TaskSelection::All).await?;
+    wait_for_group_status(shared, PUEUE_DEFAULT_GROUP, GroupStatus::Paused).await?;
+
+    let mut editable_task = create_edited_task(shared).await?.remove(0);
+    editable_task.command = "rm -rf /".to_string();
+    let response = send_message(shared, Message::Edit(vec![editable_task])).await?;
+    assert_matches!(response, Message::Failure(_), "The edit should be denied");
+
+    // The task is unlocked again and still has its previous command.
+    let task = get_task(shared, 0).await?;
+    assert_eq!(task.command, "ls");
+    assert_matches!(task.status, TaskStatus::Queued { .. });
+
+    Ok(())
+}
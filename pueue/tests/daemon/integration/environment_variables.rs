@@ -1,4 +1,6 @@
-use anyhow::Result;
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
 use pueue_lib::task::Task;
 
 use crate::helper::*;
@@ -25,3 +27,36 @@ async fn test_isolated_task_environment() -> Result<()> {
     child.kill()?;
     Ok(())
 }
+
+/// The environment variables of a task's group are injected on top of the global ones.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_group_environment_variables() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.env_vars = HashMap::from([
+        ("PUEUE_TEST_SCOPE".to_string(), "global".to_string()),
+        ("PUEUE_TEST_GLOBAL".to_string(), "global".to_string()),
+    ]);
+    settings.daemon.group_env_vars = HashMap::from([(
+        "test_2".to_string(),
+        HashMap::from([("PUEUE_TEST_SCOPE".to_string(), "group".to_string())]),
+    )]);
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    let command = "echo $PUEUE_TEST_SCOPE $PUEUE_TEST_GLOBAL";
+    assert_success(add_task_to_group(shared, command, "test_2").await?);
+    assert_success(add_task(shared, command).await?);
+
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log, "group global\n");
+
+    wait_for_task_condition(shared, 1, Task::is_done).await?;
+    let log = get_task_log(shared, 1, None).await?;
+    assert_eq!(log, "global global\n");
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use chrono::{Local, TimeDelta};
+
+use pueue_lib::network::message::*;
+use pueue_lib::state::GroupStatus;
+use pueue_lib::task::*;
+
+use crate::helper::*;
+
+/// Tasks that haven't been started before their expiry time expire and aren't started anymore.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_expire_unstarted_task() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the daemon, so the tasks cannot be started.
+    pause_tasks(shared, TaskSelection::All).await?;
+    wait_for_group_status(shared, "default", GroupStatus::Paused).await?;
+
+    let mut message = create_add_message(shared, "sleep 60");
+    message.expire_at = Some(Local::now() + TimeDelta::try_milliseconds(500).unwrap());
+    assert_success(send_message(shared, message).await?);
+
+    // A task whose expiry time is far in the future stays queued.
+    let mut message = create_add_message(shared, "sleep 60");
+    message.expire_at = Some(Local::now() + TimeDelta::try_hours(1).unwrap());
+    assert_success(send_message(shared, message).await?);
+
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert!(matches!(
+        task.status,
+        TaskStatus::Done {
+            result: TaskResult::Expired,
+            ..
+        }
+    ));
+
+    // Resuming the group doesn't start the expired task.
+    start_tasks(shared, TaskSelection::All).await?;
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    let task = get_task(shared, 0).await?;
+    assert!(task.is_done());
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use pueue_lib::{network::message::*, task::Task};
 
@@ -17,6 +17,7 @@ async fn test_add_and_remove() -> Result<()> {
     let add_message = GroupMessage::Add {
         name: "testgroup".to_string(),
         parallel_tasks: None,
+        description: None,
     };
     assert_failure(send_message(shared, add_message).await?);
 
@@ -81,3 +82,67 @@ async fn test_cannot_delete_group_with_tasks() -> Result<()> {
 
     Ok(())
 }
+
+/// Changing the default parallel tasks only affects groups that're created afterwards.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_set_default_parallel() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let add_message = GroupMessage::Add {
+        name: "before".to_string(),
+        parallel_tasks: None,
+        description: None,
+    };
+    assert_success(send_message(shared, add_message).await?);
+
+    assert_success(send_message(shared, GroupMessage::SetDefaultParallel(4)).await?);
+
+    let add_message = GroupMessage::Add {
+        name: "after".to_string(),
+        parallel_tasks: None,
+        description: None,
+    };
+    assert_success(send_message(shared, add_message).await?);
+
+    let state = get_state(shared).await?;
+    assert_eq!(
+        state
+            .groups
+            .get(PUEUE_DEFAULT_GROUP)
+            .unwrap()
+            .parallel_tasks,
+        1
+    );
+    assert_eq!(state.groups.get("before").unwrap().parallel_tasks, 1);
+    assert_eq!(state.groups.get("after").unwrap().parallel_tasks, 4);
+
+    Ok(())
+}
+
+/// Groups without tasks are removed automatically, once they've been idle for the configured
+/// amount of time. Groups with tasks and the default group are kept.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_auto_remove_empty_groups() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.auto_remove_empty_groups = Some(1);
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    add_group_with_slots(shared, "empty", 1).await?;
+    add_group_with_slots(shared, "busy", 1).await?;
+    let mut message = create_add_message(shared, "ls");
+    message.group = "busy".to_string();
+    assert_success(send_message(shared, message).await?);
+
+    wait_for_group_absence(shared, "empty").await?;
+
+    let state = get_state(shared).await?;
+    assert!(state.groups.contains_key("busy"));
+    assert!(state.groups.contains_key(PUEUE_DEFAULT_GROUP));
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use anyhow::Result;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
 
+use pueue_lib::network::client::Client;
 use pueue_lib::network::message::*;
 use pueue_lib::state::GroupStatus;
 use pueue_lib::task::*;
@@ -23,18 +24,21 @@ use crate::helper::*;
     KillMessage {
         tasks: TaskSelection::All,
         signal: None,
+        pause_groups: true,
     }, true
 )]
 #[case(
     KillMessage {
         tasks: TaskSelection::Group(PUEUE_DEFAULT_GROUP.into()),
         signal: None,
+        pause_groups: true,
     }, true
 )]
 #[case(
     KillMessage {
         tasks: TaskSelection::TaskIds(vec![0, 1, 2]),
         signal: None,
+        pause_groups: true,
     }, false
 )]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -107,18 +111,21 @@ async fn test_kill_tasks_with_pause(
     KillMessage {
         tasks: TaskSelection::All,
         signal: None,
+        pause_groups: true,
     }
 )]
 #[case(
     KillMessage {
         tasks: TaskSelection::Group(PUEUE_DEFAULT_GROUP.into()),
         signal: None,
+        pause_groups: true,
     }
 )]
 #[case(
     KillMessage {
         tasks: TaskSelection::TaskIds(vec![0, 1, 2]),
         signal: None,
+        pause_groups: true,
     }
 )]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -174,3 +181,134 @@ async fn test_kill_tasks_without_pause(#[case] kill_message: KillMessage) -> Res
 
     Ok(())
 }
+
+/// Killing all running tasks without pausing groups, i.e. `pueue kill --all-groups`.
+///
+/// Running tasks of all groups should be killed, while no group gets paused, even if there're
+/// further queued tasks. Queued tasks should stay untouched.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_kill_all_groups_without_pause() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Start a task in the default group and in a second group.
+    add_group_with_slots(shared, "testgroup", 1).await?;
+    assert_success(add_task(shared, "sleep 60").await?);
+    assert_success(add_task_to_group(shared, "sleep 60", "testgroup").await?);
+    for id in 0..2 {
+        wait_for_task_condition(shared, id, Task::is_running).await?;
+    }
+
+    // Queue another task in the default group, which is blocked by the running task.
+    assert_success(add_task(shared, "sleep 60").await?);
+
+    // Pause the test group without touching its running task and queue another task in there.
+    let pause_message = PauseMessage {
+        tasks: TaskSelection::Group("testgroup".into()),
+        wait: true,
+        restash: false,
+    };
+    assert_success(send_message(shared, pause_message).await?);
+    wait_for_group_status(shared, "testgroup", GroupStatus::Paused).await?;
+    assert_success(add_task_to_group(shared, "sleep 60", "testgroup").await?);
+
+    // Kill all running tasks without pausing any groups.
+    let kill_message = KillMessage {
+        tasks: TaskSelection::All,
+        signal: None,
+        pause_groups: false,
+    };
+    assert_success(send_message(shared, kill_message).await?);
+
+    // Make sure all running tasks get killed
+    for id in 0..2 {
+        wait_for_task_condition(shared, id, |task| {
+            matches!(
+                task.status,
+                TaskStatus::Done {
+                    result: TaskResult::Killed,
+                    ..
+                }
+            )
+        })
+        .await?;
+    }
+
+    // The default group wasn't paused, so its queued task should be started next.
+    wait_for_task_condition(shared, 2, Task::is_running).await?;
+
+    // The task in the manually paused group should still be queued.
+    let state = get_state(shared).await?;
+    assert_eq!(
+        state.groups.get(PUEUE_DEFAULT_GROUP).unwrap().status,
+        GroupStatus::Running
+    );
+    assert!(state.tasks.get(&3).unwrap().is_queued());
+
+    Ok(())
+}
+
+/// Tasks that are terminated by one of their success signals are classified as successful.
+/// The same termination counts as `Killed` for tasks without any success signals.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_success_on_signal() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // The shell terminates itself via SIGTERM.
+    let mut message = create_add_message(shared, "kill -TERM $$");
+    message.success_signals = vec![Signal::SigTerm];
+    assert_success(send_message(shared, message).await?);
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert!(
+        matches!(
+            task.status,
+            TaskStatus::Done {
+                result: TaskResult::Success,
+                ..
+            }
+        ),
+        "Task should have succeeded: {:?}",
+        task.status
+    );
+
+    assert_success(add_task(shared, "kill -TERM $$").await?);
+    let task = wait_for_task_condition(shared, 1, Task::is_done).await?;
+    assert!(
+        matches!(
+            task.status,
+            TaskStatus::Done {
+                result: TaskResult::Killed,
+                ..
+            }
+        ),
+        "Task should have been killed: {:?}",
+        task.status
+    );
+
+    Ok(())
+}
+
+/// The library client kills the selected tasks.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_kill() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 60").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let mut client = Client::connect(shared).await?;
+    client.kill(TaskSelection::TaskIds(vec![0])).await?;
+
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert!(matches!(
+        task.status,
+        TaskStatus::Done {
+            result: TaskResult::Killed,
+            ..
+        }
+    ));
+
+    Ok(())
+}
@@ -1,10 +1,15 @@
 use std::fs::read_to_string;
 use std::fs::File;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use tempfile::TempDir;
+use tokio::time::timeout;
 
+use pueue_lib::network::client::Client;
+use pueue_lib::network::protocol;
+use pueue_lib::network::stream::LogStream;
 use pueue_lib::{network::message::*, task::Task};
 
 use crate::helper::*;
@@ -224,3 +229,213 @@ async fn logs_for_all() -> Result<()> {
 
     Ok(())
 }
+
+/// Lines of a task's output that are longer than `daemon.max_log_line_length` are truncated.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_max_log_line_length() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.max_log_line_length = Some(10);
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Print an over-long line, followed by a short one.
+    assert_success(add_task(shared, "printf '%050d\\nshort\\n' 0").await?);
+    // The output is copied to the log file in a separate thread, which is joined before the
+    // task is marked as done.
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log, "0000000000…\nshort\n");
+
+    Ok(())
+}
+
+/// Cancelling a log stream makes the daemon stop sending output and close the stream,
+/// even though the followed task is still running.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_cancel_log_stream() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // A task that continuously produces output.
+    assert_success(add_task(shared, "while true; do echo test; sleep 0.1; done").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let stream = get_authenticated_stream(shared).await?;
+    let message = StreamRequestMessage {
+        task_id: Some(0),
+        lines: None,
+        offset: None,
+    };
+    let mut log_stream = LogStream::start(stream, message).await?;
+
+    let message = log_stream.next().await?;
+    assert!(
+        matches!(message, Some(Message::Stream(_))),
+        "Expected a chunk of output, got {message:?}"
+    );
+
+    // The daemon has to acknowledge the cancellation by closing the stream.
+    // If it kept sending output, this would time out.
+    timeout(Duration::from_secs(5), log_stream.cancel())
+        .await
+        .context("The daemon didn't stop sending output")??;
+    assert!(log_stream.next().await?.is_none());
+
+    // The task itself isn't affected by the cancellation.
+    let state = get_state(shared).await?;
+    assert!(state.tasks.get(&0).unwrap().is_running());
+
+    Ok(())
+}
+
+/// Requesting a log stream from a byte offset only sends the output after that offset.
+/// Each chunk reports its exact position in the log file.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_from_offset() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "printf 0123456789").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let stream = get_authenticated_stream(shared).await?;
+    let message = StreamRequestMessage {
+        task_id: Some(0),
+        lines: None,
+        offset: Some(4),
+    };
+    let mut log_stream = LogStream::start(stream, message).await?;
+
+    let Some(Message::Stream(chunk)) = log_stream.next().await? else {
+        bail!("Expected a chunk of output");
+    };
+    assert_eq!(
+        chunk,
+        StreamResponseMessage {
+            offset: 4,
+            next_offset: 10,
+            text: "456789".to_string(),
+        }
+    );
+    // The task is done, so the stream is closed after the remaining output has been sent.
+    assert!(log_stream.next().await?.is_none());
+
+    Ok(())
+}
+
+/// Once a followed task finishes, the daemon sends all remaining output and explicitly ends
+/// the stream with a `Close` message.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_ends_with_close() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1 && echo done").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    let mut stream = get_authenticated_stream(shared).await?;
+    let message = StreamRequestMessage {
+        task_id: Some(0),
+        lines: None,
+        offset: None,
+    };
+    protocol::send_message(message, &mut stream).await?;
+
+    let mut output = String::new();
+    let last_message = loop {
+        let message = timeout(
+            Duration::from_secs(5),
+            protocol::receive_message(&mut stream),
+        )
+        .await
+        .context("The stream didn't end in time")??;
+        match message {
+            Message::Stream(chunk) => output.push_str(&chunk.text),
+            message => break message,
+        }
+    };
+
+    assert_eq!(last_message, Message::Close);
+    assert_eq!(output, "done\n");
+
+    Ok(())
+}
+
+/// The library client fetches the logs of finished tasks.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_get_log() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "echo first && echo second").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let mut client = Client::connect(shared).await?;
+    assert!(!client.daemon_version().is_empty());
+
+    // Get the full log.
+    let mut logs = client.get_log(vec![0], Some(1), true).await?;
+    let log = logs
+        .remove(&0)
+        .context("Didn't find log of requested task")?;
+    let output = decompress_log(log.output.context("Didn't receive output")?)?;
+    assert_eq!(output, "first\nsecond\n");
+
+    // Only get the last line.
+    let mut logs = client.get_log(vec![0], Some(1), false).await?;
+    let log = logs
+        .remove(&0)
+        .context("Didn't find log of requested task")?;
+    let output = decompress_log(log.output.context("Didn't receive output")?)?;
+    assert_eq!(output, "second\n");
+
+    Ok(())
+}
+
+/// The library client follows a task's output until the task finishes.
+/// Missing and queued tasks can't be followed.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_follow_log() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Missing tasks can't be followed.
+    let client = Client::connect(shared).await?;
+    assert!(client.follow_log(0).await.is_err());
+
+    // A task that prints its lines over a few seconds.
+    assert_success(add_task(shared, "for i in 1 2 3; do echo $i; sleep 1; done").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    // Queued tasks can't be followed either.
+    let mut client = Client::connect(shared).await?;
+    let builder = AddMessage::builder("ls")
+        .path(shared.pueue_directory())
+        .stashed(true);
+    let stashed_id = client.add_task(builder).await?;
+    assert!(client.follow_log(stashed_id).await.is_err());
+
+    let client = Client::connect(shared).await?;
+    let mut log_stream = client.follow_log(0).await?;
+    let mut chunks = Vec::new();
+    while let Some(chunk) = timeout(Duration::from_secs(10), log_stream.next_chunk())
+        .await
+        .context("The stream didn't end in time")??
+    {
+        chunks.push(chunk);
+    }
+
+    let output: String = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+    assert_eq!(output, "1\n2\n3\n");
+    // The output has been received incrementally and the chunks are contiguous.
+    assert!(chunks.len() > 1, "Expected multiple chunks, got {chunks:?}");
+    for pair in chunks.windows(2) {
+        assert_eq!(pair[0].next_offset, pair[1].offset);
+    }
+
+    Ok(())
+}
@@ -1,26 +1,35 @@
 mod add;
 mod aliases;
+mod batch;
 mod callback;
 mod clean;
+mod connections;
 mod dependencies;
 mod edit;
 mod environment_variables;
+mod expire;
 mod group;
 mod kill;
 mod log;
 mod parallel_tasks;
 mod pause;
 mod priority;
+/// Tests for reloading the configuration while the daemon is running.
+mod reload;
 mod remove;
 mod reset;
 mod restart;
 /// Tests regarding state restoration from a previous run.
 mod restore;
+mod send;
 /// Tests for shutting down the daemon.
 mod shutdown;
 mod socket_permissions;
 mod spawn;
 mod start;
 mod stashed;
+mod status_history;
+mod timeout;
+mod wait;
 /// Test that the worker pool environment variables are properly injected.
 mod worker_environment_variables;
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use assert_matches::assert_matches;
 
-use pueue_lib::{network::message::ParallelMessage, task::*};
+use pueue_lib::{
+    network::message::{KillMessage, ParallelMessage, TaskSelection},
+    task::*,
+};
 
 use crate::helper::*;
 
@@ -65,6 +68,37 @@ async fn test_parallel_tasks() -> Result<()> {
     Ok(())
 }
 
+/// Parallel tasks record the distinct worker slots they've been started in.
+/// The worker id is kept once the task finished.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_worker_ids_are_recorded() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    add_group_with_slots(shared, "testgroup", 2).await?;
+    for _ in 0..2 {
+        assert_success(add_task_to_group(shared, "sleep 60", "testgroup").await?);
+    }
+
+    let first = wait_for_task_condition(shared, 0, Task::is_running).await?;
+    let second = wait_for_task_condition(shared, 1, Task::is_running).await?;
+    let (Some(first_worker), Some(second_worker)) = (first.worker_id, second.worker_id) else {
+        panic!("Running tasks should have a worker id: {first:?} {second:?}");
+    };
+    assert_ne!(first_worker, second_worker);
+
+    let message = KillMessage {
+        tasks: TaskSelection::TaskIds(vec![0]),
+        signal: None,
+        pause_groups: false,
+    };
+    assert_success(send_message(shared, message).await?);
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_eq!(task.worker_id, Some(first_worker));
+
+    Ok(())
+}
+
 /// Test that a group with a parallel limit of `0` has an unlimited amount of tasks.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_unlimited_parallel_tasks() -> Result<()> {
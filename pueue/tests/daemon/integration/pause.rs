@@ -78,6 +78,7 @@ async fn test_pause_with_wait() -> Result<()> {
     let message = PauseMessage {
         tasks: TaskSelection::Group(PUEUE_DEFAULT_GROUP.into()),
         wait: true,
+        restash: false,
     };
     send_message(shared, message)
         .await
@@ -101,3 +102,40 @@ async fn test_pause_with_wait() -> Result<()> {
 
     Ok(())
 }
+
+/// Pausing a group with `restash` kills its running and paused tasks and moves them back to the
+/// stash, so the group is truly idle.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_pause_with_restash() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Start a long running task and a task that's paused afterwards.
+    add_group_with_slots(shared, "build", 2).await?;
+    assert_success(add_task_to_group(shared, "sleep 60", "build").await?);
+    assert_success(add_task_to_group(shared, "sleep 60", "build").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    pause_tasks(shared, TaskSelection::TaskIds(vec![1])).await?;
+    wait_for_task_condition(shared, 1, Task::is_paused).await?;
+
+    let message = PauseMessage {
+        tasks: TaskSelection::Group("build".into()),
+        wait: false,
+        restash: true,
+    };
+    assert_success(send_message(shared, message).await?);
+
+    // Both tasks end up stashed and the group is paused.
+    wait_for_task_condition(shared, 0, Task::is_stashed).await?;
+    wait_for_task_condition(shared, 1, Task::is_stashed).await?;
+    assert_group_status(
+        shared,
+        "build",
+        GroupStatus::Paused,
+        "The group should be paused.",
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use pueue_lib::task::Task;
+
+use crate::helper::*;
+
+/// Raising the parallel tasks of a configured group via a configuration reload starts more
+/// tasks, without disturbing the ones that're already running.
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_reload_parallel_tasks() -> Result<()> {
+    let (mut settings, _tempdir) = daemon_base_setup()?;
+    let config_path = settings.shared.pueue_directory().join("pueue.yml");
+    settings.daemon.groups = HashMap::from([("reload".to_string(), 1)]);
+    settings.save(&Some(config_path.clone()))?;
+
+    let mut child = standalone_daemon(&settings.shared).await?;
+    let shared = &settings.shared;
+    wait_for_group(shared, "reload").await?;
+
+    for _ in 0..3 {
+        assert_success(add_task_to_group(shared, "sleep 60", "reload").await?);
+    }
+    let first_start = wait_for_task_condition(shared, 0, Task::is_running)
+        .await?
+        .start_and_end()
+        .0;
+    let state = get_state(shared).await?;
+    assert!(state.tasks[&1].is_queued() && state.tasks[&2].is_queued());
+
+    // Raise the parallel tasks and tell the daemon to reload its configuration.
+    settings.daemon.groups = HashMap::from([("reload".to_string(), 3)]);
+    settings.save(&Some(config_path))?;
+    let status = Command::new("kill")
+        .args(["-HUP", &child.id().to_string()])
+        .status()
+        .context("Failed to send SIGHUP")?;
+    assert!(status.success());
+
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    wait_for_task_condition(shared, 2, Task::is_running).await?;
+    let task = get_task(shared, 0).await?;
+    assert!(task.is_running());
+    assert_eq!(task.start_and_end().0, first_start);
+
+    child.kill()?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use pueue_lib::settings::CommandDenylist;
 use pueue_lib::{network::message::*, task::Task};
 
 use crate::helper::*;
@@ -80,3 +81,73 @@ async fn test_cannot_restart_running() -> Result<()> {
 
     Ok(())
 }
+
+/// Restarting a raw task in-place with an edited command drops the raw command.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_restart_raw_task_with_edited_command() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "echo original");
+    message.raw_command = Some(vec!["echo".to_string(), "original".to_string()]);
+    assert_success(send_message(shared, message).await?);
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let restart_message = RestartMessage {
+        tasks: vec![TaskToRestart {
+            task_id: 0,
+            command: "echo edited".to_string(),
+            path: task.path,
+            label: None,
+            priority: 0,
+        }],
+        start_immediately: false,
+        stashed: false,
+    };
+    assert_success(send_message(shared, restart_message).await?);
+
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert_eq!(task.raw_command, None);
+    assert_eq!(get_task_log(shared, 0, None).await?, "edited\n");
+
+    Ok(())
+}
+
+/// Commands that're edited during an in-place restart are checked against the daemon's
+/// `command_denylist`. Denied commands are rejected and the task isn't restarted.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_restart_denied_command() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.command_denylist = CommandDenylist::new(&["^rm -rf /$"])?;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_and_start_task(shared, "ls").await?);
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let restart_message = RestartMessage {
+        tasks: vec![TaskToRestart {
+            task_id: 0,
+            command: "rm -rf /".to_string(),
+            path: task.path,
+            label: None,
+            priority: 0,
+        }],
+        start_immediately: false,
+        stashed: false,
+    };
+    let response = send_message(shared, restart_message).await?;
+    assert!(
+        matches!(response, Message::Failure(_)),
+        "The restart should be denied, got {response:?}"
+    );
+
+    let task = get_task(shared, 0).await?;
+    assert_eq!(task.command, "ls");
+    assert!(task.is_done(), "The task shouldn't be restarted");
+
+    Ok(())
+}
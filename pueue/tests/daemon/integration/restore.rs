@@ -1,7 +1,8 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
-use pueue_lib::network::message::TaskSelection;
+use pueue_lib::network::message::{StashMessage, TaskSelection};
 use pueue_lib::state::GroupStatus;
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
 
 use crate::helper::*;
 
@@ -61,3 +62,48 @@ async fn test_start_paused() -> Result<()> {
     child.kill()?;
     Ok(())
 }
+
+/// If the state is persisted in a write-ahead log, all changes since the last snapshot are
+/// restored as well.
+#[tokio::test]
+async fn test_restore_from_wal() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.state_wal_max_records = Some(100);
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let mut child = standalone_daemon(&settings.shared).await?;
+    let shared = &settings.shared;
+
+    // Run a task and stash another one. Those changes are only written to the log.
+    assert_success(add_task(shared, "ls").await?);
+    wait_for_task_condition(shared, 0, Task::is_done).await?;
+    pause_tasks(shared, TaskSelection::All).await?;
+    assert_success(add_task(shared, "ls").await?);
+    let message = StashMessage {
+        tasks: TaskSelection::TaskIds(vec![1]),
+        enqueue_at: None,
+    };
+    assert_success(send_message(shared, message).await?);
+
+    // Kill the daemon and wait for it to shut down.
+    assert_success(shutdown_daemon(shared).await?);
+    wait_for_shutdown(&mut child).await?;
+
+    // Boot it up again
+    let mut child = standalone_daemon(&settings.shared).await?;
+
+    let task = get_task(shared, 0).await?;
+    assert!(matches!(
+        task.status,
+        TaskStatus::Done {
+            result: TaskResult::Success,
+            ..
+        }
+    ));
+    let task = get_task(shared, 1).await?;
+    assert!(matches!(task.status, TaskStatus::Stashed { .. }));
+
+    child.kill()?;
+    Ok(())
+}
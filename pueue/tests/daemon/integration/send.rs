@@ -0,0 +1,51 @@
+use anyhow::Result;
+
+use pueue_lib::network::message::*;
+use pueue_lib::state::PUEUE_DEFAULT_GROUP;
+use pueue_lib::task::Task;
+
+use crate::helper::*;
+
+/// The same input can be sent to multiple running tasks at once.
+/// Tasks that aren't running are reported as failed.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_to_multiple_tasks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Allow both tasks to run at the same time.
+    let message = ParallelMessage {
+        group: PUEUE_DEFAULT_GROUP.to_string(),
+        parallel_tasks: 2,
+    };
+    assert_success(send_message(shared, message).await?);
+
+    // Add two tasks that wait for input and print it.
+    for _ in 0..2 {
+        assert_success(add_task(shared, "read input && echo \"got $input\"").await?);
+    }
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+
+    // Task 2 doesn't exist.
+    let message = SendMessage {
+        task_ids: vec![0, 1, 2],
+        input: "reload\n".to_string(),
+    };
+    let response = send_message(shared, message).await?;
+    let Message::Success(text) = response else {
+        panic!("Expected success message, got {response:?}");
+    };
+    assert_eq!(
+        text,
+        "Message is being send to tasks: 0, 1\nThe command failed for tasks: 2"
+    );
+
+    for task_id in [0, 1] {
+        wait_for_task_condition(shared, task_id, Task::is_done).await?;
+        let log = get_task_log(shared, task_id, None).await?;
+        assert_eq!(log, "got reload\n");
+    }
+
+    Ok(())
+}
@@ -4,6 +4,11 @@ use std::os::unix::fs::PermissionsExt;
 
 use anyhow::Context;
 
+use pueue_lib::network::message::Message;
+use pueue_lib::network::protocol::{
+    get_client_stream, receive_bytes, receive_message, send_message,
+};
+
 use crate::helper::*;
 
 /// Make sure that the socket permissions are appropriately set.
@@ -54,3 +59,43 @@ async fn test_socket_permissions_modified() -> Result<()> {
     child.kill()?;
     Ok(())
 }
+
+/// If the socket's permissions are trusted, the socket is restricted to its owner and clients
+/// can connect without sending the shared secret.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[cfg(not(target_os = "windows"))]
+async fn test_socket_trust_permissions() -> Result<()> {
+    let (mut settings, _tempdir) = daemon_base_setup()?;
+    settings.shared.unix_socket_trust_permissions = true;
+    let shared = &settings.shared;
+    settings
+        .save(&Some(settings.shared.runtime_directory().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+
+    let mut child = standalone_daemon(shared).await?;
+
+    assert_eq!(
+        fs::metadata(shared.unix_socket_path())?
+            .permissions()
+            .mode()
+            & 0o777,
+        0o600
+    );
+
+    // The daemon sends its version right away, without waiting for the secret.
+    let mut stream = get_client_stream(shared).await?;
+    let version = receive_bytes(&mut stream).await?;
+    assert!(!version.is_empty(), "Expected the daemon's version");
+
+    send_message(Message::Status, &mut stream).await?;
+    let response = receive_message(&mut stream).await?;
+    assert!(matches!(response, Message::StatusResponse(_)));
+
+    // The secret handshake is only ever skipped for unix sockets.
+    let mut tcp_settings = settings.shared.clone();
+    tcp_settings.use_unix_socket = false;
+    assert!(!tcp_settings.skip_secret_handshake());
+
+    child.kill()?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::io::Read;
+use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use rstest::rstest;
@@ -17,7 +18,7 @@ async fn test_fail_to_spawn_task() -> Result<()> {
     // Start a custom daemon that uses a shell command that doesn't exist.
     let (mut settings, tempdir) = daemon_base_setup()?;
     settings.daemon.shell_command =
-        Some(vec!["thisshellshouldreallynotexist.hopefully".to_string()]);
+        Some(vec!["thisshellshouldreallynotexist.hopefully".to_string()].into());
     let tempdir_path = tempdir.path().to_path_buf();
     settings
         .save(&Some(tempdir_path.join("pueue.yml")))
@@ -45,3 +46,164 @@ async fn test_fail_to_spawn_task() -> Result<()> {
 
     Ok(())
 }
+
+/// Raw commands aren't wrapped in a shell, which is why shell syntax is passed to the program
+/// literally.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_raw_command() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "echo '$HOME && ls;'");
+    message.raw_command = Some(vec!["echo".to_string(), "$HOME && ls;".to_string()]);
+    assert_success(send_message(shared, message).await?);
+
+    let task = wait_for_task_condition(shared, 0, |task| task.is_done()).await?;
+    assert_eq!(task.exit_code(), Some(0));
+
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log, "$HOME && ls;\n");
+
+    Ok(())
+}
+
+/// Tasks with a missing working directory fail to spawn, unless the daemon is asked to create
+/// the directory.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_create_workdir() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+    let workdir = shared.pueue_directory().join("missing").join("workdir");
+
+    let mut message = create_add_message(shared, "ls");
+    message.path = workdir.clone();
+    assert_success(send_message(shared, message).await?);
+    let task = wait_for_task_condition(shared, 0, |task| task.is_done()).await?;
+    assert!(
+        matches!(
+            task.status,
+            TaskStatus::Done {
+                result: TaskResult::FailedToSpawn(_),
+                ..
+            }
+        ),
+        "Task should have failed to spawn: {:?}",
+        task.status
+    );
+    assert!(!workdir.exists());
+
+    let mut message = create_add_message(shared, "ls");
+    message.path = workdir.clone();
+    message.create_workdir = true;
+    assert_success(send_message(shared, message).await?);
+    let task = wait_for_task_condition(shared, 1, |task| task.is_done()).await?;
+    assert_eq!(task.exit_code(), Some(0));
+    assert!(workdir.is_dir());
+    assert_eq!(task.path, workdir);
+
+    Ok(())
+}
+
+/// The daemon only creates absolute working directories and refuses to create them for tasks
+/// that run as a different user, as the directory would belong to the daemon's user.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_create_workdir_rejected() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "ls");
+    message.path = PathBuf::from("relative/workdir");
+    message.create_workdir = true;
+    assert_failure(send_message(shared, message).await?);
+
+    let mut message = create_add_message(shared, "ls");
+    message.path = shared.pueue_directory().join("workdir");
+    message.create_workdir = true;
+    message.user = Some("nobody".into());
+    assert_failure(send_message(shared, message).await?);
+
+    let state = get_state(shared).await?;
+    assert!(state.tasks.is_empty(), "No task should have been added.");
+
+    Ok(())
+}
+
+/// Tasks with a memory limit are executed in their own cgroup, which enforces that limit.
+#[cfg(target_os = "linux")]
+#[ignore = "The cgroup is created via a systemd user instance, which isn't available everywhere"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_memory_limit() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Print the memory limit of the cgroup the task runs in.
+    let mut message = create_add_message(
+        shared,
+        "cat /sys/fs/cgroup$(cut -d: -f3 /proc/self/cgroup)/memory.max",
+    );
+    message.memory_limit = Some(256 * 1024 * 1024);
+    assert_success(send_message(shared, message).await?);
+
+    let task = wait_for_task_condition(shared, 0, |task| task.is_done()).await?;
+    assert_eq!(task.exit_code(), Some(0));
+
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log, "268435456\n");
+
+    Ok(())
+}
+
+/// Memory limits can't be combined with a different user.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_memory_limit_with_user() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "ls");
+    message.memory_limit = Some(256 * 1024 * 1024);
+    message.user = Some("nobody".into());
+    assert_failure(send_message(shared, message).await?);
+
+    let state = get_state(shared).await?;
+    assert!(state.tasks.is_empty(), "No task should have been added.");
+
+    Ok(())
+}
+
+/// Tasks of a group with its own shell command are executed by that shell, while all other
+/// tasks still use the global one.
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_group_shell_command() -> Result<()> {
+    // `sh -c` uses the argument after the command string as `$0`, which makes it observable
+    // which shell command has been used to execute a task.
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.group_shell_commands.insert(
+        "test_2".to_string(),
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "{{ pueue_command_string }}".to_string(),
+            "group-shell".to_string(),
+        ]
+        .into(),
+    );
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task_to_group(shared, "echo $0", "test_2").await?);
+    assert_success(add_task(shared, "echo $0").await?);
+
+    wait_for_task_condition(shared, 0, |task| task.is_done()).await?;
+    let log = get_task_log(shared, 0, None).await?;
+    assert_eq!(log, "group-shell\n");
+
+    wait_for_task_condition(shared, 1, |task| task.is_done()).await?;
+    let log = get_task_log(shared, 1, None).await?;
+    assert_eq!(log, "sh\n");
+
+    Ok(())
+}
@@ -36,6 +36,7 @@ async fn test_enqueued_tasks(#[case] enqueue_at: Option<DateTime<Local>>) -> Res
     let enqueue_message = EnqueueMessage {
         tasks: TaskSelection::TaskIds(vec![0]),
         enqueue_at: None,
+        start_immediately: false,
     };
     send_message(shared, enqueue_message)
         .await
@@ -77,6 +78,33 @@ async fn test_delayed_tasks() -> Result<()> {
     Ok(())
 }
 
+/// Delayed tasks whose scheduled time already passed, e.g. because the daemon wasn't running,
+/// are enqueued right away.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_delayed_tasks_in_the_past() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // Pause the daemon, so the task stays queued once it's been enqueued.
+    pause_tasks(shared, TaskSelection::All).await?;
+    wait_for_group_status(shared, "default", GroupStatus::Paused).await?;
+
+    let enqueue_at = Local::now() - TimeDelta::try_hours(1).unwrap();
+    let response = create_stashed_task(shared, "sleep 10", Some(enqueue_at)).await?;
+    assert_success(response);
+
+    let task = wait_for_task_condition(shared, 0, Task::is_queued).await?;
+    let TaskStatus::Queued { enqueued_at } = task.status else {
+        panic!("Task should be queued: {:?}", task.status);
+    };
+    assert!(
+        enqueued_at > enqueue_at,
+        "Task should've been enqueued now instead of the scheduled time."
+    );
+
+    Ok(())
+}
+
 /// Stash a task that's currently queued for execution.
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_stash_queued_task() -> Result<()> {
@@ -105,3 +133,56 @@ async fn test_stash_queued_task() -> Result<()> {
 
     Ok(())
 }
+
+/// Scheduled tasks can be unscheduled, which leaves them as plain stashed tasks.
+/// Plain stashed tasks cannot be unscheduled.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_unschedule_tasks() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let enqueue_at = Local::now() + TimeDelta::try_seconds(2).unwrap();
+    assert_success(create_stashed_task(shared, "ls", Some(enqueue_at)).await?);
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+
+    // Unscheduling a plain stashed task fails.
+    assert_failure(send_message(shared, UnscheduleMessage { task_ids: vec![1] }).await?);
+
+    // Unschedule the scheduled task.
+    assert_success(send_message(shared, UnscheduleMessage { task_ids: vec![0] }).await?);
+    let status = get_task_status(shared, 0).await?;
+    assert_eq!(status, TaskStatus::Stashed { enqueue_at: None });
+
+    // Wait until the original schedule passed. The task should still be stashed.
+    sleep_ms(2500).await;
+    assert!(get_task(shared, 0).await?.is_stashed());
+
+    Ok(())
+}
+
+/// Stashed tasks can be enqueued and started right away.
+/// This ignores the parallel limit of the group, which is already fully occupied.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_enqueue_and_start() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    // The default group only has a single slot, which is now occupied.
+    assert_success(add_task(shared, "sleep 60").await?);
+    wait_for_task_condition(shared, 0, Task::is_running).await?;
+
+    assert_success(create_stashed_task(shared, "sleep 60", None).await?);
+
+    let enqueue_message = EnqueueMessage {
+        tasks: TaskSelection::TaskIds(vec![1]),
+        enqueue_at: None,
+        start_immediately: true,
+    };
+    assert_success(send_message(shared, enqueue_message).await?);
+
+    // The task should be started, even though the group is at its limit.
+    wait_for_task_condition(shared, 1, Task::is_running).await?;
+    assert!(get_task(shared, 0).await?.is_running());
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::{Context, Result};
+
+use pueue_lib::network::message::*;
+use pueue_lib::task::Task;
+
+use crate::helper::*;
+
+/// The status changes of a task are recorded, if `daemon.track_status_history` is enabled.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_status_history() -> Result<()> {
+    let (mut settings, tempdir) = daemon_base_setup()?;
+    settings.daemon.track_status_history = true;
+    settings
+        .save(&Some(tempdir.path().join("pueue.yml")))
+        .context("Couldn't write pueue config to temporary directory")?;
+    let daemon = daemon_with_settings(settings, tempdir).await?;
+    let shared = &daemon.settings.shared;
+
+    // Every status change is recorded, no matter how short the task has been in that status.
+    assert_success(create_stashed_task(shared, "ls", None).await?);
+    let enqueue_message = EnqueueMessage {
+        tasks: TaskSelection::TaskIds(vec![0]),
+        enqueue_at: None,
+        start_immediately: false,
+    };
+    assert_success(send_message(shared, enqueue_message).await?);
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+
+    let labels: Vec<&str> = task
+        .status_history
+        .iter()
+        .map(|(_, label)| label.as_str())
+        .collect();
+    assert_eq!(labels, vec!["Stashed", "Queued", "Running", "Done"]);
+
+    // The transitions are recorded in chronological order.
+    let times: Vec<_> = task.status_history.iter().map(|(time, _)| *time).collect();
+    assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    Ok(())
+}
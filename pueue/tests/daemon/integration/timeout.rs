@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+
+use pueue_lib::task::*;
+
+use crate::helper::*;
+
+/// Tasks that run longer than their timeout are killed.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_timeout_kills_task() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut message = create_add_message(shared, "sleep 60");
+    message.timeout = Some(Duration::from_millis(500));
+    assert_success(send_message(shared, message).await?);
+
+    // A task that finishes before its timeout isn't affected.
+    let mut message = create_add_message(shared, "ls");
+    message.timeout = Some(Duration::from_secs(60));
+    assert_success(send_message(shared, message).await?);
+
+    let task = wait_for_task_condition(shared, 0, Task::is_done).await?;
+    assert!(
+        matches!(
+            task.status,
+            TaskStatus::Done {
+                result: TaskResult::Killed,
+                ..
+            }
+        ),
+        "Task should have been killed: {:?}",
+        task.status
+    );
+
+    let task = wait_for_task_condition(shared, 1, Task::is_done).await?;
+    assert_eq!(task.exit_code(), Some(0));
+
+    Ok(())
+}
+
+/// The timeout of a task survives a restart of the daemon.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_timeout_is_restored() -> Result<()> {
+    let (settings, _tempdir) = daemon_base_setup()?;
+    let mut child = standalone_daemon(&settings.shared).await?;
+    let shared = &settings.shared;
+
+    let mut message = create_add_message(shared, "sleep 60");
+    message.stashed = true;
+    message.timeout = Some(Duration::from_secs(90 * 60));
+    assert_success(send_message(shared, message).await?);
+
+    // Kill the daemon and wait for it to shut down.
+    assert_success(shutdown_daemon(shared).await?);
+    wait_for_shutdown(&mut child).await?;
+
+    // Boot it up again
+    let mut child = standalone_daemon(&settings.shared).await?;
+
+    let task = get_task(shared, 0).await?;
+    assert_eq!(task.timeout, Some(Duration::from_secs(90 * 60)));
+
+    child.kill()?;
+    Ok(())
+}
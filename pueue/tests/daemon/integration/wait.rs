@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use assert_matches::assert_matches;
+
+use pueue_lib::network::client::Client;
+use pueue_lib::task::{TaskResult, TaskStatus, WaitTargetStatus};
+
+use crate::helper::*;
+
+/// The library client waits until a task transitions to the target status.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_wait_for() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    assert_success(add_task(shared, "sleep 1").await?);
+
+    let mut client = Client::connect(shared).await?;
+    let status = client
+        .wait_for(0, WaitTargetStatus::Running, Duration::from_secs(5))
+        .await?;
+    assert_matches!(status, TaskStatus::Running { .. });
+
+    let status = client
+        .wait_for(0, WaitTargetStatus::Success, Duration::from_secs(5))
+        .await?;
+    assert_matches!(
+        status,
+        TaskStatus::Done {
+            result: TaskResult::Success,
+            ..
+        }
+    );
+
+    Ok(())
+}
+
+/// Waiting fails if the target status cannot be reached in time or at all.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_wait_for_failure() -> Result<()> {
+    let daemon = daemon().await?;
+    let shared = &daemon.settings.shared;
+
+    let mut client = Client::connect(shared).await?;
+
+    // The task finishes, but fails.
+    assert_success(add_task(shared, "exit 1").await?);
+    let result = client
+        .wait_for(0, WaitTargetStatus::Success, Duration::from_secs(5))
+        .await;
+    assert!(result.is_err(), "Expected a failed task, got {result:?}");
+
+    // The task doesn't finish before the timeout.
+    assert_success(add_task(shared, "sleep 60").await?);
+    let result = client
+        .wait_for(1, WaitTargetStatus::Done, Duration::from_millis(500))
+        .await;
+    assert!(result.is_err(), "Expected a timeout, got {result:?}");
+
+    // The task doesn't exist.
+    let result = client
+        .wait_for(2, WaitTargetStatus::Done, Duration::from_secs(5))
+        .await;
+    assert!(result.is_err(), "Expected a missing task, got {result:?}");
+
+    Ok(())
+}
@@ -10,6 +10,7 @@ pub async fn add_group_with_slots(shared: &Shared, group_name: &str, slots: usiz
     let add_message = GroupMessage::Add {
         name: group_name.to_string(),
         parallel_tasks: Some(slots),
+        description: None,
     };
     assert_success(send_message(shared, add_message.clone()).await?);
     wait_for_group(shared, group_name).await?;
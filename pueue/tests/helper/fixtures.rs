@@ -85,7 +85,7 @@ pub async fn daemon_with_settings(settings: Settings, tempdir: TempDir) -> Resul
 
 /// Internal helper function, which wraps the daemon main logic inside tokio and prints any errors.
 async fn run_and_handle_error(pueue_dir: PathBuf, test: bool) -> Result<()> {
-    if let Err(err) = run(Some(pueue_dir.join("pueue.yml")), None, test).await {
+    if let Err(err) = run(Some(pueue_dir.join("pueue.yml")), None, true, test).await {
         let mut stdout = io::stdout();
         stdout
             .write_all(format!("Entcountered error: {err:?}").as_bytes())
@@ -156,7 +156,7 @@ pub fn daemon_base_setup() -> Result<(Settings, TempDir)> {
         pueue_directory: Some(tempdir_path.to_path_buf()),
         runtime_directory: Some(tempdir_path.to_path_buf()),
         alias_file: Some(tempdir_path.join("pueue_aliases.yml")),
-        host: "localhost".to_string(),
+        host: "localhost".into(),
         port: "51230".to_string(),
         daemon_cert: Some(tempdir_path.join("certs").join("daemon.cert")),
         daemon_key: Some(tempdir_path.join("certs").join("daemon.key")),
@@ -1,11 +1,11 @@
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, Context, Result};
 
 use pueue_lib::network::message::*;
 use pueue_lib::network::protocol::{
-    get_client_stream, receive_bytes, receive_message, send_bytes,
-    send_message as internal_send_message, GenericStream,
+    get_client_stream, handshake, receive_message, send_message as internal_send_message,
+    GenericStream,
 };
-use pueue_lib::network::secret::read_shared_secret;
+use pueue_lib::network::secret::read_handshake_secret;
 use pueue_lib::settings::Shared;
 
 /// This is a small convenience wrapper that sends a message and immediately returns the response.
@@ -29,7 +29,7 @@ where
 /// Create a new stream that already finished the handshake and secret exchange.
 ///
 /// Pueue creates a new socket stream for each command, which is why we do it the same way.
-async fn get_authenticated_stream(shared: &Shared) -> Result<GenericStream> {
+pub async fn get_authenticated_stream(shared: &Shared) -> Result<GenericStream> {
     // Connect to daemon and get stream used for communication.
     let mut stream = match get_client_stream(shared).await {
         Ok(stream) => stream,
@@ -41,18 +41,10 @@ async fn get_authenticated_stream(shared: &Shared) -> Result<GenericStream> {
     // Next we do a handshake with the daemon
     // 1. Client sends the secret to the daemon.
     // 2. If successful, the daemon responds with their version.
-    let secret =
-        read_shared_secret(&shared.shared_secret_path()).context("Couldn't read shared secret.")?;
-    send_bytes(&secret, &mut stream)
+    let secret = read_handshake_secret(shared).context("Couldn't read shared secret.")?;
+    handshake(&mut stream, secret.as_deref())
         .await
-        .context("Failed to send bytes.")?;
-    let version_bytes = receive_bytes(&mut stream)
-        .await
-        .context("Failed sending secret during handshake with daemon.")?;
-
-    if version_bytes.is_empty() {
-        bail!("Daemon went away after sending secret. Did you use the correct secret?")
-    }
+        .context("Failed to do the handshake with the daemon.")?;
 
     Ok(stream)
 }
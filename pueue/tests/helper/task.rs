@@ -16,6 +16,7 @@ pub fn create_add_message<C: ToString>(shared: &Shared, command: C) -> AddMessag
     AddMessage {
         command: command.to_string(),
         path: shared.pueue_directory(),
+        create_workdir: false,
         envs: HashMap::from_iter(vars()),
         start_immediately: false,
         stashed: false,
@@ -24,7 +25,20 @@ pub fn create_add_message<C: ToString>(shared: &Shared, command: C) -> AddMessag
         dependencies: Vec::new(),
         priority: None,
         label: None,
+        user: None,
+        raw_command: None,
+        memory_limit: None,
+        expire_at: None,
+        timeout: None,
+        start_group: false,
+        key: None,
+        replace: false,
+        on_success: None,
+        on_failure: None,
+        success_signals: Vec::new(),
         print_task_id: false,
+        return_task: false,
+        create_group: false,
     }
 }
 
@@ -54,7 +68,11 @@ pub async fn start_tasks(shared: &Shared, tasks: TaskSelection) -> Result<Messag
 
 /// Helper to pause the default group of the daemon
 pub async fn pause_tasks(shared: &Shared, tasks: TaskSelection) -> Result<Message> {
-    let message = PauseMessage { tasks, wait: false };
+    let message = PauseMessage {
+        tasks,
+        wait: false,
+        restash: false,
+    };
 
     send_message(shared, message)
         .await
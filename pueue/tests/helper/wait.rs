@@ -4,6 +4,8 @@
 ///
 /// Using continuous lookups, we can allow long waiting times, while still having fast tests if
 /// things don't take that long.
+use std::path::Path;
+
 use anyhow::{bail, Result};
 
 use pueue_lib::settings::Shared;
@@ -142,3 +144,24 @@ pub async fn wait_for_group_status(
 
     bail!("Group {group} didn't change to state {expected_status:?} after ~1 second",);
 }
+
+/// This is a small helper function, which checks in very short intervals, whether a file has been
+/// written. The file is considered complete as soon as it ends with a newline.
+/// This is necessary for files that're written by asynchronously spawned processes.
+pub async fn wait_for_file_content(path: &Path) -> Result<String> {
+    let sleep = 50;
+    let tries = TIMEOUT / sleep;
+    let mut current_try = 0;
+    while current_try <= tries {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            if content.ends_with('\n') {
+                return Ok(content);
+            }
+        }
+
+        current_try += 1;
+        sleep_ms(sleep).await;
+    }
+
+    bail!("File {path:?} hasn't been written after ~1 second.")
+}
@@ -1,13 +1,20 @@
 use command_group::GroupChild;
 use std::collections::BTreeMap;
+use std::thread::JoinHandle;
 
 /// This structure is needed to manage worker pools for groups.
 /// It's a newtype pattern around a nested BTreeMap, which implements some convenience functions.
 ///
 /// The datastructure represents the following data:
-/// BTreeMap<group_name, BTreeMap<group_worker_id, (task_id, subprocess_handle)>
+/// BTreeMap<group_name, BTreeMap<group_worker_id, (task_id, subprocess_handle, log_writers)>
+///
+/// The log writers are the threads that copy the child's output into its log file, if the output
+/// passes through the daemon. They have to be joined before the task is considered finished.
 #[derive(Debug, Default)]
-pub struct Children(pub BTreeMap<String, BTreeMap<usize, (usize, GroupChild)>>);
+pub struct Children(pub BTreeMap<String, BTreeMap<usize, ChildEntry>>);
+
+/// The task id, the process handle and the log writer threads of a single child.
+pub type ChildEntry = (usize, GroupChild, Vec<JoinHandle<()>>);
 
 impl Children {
     /// Returns whether there are any active tasks across all groups.
@@ -29,7 +36,7 @@ impl Children {
     /// We have to do a nested linear search, as these datastructure aren't indexed via task_ids.
     pub fn has_child(&self, task_id: usize) -> bool {
         for pool in self.0.values() {
-            for (child_task_id, _) in pool.values() {
+            for (child_task_id, _, _) in pool.values() {
                 if child_task_id == &task_id {
                     return true;
                 }
@@ -44,7 +51,7 @@ impl Children {
     /// beceause these datastructure aren't indexed via task_ids.
     pub fn get_child_mut(&mut self, task_id: usize) -> Option<&mut GroupChild> {
         for pool in self.0.values_mut() {
-            for (child_task_id, child) in pool.values_mut() {
+            for (child_task_id, child, _) in pool.values_mut() {
                 if child_task_id == &task_id {
                     return Some(child);
                 }
@@ -58,7 +65,7 @@ impl Children {
     pub fn all_task_ids(&self) -> Vec<usize> {
         let mut task_ids = Vec::new();
         for pool in self.0.values() {
-            for (task_id, _) in pool.values() {
+            for (task_id, _, _) in pool.values() {
                 task_ids.push(*task_id)
             }
         }
@@ -103,12 +110,19 @@ impl Children {
     /// This function should only be called when spawning a new process.
     /// At this point, we're sure that the worker pool for the given group already exists, hence
     /// the expect call.
-    pub fn add_child(&mut self, group: &str, worker_id: usize, task_id: usize, child: GroupChild) {
+    pub fn add_child(
+        &mut self,
+        group: &str,
+        worker_id: usize,
+        task_id: usize,
+        child: GroupChild,
+        log_writers: Vec<JoinHandle<()>>,
+    ) {
         let pool = self
             .0
             .get_mut(group)
             .expect("The worker pool should be initialized when inserting a new child.");
 
-        pool.insert(worker_id, (task_id, child));
+        pool.insert(worker_id, (task_id, child, log_writers));
     }
 }
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+const UNITS: [(&str, u64); 4] = [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60), ("s", 1)];
+
+/// Parse a human readable duration such as `90`, `30m`, `1h30m` or `500ms`.
+///
+/// Plain numbers are interpreted as seconds. Otherwise, every number needs one of the
+/// (case insensitive) units `ms`, `s`, `m`, `h` or `d`.
+/// Returns `None` for invalid or overflowing durations.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut duration = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |index| number_end + index);
+        let amount: u64 = rest[..number_end].parse().ok()?;
+
+        let unit_duration = match rest[number_end..unit_end].to_ascii_lowercase().as_str() {
+            "ms" => Duration::from_millis(amount),
+            unit => {
+                let (_, unit_seconds) = UNITS.iter().find(|(name, _)| *name == unit)?;
+                Duration::from_secs(amount.checked_mul(*unit_seconds)?)
+            }
+        };
+
+        duration = duration.checked_add(unit_duration)?;
+        rest = &rest[unit_end..];
+    }
+
+    Some(duration)
+}
+
+/// Format a duration in the format that's understood by [parse_duration], e.g. `1h30m5s`.
+pub fn format_duration(duration: &Duration) -> String {
+    let mut seconds = duration.as_secs();
+    let mut text = String::new();
+    for (unit, unit_seconds) in UNITS {
+        let amount = seconds / unit_seconds;
+        if amount > 0 {
+            text.push_str(&format!("{amount}{unit}"));
+            seconds %= unit_seconds;
+        }
+    }
+
+    let millis = duration.subsec_millis();
+    if millis > 0 || text.is_empty() {
+        text.push_str(&format!("{millis}ms"));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for (text, expected) in [
+            ("90", Duration::from_secs(90)),
+            ("30m", Duration::from_secs(30 * 60)),
+            ("1h30m", Duration::from_secs(90 * 60)),
+            ("1D2H", Duration::from_secs(26 * 60 * 60)),
+            ("1s500ms", Duration::from_millis(1500)),
+            ("0s", Duration::ZERO),
+        ] {
+            assert_eq!(parse_duration(text), Some(expected), "{text}");
+        }
+
+        for text in [
+            "",
+            "m",
+            "5w",
+            "1h30",
+            "-5m",
+            "1.5h",
+            "99999999999999999999h",
+        ] {
+            assert_eq!(parse_duration(text), None, "{text}");
+        }
+    }
+}
@@ -12,6 +12,12 @@ pub enum Error {
     #[error("{}", .0)]
     Connection(String),
 
+    /// The daemon couldn't be reached before the timeout of
+    /// [Client::connect_with_timeout](crate::network::client::Client::connect_with_timeout)
+    /// elapsed. Contains the error of the last connection attempt.
+    #[error("Couldn't connect to the daemon within {:?}:\n{}", .0, .1)]
+    ConnectionTimeout(std::time::Duration, String),
+
     #[error("Got an empty payload")]
     EmptyPayload,
 
@@ -27,6 +33,10 @@ pub enum Error {
     #[error("Some error occurred. {}", .0)]
     Generic(String),
 
+    /// A `pueue status` query couldn't be parsed.
+    #[error("{}", .0)]
+    InvalidQuery(String),
+
     #[error("I/O error while {}:\n{}", .0, .1)]
     IoError(String, std::io::Error),
 
@@ -2,10 +2,13 @@
 
 /// Shared module for internal logic!
 /// Contains helper for command aliasing.
+#[cfg(feature = "settings")]
 pub mod aliasing;
 /// A helper newtype struct, which implements convenience methods for our child process management
 /// datastructure.
 pub mod children;
+/// Parsing and formatting of human readable durations such as `1h30m`.
+pub mod duration;
 /// Pueue lib's own Error implementation.
 pub mod error;
 /// Helper classes to read and write log files of Pueue's tasks.
@@ -13,11 +16,18 @@ pub mod log;
 pub mod network;
 /// Shared module for internal logic!
 /// Contains helper to spawn shell commands and examine and interact with processes.
+#[cfg(feature = "settings")]
 pub mod process_helper;
+/// The query language of `pueue status`, which filters, orders and limits lists of tasks.
+/// The formal syntax is defined in `query/syntax.pest`.
+#[cfg(feature = "query")]
+pub mod query;
 /// This module contains all platform unspecific default values and helper functions for working
 /// with our setting representation.
+#[cfg(feature = "settings")]
 mod setting_defaults;
 /// Pueue's configuration representation.
+#[cfg(feature = "settings")]
 pub mod settings;
 /// The main struct used to represent the daemon's current state.
 pub mod state;
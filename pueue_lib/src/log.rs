@@ -1,7 +1,9 @@
 use std::fs::{remove_file, File};
 use std::io::{self, prelude::*, Read, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use chrono::Local;
 use log::error;
 use rev_buf_reader::RevBufReader;
 use snap::write::FrameEncoder;
@@ -197,3 +199,359 @@ pub fn seek_to_last_lines(file: &mut File, amount: usize) -> Result<bool, Error>
 
     Ok(false)
 }
+
+/// A writer that truncates each line of a task's output to a maximum amount of bytes. \
+/// Truncated lines are marked with a trailing ellipsis (`…`).
+///
+/// Lines are only cut at character boundaries, so valid UTF-8 output stays valid.
+/// Lines may span several writes, which is why the length of the current line is kept around.
+pub struct LineLengthLimiter<W: Write> {
+    inner: W,
+    max_length: usize,
+    /// The amount of bytes that have been written for the current line.
+    line_length: usize,
+    /// Whether the rest of the current line is being discarded.
+    truncating: bool,
+}
+
+impl<W: Write> LineLengthLimiter<W> {
+    pub fn new(inner: W, max_length: usize) -> Self {
+        Self {
+            inner,
+            max_length,
+            line_length: 0,
+            truncating: false,
+        }
+    }
+}
+
+impl<W: Write> Write for LineLengthLimiter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut output = Vec::with_capacity(buf.len());
+
+        for byte in buf {
+            if *byte == b'\n' {
+                output.push(*byte);
+                self.line_length = 0;
+                self.truncating = false;
+                continue;
+            }
+
+            if self.truncating {
+                continue;
+            }
+
+            // Continuation bytes belong to a character whose leading byte has already been
+            // checked against the limit.
+            if *byte & 0b1100_0000 == 0b1000_0000 {
+                output.push(*byte);
+                self.line_length += 1;
+                continue;
+            }
+
+            // Check whether the whole next character still fits into the line.
+            let char_length = match byte.leading_ones() {
+                2 => 2,
+                3 => 3,
+                4 => 4,
+                _ => 1,
+            };
+            if self.line_length + char_length > self.max_length {
+                output.extend_from_slice("…".as_bytes());
+                self.truncating = true;
+                continue;
+            }
+
+            output.push(*byte);
+            self.line_length += 1;
+        }
+
+        self.inner.write_all(&output)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The format of the timestamps that prefix each line of a task's output, if
+/// `daemon.timestamp_logs` is enabled.
+pub const LOG_TIMESTAMP_FORMAT: &str = "[%Y-%m-%d %H:%M:%S%.3f] ";
+
+/// The shape of a formatted [LOG_TIMESTAMP_FORMAT]. Each `0` stands for an ASCII digit.
+const LOG_TIMESTAMP_TEMPLATE: &[u8] = b"[0000-00-00 00:00:00.000] ";
+
+/// A writer that prefixes each line of a task's output with the time it has been written at.
+/// The format of the timestamps is [LOG_TIMESTAMP_FORMAT].
+///
+/// The timestamp is written as soon as the first byte of a line arrives.
+///
+/// If several outputs are written to the same file, e.g. stdout and stderr, their timestamper
+/// have to share the line state via [LineTimestamper::sharing_line_with]. Otherwise, each of
+/// them would add timestamps in the middle of lines that have been started by the others.
+pub struct LineTimestamper<W: Write> {
+    inner: W,
+    /// Whether the next byte starts a new line. \
+    /// The lock is held while writing, so the writes of timestampers sharing it don't interleave.
+    at_line_start: Arc<Mutex<bool>>,
+}
+
+impl<W: Write> LineTimestamper<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            at_line_start: Arc::new(Mutex::new(true)),
+        }
+    }
+
+    /// Create a timestamper that writes into the same file as `other`.
+    pub fn sharing_line_with<V: Write>(inner: W, other: &LineTimestamper<V>) -> Self {
+        Self {
+            inner,
+            at_line_start: other.at_line_start.clone(),
+        }
+    }
+}
+
+impl<W: Write> Write for LineTimestamper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut output = Vec::with_capacity(buf.len() + LOG_TIMESTAMP_TEMPLATE.len());
+        // All lines of a single write get the same timestamp.
+        let timestamp = Local::now().format(LOG_TIMESTAMP_FORMAT).to_string();
+
+        let mut at_line_start = self
+            .at_line_start
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for byte in buf {
+            if *at_line_start {
+                output.extend_from_slice(timestamp.as_bytes());
+                *at_line_start = false;
+            }
+            output.push(*byte);
+            if *byte == b'\n' {
+                *at_line_start = true;
+            }
+        }
+
+        self.inner.write_all(&output)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that removes the timestamps, which have been added by a [LineTimestamper], from the
+/// start of each line. Lines without a timestamp are passed through as they are.
+///
+/// If `strip` is `false`, everything is passed through.
+///
+/// Timestamps may span several writes, which is why the start of the current line is kept
+/// around until it's clear whether it's a timestamp.
+pub struct TimestampStripper<W: Write> {
+    inner: W,
+    strip: bool,
+    /// Whether we're still at the start of a line, i.e. the line might begin with a timestamp.
+    at_line_start: bool,
+    /// The start of the current line, which matched a timestamp so far.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> TimestampStripper<W> {
+    pub fn new(inner: W, strip: bool) -> Self {
+        Self {
+            inner,
+            strip,
+            at_line_start: true,
+            pending: Vec::with_capacity(LOG_TIMESTAMP_TEMPLATE.len()),
+        }
+    }
+}
+
+impl<W: Write> Write for TimestampStripper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.strip {
+            return self.inner.write(buf);
+        }
+
+        let mut output = Vec::with_capacity(buf.len());
+        for byte in buf {
+            if !self.at_line_start {
+                output.push(*byte);
+                self.at_line_start = *byte == b'\n';
+                continue;
+            }
+
+            // Check whether the start of the line still looks like a timestamp.
+            let expected = LOG_TIMESTAMP_TEMPLATE[self.pending.len()];
+            let matches = if expected == b'0' {
+                byte.is_ascii_digit()
+            } else {
+                *byte == expected
+            };
+            self.pending.push(*byte);
+
+            if !matches {
+                // This isn't a timestamp, pass the line's start through.
+                output.append(&mut self.pending);
+                self.at_line_start = *byte == b'\n';
+            } else if self.pending.len() == LOG_TIMESTAMP_TEMPLATE.len() {
+                // A full timestamp, drop it.
+                self.pending.clear();
+                self.at_line_start = false;
+            }
+        }
+
+        self.inner.write_all(&output)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for TimestampStripper<W> {
+    /// The output ended in the middle of something that looked like a timestamp.
+    /// Make sure it isn't lost.
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            let _ = self.inner.write_all(&self.pending);
+            let _ = self.inner.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn limit(input: &[&str], max_length: usize) -> String {
+        let mut output = Vec::new();
+        {
+            let mut limiter = LineLengthLimiter::new(&mut output, max_length);
+            for chunk in input {
+                limiter.write_all(chunk.as_bytes()).unwrap();
+            }
+        }
+
+        String::from_utf8(output).expect("Output should be valid UTF-8")
+    }
+
+    #[test]
+    fn truncate_long_lines() {
+        assert_eq!(limit(&["abcdefgh\nabc\n"], 5), "abcde…\nabc\n");
+        assert_eq!(limit(&["abcde\n"], 5), "abcde\n");
+    }
+
+    #[test]
+    fn truncate_lines_across_writes() {
+        assert_eq!(limit(&["abc", "def", "gh\nab", "c\n"], 5), "abcde…\nabc\n");
+    }
+
+    #[test]
+    fn truncate_at_character_boundary() {
+        // `ä` takes two bytes. Cutting after 4 bytes would split the third character.
+        assert_eq!(limit(&["ääää\n"], 5), "ää…\n");
+        assert_eq!(limit(&["ää", "ää\n"], 4), "ää…\n");
+    }
+
+    fn strip(input: &[&str]) -> String {
+        let mut output = Vec::new();
+        {
+            let mut stripper = TimestampStripper::new(&mut output, true);
+            for chunk in input {
+                stripper.write_all(chunk.as_bytes()).unwrap();
+            }
+        }
+
+        String::from_utf8(output).expect("Output should be valid UTF-8")
+    }
+
+    #[test]
+    fn timestamp_each_line() {
+        let mut output = Vec::new();
+        {
+            let mut timestamper = LineTimestamper::new(&mut output);
+            timestamper.write_all(b"first\nsec").unwrap();
+            timestamper.write_all(b"ond\n").unwrap();
+        }
+        let output = String::from_utf8(output).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, text) in lines.iter().zip(["first", "second"]) {
+            let (timestamp, rest) = line.split_at(LOG_TIMESTAMP_TEMPLATE.len());
+            assert_eq!(rest, text);
+            assert_eq!(strip(&[timestamp]), "", "Invalid timestamp {timestamp}");
+        }
+    }
+
+    /// A writer that appends to a buffer, which is shared with other writers.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn timestamp_shared_lines() {
+        let buffer = SharedBuffer::default();
+        let mut stdout = LineTimestamper::new(buffer.clone());
+        let mut stderr = LineTimestamper::sharing_line_with(buffer.clone(), &stdout);
+
+        // The line that has been started via stdout is continued via stderr.
+        stdout.write_all(b"first ").unwrap();
+        stderr.write_all(b"line\nsecond").unwrap();
+        stdout.write_all(b" line\n").unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(strip(&[&output]), "first line\nsecond line\n");
+        for line in output.lines() {
+            assert_eq!(line.matches("] ").count(), 1, "Unexpected line {line}");
+        }
+    }
+
+    #[test]
+    fn strip_timestamps() {
+        let input = "[2024-01-10 10:00:00.123] first\n[2024-01-10 10:00:01.000] second\n";
+        assert_eq!(strip(&[input]), "first\nsecond\n");
+    }
+
+    #[test]
+    fn strip_timestamps_across_writes() {
+        assert_eq!(
+            strip(&[
+                "[2024-01-10 10:0",
+                "0:00.123] fir",
+                "st\n[2024",
+                "-01-10 10:00:01.000] x"
+            ]),
+            "first\nx"
+        );
+    }
+
+    #[test]
+    fn keep_lines_without_timestamps() {
+        assert_eq!(
+            strip(&["[info] text\n", "\n", "[2024-01"]),
+            "[info] text\n\n[2024-01"
+        );
+    }
+}
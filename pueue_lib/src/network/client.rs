@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use log::debug;
+use tokio::time::{sleep, timeout, Instant};
+
+use crate::error::Error;
+use crate::network::message::*;
+use crate::network::protocol::*;
+use crate::network::secret::read_handshake_secret;
+use crate::network::stream::LogStream;
+use crate::settings::Shared;
+use crate::state::State;
+use crate::task::{TaskStatus, WaitTargetStatus};
+
+/// The interval in which [Client::wait_for] polls the daemon for the task's status.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A minimal client for library users, which takes care of the connection and handshake with
+/// the daemon and provides convenience functions for common requests.
+pub struct Client {
+    stream: GenericStream,
+    daemon_version: String,
+}
+
+impl Client {
+    /// Connect to the daemon and do the handshake.
+    ///
+    /// The secret is skipped for trusted unix sockets, just like in the `pueue` client.
+    /// Afterwards, the client's capabilities are negotiated with the daemon, such as the
+    /// compression of large messages.
+    pub async fn connect(shared: &Shared) -> Result<Self, Error> {
+        let (stream, daemon_version) = connect_and_handshake(shared).await?;
+
+        // Daemons that don't know about capabilities close the connection after our offer.
+        // Reconnect without negotiating in that case.
+        let stream = match offer_capabilities(stream).await? {
+            Some(stream) => stream,
+            None => {
+                debug!("Daemon doesn't support capabilities, reconnecting without them");
+                connect_and_handshake(shared).await?.0
+            }
+        };
+
+        Ok(Client {
+            stream,
+            daemon_version,
+        })
+    }
+
+    /// Connect to the daemon just like [Client::connect], but retry failed attempts every
+    /// `retry_interval` until the `timeout` elapsed.
+    ///
+    /// This is useful, if the daemon might still be starting up, e.g. right after a script
+    /// spawned it. If no attempt succeeds in time, an [Error::ConnectionTimeout] is returned.
+    pub async fn connect_with_timeout(
+        shared: &Shared,
+        timeout_after: Duration,
+        retry_interval: Duration,
+    ) -> Result<Self, Error> {
+        let mut last_error = None;
+        let attempts = async {
+            loop {
+                match Client::connect(shared).await {
+                    Ok(client) => return client,
+                    Err(error) => {
+                        debug!("Failed to connect to the daemon, retrying: {error}");
+                        last_error = Some(error);
+                    }
+                }
+                sleep(retry_interval).await;
+            }
+        };
+
+        let result = timeout(timeout_after, attempts).await;
+        match result {
+            Ok(client) => Ok(client),
+            Err(_) => Err(Error::ConnectionTimeout(
+                timeout_after,
+                last_error.map_or_else(
+                    || "No connection attempt finished in time".to_string(),
+                    |error| error.to_string(),
+                ),
+            )),
+        }
+    }
+
+    /// The version of the daemon we're connected to.
+    pub fn daemon_version(&self) -> &str {
+        &self.daemon_version
+    }
+
+    /// Send a message to the daemon and receive its response.
+    pub async fn send<T: Into<Message>>(&mut self, message: T) -> Result<Message, Error> {
+        send_message(message, &mut self.stream).await?;
+        receive_message(&mut self.stream).await
+    }
+
+    /// Add a new task and return its id.
+    ///
+    /// ```no_run
+    /// use pueue_lib::network::client::Client;
+    /// use pueue_lib::network::message::AddMessage;
+    /// use pueue_lib::settings::Settings;
+    ///
+    /// # async fn add() -> Result<(), pueue_lib::error::Error> {
+    /// let (settings, _) = Settings::read(&None)?;
+    /// let mut client = Client::connect(&settings.shared).await?;
+    ///
+    /// let task = AddMessage::builder("cargo build --release")
+    ///     .path("/tmp/project")
+    ///     .group("build")
+    ///     .label("release");
+    /// let task_id = client.add_task(task).await?;
+    /// println!("Added task {task_id}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_task(&mut self, builder: AddMessageBuilder) -> Result<usize, Error> {
+        let message = builder.return_task(true).build();
+
+        match self.send(message).await? {
+            Message::AddResponse(task) => Ok(task.id),
+            Message::GroupNotFound(message) => Err(Error::Generic(format!(
+                "Group \"{}\" doesn't exist. Existing groups: {}",
+                message.group,
+                message.existing_groups.join(", ")
+            ))),
+            other => Err(unexpected_response("an add response", other)),
+        }
+    }
+
+    /// Get the current state of the daemon, including all tasks and groups.
+    pub async fn status(&mut self) -> Result<State, Error> {
+        match self.send(Message::Status).await? {
+            Message::StatusResponse(state) => Ok(*state),
+            other => Err(unexpected_response("a status response", other)),
+        }
+    }
+
+    /// Kill the given tasks.
+    ///
+    /// Killed groups aren't paused, which means that their queued tasks are started afterwards.
+    pub async fn kill(&mut self, tasks: TaskSelection) -> Result<(), Error> {
+        let message = KillMessage {
+            tasks,
+            signal: None,
+            pause_groups: false,
+        };
+
+        match self.send(message).await? {
+            Message::Success(_) => Ok(()),
+            other => Err(unexpected_response("a success response", other)),
+        }
+    }
+
+    /// Get the logs of the given tasks.
+    ///
+    /// `lines` limits the output to the last X lines of each task, unless `full` is set.
+    ///
+    /// The logs are always sent by the daemon, independent of `client.read_local_logs`, as
+    /// library users cannot be expected to share a file system with the daemon.
+    /// The output of each [TaskLogMessage] is compressed with `snap`.
+    pub async fn get_log(
+        &mut self,
+        task_ids: Vec<usize>,
+        lines: Option<usize>,
+        full: bool,
+    ) -> Result<BTreeMap<usize, TaskLogMessage>, Error> {
+        let message = LogRequestMessage {
+            tasks: TaskSelection::TaskIds(task_ids),
+            send_logs: true,
+            lines: if full { None } else { lines },
+        };
+
+        match self.send(message).await? {
+            Message::LogResponse(logs) => Ok(logs),
+            other => Err(unexpected_response("a log response", other)),
+        }
+    }
+
+    /// Follow the output of a task, i.e. the library equivalent of `pueue follow`.
+    ///
+    /// The client is consumed, as its connection is used for the stream.
+    /// Use [LogStream::next_chunk] to receive the output until the task finishes.
+    ///
+    /// Only tasks that have already been started can be followed.
+    /// An error is returned, if the task doesn't exist or is still queued.
+    pub async fn follow_log(mut self, task_id: usize) -> Result<LogStream, Error> {
+        let state = self.status().await?;
+        let Some(task) = state.tasks.get(&task_id) else {
+            return Err(Error::Generic(format!("Task {task_id} doesn't exist")));
+        };
+        if !task.is_running() && !task.is_done() {
+            return Err(Error::Generic(format!(
+                "Task {task_id} hasn't been started yet"
+            )));
+        }
+
+        let message = StreamRequestMessage {
+            task_id: Some(task_id),
+            lines: None,
+            offset: None,
+        };
+        LogStream::start(self.stream, message).await
+    }
+
+    /// Wait until the task with the given id reached the target status and return its status.
+    ///
+    /// The daemon's state is polled until either
+    /// - the task reached the target status (or a status that can only occur after it),
+    /// - the task finished without reaching the target status,
+    /// - the task has been removed,
+    /// - or the timeout elapsed.
+    ///
+    /// All cases but the first one result in an error.
+    pub async fn wait_for(
+        &mut self,
+        task_id: usize,
+        target: WaitTargetStatus,
+        timeout: Duration,
+    ) -> Result<TaskStatus, Error> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let state = self.status().await?;
+
+            let Some(task) = state.tasks.get(&task_id) else {
+                return Err(Error::Generic(format!("Task {task_id} doesn't exist")));
+            };
+
+            if target.is_reached_by(task) {
+                return Ok(task.status.clone());
+            }
+
+            // Finished tasks won't change their status anymore.
+            if task.is_done() {
+                return Err(Error::Generic(format!(
+                    "Task {task_id} finished without reaching the target status {target}"
+                )));
+            }
+
+            if Instant::now() >= deadline {
+                return Err(Error::Generic(format!(
+                    "Timed out while waiting for task {task_id} to reach status {target}"
+                )));
+            }
+
+            sleep(WAIT_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+        }
+    }
+}
+
+/// Connect to the daemon, send the secret and receive the daemon's version.
+async fn connect_and_handshake(shared: &Shared) -> Result<(GenericStream, String), Error> {
+    let mut stream = get_client_stream(shared).await?;
+    let secret = read_handshake_secret(shared)?;
+    let daemon_version = handshake(&mut stream, secret.as_deref()).await?;
+
+    Ok((stream, daemon_version))
+}
+
+/// Turn a response of the wrong type into an error.
+/// Failures of the daemon are passed on as they are.
+fn unexpected_response(expected: &str, message: Message) -> Error {
+    match message {
+        Message::Failure(text) => Error::Generic(text),
+        other => Error::Generic(format!("Expected {expected}, but got {other:?}")),
+    }
+}
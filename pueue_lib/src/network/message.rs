@@ -1,12 +1,13 @@
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 
-use crate::state::{Group, State};
-use crate::task::Task;
+use crate::state::{Group, State, PUEUE_DEFAULT_GROUP};
+use crate::task::{Task, TaskResult, TaskStatus};
 
 /// Macro to simplify creating [From] implementations for each variant-contained
 /// struct; e.g. `impl_into_message!(AddMessage, Message::Add)` to make it possible
@@ -42,10 +43,15 @@ macro_rules! failure_msg {
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub enum Message {
     Add(AddMessage),
+    /// The created task, which is returned instead of a text, if the client requested it via
+    /// [AddMessage::return_task].
+    AddResponse(Box<Task>),
     Remove(Vec<usize>),
     Switch(SwitchMessage),
     Stash(StashMessage),
     Enqueue(EnqueueMessage),
+    /// Remove the scheduled enqueue time of stashed tasks.
+    Unschedule(UnscheduleMessage),
 
     Start(StartMessage),
     Restart(RestartMessage),
@@ -68,6 +74,9 @@ pub enum Message {
 
     Env(EnvMessage),
 
+    /// Attach a note to a task or remove it.
+    Annotate(AnnotateMessage),
+
     Group(GroupMessage),
     GroupResponse(GroupResponseMessage),
 
@@ -79,7 +88,11 @@ pub enum Message {
     /// The client requests a continuous stream of a task's log.
     StreamRequest(StreamRequestMessage),
     /// The next chunk of output, that's send to the client.
-    Stream(String),
+    Stream(StreamResponseMessage),
+
+    /// Request the history of finished tasks, including tasks that have already been cleaned.
+    History(HistoryRequestMessage),
+    HistoryResponse(Vec<HistoryEntry>),
 
     Reset(ResetMessage),
     Clean(CleanMessage),
@@ -87,11 +100,23 @@ pub enum Message {
 
     Success(String),
     Failure(String),
+    /// A task has been added to a group that doesn't exist, while
+    /// [AddMessage::create_group] wasn't set.
+    GroupNotFound(GroupNotFoundMessage),
     /// Simply notify the client that the connection is now closed.
     /// This is used to, for instance, close a `follow` stream if the task finished.
+    /// It's always the last message of a log stream that ended regularly, which allows clients
+    /// to distinguish the end of a stream from a broken connection.
     Close,
 
     Parallel(ParallelMessage),
+
+    /// Execute multiple messages in a single round-trip.
+    /// The messages are executed in order. Batches cannot be nested and must not contain
+    /// messages that require special connection handling, such as `StreamRequest`.
+    Batch(Vec<Message>),
+    /// The responses to all messages of a `Batch`, in the same order.
+    BatchResponse(Vec<Message>),
 }
 
 /// This enum is used to express a selection of tasks.
@@ -108,6 +133,8 @@ pub enum TaskSelection {
 pub struct AddMessage {
     pub command: String,
     pub path: PathBuf,
+    /// Create the working directory right before the task is started, if it doesn't exist yet.
+    pub create_workdir: bool,
     pub envs: HashMap<String, String>,
     pub start_immediately: bool,
     pub stashed: bool,
@@ -116,7 +143,35 @@ pub struct AddMessage {
     pub dependencies: Vec<usize>,
     pub priority: Option<i32>,
     pub label: Option<String>,
+    /// Run the task as this user instead of the daemon's user (Unix only).
+    pub user: Option<String>,
+    /// Execute this program with the given arguments directly, without wrapping it in a shell.
+    pub raw_command: Option<Vec<String>>,
+    /// The maximum amount of memory in bytes the task may use (Linux only).
+    pub memory_limit: Option<u64>,
+    /// Don't start the task anymore after this point in time.
+    pub expire_at: Option<DateTime<Local>>,
+    /// Kill the task, if it runs for longer than this.
+    pub timeout: Option<Duration>,
+    /// Resume the task's group, in case it's paused.
+    pub start_group: bool,
+    /// Create the task's group, if it doesn't exist yet.
+    /// Otherwise, adding a task to an unknown group fails with [Message::GroupNotFound].
+    pub create_group: bool,
+    /// A unique key for this task. Adding another task with the same key fails, unless
+    /// `replace` is set.
+    pub key: Option<String>,
+    /// Replace an existing task with the same key. The new task takes over its id.
+    pub replace: bool,
+    /// A command that's executed after the task finished successfully.
+    pub on_success: Option<String>,
+    /// A command that's executed after the task failed.
+    pub on_failure: Option<String>,
+    /// Treat the termination of the task by one of these signals as a success (Unix only).
+    pub success_signals: Vec<Signal>,
     pub print_task_id: bool,
+    /// Respond with the created task via [Message::AddResponse] instead of a text.
+    pub return_task: bool,
 }
 
 /// We use a custom `Debug` implementation for [AddMessage], as the `envs` field just has
@@ -129,6 +184,7 @@ impl std::fmt::Debug for AddMessage {
         f.debug_struct("Task")
             .field("command", &self.command)
             .field("path", &self.path)
+            .field("create_workdir", &self.create_workdir)
             .field("envs", &"hidden")
             .field("start_immediately", &self.start_immediately)
             .field("stashed", &self.stashed)
@@ -136,13 +192,222 @@ impl std::fmt::Debug for AddMessage {
             .field("enqueue_at", &self.enqueue_at)
             .field("dependencies", &self.dependencies)
             .field("label", &self.label)
+            .field("user", &self.user)
+            .field("raw_command", &self.raw_command)
+            .field("memory_limit", &self.memory_limit)
+            .field("expire_at", &self.expire_at)
+            .field("timeout", &self.timeout)
+            .field("start_group", &self.start_group)
+            .field("key", &self.key)
+            .field("replace", &self.replace)
+            .field("on_success", &self.on_success)
+            .field("on_failure", &self.on_failure)
+            .field("success_signals", &self.success_signals)
             .field("print_task_id", &self.print_task_id)
+            .field("return_task", &self.return_task)
+            .field("create_group", &self.create_group)
             .finish()
     }
 }
 
 impl_into_message!(AddMessage, Message::Add);
 
+impl AddMessage {
+    /// Start building a new [AddMessage] for the given command.
+    ///
+    /// This is the preferred way for third-party tools to create tasks, as it doesn't break
+    /// whenever a new field is added to [AddMessage].
+    pub fn builder(command: impl ToString) -> AddMessageBuilder {
+        AddMessageBuilder::new(command)
+    }
+}
+
+/// Builder for [AddMessage].
+///
+/// By default, the task is added to the default group and runs in the current working
+/// directory with the current environment variables.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use pueue_lib::network::message::AddMessage;
+///
+/// let message = AddMessage::builder("./backup.sh")
+///     .path("/srv/backups")
+///     .group("maintenance")
+///     .label("nightly")
+///     .env("BACKUP_TARGET", "s3")
+///     .timeout(Duration::from_secs(3600))
+///     .build();
+///
+/// assert_eq!(message.group, "maintenance");
+/// assert_eq!(message.label.as_deref(), Some("nightly"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AddMessageBuilder {
+    message: AddMessage,
+}
+
+impl AddMessageBuilder {
+    pub fn new(command: impl ToString) -> Self {
+        AddMessageBuilder {
+            message: AddMessage {
+                command: command.to_string(),
+                path: std::env::current_dir().unwrap_or_default(),
+                envs: std::env::vars().collect(),
+                group: PUEUE_DEFAULT_GROUP.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// The working directory of the task.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.message.path = path.into();
+        self
+    }
+
+    /// Create the working directory right before the task is started, if it doesn't exist yet.
+    pub fn create_workdir(mut self, create_workdir: bool) -> Self {
+        self.message.create_workdir = create_workdir;
+        self
+    }
+
+    /// Replace all environment variables of the task.
+    pub fn envs(mut self, envs: HashMap<String, String>) -> Self {
+        self.message.envs = envs;
+        self
+    }
+
+    /// Set a single environment variable for the task.
+    pub fn env(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.message.envs.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn start_immediately(mut self, start_immediately: bool) -> Self {
+        self.message.start_immediately = start_immediately;
+        self
+    }
+
+    pub fn stashed(mut self, stashed: bool) -> Self {
+        self.message.stashed = stashed;
+        self
+    }
+
+    /// Resume the task's group when adding the task, in case it's paused.
+    pub fn start_group(mut self, start_group: bool) -> Self {
+        self.message.start_group = start_group;
+        self
+    }
+
+    pub fn group(mut self, group: impl ToString) -> Self {
+        self.message.group = group.to_string();
+        self
+    }
+
+    pub fn enqueue_at(mut self, enqueue_at: DateTime<Local>) -> Self {
+        self.message.enqueue_at = Some(enqueue_at);
+        self
+    }
+
+    pub fn dependencies(mut self, dependencies: Vec<usize>) -> Self {
+        self.message.dependencies = dependencies;
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.message.priority = Some(priority);
+        self
+    }
+
+    pub fn label(mut self, label: impl ToString) -> Self {
+        self.message.label = Some(label.to_string());
+        self
+    }
+
+    /// Run the task as this user instead of the daemon's user (Unix only).
+    pub fn user(mut self, user: impl ToString) -> Self {
+        self.message.user = Some(user.to_string());
+        self
+    }
+
+    /// Execute this program with the given arguments directly, without wrapping it in a shell.
+    pub fn raw_command(mut self, args: Vec<String>) -> Self {
+        self.message.raw_command = Some(args);
+        self
+    }
+
+    /// The maximum amount of memory in bytes the task may use (Linux only).
+    pub fn memory_limit(mut self, memory_limit: u64) -> Self {
+        self.message.memory_limit = Some(memory_limit);
+        self
+    }
+
+    /// Don't start the task anymore after this point in time.
+    pub fn expire_at(mut self, expire_at: DateTime<Local>) -> Self {
+        self.message.expire_at = Some(expire_at);
+        self
+    }
+
+    /// Kill the task, if it runs for longer than this.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.message.timeout = Some(timeout);
+        self
+    }
+
+    /// A unique key that identifies the task across repeated adds.
+    pub fn key(mut self, key: impl ToString) -> Self {
+        self.message.key = Some(key.to_string());
+        self
+    }
+
+    /// Replace an existing task with the same key, instead of failing.
+    pub fn replace(mut self, replace: bool) -> Self {
+        self.message.replace = replace;
+        self
+    }
+
+    /// A command that's executed after the task finished successfully.
+    pub fn on_success(mut self, command: impl ToString) -> Self {
+        self.message.on_success = Some(command.to_string());
+        self
+    }
+
+    /// A command that's executed after the task failed.
+    pub fn on_failure(mut self, command: impl ToString) -> Self {
+        self.message.on_failure = Some(command.to_string());
+        self
+    }
+
+    /// Create the task's group, if it doesn't exist yet.
+    pub fn create_group(mut self, create_group: bool) -> Self {
+        self.message.create_group = create_group;
+        self
+    }
+
+    /// Treat the termination of the task by this signal as a success (Unix only).
+    pub fn success_on_signal(mut self, signal: Signal) -> Self {
+        self.message.success_signals.push(signal);
+        self
+    }
+
+    pub fn print_task_id(mut self, print_task_id: bool) -> Self {
+        self.message.print_task_id = print_task_id;
+        self
+    }
+
+    /// Respond with the created task instead of a text.
+    pub fn return_task(mut self, return_task: bool) -> Self {
+        self.message.return_task = return_task;
+        self
+    }
+
+    pub fn build(self) -> AddMessage {
+        self.message
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct SwitchMessage {
     pub task_id_1: usize,
@@ -163,10 +428,20 @@ impl_into_message!(StashMessage, Message::Stash);
 pub struct EnqueueMessage {
     pub tasks: TaskSelection,
     pub enqueue_at: Option<DateTime<Local>>,
+    /// Start the tasks right away, regardless of the group's parallel limit.
+    /// This is ignored, if `enqueue_at` is set.
+    pub start_immediately: bool,
 }
 
 impl_into_message!(EnqueueMessage, Message::Enqueue);
 
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct UnscheduleMessage {
+    pub task_ids: Vec<usize>,
+}
+
+impl_into_message!(UnscheduleMessage, Message::Unschedule);
+
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct StartMessage {
     pub tasks: TaskSelection,
@@ -202,6 +477,10 @@ pub struct TaskToRestart {
 pub struct PauseMessage {
     pub tasks: TaskSelection,
     pub wait: bool,
+    /// Kill the running and paused tasks and move them back to the `Stashed` state, instead of
+    /// pausing them.
+    #[serde(default)]
+    pub restash: bool,
 }
 
 impl_into_message!(PauseMessage, Message::Pause);
@@ -230,13 +509,15 @@ pub enum Signal {
 pub struct KillMessage {
     pub tasks: TaskSelection,
     pub signal: Option<Signal>,
+    /// Whether groups with further queued tasks should be paused when they're killed as a whole.
+    pub pause_groups: bool,
 }
 
 impl_into_message!(KillMessage, Message::Kill);
 
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct SendMessage {
-    pub task_id: usize,
+    pub task_ids: Vec<usize>,
     pub input: String,
 }
 
@@ -266,7 +547,13 @@ impl From<&Task> for EditableTask {
 
 impl EditableTask {
     /// Merge a [EditableTask] back into a [Task].
+    ///
+    /// The raw command of a task is dropped, if its command has been changed.
+    /// The edited command is then executed via the shell like any other command.
     pub fn into_task(self, task: &mut Task) {
+        if task.command != self.command {
+            task.raw_command = None;
+        }
         task.command = self.command;
         task.path = self.path;
         task.label = self.label;
@@ -289,14 +576,26 @@ pub enum EnvMessage {
 
 impl_into_message!(EnvMessage, Message::Env);
 
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct AnnotateMessage {
+    pub task_id: usize,
+    /// The new note of the task. `None` removes an existing note.
+    pub note: Option<String>,
+}
+
+impl_into_message!(AnnotateMessage, Message::Annotate);
+
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub enum GroupMessage {
     Add {
         name: String,
         parallel_tasks: Option<usize>,
+        description: Option<String>,
     },
     Remove(String),
     List,
+    /// Set the amount of parallel tasks for groups that're created from now on.
+    SetDefaultParallel(usize),
 }
 
 impl_into_message!(GroupMessage, Message::Group);
@@ -304,6 +603,21 @@ impl_into_message!(GroupMessage, Message::Group);
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct GroupResponseMessage {
     pub groups: BTreeMap<String, Group>,
+    /// The recent throughput of each group.
+    #[serde(default)]
+    pub stats: BTreeMap<String, GroupStats>,
+}
+
+/// Throughput statistics of a group, computed from the tasks that finished inside the
+/// daemon's configured time window.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct GroupStats {
+    /// The size of the time window in seconds.
+    pub window: u64,
+    /// The amount of tasks that finished inside the time window.
+    pub finished_tasks: usize,
+    /// The average runtime of those tasks in milliseconds.
+    pub average_runtime: Option<u64>,
 }
 
 impl_into_message!(GroupResponseMessage, Message::GroupResponse);
@@ -332,6 +646,49 @@ pub struct CleanMessage {
 
 impl_into_message!(CleanMessage, Message::Clean);
 
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct HistoryRequestMessage {
+    /// Only return entries of this group.
+    pub group: Option<String>,
+}
+
+impl_into_message!(HistoryRequestMessage, Message::History);
+
+/// A single command that has been executed by the daemon.
+///
+/// These entries are persisted when finished tasks are cleaned, so they can be looked up and
+/// re-run later on.
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    pub command: String,
+    pub path: PathBuf,
+    pub group: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub result: TaskResult,
+}
+
+impl HistoryEntry {
+    /// Create a history entry from a task. Returns `None` if the task didn't finish yet.
+    pub fn from_task(task: &Task) -> Option<Self> {
+        let TaskStatus::Done {
+            start, end, result, ..
+        } = &task.status
+        else {
+            return None;
+        };
+
+        Some(HistoryEntry {
+            command: task.original_command.clone(),
+            path: task.path.clone(),
+            group: task.group.clone(),
+            start: *start,
+            end: *end,
+            result: result.clone(),
+        })
+    }
+}
+
 /// Determines which type of shutdown we're dealing with.
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub enum Shutdown {
@@ -347,10 +704,28 @@ impl_into_message!(Shutdown, Message::DaemonShutdown);
 pub struct StreamRequestMessage {
     pub task_id: Option<usize>,
     pub lines: Option<usize>,
+    /// Start streaming at this byte offset of the task's log file. \
+    /// This takes precedence over `lines` and allows to continue an interrupted stream without
+    /// receiving any output twice.
+    #[serde(default)]
+    pub offset: Option<u64>,
 }
 
 impl_into_message!(StreamRequestMessage, Message::StreamRequest);
 
+/// A chunk of a task's log output, including its position in the log file.
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct StreamResponseMessage {
+    /// The byte offset at which this chunk starts in the task's log file.
+    pub offset: u64,
+    /// The byte offset right after this chunk.
+    /// Request a stream from this offset to continue where this chunk ended.
+    pub next_offset: u64,
+    pub text: String,
+}
+
+impl_into_message!(StreamResponseMessage, Message::Stream);
+
 /// Request logs for specific tasks.
 ///
 /// `tasks` specifies the requested tasks.
@@ -386,6 +761,14 @@ impl std::fmt::Debug for TaskLogMessage {
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+pub struct GroupNotFoundMessage {
+    pub group: String,
+    pub existing_groups: Vec<String>,
+}
+
+impl_into_message!(GroupNotFoundMessage, Message::GroupNotFound);
+
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct ParallelMessage {
     pub parallel_tasks: usize,
@@ -401,3 +784,88 @@ pub fn create_success_message<T: ToString>(text: T) -> Message {
 pub fn create_failure_message<T: ToString>(text: T) -> Message {
     Message::Failure(text.to_string())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add_message_builder_defaults() {
+        let message = AddMessage::builder("ls").build();
+
+        assert_eq!(message.command, "ls");
+        assert_eq!(message.group, PUEUE_DEFAULT_GROUP);
+        assert_eq!(message.path, std::env::current_dir().unwrap());
+        assert!(!message.create_workdir);
+        assert_eq!(message.envs, std::env::vars().collect());
+        assert!(!message.stashed);
+        assert!(!message.start_immediately);
+        assert_eq!(message.enqueue_at, None);
+        assert!(message.dependencies.is_empty());
+        assert_eq!(message.priority, None);
+        assert_eq!(message.label, None);
+        assert_eq!(message.memory_limit, None);
+        assert_eq!(message.timeout, None);
+    }
+
+    #[test]
+    fn test_add_message_builder_all_fields() {
+        let enqueue_at = Local::now();
+        let message = AddMessage::builder("sleep 60")
+            .path("/tmp")
+            .create_workdir(true)
+            .envs(HashMap::new())
+            .env("FOO", "bar")
+            .start_immediately(true)
+            .stashed(true)
+            .group("test")
+            .enqueue_at(enqueue_at)
+            .dependencies(vec![0, 1])
+            .priority(3)
+            .label("label")
+            .user("nobody")
+            .raw_command(vec!["sleep".to_string(), "60".to_string()])
+            .memory_limit(1024)
+            .expire_at(enqueue_at)
+            .timeout(Duration::from_secs(60))
+            .start_group(true)
+            .key("key")
+            .replace(true)
+            .on_success("echo success")
+            .on_failure("echo failure")
+            .success_on_signal(Signal::SigTerm)
+            .print_task_id(true)
+            .return_task(true)
+            .create_group(true)
+            .build();
+
+        let expected = AddMessage {
+            command: "sleep 60".to_string(),
+            path: PathBuf::from("/tmp"),
+            create_workdir: true,
+            envs: HashMap::from([("FOO".to_string(), "bar".to_string())]),
+            start_immediately: true,
+            stashed: true,
+            group: "test".to_string(),
+            enqueue_at: Some(enqueue_at),
+            dependencies: vec![0, 1],
+            priority: Some(3),
+            label: Some("label".to_string()),
+            user: Some("nobody".to_string()),
+            raw_command: Some(vec!["sleep".to_string(), "60".to_string()]),
+            memory_limit: Some(1024),
+            expire_at: Some(enqueue_at),
+            timeout: Some(Duration::from_secs(60)),
+            start_group: true,
+            key: Some("key".to_string()),
+            replace: true,
+            on_success: Some("echo success".to_string()),
+            on_failure: Some("echo failure".to_string()),
+            success_signals: vec![Signal::SigTerm],
+            print_task_id: true,
+            return_task: true,
+            create_group: true,
+        };
+        assert_eq!(message, expected);
+    }
+}
@@ -25,6 +25,10 @@
 //!
 //! The serialization/deserialization format that's used by `pueue_lib` is `cbor`.
 //!
+//! Connecting to the daemon via [get_client_stream](crate::network::socket::get_client_stream)
+//! requires the `settings` feature, as the socket is determined by the configuration.
+//! Without it, the protocol can still be used on top of any [Stream](crate::network::socket::Stream).
+//!
 //! ## Protocol
 //!
 //! Before the real data exchange starts, a simple handshake + authorization is done
@@ -36,6 +40,9 @@
 //! - Connect to socket.
 //! - Send the secret's bytes.
 //! - Receive the daemon's version (utf-8 encoded), which is sent if the secret was correct.
+//! - Optionally offer the client's capabilities, such as compression of large messages, via
+//!   [offer_capabilities](crate::network::protocol::offer_capabilities).
+//!   Daemons that don't support this respond with a failure and close the connection.
 //! - Send the actual message.
 //! - Receive the daemon's response.
 //!
@@ -46,9 +53,14 @@
 //! work like a stream.
 //! I.e. the daemon continuously sends new messages with the new log output until
 //! the socket is closed by the client.
+//! The [LogStream](crate::network::stream::LogStream) takes care of this for you.
 
 /// Used by the daemon to initialize the TLS certificates.
+#[cfg(feature = "settings")]
 pub mod certificate;
+/// A minimal client, which handles the handshake and wraps common requests.
+#[cfg(feature = "settings")]
+pub mod client;
 /// This contains the main [Message](message::Message) enum and all its structs used to
 /// communicate with the daemon or client.
 pub mod message;
@@ -58,5 +70,8 @@ pub mod protocol;
 pub mod secret;
 /// Low-level socket handling code.
 pub mod socket;
+/// A handle for streaming a task's log output, which can be cancelled at any time.
+pub mod stream;
 /// Helper functions for reading and handling TLS files.
+#[cfg(feature = "settings")]
 mod tls;
@@ -1,10 +1,14 @@
-use std::io::Cursor;
+use std::io::{Cursor, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use log::debug;
+use log::{debug, info, warn};
 use serde_cbor::de::from_slice;
 use serde_cbor::ser::to_vec;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::time::sleep;
 
 use crate::error::Error;
 use crate::network::message::*;
@@ -15,6 +19,19 @@ pub use super::socket::*;
 // We choose a packet size of 1280 to be on the safe site regarding IPv6 MTU.
 pub const PACKET_SIZE: usize = 1280;
 
+/// Payloads above this size are compressed with [snap], if the peer supports it.
+pub const COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// Capability bit that signals support for compressed payloads.
+pub const CAPABILITY_COMPRESSION: u8 = 0b0000_0001;
+
+/// All capabilities that're supported by this version of the protocol.
+pub const CAPABILITIES: u8 = CAPABILITY_COMPRESSION;
+
+/// The highest bit of the size header marks a compressed payload.
+/// Real payloads never get anywhere near this size.
+const COMPRESSED_FLAG: u64 = 1 << 63;
+
 /// Convenience wrapper around send_bytes.
 /// Deserialize a message and feed the bytes into send_bytes.
 pub async fn send_message<T>(message: T, stream: &mut GenericStream) -> Result<(), Error>
@@ -34,11 +51,24 @@ where
 ///
 /// 1. Sends a u64 as 4bytes in BigEndian mode, which tells the receiver the length of the payload.
 /// 2. Send the payload in chunks of [PACKET_SIZE] bytes.
+///
+/// If compression has been negotiated for this stream, payloads above [COMPRESSION_THRESHOLD]
+/// are compressed and marked as such via the highest bit of the header.
 pub async fn send_bytes(payload: &[u8], stream: &mut GenericStream) -> Result<(), Error> {
+    let compress = stream.compresses_payloads() && payload.len() > COMPRESSION_THRESHOLD;
+    let compressed;
+    let (payload, flags) = if compress {
+        compressed = snap::raw::Encoder::new()
+            .compress_vec(payload)
+            .map_err(|err| Error::IoError("compressing payload".to_string(), err.into()))?;
+        (compressed.as_slice(), COMPRESSED_FLAG)
+    } else {
+        (payload, 0)
+    };
     let message_size = payload.len() as u64;
 
     let mut header = Vec::new();
-    WriteBytesExt::write_u64::<BigEndian>(&mut header, message_size).unwrap();
+    WriteBytesExt::write_u64::<BigEndian>(&mut header, message_size | flags).unwrap();
 
     // Send the request size header first.
     // Afterwards send the request.
@@ -67,15 +97,31 @@ pub async fn send_bytes(payload: &[u8], stream: &mut GenericStream) -> Result<()
 /// 1. First of, the client sends a u64 as a 4byte vector in BigEndian mode, which specifies
 ///    the length of the payload we're going to receive.
 /// 2. Receive chunks of [PACKET_SIZE] bytes until we finished all expected bytes.
+///
+/// Short reads and reads that're interrupted by a signal are retried until the full
+/// header and payload have been received.
+/// Compressed payloads are decompressed transparently.
 pub async fn receive_bytes(stream: &mut GenericStream) -> Result<Vec<u8>, Error> {
     // Receive the header with the overall message size
     let mut header = vec![0; 8];
-    stream
-        .read_exact(&mut header)
-        .await
-        .map_err(|err| Error::IoError("reading request size header".to_string(), err))?;
+    let mut received_header_bytes = 0;
+    while received_header_bytes < header.len() {
+        let received_bytes = read_chunk(stream, &mut header[received_header_bytes..])
+            .await
+            .map_err(|err| Error::IoError("reading request size header".to_string(), err))?;
+
+        if received_bytes == 0 {
+            return Err(Error::IoError(
+                "reading request size header".to_string(),
+                std::io::Error::from(ErrorKind::UnexpectedEof),
+            ));
+        }
+        received_header_bytes += received_bytes;
+    }
     let mut header = Cursor::new(header);
-    let message_size = ReadBytesExt::read_u64::<BigEndian>(&mut header)? as usize;
+    let header = ReadBytesExt::read_u64::<BigEndian>(&mut header)?;
+    let compressed = header & COMPRESSED_FLAG != 0;
+    let message_size = (header & !COMPRESSED_FLAG) as usize;
 
     // Buffer for the whole payload
     let mut payload_bytes = Vec::with_capacity(message_size);
@@ -94,8 +140,7 @@ pub async fn receive_bytes(stream: &mut GenericStream) -> Result<Vec<u8>, Error>
         };
 
         // Read data and get the amount of received bytes
-        let received_bytes = stream
-            .read(&mut chunk_buffer)
+        let received_bytes = read_chunk(stream, &mut chunk_buffer)
             .await
             .map_err(|err| Error::IoError("reading next chunk".to_string(), err))?;
 
@@ -110,30 +155,197 @@ pub async fn receive_bytes(stream: &mut GenericStream) -> Result<Vec<u8>, Error>
         payload_bytes.extend_from_slice(&chunk_buffer[0..received_bytes]);
     }
 
+    if compressed {
+        return snap::raw::Decoder::new()
+            .decompress_vec(&payload_bytes)
+            .map_err(|err| Error::IoError("decompressing payload".to_string(), err.into()));
+    }
+
     Ok(payload_bytes)
 }
 
+/// Read the next chunk of bytes from the stream into the buffer.
+/// Reads that're interrupted by a signal don't carry any data and are simply retried.
+async fn read_chunk(stream: &mut GenericStream, buffer: &mut [u8]) -> std::io::Result<usize> {
+    loop {
+        match stream.read(buffer).await {
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
 /// Convenience wrapper that receives a message and converts it into a Message.
 pub async fn receive_message(stream: &mut GenericStream) -> Result<Message, Error> {
     let payload_bytes = receive_bytes(stream).await?;
+    deserialize_message(&payload_bytes)
+}
+
+/// Deserialize a received payload into a Message.
+pub fn deserialize_message(payload_bytes: &[u8]) -> Result<Message, Error> {
     if payload_bytes.is_empty() {
         return Err(Error::EmptyPayload);
     }
 
     // Deserialize the message.
     let message: Message =
-        from_slice(&payload_bytes).map_err(|err| Error::MessageDeserialization(err.to_string()))?;
+        from_slice(payload_bytes).map_err(|err| Error::MessageDeserialization(err.to_string()))?;
     debug!("Received message: {message:#?}");
 
     Ok(message)
 }
 
+/// Client side of the handshake, which is done right after connecting to the daemon.
+///
+/// The client sends the `secret`, which is `None` for trusted unix sockets, and the daemon
+/// answers with its version, if the secret was correct.
+/// Returns the version of the daemon.
+pub async fn handshake(stream: &mut GenericStream, secret: Option<&[u8]>) -> Result<String, Error> {
+    if let Some(secret) = secret {
+        send_bytes(secret, stream).await?;
+    }
+
+    let version_bytes = receive_bytes(stream).await?;
+    if version_bytes.is_empty() {
+        return Err(Error::Connection(
+            "Daemon went away after sending secret. Did you use the correct secret?".into(),
+        ));
+    }
+
+    String::from_utf8(version_bytes).map_err(|_| {
+        Error::Connection("Daemon sent invalid UTF-8. Did you use the correct secret?".into())
+    })
+}
+
+/// Daemon side of the handshake.
+///
+/// Check the client's secret, unless `secret` is `None`, and answer with the daemon's `version`.
+/// Returns `false`, if the client went away before sending the secret.
+///
+/// Invalid secrets are only rejected after one second, which invalidates any timing attacks.
+pub async fn accept_handshake(
+    stream: &mut GenericStream,
+    secret: Option<&[u8]>,
+    version: &str,
+) -> Result<bool, Error> {
+    if let Some(secret) = secret {
+        let payload_bytes = receive_bytes(stream).await?;
+        // Didn't receive any bytes. The client disconnected.
+        if payload_bytes.is_empty() {
+            info!("Client went away");
+            return Ok(false);
+        }
+
+        let start = Instant::now();
+        if payload_bytes != secret {
+            let received_secret = String::from_utf8_lossy(&payload_bytes);
+            warn!("Received invalid secret: {received_secret}");
+
+            sleep(Duration::from_secs(1).saturating_sub(start.elapsed())).await;
+            return Err(Error::Connection("Received invalid secret".into()));
+        }
+    }
+
+    send_bytes(version.as_bytes(), stream).await?;
+
+    Ok(true)
+}
+
+/// Client side of the capability negotiation, which is done right after the version exchange.
+///
+/// The client sends a single byte with its [CAPABILITIES] and the daemon answers with the
+/// capabilities both sides support.
+/// A single byte is never a valid message, which is why daemons that don't know about
+/// capabilities respond with a failure message and close the connection.
+/// In that case, `None` is returned and the client has to reconnect without negotiating.
+pub async fn offer_capabilities(mut stream: GenericStream) -> Result<Option<GenericStream>, Error> {
+    send_bytes(&[CAPABILITIES], &mut stream).await?;
+
+    let response = receive_bytes(&mut stream).await?;
+    match response.as_slice() {
+        [capabilities] => Ok(Some(with_capabilities(stream, *capabilities))),
+        _ => Ok(None),
+    }
+}
+
+/// Check whether a payload that has been received by the daemon is a capability offer.
+/// Returns the offered capabilities in that case.
+pub fn capability_offer(payload: &[u8]) -> Option<u8> {
+    match payload {
+        [capabilities] => Some(*capabilities),
+        _ => None,
+    }
+}
+
+/// Daemon side of the capability negotiation.
+/// Answer the client's offer with the capabilities both sides support and enable them.
+pub async fn accept_capabilities(
+    mut stream: GenericStream,
+    offered: u8,
+) -> Result<GenericStream, Error> {
+    let capabilities = offered & CAPABILITIES;
+    send_bytes(&[capabilities], &mut stream).await?;
+
+    Ok(with_capabilities(stream, capabilities))
+}
+
+/// Enable the negotiated capabilities on the stream.
+fn with_capabilities(stream: GenericStream, capabilities: u8) -> GenericStream {
+    if capabilities & CAPABILITY_COMPRESSION != 0 {
+        Box::new(CompressedStream(stream))
+    } else {
+        stream
+    }
+}
+
+/// A stream, for which compression has been negotiated with the peer. \
+/// Payloads above [COMPRESSION_THRESHOLD] that're sent via [send_bytes] are compressed.
+pub struct CompressedStream(GenericStream);
+
+impl AsyncRead for CompressedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for CompressedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+impl Stream for CompressedStream {
+    fn compresses_payloads(&self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
     use std::time::Duration;
 
     use async_trait::async_trait;
     use pretty_assertions::assert_eq;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
     use tokio::net::{TcpListener, TcpStream};
     use tokio::task;
 
@@ -226,4 +438,191 @@ mod test {
 
         Ok(())
     }
+
+    /// A stream that hands out its data in predefined chunks.
+    /// `None` entries simulate a read that's interrupted by a signal.
+    struct ChunkedStream {
+        chunks: VecDeque<Option<Vec<u8>>>,
+    }
+
+    impl AsyncRead for ChunkedStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            match self.chunks.pop_front() {
+                // The stream is exhausted.
+                None => Poll::Ready(Ok(())),
+                Some(None) => Poll::Ready(Err(ErrorKind::Interrupted.into())),
+                Some(Some(mut chunk)) => {
+                    // Put back anything that doesn't fit into the buffer.
+                    let length = chunk.len().min(buf.remaining());
+                    let rest = chunk.split_off(length);
+                    if !rest.is_empty() {
+                        self.chunks.push_front(Some(rest));
+                    }
+                    buf.put_slice(&chunk);
+                    Poll::Ready(Ok(()))
+                }
+            }
+        }
+    }
+
+    impl AsyncWrite for ChunkedStream {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl PueueStream for ChunkedStream {}
+
+    /// Header and payload that arrive in several small chunks with interrupted reads in between
+    /// are still received as a single message.
+    #[tokio::test]
+    async fn test_chunked_and_interrupted_reads() -> Result<(), Error> {
+        let message = create_success_message("a".repeat(3000));
+        let payload = to_vec(&message).expect("Failed to serialize message.");
+
+        let mut bytes = Vec::new();
+        WriteBytesExt::write_u64::<BigEndian>(&mut bytes, payload.len() as u64).unwrap();
+        bytes.extend_from_slice(&payload);
+
+        // Split everything into chunks of 3 bytes, which also splits the header.
+        // Every other read is interrupted.
+        let mut chunks = VecDeque::new();
+        for chunk in bytes.chunks(3) {
+            chunks.push_back(None);
+            chunks.push_back(Some(chunk.to_vec()));
+        }
+
+        let mut stream: GenericStream = Box::new(ChunkedStream { chunks });
+        let received = receive_message(&mut stream).await?;
+        assert_eq!(received, message);
+
+        Ok(())
+    }
+
+    /// Small and large messages survive the round-trip on a connection, for which compression
+    /// has been negotiated.
+    #[tokio::test]
+    async fn test_negotiated_compression_round_trip() -> Result<(), Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let listener: GenericListener = Box::new(listener);
+
+        // Spawn a sub thread that:
+        // 1. Accepts a new connection and answers the capability offer.
+        // 2. Sends back every message it receives.
+        task::spawn(async move {
+            let mut stream = listener.accept().await.unwrap();
+            let offer = receive_bytes(&mut stream).await.unwrap();
+            let offered = capability_offer(&offer).expect("Expected a capability offer");
+            let mut stream = accept_capabilities(stream, offered).await.unwrap();
+            assert!(stream.compresses_payloads());
+
+            for _ in 0..2 {
+                let message = receive_message(&mut stream).await.unwrap();
+                send_message(message, &mut stream).await.unwrap();
+            }
+        });
+
+        let client: GenericStream = Box::new(TcpStream::connect(&addr).await?);
+        let mut client = offer_capabilities(client)
+            .await?
+            .expect("The peer should support capabilities");
+        assert!(client.compresses_payloads());
+
+        let small = create_success_message("a".repeat(100));
+        let large = create_success_message("a".repeat(100_000));
+        for message in [small, large] {
+            send_message(message.clone(), &mut client).await?;
+            let response = receive_message(&mut client).await?;
+            assert_eq!(response, message);
+        }
+
+        Ok(())
+    }
+
+    /// Only payloads above the threshold are compressed and marked as such in the header.
+    #[tokio::test]
+    async fn test_large_payloads_are_compressed() -> Result<(), Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        let small = vec![b'a'; 100];
+        let large = vec![b'a'; 100_000];
+        let payloads = [small.clone(), large.clone()];
+        task::spawn(async move {
+            let stream: GenericStream = Box::new(TcpStream::connect(&addr).await.unwrap());
+            let mut stream: GenericStream = Box::new(CompressedStream(stream));
+            for payload in payloads {
+                send_bytes(&payload, &mut stream).await.unwrap();
+            }
+        });
+
+        let (stream, _) = listener.accept().await?;
+        let mut stream: GenericStream = Box::new(stream);
+
+        // Read the raw header of the small payload, which is sent as is.
+        let mut header = [0; 8];
+        stream.read_exact(&mut header).await?;
+        assert_eq!(u64::from_be_bytes(header), small.len() as u64);
+        let mut received = vec![0; small.len()];
+        stream.read_exact(&mut received).await?;
+        assert_eq!(received, small);
+
+        // The large payload is flagged as compressed and a lot smaller.
+        stream.read_exact(&mut header).await?;
+        let header = u64::from_be_bytes(header);
+        assert_ne!(header & COMPRESSED_FLAG, 0);
+        let compressed_size = (header & !COMPRESSED_FLAG) as usize;
+        assert!(compressed_size < large.len());
+
+        let mut compressed = vec![0; compressed_size];
+        stream.read_exact(&mut compressed).await?;
+        let received = snap::raw::Decoder::new()
+            .decompress_vec(&compressed)
+            .expect("Failed to decompress payload");
+        assert_eq!(received, large);
+
+        Ok(())
+    }
+
+    /// The daemon answers a correct secret with its version and closes the connection on a
+    /// wrong one.
+    #[tokio::test]
+    async fn test_handshake() -> Result<(), Error> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let listener: GenericListener = Box::new(listener);
+
+        task::spawn(async move {
+            for _ in 0..2 {
+                let mut stream = listener.accept().await.unwrap();
+                let _ = accept_handshake(&mut stream, Some(b"secret"), "1.2.3").await;
+            }
+        });
+
+        let mut client: GenericStream = Box::new(TcpStream::connect(&addr).await?);
+        let version = handshake(&mut client, Some(b"secret")).await?;
+        assert_eq!(version, "1.2.3");
+
+        let mut client: GenericStream = Box::new(TcpStream::connect(&addr).await?);
+        assert!(handshake(&mut client, Some(b"wrong")).await.is_err());
+
+        Ok(())
+    }
 }
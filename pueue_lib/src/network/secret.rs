@@ -5,6 +5,8 @@ use std::path::Path;
 use rand::{distributions::Alphanumeric, Rng};
 
 use crate::error::Error;
+#[cfg(feature = "settings")]
+use crate::settings::Shared;
 
 /// Read the shared secret from a file.
 pub fn read_shared_secret(path: &Path) -> Result<Vec<u8>, Error> {
@@ -22,6 +24,19 @@ pub fn read_shared_secret(path: &Path) -> Result<Vec<u8>, Error> {
     Ok(buffer)
 }
 
+/// Read the secret that's sent during the handshake with the daemon.
+///
+/// Returns `None`, if the secret is skipped for trusted unix sockets.
+/// See [Shared::skip_secret_handshake].
+#[cfg(feature = "settings")]
+pub fn read_handshake_secret(shared: &Shared) -> Result<Option<Vec<u8>>, Error> {
+    if shared.skip_secret_handshake() {
+        return Ok(None);
+    }
+
+    read_shared_secret(&shared.shared_secret_path()).map(Some)
+}
+
 /// Generate a random secret and write it to a file.
 pub fn init_shared_secret(path: &Path) -> Result<(), Error> {
     if path.exists() {
@@ -9,3 +9,112 @@
 #[cfg_attr(target_os = "windows", path = "windows.rs")]
 mod platform;
 pub use self::platform::*;
+
+#[cfg(feature = "settings")]
+use {
+    crate::{error::Error, settings::Shared},
+    log::info,
+    tokio::net::{lookup_host, TcpListener, TcpSocket, TcpStream},
+};
+
+/// Accept the next connection on any of the given listeners.
+#[cfg(any(feature = "settings", target_os = "windows"))]
+pub(crate) async fn accept_any(
+    listeners: &[tokio::net::TcpListener],
+) -> std::io::Result<(tokio::net::TcpStream, std::net::SocketAddr)> {
+    std::future::poll_fn(|cx| {
+        for listener in listeners {
+            if let std::task::Poll::Ready(result) = listener.poll_accept(cx) {
+                return std::task::Poll::Ready(result);
+            }
+        }
+        std::task::Poll::Pending
+    })
+    .await
+}
+
+/// Combine a host and a port into an address.
+/// IPv6 addresses are wrapped in brackets, so they can be told apart from the port.
+#[cfg(feature = "settings")]
+pub(crate) fn tcp_address(host: &str, port: &str) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+/// Connect to the daemon via TCP.
+/// The configured hosts are tried in order and the first one that's reachable is used.
+#[cfg(feature = "settings")]
+pub(crate) async fn connect_tcp(settings: &Shared) -> Result<TcpStream, Error> {
+    let mut addresses = Vec::new();
+    for host in settings.host.as_slice() {
+        let address = tcp_address(host, &settings.port);
+        if let Ok(stream) = TcpStream::connect(&address).await {
+            return Ok(stream);
+        }
+        addresses.push(address);
+    }
+
+    Err(Error::Connection(format!(
+        "Failed to connect to the daemon on {}. Did you start it?",
+        addresses.join(", ")
+    )))
+}
+
+/// Bind a TCP listener for each of the configured hosts.
+///
+/// IPv6 sockets keep the operating system's default for `IPV6_V6ONLY`.
+/// On dual-stack systems such as Linux, a listener on `::` thereby also accepts IPv4
+/// connections, so configuring both `::` and `0.0.0.0` fails with "address in use".
+#[cfg(feature = "settings")]
+pub(crate) async fn bind_tcp_listeners(settings: &Shared) -> Result<Vec<TcpListener>, Error> {
+    let hosts = settings.host.as_slice();
+    if hosts.is_empty() {
+        return Err(Error::Connection(
+            "No host configured to listen on.".to_string(),
+        ));
+    }
+
+    let mut listeners = Vec::new();
+    for host in hosts {
+        let address = tcp_address(host, &settings.port);
+        info!("Binding to address: {address}");
+        let listener = bind_tcp_listener(&address, settings.listen_backlog)
+            .await
+            .map_err(|err| Error::IoError(format!("binding tcp listener to {address}"), err))?;
+        listeners.push(listener);
+    }
+
+    Ok(listeners)
+}
+
+/// Bind a TCP listener to the given address, which queues up to `backlog` pending connections.
+#[cfg(feature = "settings")]
+pub(crate) async fn bind_tcp_listener(address: &str, backlog: u32) -> std::io::Result<TcpListener> {
+    let mut last_error = None;
+    // The address might resolve to multiple socket addresses. Use the first one that works.
+    for socket_address in lookup_host(address).await? {
+        let socket = if socket_address.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        // This is also done by `TcpListener::bind`, so the daemon can be restarted right away.
+        #[cfg(not(target_os = "windows"))]
+        socket.set_reuseaddr(true)?;
+
+        match socket.bind(socket_address) {
+            Ok(()) => return socket.listen(backlog),
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Address {address} couldn't be resolved"),
+        )
+    }))
+}
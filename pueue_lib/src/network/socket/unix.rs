@@ -1,19 +1,29 @@
-use std::convert::TryFrom;
-use std::fs::{set_permissions, Permissions};
-use std::os::unix::fs::PermissionsExt;
-
 use async_trait::async_trait;
-use log::info;
-use rustls::pki_types::ServerName;
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::net::{TcpListener, TcpStream, UnixListener, UnixSocket, UnixStream};
-use tokio_rustls::TlsAcceptor;
+use tokio::net::{TcpStream, UnixListener, UnixStream};
 
 use crate::error::Error;
-use crate::network::tls::{get_tls_connector, get_tls_listener};
-use crate::settings::Shared;
+
+// Everything that's needed to create sockets from the configuration.
+#[cfg(feature = "settings")]
+use std::{
+    convert::TryFrom,
+    fs::{set_permissions, Permissions},
+    os::unix::fs::PermissionsExt,
+};
+#[cfg(feature = "settings")]
+use {
+    super::{accept_any, bind_tcp_listeners, connect_tcp},
+    crate::network::tls::{get_tls_connector, get_tls_listener},
+    crate::settings::Shared,
+    log::info,
+    rustls::pki_types::ServerName,
+    tokio::net::{TcpListener, UnixSocket},
+    tokio_rustls::TlsAcceptor,
+};
 
 /// Unix specific cleanup handling when getting a SIGINT/SIGTERM.
+#[cfg(feature = "settings")]
 pub fn socket_cleanup(settings: &Shared) -> Result<(), std::io::Error> {
     // Clean up the unix socket if we're using it and it exists.
     if settings.use_unix_socket && settings.unix_socket_path().exists() {
@@ -35,17 +45,20 @@ pub trait Listener: Sync + Send {
 /// That's why this helper exists, which encapsulates the logic of accepting a new
 /// connection and initializing the TLS layer on top of it.
 /// This way we can expose an `accept` function and implement the Listener trait.
+///
+/// The daemon might listen on multiple hosts, in which case connections are accepted on all
+/// of them concurrently.
+#[cfg(feature = "settings")]
 pub(crate) struct TlsTcpListener {
-    tcp_listener: TcpListener,
+    tcp_listeners: Vec<TcpListener>,
     tls_acceptor: TlsAcceptor,
 }
 
+#[cfg(feature = "settings")]
 #[async_trait]
 impl Listener for TlsTcpListener {
     async fn accept<'a>(&'a self) -> Result<GenericStream, Error> {
-        let (stream, _) = self
-            .tcp_listener
-            .accept()
+        let (stream, _) = accept_any(&self.tcp_listeners)
             .await
             .map_err(|err| Error::IoError("accepting new tcp connection.".to_string(), err))?;
         let tls_stream = self
@@ -71,7 +84,13 @@ impl Listener for UnixListener {
 
 /// A new trait, which can be used to represent Unix- and Tls encrypted TcpStreams. \
 /// This is necessary to write generic functions where both types can be used.
-pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {
+    /// Whether large payloads are compressed when sending them on this stream.
+    /// This is only the case, if compression has been negotiated with the peer.
+    fn compresses_payloads(&self) -> bool {
+        false
+    }
+}
 impl Stream for UnixStream {}
 impl Stream for tokio_rustls::server::TlsStream<TcpStream> {}
 impl Stream for tokio_rustls::client::TlsStream<TcpStream> {}
@@ -84,6 +103,7 @@ pub type GenericStream = Box<dyn Stream>;
 
 /// Get a new stream for the client. \
 /// This can either be a UnixStream or a Tls encrypted TCPStream, depending on the parameters.
+#[cfg(feature = "settings")]
 pub async fn get_client_stream(settings: &Shared) -> Result<GenericStream, Error> {
     // Create a unix socket, if the config says so.
     if settings.use_unix_socket {
@@ -102,12 +122,7 @@ pub async fn get_client_stream(settings: &Shared) -> Result<GenericStream, Error
     }
 
     // Connect to the daemon via TCP
-    let address = format!("{}:{}", &settings.host, &settings.port);
-    let tcp_stream = TcpStream::connect(&address).await.map_err(|_| {
-        Error::Connection(format!(
-            "Failed to connect to the daemon on {address}. Did you start it?"
-        ))
-    })?;
+    let tcp_stream = connect_tcp(settings).await?;
 
     // Get the configured rustls TlsConnector
     let tls_connector = get_tls_connector(settings)
@@ -125,6 +140,7 @@ pub async fn get_client_stream(settings: &Shared) -> Result<GenericStream, Error
 
 /// Get a new listener for the daemon. \
 /// This can either be a UnixListener or a TCPlistener, depending on the parameters.
+#[cfg(feature = "settings")]
 pub async fn get_listener(settings: &Shared) -> Result<GenericListener, Error> {
     if settings.use_unix_socket {
         let socket_path = settings.unix_socket_path();
@@ -153,7 +169,13 @@ pub async fn get_listener(settings: &Shared) -> Result<GenericListener, Error> {
             Error::IoPathError(socket_path.clone(), "binding unix socket to path", err)
         })?;
 
-        if let Some(mode) = settings.unix_socket_permissions {
+        // If the socket's permissions are used for authentication, only the owner may connect.
+        let mode = if settings.unix_socket_trust_permissions {
+            Some(0o600)
+        } else {
+            settings.unix_socket_permissions
+        };
+        if let Some(mode) = mode {
             set_permissions(&socket_path, Permissions::from_mode(mode)).map_err(|err| {
                 Error::IoPathError(
                     socket_path.clone(),
@@ -163,26 +185,22 @@ pub async fn get_listener(settings: &Shared) -> Result<GenericListener, Error> {
             })?;
         }
 
-        let unix_listener = socket.listen(1024).map_err(|err| {
+        let unix_listener = socket.listen(settings.listen_backlog).map_err(|err| {
             Error::IoPathError(socket_path.clone(), "listening on unix socket", err)
         })?;
 
         return Ok(Box::new(unix_listener));
     }
 
-    // This is the listener, which accepts low-level TCP connections
-    let address = format!("{}:{}", &settings.host, &settings.port);
-    info!("Binding to address: {address}");
-    let tcp_listener = TcpListener::bind(&address)
-        .await
-        .map_err(|err| Error::IoError("binding tcp listener to address".to_string(), err))?;
+    // These are the listeners, which accept low-level TCP connections on each configured host.
+    let tcp_listeners = bind_tcp_listeners(settings).await?;
 
     // This is the TLS acceptor, which initializes the TLS layer
     let tls_acceptor = get_tls_listener(settings)?;
 
     // Create a struct, which accepts connections and initializes a TLS layer in one go.
     let tls_listener = TlsTcpListener {
-        tcp_listener,
+        tcp_listeners,
         tls_acceptor,
     };
 
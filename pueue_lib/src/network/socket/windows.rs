@@ -1,16 +1,23 @@
-use std::convert::TryFrom;
-
 use async_trait::async_trait;
-use rustls::pki_types::ServerName;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_rustls::TlsAcceptor;
 
+use super::accept_any;
 use crate::error::Error;
-use crate::network::tls::{get_tls_connector, get_tls_listener};
-use crate::settings::Shared;
+
+// Everything that's needed to create sockets from the configuration.
+#[cfg(feature = "settings")]
+use {
+    super::{bind_tcp_listeners, connect_tcp},
+    crate::network::tls::{get_tls_connector, get_tls_listener},
+    crate::settings::Shared,
+    rustls::pki_types::ServerName,
+    std::convert::TryFrom,
+};
 
 /// Windowsspecific cleanup handling when getting a SIGINT/SIGTERM.
+#[cfg(feature = "settings")]
 pub fn socket_cleanup(_settings: &Shared) -> Result<(), Error> {
     Ok(())
 }
@@ -20,8 +27,11 @@ pub fn socket_cleanup(_settings: &Shared) -> Result<(), Error> {
 /// That's why this helper exists, which encapsulates the logic of accepting a new
 /// connection and initializing the TLS layer on top of it.
 /// This way we can expose an `accept` function and implement the GenericListener trait.
+///
+/// The daemon might listen on multiple hosts, in which case connections are accepted on all
+/// of them concurrently.
 pub struct TlsTcpListener {
-    tcp_listener: TcpListener,
+    tcp_listeners: Vec<TcpListener>,
     tls_acceptor: TlsAcceptor,
 }
 
@@ -35,14 +45,20 @@ pub trait Listener: Sync + Send {
 #[async_trait]
 impl Listener for TlsTcpListener {
     async fn accept<'a>(&'a self) -> Result<GenericStream, Error> {
-        let (stream, _) = self.tcp_listener.accept().await?;
+        let (stream, _) = accept_any(&self.tcp_listeners).await?;
         Ok(Box::new(self.tls_acceptor.accept(stream).await?))
     }
 }
 
 /// A new trait, which can be used to represent Unix- and Tls encrypted TcpStreams.
 /// This is necessary to write generic functions where both types can be used.
-pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {
+    /// Whether large payloads are compressed when sending them on this stream.
+    /// This is only the case, if compression has been negotiated with the peer.
+    fn compresses_payloads(&self) -> bool {
+        false
+    }
+}
 impl Stream for tokio_rustls::server::TlsStream<TcpStream> {}
 impl Stream for tokio_rustls::client::TlsStream<TcpStream> {}
 
@@ -52,14 +68,10 @@ pub type GenericStream = Box<dyn Stream>;
 
 /// Get a new stream for the client.
 /// This can either be a UnixStream or a Tls encrypted TCPStream, depending on the parameters.
+#[cfg(feature = "settings")]
 pub async fn get_client_stream(settings: &Shared) -> Result<GenericStream, Error> {
     // Connect to the daemon via TCP
-    let address = format!("{}:{}", settings.host, settings.port);
-    let tcp_stream = TcpStream::connect(&address).await.map_err(|_| {
-        Error::Connection(format!(
-            "Failed to connect to the daemon on {address}. Did you start it?"
-        ))
-    })?;
+    let tcp_stream = connect_tcp(settings).await?;
 
     // Get the configured rustls TlsConnector
     let tls_connector = get_tls_connector(settings)
@@ -76,19 +88,17 @@ pub async fn get_client_stream(settings: &Shared) -> Result<GenericStream, Error
 }
 
 /// Get a new tcp&tls listener for the daemon.
+#[cfg(feature = "settings")]
 pub async fn get_listener(settings: &Shared) -> Result<GenericListener, Error> {
-    // This is the listener, which accepts low-level TCP connections
-    let address = format!("{}:{}", settings.host, settings.port);
-    let tcp_listener = TcpListener::bind(&address).await.map_err(|err| {
-        Error::Connection(format!("Failed to listen on address {address}. {err}"))
-    })?;
+    // These are the listeners, which accept low-level TCP connections on each configured host.
+    let tcp_listeners = bind_tcp_listeners(settings).await?;
 
     // This is the TLS acceptor, which initializes the TLS layer
     let tls_acceptor = get_tls_listener(settings)?;
 
     // Create a struct, which accepts connections and initializes a TLS layer in one go.
     let tls_listener = TlsTcpListener {
-        tcp_listener,
+        tcp_listeners,
         tls_acceptor,
     };
 
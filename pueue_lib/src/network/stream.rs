@@ -0,0 +1,109 @@
+use log::debug;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::Error;
+use crate::network::message::*;
+use crate::network::protocol::*;
+
+/// A handle to a running log stream of a task, i.e. the library equivalent of `pueue follow`.
+///
+/// The daemon continuously sends [Message::Stream] chunks with new output, until the task
+/// finishes or the stream is cancelled via [LogStream::cancel].
+pub struct LogStream {
+    stream: GenericStream,
+    finished: bool,
+}
+
+impl LogStream {
+    /// Request a log stream from the daemon.
+    /// The given `stream` must already be authenticated, i.e. the handshake has to be done.
+    pub async fn start(
+        mut stream: GenericStream,
+        message: StreamRequestMessage,
+    ) -> Result<Self, Error> {
+        send_message(message, &mut stream).await?;
+
+        Ok(LogStream {
+            stream,
+            finished: false,
+        })
+    }
+
+    /// Receive the next message of the stream.
+    ///
+    /// This is usually a [Message::Stream] with the next chunk of output.
+    /// The daemon might also send a final [Message::Success] or [Message::Failure], for instance
+    /// if the followed task doesn't exist.
+    /// Returns `None` once the daemon ended the stream via [Message::Close].
+    /// If the connection breaks before that, an [Error::Connection] is returned instead.
+    pub async fn next(&mut self) -> Result<Option<Message>, Error> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        match receive_message(&mut self.stream).await {
+            Ok(Message::Close) => {
+                self.finished = true;
+                Ok(None)
+            }
+            Err(Error::EmptyPayload) => {
+                self.finished = true;
+                Err(Error::Connection(
+                    "The connection to the daemon closed before the log stream ended.".into(),
+                ))
+            }
+            Ok(message @ (Message::Success(_) | Message::Failure(_))) => {
+                self.finished = true;
+                Ok(Some(message))
+            }
+            Ok(message) => Ok(Some(message)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Receive the next chunk of output.
+    ///
+    /// In contrast to [LogStream::next], failures that're sent by the daemon are returned as
+    /// errors, for instance if the followed task has been removed.
+    /// Returns `None` once the stream ended.
+    pub async fn next_chunk(&mut self) -> Result<Option<StreamResponseMessage>, Error> {
+        match self.next().await? {
+            Some(Message::Stream(chunk)) => Ok(Some(chunk)),
+            Some(Message::Failure(text)) => Err(Error::Generic(text)),
+            // The daemon ended the stream with a notice, e.g. if the log file went away.
+            Some(Message::Success(text)) => {
+                debug!("Log stream ended: {text}");
+                Ok(None)
+            }
+            Some(message) => Err(Error::Generic(format!(
+                "Received unexpected message during log stream: {message:?}"
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Cancel the stream.
+    ///
+    /// This closes our side of the connection, which tells the daemon to stop sending output.
+    /// Any chunks that're still in flight are discarded and this function returns, once the
+    /// daemon acknowledged the cancellation by closing the stream.
+    pub async fn cancel(&mut self) -> Result<(), Error> {
+        if self.finished {
+            return Ok(());
+        }
+
+        self.stream
+            .shutdown()
+            .await
+            .map_err(|err| Error::IoError("closing the log stream".to_string(), err))?;
+
+        // The daemon might close the connection right away, which is fine as well.
+        loop {
+            match self.next().await {
+                Ok(Some(_)) => continue,
+                Ok(None) | Err(Error::Connection(_)) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
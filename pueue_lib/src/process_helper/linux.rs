@@ -1,3 +1,5 @@
+use std::process::Command;
+
 use procfs::process;
 
 /// Check, whether a specific process is exists or not
@@ -10,3 +12,27 @@ pub fn process_exists(pid: u32) -> bool {
         },
     }
 }
+
+/// Wrap a command, so that it's executed in a transient systemd scope with the given memory
+/// limit in bytes.
+/// The scope has its own cgroup (v2), in which the kernel enforces the limit for the whole
+/// process tree of the task.
+pub fn apply_memory_limit(command: Command, memory_limit: u64) -> Command {
+    let mut scoped = Command::new("systemd-run");
+    scoped
+        .args(["--user", "--scope", "--quiet", "--collect"])
+        .arg(format!("--property=MemoryMax={memory_limit}"))
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+
+    // Keep any environment variables that have already been set on the original command.
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => scoped.env(key, value),
+            None => scoped.env_remove(key),
+        };
+    }
+
+    scoped
+}
@@ -59,7 +59,17 @@ impl From<InternalSignal> for Signal {
 
 /// Take a platform specific shell command and insert the actual task command via templating.
 pub fn compile_shell_command(settings: &Settings, command: &str) -> Command {
-    let shell_command = get_shell_command(settings);
+    compile_group_shell_command(settings, None, command)
+}
+
+/// Same as [compile_shell_command], but the shell command of the given group is used, if the
+/// group has its own entry in `daemon.group_shell_commands`.
+pub fn compile_group_shell_command(
+    settings: &Settings,
+    group: Option<&str>,
+    command: &str,
+) -> Command {
+    let shell_command = get_shell_command(settings, group);
 
     let mut handlebars = handlebars::Handlebars::new();
     handlebars.set_strict_mode(true);
@@ -90,7 +100,35 @@ pub fn compile_shell_command(settings: &Settings, command: &str) -> Command {
         command.arg(&arg);
     }
 
-    // Inject custom environment variables.
+    inject_env_vars(settings, &mut command);
+
+    command
+}
+
+/// Build a command that directly executes the given program with its arguments.
+/// In contrast to [compile_shell_command], no shell is involved, which means that the
+/// arguments are passed to the program literally.
+///
+/// The first entry of `args` is the program, which is why `args` mustn't be empty.
+pub fn compile_raw_command(settings: &Settings, args: &[String]) -> Command {
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+
+    inject_env_vars(settings, &mut command);
+
+    command
+}
+
+/// Memory limits are enforced via cgroups, which only exist on Linux.
+/// The limit is ignored on all other platforms.
+#[cfg(not(target_os = "linux"))]
+pub fn apply_memory_limit(command: Command, _memory_limit: u64) -> Command {
+    log::warn!("Memory limits are only supported on Linux. Ignoring the limit.");
+    command
+}
+
+/// Inject the custom environment variables from the daemon's configuration.
+fn inject_env_vars(settings: &Settings, command: &mut Command) {
     if !settings.daemon.env_vars.is_empty() {
         log::info!(
             "Inject environment variables: {:?}",
@@ -98,6 +136,4 @@ pub fn compile_shell_command(settings: &Settings, command: &str) -> Command {
         );
         command.envs(&settings.daemon.env_vars);
     }
-
-    command
 }
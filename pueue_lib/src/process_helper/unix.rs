@@ -1,14 +1,19 @@
 // We allow anyhow in here, as this is a module that'll be strictly used internally.
 // As soon as it's obvious that this is code is intended to be exposed to library users, we have to
 // go ahead and replace any `anyhow` usage by proper error handling via our own Error type.
-use anyhow::Result;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus};
+
+use anyhow::{bail, Context, Result};
 use command_group::{GroupChild, Signal, UnixChildExt};
 use log::info;
+use nix::unistd::{geteuid, User};
 
+use crate::network::message::Signal as InternalSignal;
 use crate::settings::Settings;
 
-pub fn get_shell_command(settings: &Settings) -> Vec<String> {
-    let Some(ref shell_command) = settings.daemon.shell_command else {
+pub fn get_shell_command(settings: &Settings, group: Option<&str>) -> Vec<String> {
+    let Some(shell_command) = settings.daemon.shell_command_for_group(group) else {
         return vec![
             "sh".into(),
             "-c".into(),
@@ -19,6 +24,40 @@ pub fn get_shell_command(settings: &Settings) -> Vec<String> {
     shell_command.clone()
 }
 
+/// Look up a user by name and make sure that the current process is allowed to start processes
+/// as that user.
+/// Only root is allowed to switch to another user. Everybody may "switch" to themselves.
+fn resolve_user(name: &str) -> Result<User> {
+    let user = User::from_name(name)
+        .context(format!("Failed to look up user '{name}'"))?
+        .context(format!("User '{name}' doesn't exist"))?;
+
+    let euid = geteuid();
+    if !euid.is_root() && euid != user.uid {
+        bail!(
+            "The daemon isn't permitted to run tasks as user '{name}'. \
+            This is only possible if the daemon runs as root."
+        );
+    }
+
+    Ok(user)
+}
+
+/// Check whether the current process is allowed to start processes as the given user.
+pub fn ensure_user_permission(name: &str) -> Result<()> {
+    resolve_user(name).map(|_| ())
+}
+
+/// Configure a command, so that its process is executed as the given user.
+/// This sets the uid and the primary gid of the user.
+/// The process doesn't inherit the supplementary groups of the daemon.
+pub fn run_as_user(command: &mut Command, name: &str) -> Result<()> {
+    let user = resolve_user(name)?;
+    command.uid(user.uid.as_raw()).gid(user.gid.as_raw());
+
+    Ok(())
+}
+
 /// Send a signal to one of Pueue's child process group handle.
 pub fn send_signal_to_child<T>(child: &mut GroupChild, signal: T) -> Result<()>
 where
@@ -28,6 +67,11 @@ where
     Ok(())
 }
 
+/// Check whether a process has been terminated by the given signal.
+pub fn terminated_by_signal(status: &ExitStatus, signal: &InternalSignal) -> bool {
+    status.signal() == Some(Signal::from(signal.clone()) as i32)
+}
+
 /// This is a helper function to safely kill a child process group.
 /// Its purpose is to properly kill all processes and prevent any dangling processes.
 pub fn kill_child(task_id: usize, child: &mut GroupChild) -> std::io::Result<()> {
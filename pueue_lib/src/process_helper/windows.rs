@@ -15,6 +15,7 @@ use winapi::um::tlhelp32::{
 };
 use winapi::um::winnt::THREAD_SUSPEND_RESUME;
 
+use crate::network::message::Signal as InternalSignal;
 use crate::settings::Settings;
 
 /// Shim signal enum for windows.
@@ -26,8 +27,8 @@ pub enum Signal {
     SIGSTOP,
 }
 
-pub fn get_shell_command(settings: &Settings) -> Vec<String> {
-    let Some(ref shell_command) = settings.daemon.shell_command else {
+pub fn get_shell_command(settings: &Settings, group: Option<&str>) -> Vec<String> {
+    let Some(shell_command) = settings.daemon.shell_command_for_group(group) else {
         // Chain two `powershell` commands, one that sets the output encoding to utf8 and then the user provided one.
         return vec![
             "powershell".into(),
@@ -75,6 +76,11 @@ where
     Ok(())
 }
 
+/// Processes cannot be terminated by signals on Windows.
+pub fn terminated_by_signal(_status: &std::process::ExitStatus, _signal: &InternalSignal) -> bool {
+    false
+}
+
 /// Kill a child process
 pub fn kill_child(task_id: usize, child: &mut GroupChild) -> std::io::Result<()> {
     match child.kill() {
@@ -225,6 +231,16 @@ fn resume_thread(tid: u32) {
     }
 }
 
+/// Running tasks as a different user isn't supported on Windows.
+pub fn ensure_user_permission(_name: &str) -> Result<()> {
+    bail!("Running tasks as a different user is only supported on Unix.");
+}
+
+/// Running tasks as a different user isn't supported on Windows.
+pub fn run_as_user(_command: &mut std::process::Command, _name: &str) -> Result<()> {
+    bail!("Running tasks as a different user is only supported on Unix.");
+}
+
 /// Assert that certain process id no longer exists
 pub fn process_exists(pid: u32) -> bool {
     unsafe {
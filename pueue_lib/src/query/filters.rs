@@ -1,10 +1,11 @@
 #![allow(bindings_with_variant_name)]
+use crate::task::{Task, TaskResult, TaskStatus};
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 use pest::iterators::Pair;
-use pueue_lib::task::{Task, TaskResult, TaskStatus};
+use regex::Regex;
 
-use super::{QueryResult, Rule};
+use super::{FilterFunction, Rule};
 
 enum DateOrDateTime {
     DateTime(DateTime<Local>),
@@ -65,7 +66,7 @@ enum DateOrDateTime {
 ///         },
 ///     ],
 /// }
-pub fn datetime(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<()> {
+pub fn datetime(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
     let mut filter = section.into_inner();
     // Get the column this filter should be applied to.
     // Either of [Rule::column_enqueue_at | Rule::column_start | Rule::column_end]
@@ -101,7 +102,7 @@ pub fn datetime(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Resu
         _ => bail!("Expected either a date, datetime or time expression."),
     };
 
-    let filter_function = Box::new(move |task: &Task| -> bool {
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
         // Get the field we should apply the filter to.
         let field = match column {
             Rule::column_enqueue_at => {
@@ -177,15 +178,61 @@ pub fn datetime(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Resu
             }
         }
     });
-    query_result.filters.push(filter_function);
 
-    Ok(())
+    Ok(filter_function)
+}
+
+/// Parse a filter for the runtime of tasks.
+///
+/// This filter syntax looks like this:
+/// `runtime [=|!=|<|>|<=|>=] duration`
+///
+/// The duration consists of an integer and a unit, which is either of `s`, `m`, `h` or `d`,
+/// e.g. `runtime>2h`. The runtime of running or paused tasks is the time since their start.
+/// Tasks that haven't been started yet never match.
+pub fn runtime(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
+    let mut filter = section.into_inner();
+    // The first word should be the `runtime` keyword.
+    let _runtime = filter.next().unwrap();
+
+    // Get the operator that should be applied in this filter.
+    // Can be either of [Rule::eq | Rule::neq | Rule::lt | Rule::gt | Rule::lte | Rule::gte].
+    let operator = filter.next().unwrap().as_rule();
+
+    // Get the duration we should compare with.
+    let operand = parse_duration(filter.next().unwrap().as_str())?;
+
+    // Build the runtime filter function.
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
+        let Some(runtime) = task.duration() else {
+            return false;
+        };
+
+        match operator {
+            Rule::eq => runtime == operand,
+            Rule::neq => runtime != operand,
+            Rule::lt => runtime < operand,
+            Rule::gt => runtime > operand,
+            Rule::lte => runtime <= operand,
+            Rule::gte => runtime >= operand,
+            _ => false,
+        }
+    });
+
+    Ok(filter_function)
+}
+
+/// Parse the duration of a runtime filter, e.g. `30s`, `5m`, `2h` or `1d`.
+fn parse_duration(duration: &str) -> Result<TimeDelta> {
+    crate::duration::parse_duration(duration)
+        .and_then(|duration| TimeDelta::from_std(duration).ok())
+        .with_context(|| format!("Failed to parse query: Invalid duration \"{duration}\""))
 }
 
 /// Parse a filter for the label field.
 ///
 /// This filter syntax looks like this:
-/// `label [=|!=] string`
+/// `label [=|!=|%=|~%=|~=] string`
 ///
 /// The data structure looks something like this:
 ///  Pair {
@@ -225,20 +272,23 @@ pub fn datetime(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Resu
 ///         },
 ///     ],
 /// }
-pub fn label(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<()> {
+pub fn label(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
     let mut filter = section.into_inner();
     // The first word should be the `label` keyword.
     let _label = filter.next().unwrap();
 
     // Get the operator that should be applied in this filter.
-    // Can be either of [Rule::eq | Rule::neq].
+    // Can be either of [Rule::eq | Rule::neq | Rule::contains | Rule::contains_ignore_case |
+    // Rule::regex_match].
     let operator = filter.next().unwrap().as_rule();
 
     // Get the name of the label we should filter for.
     let operand = filter.next().unwrap().as_str().to_string();
+    let lowercase_operand = operand.to_lowercase();
+    let regex = compile_regex(operator, &operand)?;
 
     // Build the label filter function.
-    let filter_function = Box::new(move |task: &Task| -> bool {
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
         let Some(label) = &task.label else {
             return operator == Rule::neq;
         };
@@ -247,43 +297,154 @@ pub fn label(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<
             Rule::eq => label == &operand,
             Rule::neq => label != &operand,
             Rule::contains => label.contains(&operand),
+            Rule::contains_ignore_case => label.to_lowercase().contains(&lowercase_operand),
+            Rule::regex_match => regex.as_ref().is_some_and(|regex| regex.is_match(label)),
             _ => false,
         }
     });
-    query_result.filters.push(filter_function);
 
-    Ok(())
+    Ok(filter_function)
 }
 
 /// Parse a filter for the command field.
 ///
 /// This filter syntax is exactly the same as the [label] filter.
 /// Only the keyword changed from `label` to `command`.
-pub fn command(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<()> {
+pub fn command(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
     let mut filter = section.into_inner();
     // The first word should be the `command` keyword.
     let _command = filter.next().unwrap();
 
     // Get the operator that should be applied in this filter.
-    // Can be either of [Rule::eq | Rule::neq].
+    // Can be either of [Rule::eq | Rule::neq | Rule::contains | Rule::contains_ignore_case |
+    // Rule::regex_match].
     let operator = filter.next().unwrap().as_rule();
 
     // Get the name of the command we should filter for.
     let operand = filter.next().unwrap().as_str().to_string();
+    let lowercase_operand = operand.to_lowercase();
+    let regex = compile_regex(operator, &operand)?;
 
     // Build the command filter function.
-    let filter_function = Box::new(move |task: &Task| -> bool {
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
         let command = task.command.as_str();
         match operator {
             Rule::eq => command == operand,
             Rule::neq => command != operand,
             Rule::contains => command.contains(&operand),
+            Rule::contains_ignore_case => command.to_lowercase().contains(&lowercase_operand),
+            Rule::regex_match => regex.as_ref().is_some_and(|regex| regex.is_match(command)),
             _ => false,
         }
     });
-    query_result.filters.push(filter_function);
 
-    Ok(())
+    Ok(filter_function)
+}
+
+/// Parse a filter for the id field.
+///
+/// This filter syntax looks like this:
+/// `id [=|!=|<|>|<=|>=] integer` or `id in start..end`
+///
+/// Just like Rust's ranges, `start..end` includes `start` but excludes `end`.
+pub fn id(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
+    let mut filter = section.into_inner();
+    // The first word should be the `id` keyword.
+    let _id = filter.next().unwrap();
+
+    // Get the operator that should be applied in this filter.
+    // Can be either of [Rule::eq | Rule::neq | Rule::lt | Rule::gt | Rule::lte | Rule::gte]
+    // or the `in` keyword of a range.
+    let operator = filter.next().unwrap().as_rule();
+
+    let operand = filter.next().unwrap();
+    if operator == Rule::in_range {
+        let mut bounds = operand.into_inner();
+        let start = parse_task_id(bounds.next().unwrap().as_str())?;
+        let end = parse_task_id(bounds.next().unwrap().as_str())?;
+        if start > end {
+            bail!("Failed to parse query: Invalid id range {start}..{end}, start is after end");
+        }
+
+        let range = start..end;
+        return Ok(Box::new(move |task: &Task| -> bool {
+            range.contains(&task.id)
+        }));
+    }
+
+    // Get the id we should compare with.
+    let operand = parse_task_id(operand.as_str())?;
+
+    // Build the id filter function.
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
+        match operator {
+            Rule::eq => task.id == operand,
+            Rule::neq => task.id != operand,
+            Rule::lt => task.id < operand,
+            Rule::gt => task.id > operand,
+            Rule::lte => task.id <= operand,
+            Rule::gte => task.id >= operand,
+            _ => false,
+        }
+    });
+
+    Ok(filter_function)
+}
+
+/// Parse the task id of an id filter.
+fn parse_task_id(task_id: &str) -> Result<usize> {
+    task_id
+        .parse()
+        .with_context(|| format!("Failed to parse query: Invalid task id \"{task_id}\""))
+}
+
+/// Parse a filter for the priority field.
+///
+/// This filter syntax looks like this:
+/// `priority [=|!=|<|>|<=|>=] integer`
+///
+/// The integer may be negative, e.g. `priority<-1`.
+pub fn priority(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
+    let mut filter = section.into_inner();
+    // The first word should be the `priority` keyword.
+    let _priority = filter.next().unwrap();
+
+    // Get the operator that should be applied in this filter.
+    // Can be either of [Rule::eq | Rule::neq | Rule::lt | Rule::gt | Rule::lte | Rule::gte].
+    let operator = filter.next().unwrap().as_rule();
+
+    // Get the priority we should compare with.
+    let operand = filter.next().unwrap().as_str();
+    let operand: i32 = operand
+        .parse()
+        .with_context(|| format!("Failed to parse query: Invalid priority \"{operand}\""))?;
+
+    // Build the priority filter function.
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
+        match operator {
+            Rule::eq => task.priority == operand,
+            Rule::neq => task.priority != operand,
+            Rule::lt => task.priority < operand,
+            Rule::gt => task.priority > operand,
+            Rule::lte => task.priority <= operand,
+            Rule::gte => task.priority >= operand,
+            _ => false,
+        }
+    });
+
+    Ok(filter_function)
+}
+
+/// Compile the operand of a `~=` filter to a regex.
+/// This is done once while parsing, so the regex doesn't have to be compiled for each task.
+fn compile_regex(operator: Rule, operand: &str) -> Result<Option<Regex>> {
+    if operator != Rule::regex_match {
+        return Ok(None);
+    }
+
+    let regex = Regex::new(operand)
+        .with_context(|| format!("Failed to parse query: Invalid regex \"{operand}\""))?;
+    Ok(Some(regex))
 }
 
 /// Parse a filter for the status field.
@@ -329,7 +490,7 @@ pub fn command(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Resul
 ///         },
 ///     ],
 /// }
-pub fn status(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<()> {
+pub fn status(section: Pair<'_, Rule>) -> Result<Box<FilterFunction>> {
     let mut filter = section.into_inner();
     // The first word should be the `status` keyword.
     let _status = filter.next().unwrap();
@@ -342,12 +503,13 @@ pub fn status(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result
     let operand = filter.next().unwrap().as_rule();
 
     // Build the filter function for the task's status.
-    let filter_function = Box::new(move |task: &Task| -> bool {
+    let filter_function: Box<FilterFunction> = Box::new(move |task: &Task| -> bool {
         let matches = match operand {
             Rule::status_queued => matches!(task.status, TaskStatus::Queued { .. }),
             Rule::status_stashed => matches!(task.status, TaskStatus::Stashed { .. }),
             Rule::status_running => matches!(task.status, TaskStatus::Running { .. }),
             Rule::status_paused => matches!(task.status, TaskStatus::Paused { .. }),
+            Rule::status_locked => matches!(task.status, TaskStatus::Locked { .. }),
             Rule::status_success => {
                 matches!(
                     &task.status,
@@ -375,7 +537,6 @@ pub fn status(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result
             _ => false,
         }
     });
-    query_result.filters.push(filter_function);
 
-    Ok(())
+    Ok(filter_function)
 }
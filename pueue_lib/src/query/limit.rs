@@ -5,15 +5,17 @@ use super::{QueryResult, Rule};
 
 /// An enum indicating whether the first or the first or the last tasks in the
 /// `pueue status` command should be shown.
+///
+/// The offset is the amount of tasks that's skipped, counted from the respective end of the list.
 pub enum Limit {
-    First,
-    Last,
+    First { offset: usize },
+    Last { offset: usize },
 }
 
 /// Parse a limit condition.
 ///
 /// This limit syntax looks like this:
-/// `[first|last] [count]`
+/// `[first|last] [count] (offset [offset])?`
 ///
 /// The data structure looks something like this:
 /// Pair {
@@ -47,12 +49,7 @@ pub enum Limit {
 pub fn limit(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<()> {
     let mut limit_condition = section.into_inner();
     // The first word should be the `label` keyword.
-    let direction = limit_condition.next().unwrap();
-    let direction = match direction.as_rule() {
-        Rule::first => Limit::First,
-        Rule::last => Limit::Last,
-        _ => bail!("Expected either of [first|last]"),
-    };
+    let direction = limit_condition.next().unwrap().as_rule();
 
     // Get the label we should order by.
     let amount = limit_condition.next().unwrap();
@@ -65,6 +62,21 @@ pub fn limit(section: Pair<'_, Rule>, query_result: &mut QueryResult) -> Result<
         bail!("Expected a number >0 for limit condition");
     }
 
+    // Get the optional offset, which follows the `offset` keyword.
+    let offset: usize = match limit_condition.nth(1) {
+        Some(offset) => offset
+            .as_str()
+            .parse()
+            .context("Expected a number for the offset of the limit condition")?,
+        None => 0,
+    };
+
+    let direction = match direction {
+        Rule::first => Limit::First { offset },
+        Rule::last => Limit::Last { offset },
+        _ => bail!("Expected either of [first|last]"),
+    };
+
     query_result.limit = Some((direction, count));
     Ok(())
 }
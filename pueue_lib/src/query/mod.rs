@@ -0,0 +1,342 @@
+// Clippy generates a false-positive for an empty generated docstring in the query parser code.
+#![allow(clippy::empty_docs)]
+
+use std::cmp::Ordering;
+
+use anyhow::{bail, Context, Result};
+use chrono::prelude::*;
+use pest::{iterators::Pair, Parser};
+use pest_derive::Parser;
+
+use crate::error::Error;
+use crate::task::{Task, TaskStatus};
+
+mod column_selection;
+mod filters;
+mod limit;
+mod order_by;
+
+use limit::Limit;
+use order_by::Direction;
+
+/// See the pest docs on how this derive macro works and how to use pest:
+/// https://docs.rs/pest/latest/pest/
+#[derive(Parser)]
+#[grammar = "./src/query/syntax.pest"]
+struct QueryParser;
+
+type FilterFunction = dyn Fn(&Task) -> bool;
+
+/// A boolean expression of filters, as built from the `and`/`or` keywords and parenthesis of
+/// a query.
+enum FilterExpression {
+    Filter(Box<FilterFunction>),
+    And(Vec<FilterExpression>),
+    Or(Vec<FilterExpression>),
+}
+
+impl FilterExpression {
+    /// Check whether a task matches this expression.
+    /// Evaluation short-circuits as soon as the result of an `and`/`or` is known.
+    fn matches(&self, task: &Task) -> bool {
+        match self {
+            FilterExpression::Filter(filter) => filter(task),
+            FilterExpression::And(expressions) => expressions.iter().all(|expr| expr.matches(task)),
+            FilterExpression::Or(expressions) => expressions.iter().any(|expr| expr.matches(task)),
+        }
+    }
+}
+
+/// All applicable information that has been extracted from the query.
+#[derive(Default)]
+pub struct QueryResult {
+    /// Filter results for a single group.
+    group: Option<String>,
+
+    /// The list of selected columns based.
+    pub selected_columns: Vec<Rule>,
+
+    /// The filter expression that should be applied to the list of tasks.
+    filter: Option<FilterExpression>,
+
+    /// The columns the tasks should be ordered by.
+    /// Further columns are used to break ties of the previous ones.
+    order_by: Vec<(Rule, Direction)>,
+
+    /// Limit
+    limit: Option<(Limit, usize)>,
+}
+
+impl QueryResult {
+    /// Take a list of tasks and apply all filters to it.
+    pub fn apply_filters(&self, tasks: Vec<Task>) -> Vec<Task> {
+        let mut iter = tasks.into_iter();
+
+        // If requested, only look at tasks of a specific group.
+        if let Some(group) = &self.group {
+            iter = iter
+                .filter(|task| task.group == *group)
+                .collect::<Vec<Task>>()
+                .into_iter();
+        }
+
+        match &self.filter {
+            Some(filter) => iter.filter(|task| filter.matches(task)).collect(),
+            None => iter.collect(),
+        }
+    }
+
+    /// Take a list of tasks and order them by the requested columns.
+    pub fn order_tasks(&self, mut tasks: Vec<Task>) -> Vec<Task> {
+        // Only apply ordering if it was requested.
+        if self.order_by.is_empty() {
+            return tasks;
+        }
+
+        // Sort the tasks by the specified columns.
+        // Each further column is only used to break ties of the previous columns.
+        // The runtime of running tasks is measured at the same point in time for all tasks.
+        let now = Local::now();
+        tasks.sort_by(|task1, task2| {
+            self.order_by
+                .iter()
+                .fold(Ordering::Equal, |ordering, (sort_column, direction)| {
+                    ordering.then_with(|| {
+                        let ordering = compare_by_column(*sort_column, task1, task2, now);
+                        match direction {
+                            Direction::Ascending => ordering,
+                            Direction::Descending => ordering.reverse(),
+                        }
+                    })
+                })
+        });
+
+        tasks
+    }
+
+    /// Take a list of tasks and apply all filters to it.
+    pub fn limit_tasks(&self, tasks: Vec<Task>) -> Vec<Task> {
+        // Only apply limits if it was requested.
+        let Some((direction, count)) = &self.limit else {
+            return tasks;
+        };
+
+        // Determine the range of tasks that should be kept.
+        // The range is clamped to the list, an offset past its end results in an empty list.
+        let len = tasks.len();
+        let (start, end) = match direction {
+            Limit::First { offset } => {
+                let start = (*offset).min(len);
+                (start, start.saturating_add(*count).min(len))
+            }
+            Limit::Last { offset } => {
+                let end = len.saturating_sub(*offset);
+                (end.saturating_sub(*count), end)
+            }
+        };
+
+        tasks[start..end].to_vec()
+    }
+}
+
+/// Compare two tasks by the value of the given column.
+/// Tasks that haven't been started yet have no runtime and come before all other tasks.
+fn compare_by_column(
+    sort_column: Rule,
+    task1: &Task,
+    task2: &Task,
+    now: DateTime<Local>,
+) -> Ordering {
+    match sort_column {
+        Rule::column_id => task1.id.cmp(&task2.id),
+        Rule::column_status => {
+            /// Rank a task status to allow ordering by status.
+            /// Waiting tasks come first, followed by active and terminal tasks.
+            fn rank_status(task: &Task) -> u8 {
+                if task.is_terminal() {
+                    return if task.failed() { 5 } else { 6 };
+                }
+                if task.is_active() {
+                    return if task.is_paused() { 3 } else { 4 };
+                }
+
+                match &task.status {
+                    TaskStatus::Stashed { .. } => 0,
+                    TaskStatus::Locked { .. } => 1,
+                    // Queued tasks
+                    _ => 2,
+                }
+            }
+
+            rank_status(task1).cmp(&rank_status(task2))
+        }
+        Rule::column_label => task1.label.cmp(&task2.label),
+        Rule::column_command => task1.command.cmp(&task2.command),
+        Rule::column_path => task1.path.cmp(&task2.path),
+        Rule::column_enqueue_at => {
+            fn enqueue_date(task: &Task) -> DateTime<Local> {
+                match &task.status {
+                    TaskStatus::Queued { enqueued_at, .. }
+                    | TaskStatus::Running { enqueued_at, .. }
+                    | TaskStatus::Paused { enqueued_at, .. }
+                    | TaskStatus::Done { enqueued_at, .. }
+                    | TaskStatus::Stashed {
+                        enqueue_at: Some(enqueued_at),
+                        ..
+                    } => *enqueued_at,
+                    // considered far in the future when no explicit date:
+                    _ => DateTime::<Utc>::MAX_UTC.into(),
+                }
+            }
+
+            enqueue_date(task1).cmp(&enqueue_date(task2))
+        }
+        Rule::column_start => {
+            let (start1, _) = task1.start_and_end();
+            let (start2, _) = task2.start_and_end();
+            start1.cmp(&start2)
+        }
+        Rule::column_end => {
+            let (_, end1) = task1.start_and_end();
+            let (_, end2) = task2.start_and_end();
+            end1.cmp(&end2)
+        }
+        Rule::column_worker => task1.worker_id.cmp(&task2.worker_id),
+        Rule::column_runtime => task1.duration_at(now).cmp(&task2.duration_at(now)),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Parse a given `pueue status QUERY` into a [QueryResult], which can then be applied to a list
+/// of tasks.
+///
+/// If a `group` is given, only tasks of that group are kept by [QueryResult::apply_filters].
+/// A `columns [columns]` statement defines the set of visible columns, which is exposed via
+/// [QueryResult::selected_columns].
+///
+/// ```
+/// use pueue_lib::query::apply_query;
+///
+/// let query_result = apply_query("status=running order_by start desc first 5", None)?;
+/// let tasks = query_result.apply_filters(Vec::new());
+/// let tasks = query_result.order_tasks(tasks);
+/// let _tasks = query_result.limit_tasks(tasks);
+/// # Ok::<(), pueue_lib::error::Error>(())
+/// ```
+pub fn apply_query(query: &str, group: Option<&str>) -> Result<QueryResult, Error> {
+    parse_query(query, group).map_err(|err| Error::InvalidQuery(format!("{err:#}")))
+}
+
+fn parse_query(query: &str, group: Option<&str>) -> Result<QueryResult> {
+    let mut parsed = QueryParser::parse(Rule::query, query).context("Failed to parse query")?;
+
+    let mut query_result = QueryResult {
+        group: group.map(ToString::to_string),
+        ..Default::default()
+    };
+
+    // Expect there to be exactly one pair for the full query.
+    // Return early if we got an empty query.
+    let Some(parsed) = parsed.next() else {
+        return Ok(query_result);
+    };
+
+    // Make sure we really got a query.
+    if parsed.as_rule() != Rule::query {
+        bail!("Expected a valid query");
+    }
+
+    // Get the sections of the query
+    let sections = parsed.into_inner();
+    // Go through each section and handle it accordingly
+    for section in sections {
+        // The `columns=[columns]` section
+        // E.g. `columns=id,status,start,end`
+        match section.as_rule() {
+            Rule::column_selection => column_selection::apply(section, &mut query_result)?,
+            Rule::filter_expression => {
+                query_result.filter = Some(parse_filter_expression(section)?)
+            }
+            Rule::order_by_condition => order_by::order_by(section, &mut query_result)?,
+            Rule::limit_condition => limit::limit(section, &mut query_result)?,
+            _ => (),
+        }
+    }
+
+    Ok(query_result)
+}
+
+/// Build the boolean expression tree of a `filter_expression` section.
+///
+/// The expression is a flat list of filters and parenthesized groups, separated by optional
+/// `and`/`or` keywords. A missing keyword means `and`, which binds stronger than `or`.
+/// The list is therefore split at each `or` into groups of filters that are `and`ed together.
+fn parse_filter_expression(section: Pair<'_, Rule>) -> Result<FilterExpression> {
+    let mut alternatives = Vec::new();
+    let mut conjunction = Vec::new();
+
+    for term in section.into_inner() {
+        let expression = match term.as_rule() {
+            Rule::and => continue,
+            Rule::or => {
+                alternatives.push(FilterExpression::And(std::mem::take(&mut conjunction)));
+                continue;
+            }
+            Rule::filter_group => {
+                let inner = term
+                    .into_inner()
+                    .next()
+                    .context("Expected an expression in parenthesis")?;
+                parse_filter_expression(inner)?
+            }
+            Rule::datetime_filter => FilterExpression::Filter(filters::datetime(term)?),
+            Rule::runtime_filter => FilterExpression::Filter(filters::runtime(term)?),
+            Rule::label_filter => FilterExpression::Filter(filters::label(term)?),
+            Rule::command_filter => FilterExpression::Filter(filters::command(term)?),
+            Rule::status_filter => FilterExpression::Filter(filters::status(term)?),
+            Rule::priority_filter => FilterExpression::Filter(filters::priority(term)?),
+            Rule::id_filter => FilterExpression::Filter(filters::id(term)?),
+            _ => continue,
+        };
+        conjunction.push(expression);
+    }
+
+    // Without any `or`, this behaves exactly like a plain list of filters.
+    if alternatives.is_empty() {
+        return Ok(FilterExpression::And(conjunction));
+    }
+    alternatives.push(FilterExpression::And(conjunction));
+
+    Ok(FilterExpression::Or(alternatives))
+}
+
+/// Apply a `pueue status --sort [column]:[asc|desc]` shortcut to an already parsed query.
+///
+/// The shortcut is translated into the respective `order_by` condition of the query syntax.
+/// It's an error, if the query already contains an `order_by` condition.
+pub fn apply_sort(sort: &str, query_result: &mut QueryResult) -> Result<(), Error> {
+    parse_sort(sort, query_result).map_err(|err| Error::InvalidQuery(format!("{err:#}")))
+}
+
+fn parse_sort(sort: &str, query_result: &mut QueryResult) -> Result<()> {
+    if !query_result.order_by.is_empty() {
+        bail!("`--sort` cannot be combined with an `order_by` condition in the query");
+    }
+
+    let (column, direction) = sort.split_once(':').unwrap_or((sort, "asc"));
+    if !["asc", "desc"].contains(&direction.to_lowercase().as_str()) {
+        bail!("Invalid sort direction \"{direction}\", expected `asc` or `desc`");
+    }
+
+    let condition = format!("order_by {column} {direction}");
+    let section = QueryParser::parse(Rule::order_by_condition, &condition)
+        .with_context(|| format!("Invalid sort column \"{column}\""))?
+        .next()
+        .context("Expected an order_by condition")?;
+    // The rule matches prefixes, e.g. `id` of `idx`, which is why the whole input is checked.
+    if section.as_str() != condition {
+        bail!("Invalid sort column \"{column}\"");
+    }
+
+    order_by::order_by(section, query_result)
+}
@@ -1,11 +1,13 @@
+use crate::settings::Hosts;
+
 /// The `Default` impl for `bool` is `false`.
 /// This function covers the `true` case.
 pub(crate) fn default_true() -> bool {
     true
 }
 
-pub(crate) fn default_host() -> String {
-    "127.0.0.1".to_string()
+pub(crate) fn default_host() -> Hosts {
+    Hosts::from("127.0.0.1")
 }
 
 pub(crate) fn default_port() -> String {
@@ -20,6 +22,18 @@ pub(crate) fn default_status_datetime_format() -> String {
     "%Y-%m-%d\n%H:%M:%S".to_string()
 }
 
+pub(crate) fn default_max_connections() -> usize {
+    100
+}
+
+pub(crate) fn default_listen_backlog() -> u32 {
+    1024
+}
+
 pub(crate) fn default_callback_log_lines() -> usize {
     10
 }
+
+pub(crate) fn default_group_stats_window() -> u64 {
+    3600
+}
@@ -3,8 +3,10 @@ use std::fs::{create_dir_all, File};
 use std::io::{prelude::*, BufReader};
 use std::path::{Path, PathBuf};
 
+use chrono::{DateTime, Local};
 use log::info;
-use serde::{Deserialize, Serialize};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use shellexpand::tilde;
 
 use crate::error::Error;
@@ -13,6 +15,9 @@ use crate::setting_defaults::*;
 /// The environment variable that can be set to overwrite pueue's config path.
 pub const PUEUE_CONFIG_PATH_ENV: &str = "PUEUE_CONFIG_PATH";
 
+/// The config path that tells pueue to read its configuration from stdin.
+pub const CONFIG_FROM_STDIN: &str = "-";
+
 /// All settings which are used by both, the client and the daemon
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct Shared {
@@ -52,13 +57,34 @@ pub struct Shared {
     /// read/write permissions.
     #[cfg(not(target_os = "windows"))]
     pub unix_socket_permissions: Option<u32>,
+    /// Skip the shared secret handshake for connections via the unix socket and rely on the
+    /// socket's filesystem permissions for authentication instead.
+    /// If enabled, the daemon restricts the socket to its owner (`0o600`), regardless of
+    /// `unix_socket_permissions`. TCP connections always require the shared secret.
+    #[cfg(not(target_os = "windows"))]
+    #[serde(default = "Default::default")]
+    pub unix_socket_trust_permissions: bool,
 
-    /// The TCP hostname/ip address.
+    /// The TCP hostname/ip address. \
+    /// Multiple hosts can be given as a list, e.g. `["127.0.0.1", "::1"]`. The daemon listens on
+    /// all of them, while the client connects to the first one that's reachable. \
+    /// Don't combine `::` and `0.0.0.0`, as `::` usually covers IPv4 as well.
     #[serde(default = "default_host")]
-    pub host: String,
+    pub host: Hosts,
     /// The TCP port.
     #[serde(default = "default_port")]
     pub port: String,
+    /// The maximum amount of concurrent client connections the daemon handles.
+    /// Any connections beyond this limit are rejected with an error message.
+    #[serde(
+        default = "default_max_connections",
+        deserialize_with = "deserialize_max_connections"
+    )]
+    pub max_connections: usize,
+    /// The maximum amount of pending connections, that the operating system queues up for the
+    /// daemon's socket, before it refuses new ones.
+    #[serde(default = "default_listen_backlog")]
+    pub listen_backlog: u32,
 
     /// The path where the daemon's PID is located.
     /// This is by default in `runtime_directory/pueue.pid`.
@@ -92,6 +118,150 @@ pub enum EditMode {
     Files,
 }
 
+/// The shell command that's used to execute tasks.
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ShellCommand {
+    /// The same command is used on all platforms.
+    All(Vec<String>),
+    /// A separate command for each platform.
+    PerPlatform {
+        unix: Option<Vec<String>>,
+        windows: Option<Vec<String>>,
+    },
+}
+
+impl ShellCommand {
+    /// Get the command for the platform we're running on.
+    /// `None` means that the platform's default should be used.
+    pub fn for_current_platform(&self) -> Option<&Vec<String>> {
+        match self {
+            ShellCommand::All(command) => Some(command),
+            #[cfg(not(target_os = "windows"))]
+            ShellCommand::PerPlatform { unix, .. } => unix.as_ref(),
+            #[cfg(target_os = "windows")]
+            ShellCommand::PerPlatform { windows, .. } => windows.as_ref(),
+        }
+    }
+}
+
+impl From<Vec<String>> for ShellCommand {
+    fn from(command: Vec<String>) -> Self {
+        ShellCommand::All(command)
+    }
+}
+
+/// The TCP hosts the daemon listens on.
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Hosts {
+    /// A single host.
+    Single(String),
+    /// Multiple hosts, e.g. the IPv4 and IPv6 loopback addresses on a dual-stack machine.
+    Multiple(Vec<String>),
+}
+
+impl Hosts {
+    /// Get all hosts as a slice.
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Hosts::Single(host) => std::slice::from_ref(host),
+            Hosts::Multiple(hosts) => hosts,
+        }
+    }
+}
+
+impl From<&str> for Hosts {
+    fn from(host: &str) -> Self {
+        Hosts::Single(host.to_string())
+    }
+}
+
+impl From<Vec<String>> for Hosts {
+    fn from(hosts: Vec<String>) -> Self {
+        Hosts::Multiple(hosts)
+    }
+}
+
+/// A list of regex patterns of commands that're rejected by the daemon.
+///
+/// The patterns are compiled once while the configuration is read, which is why invalid
+/// patterns result in an error while reading the configuration.
+#[derive(Clone, Debug, Default)]
+pub struct CommandDenylist(Vec<Regex>);
+
+impl CommandDenylist {
+    /// Compile the given patterns.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, regex::Error> {
+        let regexes = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern.as_ref()))
+            .collect::<Result<Vec<Regex>, regex::Error>>()?;
+        Ok(CommandDenylist(regexes))
+    }
+
+    /// Return the first pattern that matches the given command, if any.
+    pub fn find_match(&self, command: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|regex| regex.is_match(command))
+            .map(Regex::as_str)
+    }
+}
+
+impl PartialEq for CommandDenylist {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|(left, right)| left.as_str() == right.as_str())
+    }
+}
+
+impl Eq for CommandDenylist {}
+
+impl Serialize for CommandDenylist {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(Regex::as_str))
+    }
+}
+
+impl<'de> Deserialize<'de> for CommandDenylist {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let patterns = Vec::<String>::deserialize(deserializer)?;
+        CommandDenylist::new(&patterns).map_err(|err| {
+            serde::de::Error::custom(format!("Invalid pattern in command_denylist: {err}"))
+        })
+    }
+}
+
+/// The daemon couldn't handle any connection without at least one allowed connection.
+fn deserialize_max_connections<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<usize, D::Error> {
+    let max_connections = usize::deserialize(deserializer)?;
+    if max_connections == 0 {
+        return Err(serde::de::Error::custom(
+            "max_connections must be greater than 0",
+        ));
+    }
+
+    Ok(max_connections)
+}
+
+/// How timestamps are displayed by the client.
+#[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// Use the `status_time_format` and `status_datetime_format` strftime patterns.
+    #[default]
+    Strftime,
+    /// Always display the full date and time as RFC3339, e.g. `2024-01-31T18:30:00+01:00`.
+    Rfc3339,
+}
+
 /// All settings which are used by the client
 #[derive(PartialEq, Eq, Clone, Debug, Deserialize, Serialize)]
 pub struct Client {
@@ -125,6 +295,32 @@ pub struct Client {
     /// The format that will be used to display datetime formats in `pueue status`.
     #[serde(default = "default_status_datetime_format")]
     pub status_datetime_format: String,
+    /// Whether timestamps are formatted via the strftime patterns above or as RFC3339.
+    #[serde(default = "Default::default")]
+    pub timestamp_format: TimestampFormat,
+    /// Show the start and end of tasks and the enqueue time of scheduled tasks in `pueue status`
+    /// relative to the current time, e.g. `5m ago` or `in 10m`.
+    #[serde(default = "Default::default")]
+    pub relative_times: bool,
+    /// The order of the columns in `pueue status`, e.g. `["id", "command", "status"]`.
+    /// Unknown column names are ignored and visible columns that aren't listed are appended in
+    /// their default order.
+    #[serde(default = "Default::default")]
+    pub column_order: Vec<String>,
+    /// Colors for the headlines of specific groups in `pueue status` and `pueue group`,
+    /// e.g. `prod: red`. Groups without a color use the default styling.
+    #[serde(default = "Default::default")]
+    pub group_colors: HashMap<String, String>,
+    /// Named queries for `pueue status`, which can be recalled via `pueue status --view [name]`.
+    /// New views are added via `pueue status --save-view [name] [query]`.
+    #[serde(default = "Default::default")]
+    pub views: HashMap<String, String>,
+    /// A command that's executed locally by the client whenever a task finishes, while the
+    /// client waits for it via `pueue wait` or follows it via `pueue follow`.
+    /// Information about the task is passed via the `PUEUE_TASK_ID`, `PUEUE_TASK_GROUP`,
+    /// `PUEUE_TASK_COMMAND`, `PUEUE_TASK_RESULT` and `PUEUE_TASK_EXIT_CODE` environment variables.
+    /// Only works for `pueue follow`, if `read_local_logs` is enabled.
+    pub on_complete_command: Option<String>,
 }
 
 /// All settings which are used by the daemon
@@ -141,6 +337,11 @@ pub struct Daemon {
     /// Environment variables that can be will be injected into all executed processes.
     #[serde(default = "Default::default")]
     pub env_vars: HashMap<String, String>,
+    /// Environment variables that are injected into the processes of specific groups,
+    /// e.g. `build: {CARGO_TARGET_DIR: /tmp/target}`.
+    /// They take precedence over the global `env_vars`.
+    #[serde(default = "Default::default")]
+    pub group_env_vars: HashMap<String, HashMap<String, String>>,
     /// The amount of log lines from stdout/stderr that are passed to the callback command.
     #[serde(default = "default_callback_log_lines")]
     pub callback_log_lines: usize,
@@ -152,7 +353,46 @@ pub struct Daemon {
     ///
     /// Windows default:
     /// `vec!["powershell", "-c", "[Console]::OutputEncoding = [Text.UTF8Encoding]::UTF8; {{ pueue_command_string }}"]`
-    pub shell_command: Option<Vec<String>>,
+    ///
+    /// This can either be a single command for all platforms or a map with a `unix` and/or
+    /// `windows` command. Platforms without an entry use their default.
+    pub shell_command: Option<ShellCommand>,
+    /// Shell commands that are used for the tasks of specific groups instead of `shell_command`,
+    /// e.g. `scripts: [bash, -c, '{{ pueue_command_string }}']`.
+    #[serde(default = "Default::default")]
+    pub group_shell_commands: HashMap<String, ShellCommand>,
+    /// The maximum length of a single line in a task's log in bytes.
+    /// Longer lines are truncated and marked with an ellipsis (`…`).
+    /// By default, lines aren't truncated.
+    pub max_log_line_length: Option<usize>,
+    /// Prefix each line of a task's output with the time it has been written at.
+    /// The timestamps can be hidden via `pueue log --no-timestamps`.
+    #[serde(default = "Default::default")]
+    pub timestamp_logs: bool,
+    /// Whether each task should keep a history of its status changes and when they happened.
+    #[serde(default = "Default::default")]
+    pub track_status_history: bool,
+    /// The time window in seconds that's used to compute the throughput of groups.
+    /// Only tasks that finished inside this window are considered.
+    #[serde(default = "default_group_stats_window")]
+    pub group_stats_window: u64,
+    /// Groups that are managed via the configuration, mapped to their amount of parallel tasks,
+    /// e.g. `build: 4`. Missing groups are created and their parallel tasks are set on startup
+    /// and whenever the configuration is reloaded. Groups that are removed from this list are
+    /// removed on reload, as long as they don't contain any tasks.
+    #[serde(default = "Default::default")]
+    pub groups: HashMap<String, usize>,
+    /// If set, groups that don't contain any tasks are removed automatically, once they've been
+    /// idle for this amount of seconds. The default group is never removed.
+    pub auto_remove_empty_groups: Option<u64>,
+    /// Regex patterns of commands that aren't allowed to be added, e.g. `^rm -rf /$`.
+    /// Both the command and its expanded alias are checked.
+    #[serde(default = "Default::default")]
+    pub command_denylist: CommandDenylist,
+    /// If set, changes to the state are appended to a write-ahead log instead of writing the
+    /// full state on every change. Once the log contains this many records, it's compacted into
+    /// a full snapshot of the state.
+    pub state_wal_max_records: Option<usize>,
 }
 
 impl Default for Shared {
@@ -168,8 +408,12 @@ impl Default for Shared {
             use_unix_socket: true,
             #[cfg(not(target_os = "windows"))]
             unix_socket_permissions: Some(0o700),
+            #[cfg(not(target_os = "windows"))]
+            unix_socket_trust_permissions: false,
             host: default_host(),
             port: default_port(),
+            max_connections: default_max_connections(),
+            listen_backlog: default_listen_backlog(),
 
             pid_path: None,
             daemon_cert: None,
@@ -191,6 +435,12 @@ impl Default for Client {
             max_status_lines: None,
             status_time_format: default_status_time_format(),
             status_datetime_format: default_status_datetime_format(),
+            timestamp_format: Default::default(),
+            relative_times: false,
+            column_order: Vec::new(),
+            group_colors: HashMap::new(),
+            views: HashMap::new(),
+            on_complete_command: None,
         }
     }
 }
@@ -203,7 +453,17 @@ impl Default for Daemon {
             callback: None,
             callback_log_lines: default_callback_log_lines(),
             shell_command: None,
+            group_shell_commands: HashMap::new(),
             env_vars: HashMap::new(),
+            group_env_vars: HashMap::new(),
+            max_log_line_length: None,
+            timestamp_logs: false,
+            track_status_history: false,
+            group_stats_window: default_group_stats_window(),
+            groups: HashMap::new(),
+            auto_remove_empty_groups: None,
+            command_denylist: CommandDenylist::default(),
+            state_wal_max_records: None,
         }
     }
 }
@@ -291,6 +551,19 @@ impl Shared {
         }
     }
 
+    /// Whether the shared secret handshake is skipped, as the connection happens via a unix
+    /// socket that's only accessible by its owner.
+    pub fn skip_secret_handshake(&self) -> bool {
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.use_unix_socket && self.unix_socket_trust_permissions
+        }
+        #[cfg(target_os = "windows")]
+        {
+            false
+        }
+    }
+
     /// The location of the alias file used by the daemon/client when working with
     /// task aliases.
     pub fn alias_file(&self) -> PathBuf {
@@ -338,27 +611,66 @@ impl Shared {
     }
 }
 
+impl Client {
+    /// Format a timestamp for display.
+    ///
+    /// With the default strftime formatting, the date is only shown if `with_date` is set.
+    /// RFC3339 timestamps always contain the full date and time.
+    pub fn format_timestamp(&self, timestamp: &DateTime<Local>, with_date: bool) -> String {
+        match self.timestamp_format {
+            TimestampFormat::Rfc3339 => timestamp.to_rfc3339(),
+            TimestampFormat::Strftime if with_date => {
+                timestamp.format(&self.status_datetime_format).to_string()
+            }
+            TimestampFormat::Strftime => timestamp.format(&self.status_time_format).to_string(),
+        }
+    }
+}
+
+impl Daemon {
+    /// Get the shell command for the current platform that should be used for the given group.
+    /// The group's own shell command takes precedence over the global one.
+    /// `None` means that the platform's default should be used.
+    pub fn shell_command_for_group(&self, group: Option<&str>) -> Option<&Vec<String>> {
+        group
+            .and_then(|group| self.group_shell_commands.get(group))
+            .and_then(ShellCommand::for_current_platform)
+            .or_else(|| {
+                self.shell_command
+                    .as_ref()
+                    .and_then(ShellCommand::for_current_platform)
+            })
+    }
+}
+
 impl Settings {
     /// Try to read existing config files, while using default values for non-existing fields.
     /// If successful, this will return a full config as well as a boolean on whether we found an
     /// existing configuration file or not.
     ///
     /// The default local config locations depends on the current target.
+    ///
+    /// If the path is `-`, the configuration is read from stdin.
     pub fn read(from_file: &Option<PathBuf>) -> Result<(Settings, bool), Error> {
         // If no explicit path is provided, we look for the PUEUE_CONFIG_PATH env variable.
         let from_file = from_file
             .clone()
             .or_else(|| std::env::var(PUEUE_CONFIG_PATH_ENV).map(PathBuf::from).ok());
 
+        // Load the config from stdin.
+        // This counts as a found config, so no default config file is created.
+        if from_file.as_deref() == Some(Path::new(CONFIG_FROM_STDIN)) {
+            info!("Reading config from stdin");
+            let settings = Settings::from_reader(std::io::stdin().lock())?;
+            return Ok((settings, true));
+        }
+
         // Load the config from a very specific file path
         if let Some(path) = &from_file {
             // Open the file in read-only mode with buffer.
             let file = File::open(path)
                 .map_err(|err| Error::IoPathError(path.clone(), "opening config file", err))?;
-            let reader = BufReader::new(file);
-
-            let settings = serde_yaml::from_reader(reader)
-                .map_err(|err| Error::ConfigDeserialization(err.to_string()))?;
+            let settings = Settings::from_reader(BufReader::new(file))?;
             return Ok((settings, true));
         };
 
@@ -376,10 +688,7 @@ impl Settings {
                 // Open the file in read-only mode with buffer.
                 let file = File::open(&path)
                     .map_err(|err| Error::IoPathError(path, "opening config file.", err))?;
-                let reader = BufReader::new(file);
-
-                let settings = serde_yaml::from_reader(reader)
-                    .map_err(|err| Error::ConfigDeserialization(err.to_string()))?;
+                let settings = Settings::from_reader(BufReader::new(file))?;
                 return Ok((settings, true));
             }
         }
@@ -389,6 +698,12 @@ impl Settings {
         Ok((Settings::default(), false))
     }
 
+    /// Parse a configuration from any reader, e.g. a file or stdin.
+    /// Missing fields are filled with their default values.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Settings, Error> {
+        serde_yaml::from_reader(reader).map_err(|err| Error::ConfigDeserialization(err.to_string()))
+    }
+
     /// Save the current configuration as a file to the given path. \
     /// If no path is given, the default configuration path will be used. \
     /// The file is then written to the main configuration directory of the respective OS.
@@ -472,7 +787,7 @@ mod test {
         let mut profile = Settings::default();
         profile.client.status_time_format = "test".to_string();
         profile.daemon.callback_log_lines = 100_000;
-        profile.shared.host = "quatschhost".to_string();
+        profile.shared.host = "quatschhost".into();
         let profile = NestedSettings {
             client: profile.client,
             daemon: profile.daemon,
@@ -488,7 +803,7 @@ mod test {
 
         assert_eq!(settings.client.status_time_format, "test");
         assert_eq!(settings.daemon.callback_log_lines, 100_000);
-        assert_eq!(settings.shared.host, "quatschhost");
+        assert_eq!(settings.shared.host, Hosts::from("quatschhost"));
     }
 
     /// A proper pueue [Error] should be thrown if the profile cannot be found.
@@ -505,4 +820,177 @@ mod test {
 
         panic!("Got unexpected result when expecting missing profile error: {result:?}");
     }
+
+    /// RFC3339 formatting ignores the strftime patterns and always includes the date.
+    #[test]
+    fn test_format_timestamp_rfc3339() {
+        use chrono::{NaiveDate, TimeZone};
+
+        let naive = NaiveDate::from_ymd_opt(2024, 1, 31)
+            .unwrap()
+            .and_hms_opt(18, 30, 5)
+            .unwrap();
+        let timestamp = Local.from_local_datetime(&naive).unwrap();
+
+        let mut client = Client::default();
+        assert_eq!(client.format_timestamp(&timestamp, false), "18:30:05");
+
+        client.timestamp_format = TimestampFormat::Rfc3339;
+        let formatted = client.format_timestamp(&timestamp, false);
+        assert!(
+            formatted.starts_with("2024-01-31T18:30:05"),
+            "Unexpected RFC3339 output: {formatted}"
+        );
+        assert_eq!(DateTime::parse_from_rfc3339(&formatted).unwrap(), timestamp);
+    }
+
+    /// The shell command can be a plain list or a map with one entry per platform.
+    #[test]
+    fn test_shell_command_shapes() {
+        let list: ShellCommand = serde_yaml::from_str("[bash, -c, '{{ pueue_command_string }}']")
+            .expect("Failed to deserialize list");
+        assert_eq!(
+            list.for_current_platform(),
+            Some(&vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "{{ pueue_command_string }}".to_string()
+            ])
+        );
+
+        let per_platform: ShellCommand =
+            serde_yaml::from_str("unix: [bash, -c, x]\nwindows: [pwsh, -c, x]")
+                .expect("Failed to deserialize map");
+        let expected = if cfg!(target_os = "windows") {
+            vec!["pwsh".to_string(), "-c".to_string(), "x".to_string()]
+        } else {
+            vec!["bash".to_string(), "-c".to_string(), "x".to_string()]
+        };
+        assert_eq!(per_platform.for_current_platform(), Some(&expected));
+
+        // Platforms without an entry fall back to their default.
+        let unix_only: ShellCommand =
+            serde_yaml::from_str("unix: [bash, -c, x]").expect("Failed to deserialize map");
+        assert_eq!(
+            unix_only.for_current_platform().is_none(),
+            cfg!(target_os = "windows")
+        );
+    }
+
+    /// A group's shell command takes precedence over the global one.
+    #[test]
+    fn test_shell_command_for_group() {
+        let global = vec!["sh".to_string(), "-c".to_string(), "x".to_string()];
+        let group = vec!["bash".to_string(), "-c".to_string(), "x".to_string()];
+        let mut daemon = Daemon {
+            shell_command: Some(global.clone().into()),
+            ..Default::default()
+        };
+        daemon
+            .group_shell_commands
+            .insert("scripts".to_string(), group.clone().into());
+
+        assert_eq!(
+            daemon.shell_command_for_group(Some("scripts")),
+            Some(&group)
+        );
+        assert_eq!(
+            daemon.shell_command_for_group(Some("default")),
+            Some(&global)
+        );
+        assert_eq!(daemon.shell_command_for_group(None), Some(&global));
+
+        daemon.shell_command = None;
+        assert_eq!(daemon.shell_command_for_group(Some("default")), None);
+    }
+
+    /// Configs can be read from any reader, which is used for configs that are passed via stdin.
+    /// Profiles still apply to those configs.
+    #[test]
+    fn test_read_from_reader() -> Result<(), Error> {
+        let config = r#"
+daemon:
+  callback_log_lines: 5
+shared:
+  host: "otherhost"
+profiles:
+  testprofile:
+    client: {}
+    daemon:
+      callback_log_lines: 20
+    shared: {}
+"#;
+
+        let mut settings = Settings::from_reader(config.as_bytes())?;
+        assert_eq!(settings.daemon.callback_log_lines, 5);
+        assert_eq!(settings.shared.host, Hosts::from("otherhost"));
+        // Missing values are filled with defaults.
+        assert_eq!(
+            settings.client.status_time_format,
+            default_status_time_format()
+        );
+
+        settings.load_profile("testprofile")?;
+        assert_eq!(settings.daemon.callback_log_lines, 20);
+
+        Ok(())
+    }
+
+    /// The command denylist is compiled while reading the config, invalid patterns are an error.
+    #[test]
+    fn test_command_denylist() -> Result<(), Error> {
+        let config = r#"
+daemon:
+  command_denylist:
+    - "^rm -rf /$"
+    - "shutdown"
+"#;
+        let settings = Settings::from_reader(config.as_bytes())?;
+        let denylist = &settings.daemon.command_denylist;
+        assert_eq!(denylist.find_match("rm -rf /"), Some("^rm -rf /$"));
+        assert_eq!(denylist.find_match("sudo shutdown now"), Some("shutdown"));
+        assert_eq!(denylist.find_match("rm -rf /tmp/test"), None);
+
+        let config = r#"
+daemon:
+  command_denylist:
+    - "rm -rf ("
+"#;
+        let Err(Error::ConfigDeserialization(message)) = Settings::from_reader(config.as_bytes())
+        else {
+            panic!("Expected an invalid pattern to fail");
+        };
+        assert!(
+            message.contains("Invalid pattern in command_denylist"),
+            "Got unexpected error: {message}"
+        );
+
+        Ok(())
+    }
+
+    /// The daemon needs to accept at least a single connection.
+    #[test]
+    fn test_max_connections() -> Result<(), Error> {
+        let config = r#"
+shared:
+  max_connections: 5
+"#;
+        let settings = Settings::from_reader(config.as_bytes())?;
+        assert_eq!(settings.shared.max_connections, 5);
+
+        let config = r#"
+shared:
+  max_connections: 0
+"#;
+        let Err(Error::ConfigDeserialization(message)) = Settings::from_reader(config.as_bytes())
+        else {
+            panic!("Expected max_connections of 0 to fail");
+        };
+        assert!(
+            message.contains("max_connections must be greater than 0"),
+            "Got unexpected error: {message}"
+        );
+
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::process::Child;
 use std::sync::{Arc, Mutex};
 
@@ -29,6 +29,8 @@ pub enum GroupStatus {
 pub struct Group {
     pub status: GroupStatus,
     pub parallel_tasks: usize,
+    /// An optional note on what this group is used for.
+    pub description: Option<String>,
 }
 
 /// This is the full representation of the current state of the Pueue daemon.
@@ -65,6 +67,16 @@ pub struct State {
     /// This is runtime state and won't be serialised to disk.
     #[serde(default, skip)]
     pub shutdown: Option<Shutdown>,
+    /// The amount of parallel tasks for newly created groups, if it has been changed at runtime.
+    /// Groups are created with a single parallel task by default.
+    /// This is runtime state and won't be serialised to disk.
+    #[serde(default, skip)]
+    pub default_parallel_tasks: Option<usize>,
+    /// Tasks that're being killed and should be stashed again once their process exited, instead
+    /// of being marked as done. See `pueue pause --restash`.
+    /// This is runtime state and won't be serialised to disk.
+    #[serde(default, skip)]
+    pub stash_on_exit: HashSet<usize>,
 
     /// Pueue's subprocess and worker pool representation.
     /// Take a look at [Children] for more info.
@@ -83,6 +95,7 @@ impl Clone for State {
             tasks: self.tasks.clone(),
             groups: self.groups.clone(),
             shutdown: self.shutdown.clone(),
+            default_parallel_tasks: self.default_parallel_tasks,
             ..Default::default()
         }
     }
@@ -118,16 +131,52 @@ impl State {
 
     /// Add a new task
     pub fn add_task(&mut self, mut task: Task) -> usize {
-        let next_id = match self.tasks.keys().max() {
-            None => 0,
-            Some(id) => id + 1,
-        };
+        let next_id = self.next_task_id();
         task.id = next_id;
         self.tasks.insert(next_id, task);
 
         next_id
     }
 
+    /// The id that'll be assigned to the next task that's added.
+    pub fn next_task_id(&self) -> usize {
+        match self.tasks.keys().max() {
+            None => 0,
+            Some(id) => id + 1,
+        }
+    }
+
+    /// Check whether adding a new task with the given id and dependencies would result in a
+    /// dependency cycle.
+    ///
+    /// This can happen if existing tasks still depend on a task id that no longer exists and
+    /// which is going to be reused for the new task, or if an existing task is replaced.
+    pub fn would_create_cycle(&self, new_id: usize, dependencies: &[usize]) -> bool {
+        let mut visited = HashSet::new();
+        let mut to_visit: Vec<usize> = dependencies.to_vec();
+        while let Some(id) = to_visit.pop() {
+            if id == new_id {
+                return true;
+            }
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(task) = self.tasks.get(&id) {
+                to_visit.extend(task.dependencies.iter().copied());
+            }
+        }
+
+        false
+    }
+
+    /// Get the id of the task with the given key.
+    pub fn task_id_by_key(&self, key: &str) -> Option<usize> {
+        self.tasks
+            .iter()
+            .find(|(_, task)| task.key.as_deref() == Some(key))
+            .map(|(id, _)| *id)
+    }
+
     /// A small helper to change the status of a specific task.
     pub fn change_status(&mut self, id: usize, new_status: TaskStatus) {
         if let Some(ref mut task) = self.tasks.get_mut(&id) {
@@ -141,7 +190,8 @@ impl State {
     pub fn create_group(&mut self, name: &str) -> &mut Group {
         self.groups.entry(name.into()).or_insert(Group {
             status: GroupStatus::Running,
-            parallel_tasks: 1,
+            parallel_tasks: self.default_parallel_tasks.unwrap_or(1),
+            description: None,
         })
     }
 
@@ -160,6 +210,14 @@ impl State {
         Ok(())
     }
 
+    /// Check whether a group is idle, i.e. it exists, doesn't contain any tasks and isn't the
+    /// default group.
+    pub fn group_is_idle(&self, group: &str) -> bool {
+        group != PUEUE_DEFAULT_GROUP
+            && self.groups.contains_key(group)
+            && !self.tasks.values().any(|task| task.group == group)
+    }
+
     /// Set the group status (running/paused) for all groups including the default queue.
     pub fn set_status_for_all_groups(&mut self, status: GroupStatus) {
         for (_, group) in self.groups.iter_mut() {
@@ -251,3 +309,34 @@ impl State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn group_is_idle() {
+        let mut state = State::new();
+        state.create_group("empty");
+        state.create_group("busy");
+        state.add_task(Task::new(
+            "ls".into(),
+            PathBuf::from("/tmp"),
+            HashMap::new(),
+            "busy".into(),
+            TaskStatus::Stashed { enqueue_at: None },
+            Vec::new(),
+            0,
+            None,
+        ));
+
+        assert!(state.group_is_idle("empty"));
+        assert!(!state.group_is_idle("busy"));
+        assert!(!state.group_is_idle("missing"));
+        // The default group is never considered idle.
+        assert!(!state.group_is_idle(PUEUE_DEFAULT_GROUP));
+    }
+}
@@ -1,8 +1,10 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
-use chrono::prelude::*;
+use chrono::{prelude::*, TimeDelta};
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use strum::{Display, EnumString};
+
+use crate::network::message::Signal;
 
 /// This enum represents the status of the internal task handling of Pueue.
 /// They basically represent the internal task life-cycle.
@@ -49,6 +51,60 @@ pub enum TaskResult {
     Errored,
     /// A dependency of the task failed.
     DependencyFailed,
+    /// The task didn't start before its expiry time.
+    Expired,
+}
+
+/// The stati that can be waited for, e.g. via `pueue wait` or [crate::network::client::Client::wait_for].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Display, EnumString)]
+pub enum WaitTargetStatus {
+    #[default]
+    #[strum(serialize = "done", serialize = "Done")]
+    Done,
+    #[strum(serialize = "success", serialize = "Success")]
+    Success,
+    #[strum(serialize = "queued", serialize = "Queued")]
+    Queued,
+    #[strum(serialize = "running", serialize = "Running")]
+    Running,
+}
+
+impl WaitTargetStatus {
+    /// Check if a task reached this target status.
+    /// Other stati that can only occur after that status will also qualify.
+    pub fn is_reached_by(&self, task: &Task) -> bool {
+        match self {
+            WaitTargetStatus::Queued => {
+                matches!(
+                    task.status,
+                    TaskStatus::Queued { .. }
+                        | TaskStatus::Running { .. }
+                        | TaskStatus::Done { .. }
+                )
+            }
+            WaitTargetStatus::Running => match &task.status {
+                TaskStatus::Running { .. } => true,
+                // Some tasks finish without ever being started.
+                TaskStatus::Done { result, .. } => !matches!(
+                    result,
+                    TaskResult::DependencyFailed
+                        | TaskResult::FailedToSpawn(_)
+                        | TaskResult::Expired
+                ),
+                _ => false,
+            },
+            WaitTargetStatus::Done => matches!(task.status, TaskStatus::Done { .. }),
+            WaitTargetStatus::Success => {
+                matches!(
+                    task.status,
+                    TaskStatus::Done {
+                        result: TaskResult::Success,
+                        ..
+                    }
+                )
+            }
+        }
+    }
 }
 
 /// Representation of a task.
@@ -61,11 +117,51 @@ pub struct Task {
     pub original_command: String,
     pub command: String,
     pub path: PathBuf,
+    /// Create the working directory `path` right before the task is started,
+    /// if it doesn't exist yet.
+    #[serde(default)]
+    pub create_workdir: bool,
     pub envs: HashMap<String, String>,
     pub group: String,
     pub dependencies: Vec<usize>,
     pub priority: i32,
     pub label: Option<String>,
+    /// The user as which the task's process is executed (Unix only).
+    /// If not set, the task runs as the user of the daemon.
+    pub user: Option<String>,
+    /// The program and its arguments, if the task is executed directly without a shell.
+    /// In that case, `command` is only a human readable representation of these arguments.
+    pub raw_command: Option<Vec<String>>,
+    /// The maximum amount of memory in bytes the task may use (Linux only).
+    pub memory_limit: Option<u64>,
+    /// If the task hasn't been started by this point in time, it expires and won't be started.
+    pub expire_at: Option<DateTime<Local>>,
+    /// The task is killed, if it runs for longer than this.
+    /// It's represented in a human readable format such as `1h30m`.
+    #[serde(default, with = "duration_format")]
+    pub timeout: Option<Duration>,
+    /// The id of the group's worker slot the task's process has been started in.
+    /// It's exposed to the process via the `PUEUE_WORKER_ID` environment variable.
+    #[serde(default)]
+    pub worker_id: Option<usize>,
+    /// A unique, user-supplied key, which identifies this task across repeated adds.
+    pub key: Option<String>,
+    /// A free-form note, which can be attached to the task at any time via `pueue annotate`.
+    pub note: Option<String>,
+    /// A command that's executed after the task finished successfully.
+    /// This runs in addition to the global `daemon.callback`.
+    pub on_success: Option<String>,
+    /// A command that's executed after the task failed.
+    /// This runs in addition to the global `daemon.callback`.
+    pub on_failure: Option<String>,
+    /// Signals that count as a successful exit, if the task's process is terminated by them,
+    /// e.g. for services that exit via `SIGTERM` on a graceful shutdown (Unix only).
+    #[serde(default)]
+    pub success_signals: Vec<Signal>,
+    /// The status changes of this task and when they happened, e.g. `Queued`, `Running`, `Done`.
+    /// This is only recorded if `daemon.track_status_history` is enabled.
+    #[serde(default)]
+    pub status_history: Vec<(DateTime<Local>, String)>,
     pub status: TaskStatus,
 }
 
@@ -87,15 +183,50 @@ impl Task {
             original_command: original_command.clone(),
             command: original_command,
             path,
+            create_workdir: false,
             envs,
             group,
             dependencies,
             priority,
             label,
+            user: None,
+            raw_command: None,
+            memory_limit: None,
+            expire_at: None,
+            timeout: None,
+            worker_id: None,
+            key: None,
+            note: None,
+            on_success: None,
+            on_failure: None,
+            success_signals: Vec::new(),
+            status_history: Vec::new(),
             status: starting_status.clone(),
         }
     }
 
+    /// Append the current status to the status history, if it changed since the last entry.
+    ///
+    /// The time of the change is taken from the status itself, if it contains it.
+    pub fn record_status_change(&mut self) {
+        let label = self.status.to_string();
+        if self
+            .status_history
+            .last()
+            .is_some_and(|(_, last_label)| *last_label == label)
+        {
+            return;
+        }
+
+        let time = match self.status {
+            TaskStatus::Queued { enqueued_at } => enqueued_at,
+            TaskStatus::Running { start, .. } => start,
+            TaskStatus::Done { end, .. } => end,
+            _ => Local::now(),
+        };
+        self.status_history.push((time, label));
+    }
+
     pub fn start_and_end(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
         match self.status {
             TaskStatus::Running { start, .. } => (Some(start), None),
@@ -105,6 +236,44 @@ impl Task {
         }
     }
 
+    /// How long the task's process has been running. \
+    /// This is `end - start` for finished tasks and `now - start` for running or paused tasks.
+    /// Tasks that didn't start yet don't have a duration.
+    pub fn duration(&self) -> Option<TimeDelta> {
+        self.duration_at(Local::now())
+    }
+
+    /// Same as [Task::duration], but running and paused tasks are measured up to `now`.
+    pub fn duration_at(&self, now: DateTime<Local>) -> Option<TimeDelta> {
+        match self.start_and_end() {
+            (Some(start), Some(end)) => Some(end - start),
+            (Some(start), None) => Some(now - start),
+            _ => None,
+        }
+    }
+
+    /// Whether the task reached its final status, i.e. it's done.
+    /// The task won't change anymore, unless it's restarted.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.status, TaskStatus::Done { .. })
+    }
+
+    /// Whether the task currently has a process, i.e. it's running or paused.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Running { .. } | TaskStatus::Paused { .. }
+        )
+    }
+
+    /// Whether the task didn't start yet, i.e. it's queued, stashed or locked.
+    pub fn is_waiting(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Queued { .. } | TaskStatus::Stashed { .. } | TaskStatus::Locked { .. }
+        )
+    }
+
     /// Whether the task is having a running process managed by the TaskHandler
     pub fn is_running(&self) -> bool {
         matches!(
@@ -134,11 +303,35 @@ impl Task {
         }
     }
 
+    /// The exit code of the task's process. \
+    /// This is `None`, if the task didn't finish yet or if it didn't exit by itself,
+    /// e.g. if it has been killed or failed to spawn.
+    pub fn exit_code(&self) -> Option<i32> {
+        match &self.status {
+            TaskStatus::Done { result, .. } => match result {
+                TaskResult::Success => Some(0),
+                TaskResult::Failed(code) => Some(*code),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Convenience helper on whether a task is stashed
     pub fn is_stashed(&self) -> bool {
         matches!(self.status, TaskStatus::Stashed { .. })
     }
 
+    /// Convenience helper on whether a task is stashed and scheduled to be enqueued at some point.
+    pub fn is_scheduled(&self) -> bool {
+        matches!(
+            self.status,
+            TaskStatus::Stashed {
+                enqueue_at: Some(_)
+            }
+        )
+    }
+
     /// Check whether a task is queued or might soon be enqueued.
     pub fn is_queued(&self) -> bool {
         matches!(
@@ -163,12 +356,255 @@ impl std::fmt::Debug for Task {
             .field("original_command", &self.original_command)
             .field("command", &self.command)
             .field("path", &self.path)
+            .field("create_workdir", &self.create_workdir)
             .field("envs", &"hidden")
             .field("group", &self.group)
             .field("dependencies", &self.dependencies)
             .field("label", &self.label)
+            .field("user", &self.user)
+            .field("raw_command", &self.raw_command)
+            .field("memory_limit", &self.memory_limit)
+            .field("expire_at", &self.expire_at)
+            .field("timeout", &self.timeout)
+            .field("worker_id", &self.worker_id)
+            .field("key", &self.key)
+            .field("note", &self.note)
+            .field("on_success", &self.on_success)
+            .field("on_failure", &self.on_failure)
+            .field("success_signals", &self.success_signals)
+            .field("status_history", &self.status_history)
             .field("status", &self.status)
             .field("priority", &self.priority)
             .finish()
     }
 }
+
+/// (De-)serialization of optional durations in a human readable format, e.g. `1h30m` or `500ms`.
+pub(crate) mod duration_format {
+    use std::time::Duration;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::duration::{format_duration, parse_duration};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_str(&format_duration(duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        let Some(text) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+
+        parse_duration(&text)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("invalid duration '{text}'")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_with_status(status: TaskStatus) -> Task {
+        Task::new(
+            "ls".into(),
+            PathBuf::from("/tmp"),
+            HashMap::new(),
+            "default".into(),
+            status,
+            Vec::new(),
+            0,
+            None,
+        )
+    }
+
+    fn done_task(result: TaskResult) -> Task {
+        task_with_status(TaskStatus::Done {
+            enqueued_at: Local::now(),
+            start: Local::now(),
+            end: Local::now(),
+            result,
+        })
+    }
+
+    /// Every status belongs to exactly one of the terminal, active and waiting classes.
+    #[test]
+    fn status_classification() {
+        let now = Local::now();
+        let statuses = [
+            (
+                TaskStatus::Locked {
+                    previous_status: Box::new(TaskStatus::Queued { enqueued_at: now }),
+                },
+                (false, false, true),
+            ),
+            (
+                TaskStatus::Stashed { enqueue_at: None },
+                (false, false, true),
+            ),
+            (
+                TaskStatus::Stashed {
+                    enqueue_at: Some(now),
+                },
+                (false, false, true),
+            ),
+            (
+                TaskStatus::Queued { enqueued_at: now },
+                (false, false, true),
+            ),
+            (
+                TaskStatus::Running {
+                    enqueued_at: now,
+                    start: now,
+                },
+                (false, true, false),
+            ),
+            (
+                TaskStatus::Paused {
+                    enqueued_at: now,
+                    start: now,
+                },
+                (false, true, false),
+            ),
+            (
+                TaskStatus::Done {
+                    enqueued_at: now,
+                    start: now,
+                    end: now,
+                    result: TaskResult::Success,
+                },
+                (true, false, false),
+            ),
+            (
+                TaskStatus::Done {
+                    enqueued_at: now,
+                    start: now,
+                    end: now,
+                    result: TaskResult::Killed,
+                },
+                (true, false, false),
+            ),
+        ];
+
+        for (status, expected) in statuses {
+            let task = task_with_status(status);
+            let actual = (task.is_terminal(), task.is_active(), task.is_waiting());
+            assert_eq!(
+                actual, expected,
+                "Wrong classification of {:?}",
+                task.status
+            );
+        }
+    }
+
+    #[test]
+    fn exit_code() {
+        assert_eq!(done_task(TaskResult::Success).exit_code(), Some(0));
+        assert_eq!(done_task(TaskResult::Failed(3)).exit_code(), Some(3));
+        assert_eq!(done_task(TaskResult::Killed).exit_code(), None);
+        assert_eq!(done_task(TaskResult::Errored).exit_code(), None);
+        assert_eq!(done_task(TaskResult::DependencyFailed).exit_code(), None);
+        assert_eq!(
+            done_task(TaskResult::FailedToSpawn("error".into())).exit_code(),
+            None
+        );
+    }
+
+    #[test]
+    fn duration_of_done_task() {
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let task = task_with_status(TaskStatus::Done {
+            enqueued_at: start,
+            start,
+            end: start + TimeDelta::minutes(5),
+            result: TaskResult::Success,
+        });
+        assert_eq!(task.duration(), Some(TimeDelta::minutes(5)));
+    }
+
+    #[test]
+    fn duration_of_running_task() {
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let now = start + TimeDelta::hours(2);
+
+        let task = task_with_status(TaskStatus::Running {
+            enqueued_at: start,
+            start,
+        });
+        assert_eq!(task.duration_at(now), Some(TimeDelta::hours(2)));
+
+        let task = task_with_status(TaskStatus::Paused {
+            enqueued_at: start,
+            start,
+        });
+        assert_eq!(task.duration_at(now), Some(TimeDelta::hours(2)));
+    }
+
+    #[test]
+    fn duration_of_unstarted_task() {
+        let now = Local::now();
+        let task = task_with_status(TaskStatus::Queued { enqueued_at: now });
+        assert_eq!(task.duration(), None);
+
+        let task = task_with_status(TaskStatus::Stashed { enqueue_at: None });
+        assert_eq!(task.duration(), None);
+
+        let task = task_with_status(TaskStatus::Stashed {
+            enqueue_at: Some(now),
+        });
+        assert_eq!(task.duration_at(now), None);
+
+        let task = task_with_status(TaskStatus::Locked {
+            previous_status: Box::new(TaskStatus::Queued { enqueued_at: now }),
+        });
+        assert_eq!(task.duration(), None);
+    }
+
+    #[test]
+    fn timeout_format() {
+        let mut task = task_with_status(TaskStatus::Stashed { enqueue_at: None });
+        for (timeout, text) in [
+            (Duration::from_secs(30 * 60), "30m"),
+            (Duration::from_secs(90 * 60 + 5), "1h30m5s"),
+            (Duration::from_secs(2 * 24 * 60 * 60), "2d"),
+            (Duration::from_millis(1500), "1s500ms"),
+            (Duration::ZERO, "0ms"),
+        ] {
+            task.timeout = Some(timeout);
+            let json = serde_json::to_value(&task).unwrap();
+            assert_eq!(json["timeout"], text);
+
+            let deserialized: Task = serde_json::from_value(json).unwrap();
+            assert_eq!(deserialized.timeout, Some(timeout));
+        }
+
+        // Tasks of previous versions don't have a timeout yet.
+        let mut json = serde_json::to_value(&task).unwrap();
+        json.as_object_mut().unwrap().remove("timeout");
+        let deserialized: Task = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.timeout, None);
+    }
+
+    #[test]
+    fn exit_code_of_unfinished_task() {
+        let task = task_with_status(TaskStatus::Queued {
+            enqueued_at: Local::now(),
+        });
+        assert_eq!(task.exit_code(), None);
+
+        let task = task_with_status(TaskStatus::Running {
+            enqueued_at: Local::now(),
+            start: Local::now(),
+        });
+        assert_eq!(task.exit_code(), None);
+    }
+}
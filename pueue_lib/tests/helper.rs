@@ -22,11 +22,15 @@ pub fn get_shared_settings(
         unix_socket_path: None,
         #[cfg(not(target_os = "windows"))]
         unix_socket_permissions: Some(0o700),
+        #[cfg(not(target_os = "windows"))]
+        unix_socket_trust_permissions: false,
         pid_path: None,
-        host: "localhost".to_string(),
+        host: "localhost".into(),
         port: pick_unused_port()
             .expect("There should be a free port")
             .to_string(),
+        max_connections: 100,
+        listen_backlog: 1024,
         daemon_cert: Some(tempdir_path.join("certs").join("daemon.cert")),
         daemon_key: Some(tempdir_path.join("certs").join("daemon.key")),
         shared_secret_path: Some(tempdir_path.join("secret")),
@@ -0,0 +1,104 @@
+#![cfg(feature = "query")]
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use pretty_assertions::assert_eq;
+
+use pueue_lib::query::{apply_query, Rule};
+use pueue_lib::state::PUEUE_DEFAULT_GROUP;
+use pueue_lib::task::{Task, TaskResult, TaskStatus};
+
+fn build_task(id: usize, group: &str, status: TaskStatus) -> Task {
+    let mut task = Task::new(
+        "sleep 60".to_owned(),
+        PathBuf::from("/tmp"),
+        HashMap::new(),
+        group.to_owned(),
+        status,
+        Vec::new(),
+        0,
+        None,
+    );
+    task.id = id;
+    task
+}
+
+fn test_tasks() -> Vec<Task> {
+    let enqueued_at = Local.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let start = Local.with_ymd_and_hms(2024, 1, 1, 10, 5, 0).unwrap();
+    let end = Local.with_ymd_and_hms(2024, 1, 1, 10, 10, 0).unwrap();
+
+    vec![
+        build_task(0, PUEUE_DEFAULT_GROUP, TaskStatus::Queued { enqueued_at }),
+        build_task(
+            1,
+            PUEUE_DEFAULT_GROUP,
+            TaskStatus::Running { enqueued_at, start },
+        ),
+        build_task(
+            2,
+            "build",
+            TaskStatus::Done {
+                enqueued_at,
+                start,
+                end,
+                result: TaskResult::Success,
+            },
+        ),
+        build_task(
+            3,
+            "build",
+            TaskStatus::Done {
+                enqueued_at,
+                start,
+                end,
+                result: TaskResult::Failed(1),
+            },
+        ),
+    ]
+}
+
+/// A query can be parsed and applied to a plain list of tasks, just like the CLI does.
+#[test]
+fn filter_order_and_limit_tasks() -> Result<()> {
+    let query_result = apply_query(
+        "columns=id,status status=success or status=running order_by id desc first 1",
+        None,
+    )?;
+    assert_eq!(
+        query_result.selected_columns,
+        vec![Rule::column_id, Rule::column_status]
+    );
+
+    let tasks = query_result.apply_filters(test_tasks());
+    let tasks = query_result.order_tasks(tasks);
+    let tasks = query_result.limit_tasks(tasks);
+    let ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![2]);
+
+    Ok(())
+}
+
+/// Only tasks of the given group are kept.
+#[test]
+fn filter_by_group() -> Result<()> {
+    let query_result = apply_query("", Some("build"))?;
+    let tasks = query_result.apply_filters(test_tasks());
+    let ids: Vec<usize> = tasks.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![2, 3]);
+
+    Ok(())
+}
+
+/// Invalid queries result in a proper error.
+#[test]
+fn invalid_query() {
+    let Err(err) = apply_query("status=unknown", None) else {
+        panic!("Expected the query to fail");
+    };
+    assert!(
+        err.to_string().contains("Failed to parse query"),
+        "Got unexpected error: {err}"
+    );
+}
@@ -47,3 +47,45 @@ async fn test_tls_socket() -> Result<()> {
 
     Ok(())
 }
+
+/// The listener accepts TLS connections on each of multiple configured hosts.
+#[tokio::test]
+async fn test_tls_socket_multiple_hosts() -> Result<()> {
+    better_panic::install();
+    // Some environments (e.g. containers) don't have IPv6 support.
+    if std::net::TcpListener::bind("[::1]:0").is_err() {
+        eprintln!("Skipping test, as the IPv6 loopback address can't be bound.");
+        return Ok(());
+    }
+
+    let (mut shared_settings, _tempdir) = helper::get_shared_settings(false);
+    let hosts = vec!["127.0.0.1".to_string(), "::1".to_string()];
+    shared_settings.host = hosts.clone().into();
+
+    create_certificates(&shared_settings).unwrap();
+
+    let listener = get_listener(&shared_settings).await.unwrap();
+
+    // Spawn a sub thread that answers each connection with the message it received.
+    task::spawn(async move {
+        loop {
+            let mut stream = listener.accept().await.unwrap();
+            let message = receive_message(&mut stream).await.unwrap();
+            send_message(message, &mut stream).await.unwrap();
+        }
+    });
+
+    // Connect to each host separately.
+    for host in hosts {
+        let mut settings = shared_settings.clone();
+        settings.host = host.as_str().into();
+        let mut client = get_client_stream(&settings).await.unwrap();
+
+        let message = create_success_message(format!("Hello via {host}"));
+        send_message(message.clone(), &mut client).await.unwrap();
+        let response = receive_message(&mut client).await.unwrap();
+        assert_eq!(response, message);
+    }
+
+    Ok(())
+}